@@ -0,0 +1,98 @@
+#[cfg(feature = "nnue")]
+use crate::load_network;
+use crate::{parse_flag, parse_position};
+use std::time::Instant;
+use thermite_core::node_count::NodeCount;
+use thermite_core::ply_count::PlyCount;
+use thermite_core::search::{Search, SearchLimits, DEFAULT_HASH_MEGABYTES, MAX_THREADS};
+
+/// The default depth each position is searched to
+const DEFAULT_DEPTH: u8 = 9;
+/// The positions searched, a mix of openings, middlegames and endgames (including some with mates and promotions)
+const POSITIONS: [&str; 24] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 11",
+    "4rrk1/pp1n3p/3q2pQ/2p1pb2/2PP4/2P3N1/P2B2PP/4RRK1 b - - 7 19",
+    "rq3rk1/ppp2ppp/1bnpb3/3N2B1/3NP3/7P/PPPQ1PP1/2KR3R w - - 7 14",
+    "r1bq1r1k/1pp1n1pp/1p1p4/4p2Q/4Pp2/1BNP4/PPP2PPP/3R1RK1 w - - 2 14",
+    "r3r1k1/2p2ppp/p1p1bn2/8/1q2P3/2NPQN2/PPP3PP/R4RK1 b - - 2 15",
+    "r1bbk1nr/pp3p1p/2n5/1N4p1/2Np1B2/8/PPP2PPP/2KR1B1R w kq - 0 13",
+    "r1bq1rk1/ppp1nppp/4n3/3p3Q/3P4/1BP1B3/PP1N2PP/R4RK1 w - - 1 16",
+    "4r1k1/r1q2ppp/ppp2n2/4P3/5Rb1/1N1BQ3/PPP3PP/R5K1 w - - 1 17",
+    "2rqkb1r/ppp2p2/2npb1p1/1N1Nn2p/2P1PP2/8/PP2B1PP/R1BQK2R b KQ - 0 11",
+    "r1bq1r1k/b1p1npp1/p2p3p/1p6/3PP3/1B2NN2/PP3PPP/R2Q1RK1 w - - 1 16",
+    "3r1rk1/p5pp/bpp1pp2/8/q1PP1P2/b3P3/P2NQRPP/1R2B1K1 b - - 6 22",
+    "r1q2rk1/2p1bppp/2Pp4/p6b/Q1PNp3/4B3/PP1R1PPP/2K4R w - - 2 18",
+    "4k2r/1pb2ppp/1p2p3/1R1p4/3P4/2r1PN2/P4PPP/1R4K1 b - - 3 22",
+    "3q2k1/pb3p1p/4pbp1/2r5/PpN2N2/1P2P2P/5PP1/Q2R2K1 b - - 4 26",
+    "6k1/6p1/6Pp/ppp5/3pn2P/1P3K2/1PP2P2/3N4 b - - 0 1",
+    "3b4/5kp1/1p1p1p1p/pP1PpP1P/P1P1P3/3KN3/8/8 w - - 0 1",
+    "2K5/p7/7P/5pR1/8/5k2/r7/8 w - - 0 1",
+    "8/6pk/1p6/8/PP3p1p/5P2/4KP1q/3Q4 w - - 0 1",
+    "7k/3p2pp/4q3/8/4Q3/5Kp1/P6b/8 w - - 0 1",
+    "8/2p5/8/2kPKp1p/2p4P/2P5/3P4/8 w - - 0 1",
+    "8/1p3pp1/7p/5P1P/2k3P1/8/2K2P2/8 w - - 0 1",
+    "5k2/7R/4P2p/5K2/p1r2P1p/8/8/8 b - - 0 1",
+];
+
+/// Search the built-in positions to a fixed depth and print the total nodes and speed
+///
+/// Takes optional positional arguments: the depth, the number of threads and the transposition table size in
+/// megabytes. With the `nnue` feature, a leading `--nnue <file>` evaluates with a network. Each position starts from a cleared search, so with a single thread the total node count is the same
+/// on every run and identifies the search's behaviour (its bench signature).
+///
+/// # Errors
+/// If any of the arguments are invalid
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut args = args.peekable();
+    #[cfg(feature = "nnue")]
+    let network = if args.next_if(|arg| arg == "--nnue").is_some() {
+        Some(load_network(&parse_flag::<String>("--nnue", args.next())?)?)
+    } else {
+        None
+    };
+    let depth = args
+        .next()
+        .map_or(Ok(DEFAULT_DEPTH), |depth| parse_flag("depth", Some(depth)))?;
+    let threads = args
+        .next()
+        .map_or(Ok(1), |threads| parse_flag("threads", Some(threads)))?;
+    let hash = args.next().map_or(Ok(DEFAULT_HASH_MEGABYTES), |hash| {
+        parse_flag("hash", Some(hash))
+    })?;
+
+    let mut search = Search::new(hash.max(1));
+    search.set_threads(threads.clamp(1, MAX_THREADS));
+    #[cfg(feature = "nnue")]
+    search.set_network(network);
+    let limits = SearchLimits {
+        depth: Some(PlyCount::new(depth.max(1))),
+        ..SearchLimits::default()
+    };
+
+    let mut total = NodeCount::default();
+    let start = Instant::now();
+    for (index, fen) in POSITIONS.iter().enumerate() {
+        let position = parse_position(fen)?;
+        search.clear();
+        let result = search.search(&position, &[], limits.clone());
+        println!(
+            "position {}/{}: {} nodes ({fen})",
+            index + 1,
+            POSITIONS.len(),
+            result.nodes
+        );
+        total += result.nodes;
+    }
+    let millis = start.elapsed().as_millis();
+    let nps = u128::from(*total.as_ref()) * 1000 / millis.max(1);
+
+    println!();
+    println!("Total time (ms) : {millis}");
+    println!("Nodes searched  : {total}");
+    println!("Nodes/second    : {nps}");
+    println!("{total} nodes {nps} nps");
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+#[cfg(feature = "nnue")]
+use crate::load_network;
+use crate::parse_flag;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use thermite_core::evaluation::Score;
+use thermite_core::game::Game;
+use thermite_core::node_count::NodeCount;
+use thermite_core::pgn::GameResult;
+use thermite_core::player_color::PlayerColor;
+use thermite_core::ply_count::PlyCount;
+use thermite_core::position::{PackedBoard, PackedEntry, PackedWriter, PositionBuilder};
+use thermite_core::search::{Search, SearchLimits};
+
+/// The default number of games played
+const DEFAULT_GAMES: u32 = 1000;
+/// The default number of nodes searched for each move
+const DEFAULT_NODES: u64 = 5000;
+/// The default number of random moves played from the starting position before the engine takes over
+const DEFAULT_RANDOM_PLIES: u8 = 8;
+/// The size (in megabytes) of each thread's transposition table
+const HASH_MEGABYTES: usize = 16;
+/// Openings the engine scores beyond this (in centipawns) are too lopsided to learn much from, and are replaced
+const MAX_OPENING_SCORE: i32 = 1000;
+/// The largest fullmove number a sample's position can hold, positions after it are played but not kept
+const MAX_FULLMOVE: u16 = 127;
+
+/// A position reached in self-play, with its search score in centipawns from white's point of view
+struct Sample {
+    position: PositionBuilder,
+    score: i16,
+}
+
+/// Write a sample as a line of text, `<fen> | <score> | <result>` with the result as `1.0`, `0.5` or `0.0`
+fn write_text(output: &mut impl Write, sample: &Sample, result: GameResult) -> std::io::Result<()> {
+    let result = match result {
+        GameResult::WhiteWins => "1.0",
+        GameResult::BlackWins => "0.0",
+        GameResult::Draw | GameResult::Unknown => "0.5",
+    };
+
+    writeln!(output, "{} | {} | {result}", sample.position, sample.score)
+}
+
+/// The centipawns of a score from white's point of view, `None` for mates
+fn white_centipawns(score: Score, player: PlayerColor) -> Option<i16> {
+    let centipawns = match score {
+        Score::Approximate(evaluation) => evaluation.centipawns(),
+        Score::Stalemate => 0,
+        Score::Mating(_) | Score::Mated(_) => return None,
+    };
+    let centipawns = i16::try_from(centipawns).ok()?;
+
+    Some(match player {
+        PlayerColor::White => centipawns,
+        PlayerColor::Black => -centipawns,
+    })
+}
+
+/// Play random legal moves from the starting position, getting `None` if the game ends before they're all played
+fn random_opening(rng: &mut fastrand::Rng, plies: u8) -> Option<Game> {
+    let mut game = Game::default();
+    for _ in 0..plies {
+        let moves = game.position().generate_legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+        game.push_legal(moves[rng.usize(..moves.len())]);
+    }
+
+    game.outcome().is_none().then_some(game)
+}
+
+/// Play one self-play game from a random opening, getting its quiet positions and result
+fn play_game(
+    search: &mut Search,
+    rng: &mut fastrand::Rng,
+    limits: &SearchLimits,
+    random_plies: u8,
+) -> (Vec<Sample>, GameResult) {
+    search.clear();
+    let mut game = loop {
+        let Some(game) = random_opening(rng, random_plies) else {
+            continue;
+        };
+        let result = search.search(game.position(), game.history(), limits.clone());
+        if let Score::Approximate(evaluation) = result.score {
+            if evaluation.centipawns().abs() <= MAX_OPENING_SCORE {
+                break game;
+            }
+        }
+    };
+
+    let mut samples = Vec::new();
+    while game.outcome().is_none() {
+        let position = game.position();
+        let result = search.search(position, game.history(), limits.clone());
+        let Some(best_move) = result.best_move else {
+            break;
+        };
+        // Only keep quiet positions, whose scores the static evaluation can hope to match
+        let fullmove = game.fullmove_number();
+        if !position.in_check() && !best_move.is_capture() && fullmove <= MAX_FULLMOVE {
+            if let Some(score) = white_centipawns(result.score, position.player_to_move()) {
+                samples.push(Sample {
+                    position: PositionBuilder::from(position)
+                        .with_fullmove_count(PlyCount::new(fullmove as u8)),
+                    score,
+                });
+            }
+        }
+        game.push_legal(best_move);
+    }
+
+    (samples, game.result())
+}
+
+/// The file samples are written to, in the packed format or as text
+enum Writer {
+    Packed(PackedWriter<BufWriter<File>>),
+    Text(BufWriter<File>),
+}
+
+/// Where the samples of finished games are written, and how many have been
+struct Output {
+    writer: Writer,
+    games: u32,
+    positions: u64,
+}
+
+impl Output {
+    /// Write the samples of a finished game with its result
+    fn write(&mut self, samples: &[Sample], result: GameResult) -> Result<(), String> {
+        for sample in samples {
+            match &mut self.writer {
+                Writer::Packed(writer) => {
+                    let board = PackedBoard::try_from(&sample.position)
+                        .map_err(|error| format!("unable to pack a sample: {error:?}"))?;
+                    writer.write(&PackedEntry {
+                        board,
+                        score: sample.score,
+                        result,
+                    })
+                }
+                Writer::Text(writer) => write_text(writer, sample, result),
+            }
+            .map_err(|error| format!("unable to write samples: {error}"))?;
+        }
+        self.games += 1;
+        self.positions += samples.len() as u64;
+
+        Ok(())
+    }
+
+    /// Write any samples still buffered
+    fn finish(self) -> std::io::Result<()> {
+        match self.writer {
+            Writer::Packed(writer) => writer.finish()?.flush(),
+            Writer::Text(mut writer) => writer.flush(),
+        }
+    }
+}
+
+/// Generate training data by playing fixed-node self-play games from random openings on several threads
+///
+/// Only quiet positions (not in check, and without a capture as the best move) are kept, each with its search
+/// score and the result of its game, written as a stream of [`PackedEntry`]s or as FEN text.
+///
+/// # Errors
+/// If the arguments are invalid, or the output cannot be written
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut output = None;
+    let mut games = DEFAULT_GAMES;
+    let mut threads = 1;
+    let mut nodes = DEFAULT_NODES;
+    let mut random_plies = DEFAULT_RANDOM_PLIES;
+    let mut text = false;
+    let mut seed = None;
+    #[cfg(feature = "nnue")]
+    let mut network = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = Some(parse_flag::<String>(&arg, args.next())?),
+            "--games" => games = parse_flag(&arg, args.next())?,
+            "--threads" => threads = parse_flag::<usize>(&arg, args.next())?.max(1),
+            "--nodes" => nodes = parse_flag::<u64>(&arg, args.next())?.max(1),
+            "--random-plies" => random_plies = parse_flag(&arg, args.next())?,
+            "--seed" => seed = Some(parse_flag::<u64>(&arg, args.next())?),
+            "--text" => text = true,
+            #[cfg(feature = "nnue")]
+            "--nnue" => network = Some(load_network(&parse_flag::<String>(&arg, args.next())?)?),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    let path = output.ok_or("missing `--output` file")?;
+    let file = File::create(&path)
+        .map(BufWriter::new)
+        .map_err(|error| format!("unable to create `{path}`: {error}"))?;
+    let writer = if text {
+        Writer::Text(file)
+    } else {
+        PackedWriter::new(file)
+            .map(Writer::Packed)
+            .map_err(|error| format!("unable to write `{path}`: {error}"))?
+    };
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+
+    let limits = SearchLimits {
+        nodes: Some(NodeCount::new(nodes)),
+        ..SearchLimits::default()
+    };
+    let started = AtomicU32::new(0);
+    let output = Mutex::new(Output {
+        writer,
+        games: 0,
+        positions: 0,
+    });
+    let start = Instant::now();
+
+    thread::scope(|scope| {
+        let workers = (0..threads as u64)
+            .map(|thread| {
+                let (limits, started, output) = (&limits, &started, &output);
+                #[cfg(feature = "nnue")]
+                let network = network.clone();
+                scope.spawn(move || {
+                    let mut search = Search::new(HASH_MEGABYTES);
+                    #[cfg(feature = "nnue")]
+                    search.set_network(network);
+                    let mut rng = fastrand::Rng::with_seed(seed.wrapping_add(thread));
+                    while started.fetch_add(1, Ordering::Relaxed) < games {
+                        let (samples, result) =
+                            play_game(&mut search, &mut rng, limits, random_plies);
+                        let mut output = output.lock().expect("datagen output poisoned");
+                        output.write(&samples, result)?;
+                        if output.games % 100 == 0 {
+                            eprintln!(
+                                "{} games, {} positions, {:.0} positions/second",
+                                output.games,
+                                output.positions,
+                                output.positions as f64 / start.elapsed().as_secs_f64()
+                            );
+                        }
+                    }
+
+                    Ok::<(), String>(())
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .try_for_each(|worker| worker.join().expect("datagen worker panicked"))
+    })?;
+
+    let output = output.into_inner().expect("datagen output poisoned");
+    let (positions, games) = (output.positions, output.games);
+    output
+        .finish()
+        .map_err(|error| format!("unable to write `{path}`: {error}"))?;
+    eprintln!(
+        "wrote {positions} positions from {games} games to `{path}` in {:.1}s",
+        start.elapsed().as_secs_f64()
+    );
+
+    Ok(())
+}
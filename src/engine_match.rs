@@ -0,0 +1,615 @@
+use crate::uci_client::{EngineError, UciEngine};
+use crate::{parse_flag, STARTPOS};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use thermite_core::chess_move::ChessMove;
+use thermite_core::game::{Game, GameOutcome};
+use thermite_core::pgn::{GameResult, PgnGame, PgnReader};
+use thermite_core::player_color::PlayerColor;
+use thermite_core::sprt::{MatchScore, Sprt, SprtDecision};
+use thermite_core::tablebase::{SyzygyTablebase, Wdl};
+
+/// The number of games played by default, unless an SPRT stops the match
+const DEFAULT_GAMES: u32 = 100;
+/// The default chance of an SPRT accepting either hypothesis when the other is true
+const DEFAULT_SPRT_ERROR: f64 = 0.05;
+/// The default time (in milliseconds) an engine may overrun its clock by before it forfeits
+const DEFAULT_TIME_MARGIN: u64 = 100;
+/// The default time control, 10 seconds plus a tenth of a second per move
+const DEFAULT_TIME_CONTROL: TimeControl = TimeControl::Clock {
+    base: Duration::from_secs(10),
+    increment: Duration::from_millis(100),
+};
+
+/// How long engines have to move
+#[derive(Copy, Clone, Debug)]
+enum TimeControl {
+    /// Each engine has a clock for the whole game, gaining an increment after each move
+    Clock { base: Duration, increment: Duration },
+    /// Each engine has a fixed time per move
+    MoveTime(Duration),
+}
+
+impl TimeControl {
+    /// Parse a clock time control in seconds, `base+increment` or just `base`
+    fn parse(written: &str) -> Result<Self, String> {
+        let seconds = |value: &str| {
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(|value| Duration::try_from_secs_f64(value).ok())
+                .ok_or_else(|| format!("invalid time control `{written}`"))
+        };
+        let (base, increment) = written.split_once('+').unwrap_or((written, "0"));
+
+        Ok(Self::Clock {
+            base: seconds(base)?,
+            increment: seconds(increment)?,
+        })
+    }
+
+    /// The value of the PGN `TimeControl` tag, in seconds
+    fn pgn_tag(self) -> String {
+        match self {
+            Self::Clock { base, increment } if increment.is_zero() => {
+                format!("{}", base.as_secs_f64())
+            }
+            Self::Clock { base, increment } => {
+                format!("{}+{}", base.as_secs_f64(), increment.as_secs_f64())
+            }
+            Self::MoveTime(_) => "-".to_string(),
+        }
+    }
+}
+
+/// When games are ended early by the engines' scores, or by a tablebase
+#[derive(Default)]
+struct Adjudication {
+    /// An engine resigns once its score is at or below minus the centipawns for the moves in a row
+    resign: Option<(i32, u32)>,
+    /// A game is drawn from the move number once both engines' scores are within the centipawns for the moves in a row
+    draw: Option<(u16, i32, u32)>,
+    /// Games are decided as soon as they reach a position in the tablebase
+    tablebase: Option<SyzygyTablebase>,
+}
+
+/// How the games of a match are played
+struct Settings {
+    commands: [String; 2],
+    options: Vec<(String, String)>,
+    time_control: TimeControl,
+    time_margin: Duration,
+    adjudication: Adjudication,
+}
+
+/// A position games start from, with the moves played to reach it
+struct Opening {
+    fen: String,
+    moves: Vec<ChessMove>,
+}
+
+/// Load the openings of an EPD (a position per line) or, by its extension, a PGN file
+fn load_openings(path: &str) -> Result<Vec<Opening>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|error| format!("unable to read `{path}`: {error}"))?;
+
+    let openings = if path.to_ascii_lowercase().ends_with(".pgn") {
+        PgnReader::new(&contents)
+            .map(|game| {
+                let game = game.map_err(|error| format!("{path}: {error}"))?;
+
+                Ok(Opening {
+                    fen: game.tag("FEN").unwrap_or(STARTPOS).to_string(),
+                    moves: game.moves().collect(),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    } else {
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|(index, line)| {
+                let fields = line.split_whitespace().collect::<Vec<_>>();
+                // Keep the clocks of a FEN, but not the operations of an EPD record
+                let clocks = fields
+                    .get(4..6)
+                    .filter(|clocks| clocks.iter().all(|clock| clock.parse::<u16>().is_ok()))
+                    .map_or_else(|| "0 1".to_string(), |clocks| clocks.join(" "));
+                let fen = format!("{} {clocks}", fields[..fields.len().min(4)].join(" "));
+                Game::from_fen(&fen)
+                    .map(|_| Opening {
+                        fen,
+                        moves: Vec::new(),
+                    })
+                    .map_err(|error| format!("{path}:{}: {error:?}", index + 1))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    };
+
+    if openings.is_empty() {
+        Err(format!("no openings in `{path}`"))
+    } else {
+        Ok(openings)
+    }
+}
+
+/// The index of a player's engine and clock
+const fn side(player: PlayerColor) -> usize {
+    match player {
+        PlayerColor::White => 0,
+        PlayerColor::Black => 1,
+    }
+}
+
+/// The name of a player for describing how a game ended
+const fn player_name(player: PlayerColor) -> &'static str {
+    match player {
+        PlayerColor::White => "white",
+        PlayerColor::Black => "black",
+    }
+}
+
+/// A finished game, how it ended and why
+struct GameRecord {
+    game: Game,
+    result: GameResult,
+    /// The value of the PGN `Termination` tag
+    termination: &'static str,
+    reason: String,
+}
+
+impl GameRecord {
+    /// A game ended by the rules of chess
+    fn finished(game: Game, outcome: GameOutcome) -> Self {
+        let reason = match outcome {
+            GameOutcome::Checkmate(winner) => format!("{} mates", player_name(winner)),
+            GameOutcome::Stalemate => "stalemate".to_string(),
+            GameOutcome::FiftyMoveRule => "fifty-move rule".to_string(),
+            GameOutcome::ThreefoldRepetition => "threefold repetition".to_string(),
+            GameOutcome::InsufficientMaterial => "insufficient material".to_string(),
+        };
+
+        Self {
+            game,
+            result: outcome.result(),
+            termination: "normal",
+            reason,
+        }
+    }
+
+    /// A game lost by a player, without it being checkmated
+    fn lost(game: Game, loser: PlayerColor, termination: &'static str, reason: &str) -> Self {
+        let result = match loser {
+            PlayerColor::White => GameResult::BlackWins,
+            PlayerColor::Black => GameResult::WhiteWins,
+        };
+
+        Self {
+            game,
+            result,
+            termination,
+            reason: format!("{} {reason}", player_name(loser)),
+        }
+    }
+
+    /// A game adjudicated as a draw
+    fn drawn(game: Game, reason: &str) -> Self {
+        Self {
+            game,
+            result: GameResult::Draw,
+            termination: "adjudication",
+            reason: reason.to_string(),
+        }
+    }
+
+    /// A game lost by a player whose engine failed to move
+    fn failed(game: Game, loser: PlayerColor, error: EngineError) -> Self {
+        match error {
+            EngineError::TimedOut => Self::lost(game, loser, "time forfeit", "loses on time"),
+            EngineError::Disconnected => Self::lost(game, loser, "abandoned", "disconnects"),
+        }
+    }
+}
+
+/// Decide a game by the tablebase once it reaches a position with few enough pieces
+fn adjudicate_tablebase(game: &Game, tablebase: &SyzygyTablebase) -> Option<GameRecord> {
+    let position = game.position();
+    if !tablebase.can_probe(position) {
+        return None;
+    }
+    let player = position.player_to_move();
+
+    Some(match tablebase.probe_wdl(&mut position.clone())? {
+        Wdl::Win => GameRecord::lost(
+            game.clone(),
+            player.switch(),
+            "adjudication",
+            "loses by tablebase",
+        ),
+        Wdl::Loss => GameRecord::lost(game.clone(), player, "adjudication", "loses by tablebase"),
+        Wdl::CursedWin | Wdl::Draw | Wdl::BlessedLoss => {
+            GameRecord::drawn(game.clone(), "tablebase draw")
+        }
+    })
+}
+
+/// Play a game between two engines from an opening, refereed by the moves and outcome of a [`Game`]
+fn play_game(
+    white: &mut UciEngine,
+    black: &mut UciEngine,
+    opening: &Opening,
+    settings: &Settings,
+) -> GameRecord {
+    let mut game = Game::from_fen(&opening.fen).expect("openings are checked when loaded");
+    for &chess_move in &opening.moves {
+        // The moves of PGN openings are legal once read
+        game.push_legal(chess_move);
+    }
+    let mut engines = [white, black];
+    for (engine, player) in engines
+        .iter_mut()
+        .zip([PlayerColor::White, PlayerColor::Black])
+    {
+        if let Err(error) = engine.new_game() {
+            return GameRecord::failed(game, player, error);
+        }
+    }
+
+    let adjudication = &settings.adjudication;
+    let mut clocks = match settings.time_control {
+        TimeControl::Clock { base, .. } => [base; 2],
+        TimeControl::MoveTime(movetime) => [movetime; 2],
+    };
+    let mut losing_moves = [0; 2];
+    let mut drawn_plies = 0;
+    loop {
+        if let Some(outcome) = game.outcome() {
+            return GameRecord::finished(game, outcome);
+        }
+        if let Some(record) = adjudication
+            .tablebase
+            .as_ref()
+            .and_then(|tablebase| adjudicate_tablebase(&game, tablebase))
+        {
+            return record;
+        }
+
+        let player = game.position().player_to_move();
+        let go = match settings.time_control {
+            TimeControl::Clock { increment, .. } => format!(
+                "go wtime {} btime {} winc {} binc {}",
+                clocks[0].as_millis(),
+                clocks[1].as_millis(),
+                increment.as_millis(),
+                increment.as_millis()
+            ),
+            TimeControl::MoveTime(movetime) => format!("go movetime {}", movetime.as_millis()),
+        };
+        let timeout = clocks[side(player)] + settings.time_margin;
+        let answer = match engines[side(player)].go(&game, &go, timeout) {
+            Ok(answer) => answer,
+            Err(error) => return GameRecord::failed(game, player, error),
+        };
+        if let TimeControl::Clock { increment, .. } = settings.time_control {
+            let clock = &mut clocks[side(player)];
+            *clock = clock.saturating_sub(answer.elapsed) + increment;
+        }
+
+        let position = game.position();
+        let Some(chess_move) = position
+            .parse_uci(&answer.best_move, false)
+            .or_else(|| position.parse_uci(&answer.best_move, true))
+        else {
+            let reason = format!("plays the illegal move `{}`", answer.best_move);
+            return GameRecord::lost(game, player, "rules infraction", &reason);
+        };
+        game.push_legal(chess_move);
+
+        let losing = &mut losing_moves[side(player)];
+        match (adjudication.resign, answer.score) {
+            (Some((score, moves)), Some(engine_score)) if engine_score <= -score => {
+                *losing += 1;
+                if *losing >= moves {
+                    return GameRecord::lost(game, player, "adjudication", "resigns");
+                }
+            }
+            _ => *losing = 0,
+        }
+        match (adjudication.draw, answer.score) {
+            (Some((move_number, score, moves)), Some(engine_score))
+                if game.fullmove_number() >= move_number && engine_score.abs() <= score =>
+            {
+                drawn_plies += 1;
+                if drawn_plies >= 2 * moves {
+                    return GameRecord::drawn(game, "draw by adjudication");
+                }
+            }
+            _ => drawn_plies = 0,
+        }
+    }
+}
+
+/// The state of a match shared between the threads playing its games
+struct Progress {
+    /// The number of games started
+    started: u32,
+    /// The results of the first engine against the second
+    score: MatchScore,
+    /// The SPRT has made its decision, so no more games are started
+    decided: Option<SprtDecision>,
+    pgn: Option<File>,
+}
+
+/// Build the PGN of a finished game
+fn export_pgn(
+    record: &GameRecord,
+    number: u32,
+    names: [&str; 2],
+    time_control: TimeControl,
+) -> PgnGame {
+    let fen = record.game.starting_fen();
+    let mut pgn = if fen == STARTPOS {
+        PgnGame::default()
+    } else {
+        PgnGame::from_fen(fen).expect("openings are checked when loaded")
+    };
+    // The game only holds legal moves
+    let _ = pgn.push_moves(record.game.moves());
+    pgn.set_tag("Event", "thermite match");
+    pgn.set_tag("Round", &number.to_string());
+    pgn.set_tag("White", names[0]);
+    pgn.set_tag("Black", names[1]);
+    pgn.set_tag("TimeControl", &time_control.pgn_tag());
+    pgn.set_tag("Termination", record.termination);
+    pgn.set_result(record.result);
+
+    pgn
+}
+
+impl Progress {
+    /// Count a finished game, print the standings and write its PGN
+    fn record(
+        &mut self,
+        number: u32,
+        record: &GameRecord,
+        names: [&str; 2],
+        first_plays_white: bool,
+        settings: &Settings,
+        sprt: Option<Sprt>,
+    ) -> Result<(), String> {
+        let (white, black) = if first_plays_white {
+            (names[0], names[1])
+        } else {
+            (names[1], names[0])
+        };
+        match (record.result, first_plays_white) {
+            (GameResult::WhiteWins, true) | (GameResult::BlackWins, false) => self.score.wins += 1,
+            (GameResult::WhiteWins, false) | (GameResult::BlackWins, true) => {
+                self.score.losses += 1;
+            }
+            _ => self.score.draws += 1,
+        }
+
+        println!(
+            "Finished game {number} ({white} vs {black}): {} {{{}}}",
+            record.result, record.reason
+        );
+        println!(
+            "Score of {} vs {}: {} - {} - {} [{:.3}] {}",
+            names[0],
+            names[1],
+            self.score.wins,
+            self.score.losses,
+            self.score.draws,
+            self.score.score().unwrap_or(0.5),
+            self.score.games()
+        );
+        if let Some(elo) = self.score.elo() {
+            println!(
+                "Elo difference: {:.1} +/- {:.1}",
+                elo.difference, elo.margin
+            );
+        }
+        if let Some(sprt) = sprt {
+            let (elo0, elo1) = sprt.hypotheses();
+            let (lower, upper) = sprt.bounds();
+            println!(
+                "SPRT: llr {:.3} ({lower:.3}, {upper:.3}) [{elo0}, {elo1}]",
+                sprt.log_likelihood_ratio(&self.score)
+            );
+            self.decided = self.decided.or_else(|| sprt.decision(&self.score));
+        }
+
+        if let Some(file) = &mut self.pgn {
+            let pgn = export_pgn(record, number, [white, black], settings.time_control);
+            writeln!(file, "{pgn}").map_err(|error| format!("unable to write pgn: {error}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Play games on one thread with its own pair of engines until the match is over
+fn run_worker(
+    settings: &Settings,
+    openings: &[Opening],
+    games: u32,
+    sprt: Option<Sprt>,
+    progress: &Mutex<Progress>,
+) -> Result<(), String> {
+    let start = |command: &str| UciEngine::start(command, &settings.options);
+    let mut engines = [start(&settings.commands[0])?, start(&settings.commands[1])?];
+
+    loop {
+        let number = {
+            let mut progress = progress.lock().expect("match progress poisoned");
+            if progress.decided.is_some() || progress.started >= games {
+                return Ok(());
+            }
+            progress.started += 1;
+            progress.started
+        };
+        // Each opening is played twice, with the engines swapping colors
+        let opening = &openings[((number - 1) / 2) as usize % openings.len()];
+        let first_plays_white = number % 2 == 1;
+
+        let [first, second] = &mut engines;
+        let record = if first_plays_white {
+            play_game(first, second, opening, settings)
+        } else {
+            play_game(second, first, opening, settings)
+        };
+        let names = [engines[0].name(), engines[1].name()];
+        progress.lock().expect("match progress poisoned").record(
+            number,
+            &record,
+            names,
+            first_plays_white,
+            settings,
+            sprt,
+        )?;
+
+        for (engine, command) in engines.iter_mut().zip(&settings.commands) {
+            if !engine.is_running() {
+                *engine = start(command)?;
+            }
+        }
+    }
+}
+
+/// Parse an engine option given as `NAME=VALUE`
+fn parse_option(written: &str) -> Result<(String, String), String> {
+    written
+        .split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("invalid engine option `{written}`, expected `NAME=VALUE`"))
+}
+
+/// Play a match between two UCI engines, printing the score, Elo difference and SPRT state after each game
+///
+/// Each opening is played twice with the engines swapping colors. Games are refereed by [`Game`], and can be
+/// adjudicated by resignation, agreed draws or a tablebase.
+///
+/// # Errors
+/// If the arguments are invalid, the openings cannot be loaded, or an engine fails to start
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut commands = Vec::new();
+    let mut openings = None;
+    let mut games = None;
+    let mut concurrency = 1;
+    let mut time_control = DEFAULT_TIME_CONTROL;
+    let mut time_margin = DEFAULT_TIME_MARGIN;
+    let mut pgn = None;
+    let mut options = Vec::new();
+    let mut hypotheses = None;
+    let mut alpha = DEFAULT_SPRT_ERROR;
+    let mut beta = DEFAULT_SPRT_ERROR;
+    let mut adjudication = Adjudication::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--openings" => openings = Some(parse_flag::<String>(&arg, args.next())?),
+            "--games" => games = Some(parse_flag::<u32>(&arg, args.next())?),
+            "--concurrency" => concurrency = parse_flag::<usize>(&arg, args.next())?.max(1),
+            "--tc" => time_control = TimeControl::parse(&parse_flag::<String>(&arg, args.next())?)?,
+            "--movetime" => {
+                time_control =
+                    TimeControl::MoveTime(Duration::from_millis(parse_flag(&arg, args.next())?));
+            }
+            "--timemargin" => time_margin = parse_flag(&arg, args.next())?,
+            "--pgn" => pgn = Some(parse_flag::<String>(&arg, args.next())?),
+            "--option" => options.push(parse_option(&parse_flag::<String>(&arg, args.next())?)?),
+            "--sprt" => {
+                hypotheses = Some((
+                    parse_flag::<f64>(&arg, args.next())?,
+                    parse_flag::<f64>(&arg, args.next())?,
+                ));
+            }
+            "--alpha" => alpha = parse_flag(&arg, args.next())?,
+            "--beta" => beta = parse_flag(&arg, args.next())?,
+            "--resign" => {
+                adjudication.resign = Some((
+                    parse_flag(&arg, args.next())?,
+                    parse_flag(&arg, args.next())?,
+                ));
+            }
+            "--draw" => {
+                adjudication.draw = Some((
+                    parse_flag(&arg, args.next())?,
+                    parse_flag(&arg, args.next())?,
+                    parse_flag(&arg, args.next())?,
+                ));
+            }
+            "--syzygy" => {
+                let directories = parse_flag::<String>(&arg, args.next())?;
+                let tablebase = SyzygyTablebase::open(&directories).map_err(|error| {
+                    format!("unable to open tablebase `{directories}`: {error}")
+                })?;
+                adjudication.tablebase = Some(tablebase);
+            }
+            _ if commands.len() < 2 => commands.push(arg),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    let [first, second]: [String; 2] = commands
+        .try_into()
+        .map_err(|_| "expected two engine commands")?;
+
+    let openings = match openings {
+        Some(path) => load_openings(&path)?,
+        None => vec![Opening {
+            fen: STARTPOS.to_string(),
+            moves: Vec::new(),
+        }],
+    };
+    let sprt = hypotheses.map(|(elo0, elo1)| Sprt::new(elo0, elo1, alpha, beta));
+    // An SPRT plays until it's decided unless the games are limited
+    let games = games.unwrap_or(if sprt.is_some() {
+        u32::MAX
+    } else {
+        DEFAULT_GAMES
+    });
+    let pgn = pgn
+        .map(|path| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|error| format!("unable to open `{path}`: {error}"))
+        })
+        .transpose()?;
+    let settings = Settings {
+        commands: [first, second],
+        options,
+        time_control,
+        time_margin: Duration::from_millis(time_margin),
+        adjudication,
+    };
+    let progress = Mutex::new(Progress {
+        started: 0,
+        score: MatchScore::default(),
+        decided: None,
+        pgn,
+    });
+
+    thread::scope(|scope| {
+        let workers = (0..concurrency)
+            .map(|_| scope.spawn(|| run_worker(&settings, &openings, games, sprt, &progress)))
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .try_for_each(|worker| worker.join().expect("match worker panicked"))
+    })?;
+
+    let progress = progress.into_inner().expect("match progress poisoned");
+    match progress.decided {
+        Some(SprtDecision::AcceptH0) => println!("SPRT: H0 accepted"),
+        Some(SprtDecision::AcceptH1) => println!("SPRT: H1 accepted"),
+        None => println!("Finished match"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use crate::parse_flag;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use thermite_core::chess_move::ChessMove;
+use thermite_core::evaluation::Score;
+use thermite_core::position::{EpdRecord, LegalPosition};
+use thermite_core::search::{Search, SearchLimits, DEFAULT_HASH_MEGABYTES};
+use thermite_core::tablebase::SyzygyTablebase;
+
+/// The default time (in milliseconds) to search each position for
+const DEFAULT_MOVETIME: u64 = 1000;
+
+/// Load every record from a file with its line number, skipping blank lines and `#` comments
+fn load_records(path: &str) -> Result<Vec<(usize, EpdRecord)>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|error| format!("unable to read `{path}`: {error}"))?;
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            line.parse::<EpdRecord>()
+                .map(|record| (index + 1, record))
+                .map_err(|error| format!("{path}:{}: {error:?}", index + 1))
+        })
+        .collect()
+}
+
+/// Resolve the moves of a `bm` or `am` operation, written in SAN (or, by some suites, in UCI notation)
+fn resolve_moves(position: &LegalPosition, moves: &[String]) -> Result<Vec<ChessMove>, String> {
+    moves
+        .iter()
+        .map(|written| {
+            position.parse_san(written).or_else(|error| {
+                position
+                    .parse_uci(written, false)
+                    .or_else(|| position.parse_uci(written, true))
+                    .ok_or_else(|| format!("invalid move `{written}`: {error:?}"))
+            })
+        })
+        .collect()
+}
+
+/// Check a record's perft node counts, getting whether every count matched
+fn run_perft(name: &str, position: &mut LegalPosition, record: &EpdRecord) -> bool {
+    record.perft_nodes().all(|(depth, expected)| {
+        let nodes = position.perft(depth);
+        let matches = nodes == expected;
+        println!(
+            "{name}: D{depth} {nodes} {}",
+            if matches {
+                "ok".to_string()
+            } else {
+                format!("failed (expected {expected})")
+            }
+        );
+
+        matches
+    })
+}
+
+/// Search a record's position, getting whether the move played satisfies its `bm`, `am` and `dm` operations
+fn run_search(
+    name: &str,
+    search: &mut Search,
+    position: &mut LegalPosition,
+    record: &EpdRecord,
+    limits: SearchLimits,
+) -> Result<bool, String> {
+    let best_moves = resolve_moves(position, record.best_moves())?;
+    let avoid_moves = resolve_moves(position, record.avoid_moves())?;
+
+    let result = search.search(position, &[], limits);
+    let mates = record.direct_mate().is_none_or(|moves| {
+        matches!(result.score, Score::Mating(plies) if u16::from(u8::from(plies)) < 2 * u16::from(moves))
+    });
+    let solved = result.best_move.is_some_and(|best_move| {
+        (best_moves.is_empty() || best_moves.contains(&best_move))
+            && !avoid_moves.contains(&best_move)
+    }) && mates;
+
+    let played = result.best_move.map_or_else(
+        || "(none)".to_string(),
+        |best_move| position.to_san(best_move),
+    );
+    let mut expected = Vec::new();
+    if !record.best_moves().is_empty() {
+        expected.push(format!("bm {}", record.best_moves().join(" ")));
+    }
+    if !record.avoid_moves().is_empty() {
+        expected.push(format!("am {}", record.avoid_moves().join(" ")));
+    }
+    if let Some(moves) = record.direct_mate() {
+        expected.push(format!("dm {moves}"));
+    }
+    println!(
+        "{name}: {} (played {played}, score {:?}, depth {}, expected {})",
+        if solved { "solved" } else { "failed" },
+        result.score,
+        result.depth,
+        expected.join(", ")
+    );
+
+    Ok(solved)
+}
+
+/// Run a suite of EPD records, searching positions with `bm`, `am` or `dm` operations and checking the perft of those
+/// with `D1`..`D6` node counts, then print how many were solved
+///
+/// # Errors
+/// If the arguments are invalid, or the suite cannot be read or contains an invalid record
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut path = None;
+    let mut movetime = DEFAULT_MOVETIME;
+    let mut hash = DEFAULT_HASH_MEGABYTES;
+    let mut tablebase = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--movetime" => movetime = parse_flag(&arg, args.next())?,
+            "--hash" => hash = parse_flag(&arg, args.next())?,
+            "--syzygy" => tablebase = Some(parse_flag::<String>(&arg, args.next())?),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    let path = path.ok_or("missing EPD file")?;
+
+    let mut search = Search::new(hash);
+    if let Some(directories) = tablebase {
+        let tablebase = SyzygyTablebase::open(&directories)
+            .map_err(|error| format!("unable to open tablebase `{directories}`: {error}"))?;
+        search.set_tablebase(Some(Arc::new(tablebase)));
+    }
+    let limits = SearchLimits {
+        movetime: Some(Duration::from_millis(movetime)),
+        ..SearchLimits::default()
+    };
+
+    let records = load_records(&path)?;
+    let (mut solved, mut failed, mut skipped) = (0, 0, 0);
+    for (line, record) in records {
+        let name = record
+            .id()
+            .map_or_else(|| format!("{path}:{line}"), ToString::to_string);
+        let mut position = LegalPosition::try_from(record.position())
+            .map_err(|error| format!("{path}:{line}: illegal position {error:?}"))?;
+
+        let passed = if record.perft_nodes().next().is_some() {
+            run_perft(&name, &mut position, &record)
+        } else if !record.best_moves().is_empty()
+            || !record.avoid_moves().is_empty()
+            || record.direct_mate().is_some()
+        {
+            search.clear();
+            run_search(&name, &mut search, &mut position, &record, limits.clone())
+                .map_err(|error| format!("{path}:{line}: {error}"))?
+        } else {
+            println!("{name}: skipped (no bm, am, dm or perft opcodes)");
+            skipped += 1;
+            continue;
+        };
+
+        if passed {
+            solved += 1;
+        } else {
+            failed += 1;
+        }
+    }
+
+    println!(
+        "solved {solved}/{}, failed {failed}, skipped {skipped}",
+        solved + failed
+    );
+
+    Ok(())
+}
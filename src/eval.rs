@@ -0,0 +1,39 @@
+#[cfg(feature = "nnue")]
+use crate::load_network;
+use crate::{parse_position, STARTPOS};
+
+/// Print the board and [evaluation trace](thermite_core::evaluation::EvaluationTrace) table for a FEN, or the starting position if none is given.
+/// With the `nnue` feature, `--nnue <file>` also prints the evaluation of a network
+///
+/// # Errors
+/// If the FEN cannot be parsed or is not a legal position
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+    #[cfg(feature = "nnue")]
+    let (network, args) = {
+        let mut args = args.peekable();
+        let network = if args.next_if(|arg| arg == "--nnue").is_some() {
+            let path = args.next().ok_or("missing value for `--nnue`")?;
+            Some(load_network(&path)?)
+        } else {
+            None
+        };
+        (network, args)
+    };
+    let fen = args.collect::<Vec<_>>().join(" ");
+    let fen = if fen.is_empty() { STARTPOS } else { &fen };
+    let position = parse_position(fen)?;
+
+    println!("{position}");
+    println!("{}", position.evaluate_with_trace());
+    #[cfg(feature = "nnue")]
+    if let Some(network) = network {
+        let mut position = position;
+        position.set_network(network);
+        println!(
+            "NNUE evaluation: {:+.2} (side to move)",
+            *position.evaluate()
+        );
+    }
+
+    Ok(())
+}
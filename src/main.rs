@@ -1,4 +1,124 @@
+//! Thermite CLI engine driver
+mod bench;
+mod datagen;
+mod engine_match;
+mod epd;
+mod eval;
+mod play;
+mod tune;
+mod uci;
+mod uci_client;
+mod xboard;
+
+use std::env;
+use std::process::ExitCode;
+#[cfg(feature = "nnue")]
+use std::sync::Arc;
+#[cfg(feature = "nnue")]
+use thermite_core::nnue::Network;
+use thermite_core::position::{LegalPosition, PositionBuilder};
+
+/// The standard chess starting position
+pub const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Parse the value following a command line flag
+///
+/// # Errors
+/// If the value is missing or cannot be parsed
+pub fn parse_flag<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing value for `{flag}`"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for `{flag}`"))
+}
+
+/// Parse a FEN into a legal position
+///
+/// # Errors
+/// If the FEN cannot be parsed or is not a legal position
+pub fn parse_position(fen: &str) -> Result<LegalPosition, String> {
+    let builder = fen
+        .parse::<PositionBuilder>()
+        .map_err(|error| format!("invalid fen `{fen}`: {error:?}"))?;
+
+    LegalPosition::try_from(builder).map_err(|error| format!("illegal position `{fen}`: {error:?}"))
+}
+
+/// Load an NNUE network file
+///
+/// # Errors
+/// If the file cannot be read or is not a valid network
+#[cfg(feature = "nnue")]
+pub fn load_network(path: &str) -> Result<Arc<Network>, String> {
+    let bytes = std::fs::read(path).map_err(|error| format!("unable to read `{path}`: {error}"))?;
+    let network = Network::from_bytes(&bytes)
+        .map_err(|error| format!("invalid network `{path}`: {error:?}"))?;
+
+    Ok(Arc::new(network))
+}
+
+/// Print the supported sub-commands
+fn print_usage() {
+    eprintln!("usage: thermite <command> [args]");
+    eprintln!();
+    eprintln!("commands:");
+    eprintln!(
+        "  bench [--nnue FILE] [depth] [threads] [hash]\n                search the built-in positions to a fixed depth (default 9) and print the total nodes and speed"
+    );
+    eprintln!(
+        "  datagen --output FILE [--games N] [--threads N] [--nodes N] [--random-plies N] [--seed N] [--text] [--nnue FILE]\n                \
+         play fixed-node self-play games from random openings, writing quiet positions with their scores and results"
+    );
+    eprintln!(
+        "  eval [--nnue FILE] [fen]\n                print the board and static evaluation breakdown of a position (default startpos)"
+    );
+    eprintln!(
+        "  epd <file> [--movetime MS] [--hash MB] [--syzygy DIRS]\n                run a test suite, searching `bm`/`am`/`dm` positions and checking `D1`..`D6` perft counts"
+    );
+    eprintln!(
+        "  match <engine> <engine> [--openings FILE] [--games N] [--tc SECONDS+INC | --movetime MS] [--concurrency N]\n        \
+         [--pgn FILE] [--option NAME=VALUE] [--sprt ELO0 ELO1] [--alpha A] [--beta B] [--resign CP MOVES]\n        \
+         [--draw MOVENUMBER CP MOVES] [--syzygy DIRS] [--timemargin MS]\n                \
+         play two UCI engines against each other from EPD/PGN openings with paired colors"
+    );
+    eprintln!(
+        "  play [--color white|black] [--level 1-20] [--movetime MS] [--fen FEN] [--nnue FILE]\n                play a game against the engine on the terminal"
+    );
+    eprintln!(
+        "  uci\n                speak the Universal Chess Interface over stdin/stdout (the default without a command)"
+    );
+    eprintln!(
+        "  tune <file> [--epochs N] [--learning-rate X] [--output FILE]\n                tune the evaluation constants against labelled positions (`<fen> [result]` per line)"
+    );
+    eprintln!(
+        "  xboard\n                speak the Chess Engine Communication Protocol over stdin/stdout (also chosen by an `xboard` command)"
+    );
+}
+
 /// Thermite CLI engine driver
-pub fn main() {
-    todo!()
+pub fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+    let result = match args.next().as_deref() {
+        Some("bench") => bench::run(args),
+        Some("datagen") => datagen::run(args),
+        Some("epd") => epd::run(args),
+        Some("eval") => eval::run(args),
+        Some("match") => engine_match::run(args),
+        Some("play") => play::run(args),
+        Some("tune") => tune::run(args),
+        Some("uci") | None => uci::run(args),
+        Some("xboard") => xboard::run(args),
+        _ => {
+            print_usage();
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,241 @@
+#[cfg(feature = "nnue")]
+use crate::load_network;
+use crate::parse_flag;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+use thermite_core::chess_move::ChessMove;
+use thermite_core::evaluation::Score;
+use thermite_core::game::{Game, GameOutcome};
+use thermite_core::node_count::NodeCount;
+use thermite_core::pieces::OwnedPiece;
+use thermite_core::player_color::PlayerColor;
+use thermite_core::ply_count::PlyCount;
+use thermite_core::search::{Search, SearchLimits, DEFAULT_HASH_MEGABYTES};
+use thermite_core::square::Square;
+
+/// The strongest level, the default
+const MAX_LEVEL: u8 = 20;
+/// The default longest time (in milliseconds) the engine thinks about each move
+const DEFAULT_MOVETIME: u64 = 1000;
+/// The commands understood between moves
+const HELP: &str = "enter a move in SAN (Nf3) or coordinates (g1f3), or one of:
+  moves      list the legal moves
+  undo       take back your last move
+  flip       turn the board around
+  level <N>  set the engine's strength from 1 to 20
+  help       show this help
+  quit       leave the game";
+
+/// Print the board as seen from a player's side, with rank and file labels
+fn print_board(game: &Game, perspective: PlayerColor) {
+    let position = game.position();
+    let (ranks, files): (Vec<u8>, Vec<u8>) = match perspective {
+        PlayerColor::White => ((0..8).rev().collect(), (0..8).collect()),
+        PlayerColor::Black => ((0..8).collect(), (0..8).rev().collect()),
+    };
+
+    println!();
+    for &rank in &ranks {
+        let squares = files
+            .iter()
+            .map(|&file| {
+                Square::try_from(rank * 8 + file)
+                    .ok()
+                    .and_then(|square| position.owned_piece_on(square))
+                    .map_or('·', OwnedPiece::unicode_char)
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("{} {squares}", rank + 1);
+    }
+    let labels = files
+        .iter()
+        .map(|&file| char::from(b'a' + file).to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    println!("  {labels}");
+    println!();
+}
+
+/// Describe a score from the engine's point of view, in pawns or moves to mate
+fn describe_score(score: Score) -> String {
+    match score {
+        Score::Stalemate => "0.00".to_string(),
+        Score::Approximate(evaluation) => {
+            format!("{:+.2}", f64::from(evaluation.centipawns()) / 100.0)
+        }
+        Score::Mating(plies) => format!("mate in {}", u8::from(plies).div_ceil(2)),
+        Score::Mated(plies) => format!("mated in {}", u8::from(plies) / 2),
+    }
+}
+
+/// Announce how a game ended
+fn announce(outcome: GameOutcome) {
+    let reason = match outcome {
+        GameOutcome::Checkmate(PlayerColor::White) => "checkmate, white wins",
+        GameOutcome::Checkmate(PlayerColor::Black) => "checkmate, black wins",
+        GameOutcome::Stalemate => "stalemate",
+        GameOutcome::FiftyMoveRule => "draw by the fifty-move rule",
+        GameOutcome::ThreefoldRepetition => "draw by threefold repetition",
+        GameOutcome::InsufficientMaterial => "draw by insufficient material",
+    };
+
+    println!("game over: {reason} ({})", outcome.result());
+}
+
+/// The limits the engine searches each move with at a level, a ply deeper and up to twice as many nodes per level
+fn level_limits(level: u8, movetime: Duration) -> SearchLimits {
+    SearchLimits {
+        depth: Some(PlyCount::new(level)),
+        movetime: Some(movetime),
+        nodes: (level < MAX_LEVEL).then(|| NodeCount::new(1 << (level + 6))),
+        ..SearchLimits::default()
+    }
+}
+
+/// Parse a move written by the user, in SAN or coordinates
+fn parse_move(game: &Game, written: &str) -> Option<ChessMove> {
+    let position = game.position();
+
+    position
+        .parse_san(written)
+        .ok()
+        .or_else(|| position.parse_uci(written, false))
+}
+
+/// Take back the user's last move, along with the engine's reply to it, getting whether there was one to take back
+fn undo(game: &mut Game, player: PlayerColor) -> bool {
+    let mut taken_back = Vec::new();
+    while let Some(chess_move) = game.pop() {
+        taken_back.push(chess_move);
+        if game.position().player_to_move() == player {
+            return true;
+        }
+    }
+    // The user hasn't moved yet, only the engine has
+    for chess_move in taken_back.into_iter().rev() {
+        game.push_legal(chess_move);
+    }
+
+    false
+}
+
+/// Search for the engine's move and play it
+fn play_engine_move(game: &mut Game, search: &mut Search, limits: SearchLimits) {
+    let result = search.search(game.position(), game.history(), limits);
+    let Some(best_move) = result.best_move else {
+        return;
+    };
+    let san = game.position().clone().to_san(best_move);
+    println!("thermite plays {san} ({})", describe_score(result.score));
+    game.push_legal(best_move);
+}
+
+/// Play a game against the engine on the terminal, showing the board after each move
+///
+/// # Errors
+/// If the arguments are invalid, the FEN isn't a legal position, or stdin cannot be read
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut player = PlayerColor::White;
+    let mut level = MAX_LEVEL;
+    let mut movetime = DEFAULT_MOVETIME;
+    let mut fen = None;
+    #[cfg(feature = "nnue")]
+    let mut network = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--color" => {
+                player = match parse_flag::<String>(&arg, args.next())?.as_str() {
+                    "white" => PlayerColor::White,
+                    "black" => PlayerColor::Black,
+                    color => return Err(format!("invalid value `{color}` for `--color`")),
+                };
+            }
+            "--level" => level = parse_flag::<u8>(&arg, args.next())?.clamp(1, MAX_LEVEL),
+            "--movetime" => movetime = parse_flag(&arg, args.next())?,
+            "--fen" => fen = Some(parse_flag::<String>(&arg, args.next())?),
+            #[cfg(feature = "nnue")]
+            "--nnue" => network = Some(load_network(&parse_flag::<String>(&arg, args.next())?)?),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+
+    let mut game = fen.map_or_else(
+        || Ok(Game::default()),
+        |fen| Game::from_fen(&fen).map_err(|error| format!("invalid fen `{fen}`: {error:?}")),
+    )?;
+    let mut search = Search::new(DEFAULT_HASH_MEGABYTES);
+    #[cfg(feature = "nnue")]
+    search.set_network(network);
+    let mut perspective = player;
+    let mut lines = io::stdin().lock().lines();
+    println!("{HELP}");
+    print_board(&game, perspective);
+
+    loop {
+        if let Some(outcome) = game.outcome() {
+            announce(outcome);
+            return Ok(());
+        }
+        if game.position().player_to_move() != player {
+            let limits = level_limits(level, Duration::from_millis(movetime));
+            play_engine_move(&mut game, &mut search, limits);
+            print_board(&game, perspective);
+            continue;
+        }
+
+        print!("{}> ", game.fullmove_number());
+        io::stdout()
+            .flush()
+            .map_err(|error| format!("unable to write stdout: {error}"))?;
+        let Some(line) = lines.next() else {
+            return Ok(());
+        };
+        let line = line.map_err(|error| format!("unable to read stdin: {error}"))?;
+
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [] => {}
+            ["quit" | "exit"] => return Ok(()),
+            ["help"] => println!("{HELP}"),
+            ["moves"] => {
+                let mut position = game.position().clone();
+                let moves = position
+                    .generate_legal_moves()
+                    .into_iter()
+                    .map(|chess_move| position.to_san(chess_move))
+                    .collect::<Vec<_>>();
+                println!("{}", moves.join(" "));
+            }
+            ["undo"] => {
+                if undo(&mut game, player) {
+                    print_board(&game, perspective);
+                } else {
+                    println!("there's no move of yours to take back");
+                }
+            }
+            ["flip"] => {
+                perspective = perspective.switch();
+                print_board(&game, perspective);
+            }
+            ["level", value] => match value.parse::<u8>() {
+                Ok(value) => {
+                    level = value.clamp(1, MAX_LEVEL);
+                    println!("level {level}");
+                }
+                Err(_) => println!("invalid level `{value}`"),
+            },
+            [written] => match parse_move(&game, written) {
+                Some(chess_move) => {
+                    game.push_legal(chess_move);
+                    print_board(&game, perspective);
+                }
+                None => println!("illegal move `{written}`, enter `moves` to list the legal moves"),
+            },
+            _ => println!(
+                "unknown command `{}`, enter `help` for the commands",
+                line.trim()
+            ),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::parse_flag;
+use std::fs;
+use thermite_core::tuning::{LabelledPosition, Tuner};
+
+/// The default number of full passes over the positions
+const DEFAULT_EPOCHS: u32 = 1000;
+/// The default step size (in pawns) of each parameter update
+const DEFAULT_LEARNING_RATE: f32 = 0.01;
+
+/// Load every labelled position from a file, one per line, skipping blank lines and `#` comments
+fn load_positions(path: &str) -> Result<Vec<LabelledPosition>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|error| format!("unable to read `{path}`: {error}"))?;
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            line.parse::<LabelledPosition>()
+                .map_err(|error| format!("{path}:{}: {error:?}", index + 1))
+        })
+        .collect()
+}
+
+/// Tune the evaluation parameters against a file of labelled positions and print (or write) the new constants
+///
+/// # Errors
+/// If the arguments are invalid, or the positions file cannot be read or parsed
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut path = None;
+    let mut epochs = DEFAULT_EPOCHS;
+    let mut learning_rate = DEFAULT_LEARNING_RATE;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--epochs" => epochs = parse_flag(&arg, args.next())?,
+            "--learning-rate" => learning_rate = parse_flag(&arg, args.next())?,
+            "--output" => output = Some(parse_flag::<String>(&arg, args.next())?),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    let path = path.ok_or("missing labelled positions file")?;
+
+    let positions = load_positions(&path)?;
+    eprintln!("loaded {} positions", positions.len());
+    let mut tuner = Tuner::new(positions);
+    let scaling = tuner.fit_scaling();
+    eprintln!(
+        "scaling constant K = {scaling:.4}, initial error {:.6}",
+        tuner.error()
+    );
+    for epoch in 1..=epochs {
+        let error = tuner.epoch(learning_rate);
+        if epoch % 50 == 0 || epoch == epochs {
+            eprintln!("epoch {epoch}: error {error:.6}");
+        }
+    }
+
+    let constants = tuner.parameters().to_string();
+    match output {
+        Some(output) => fs::write(&output, constants + "\n")
+            .map_err(|error| format!("unable to write `{output}`: {error}"))?,
+        None => println!("{constants}"),
+    }
+
+    Ok(())
+}
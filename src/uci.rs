@@ -0,0 +1,403 @@
+#[cfg(feature = "nnue")]
+use crate::load_network;
+use crate::{xboard, STARTPOS};
+use std::io::{self, BufRead};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use thermite_core::evaluation::Score;
+use thermite_core::game::Game;
+use thermite_core::node_count::NodeCount;
+use thermite_core::player_color::PlayerColor;
+use thermite_core::ply_count::PlyCount;
+use thermite_core::search::{
+    PonderSignal, Search, SearchLimits, SearchResult, StopSignal, DEFAULT_HASH_MEGABYTES,
+    DEFAULT_MOVE_OVERHEAD_MILLISECONDS, MAX_MULTIPV, MAX_THREADS,
+};
+use thermite_core::tablebase::SyzygyTablebase;
+
+/// The largest transposition table (in megabytes) that can be set with the `Hash` option
+const MAX_HASH_MEGABYTES: usize = 65536;
+/// The largest time (in milliseconds) that can be set with the `Move Overhead` option
+const MAX_MOVE_OVERHEAD: u64 = 5000;
+/// The `check` options switching each pruning technique on or off, so what each gains can be measured
+const PRUNING_OPTIONS: [&str; 6] = [
+    "NullMovePruning",
+    "LateMoveReductions",
+    "ReverseFutilityPruning",
+    "FutilityPruning",
+    "Razoring",
+    "LateMovePruning",
+];
+
+/// Format a score as UCI's `cp <centipawns>` or `mate <moves>`, negative when the engine is being mated
+fn format_score(score: Score) -> String {
+    match score {
+        Score::Stalemate => "cp 0".to_string(),
+        Score::Approximate(evaluation) => format!("cp {}", evaluation.centipawns()),
+        Score::Mating(plies) => format!("mate {}", u8::from(plies).div_ceil(2)),
+        Score::Mated(plies) => format!("mate -{}", u8::from(plies) / 2),
+    }
+}
+
+/// Print the `info` line for a line of a completed iteration
+fn print_info(result: &SearchResult, chess960: bool) {
+    let millis = result.elapsed.as_millis();
+    let nodes = *result.nodes.as_ref();
+    let nps = u128::from(nodes) * 1000 / millis.max(1);
+    let principal_variation = result
+        .principal_variation
+        .iter()
+        .map(|chess_move| chess_move.to_uci(chess960))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    println!(
+        "info depth {} multipv {} score {} nodes {nodes} nps {nps} time {millis} pv {principal_variation}",
+        result.depth,
+        result.multipv,
+        format_score(result.score),
+    );
+}
+
+/// Print the `bestmove` line for the result of a search, along with the reply expected from its principal variation
+/// (to ponder on) when there is one
+fn print_best_move(result: &SearchResult, chess960: bool) {
+    let best_move = result.best_move.map_or_else(
+        || "0000".to_string(),
+        |best_move| best_move.to_uci(chess960),
+    );
+    match result.principal_variation.get(1) {
+        Some(reply) => println!("bestmove {best_move} ponder {}", reply.to_uci(chess960)),
+        None => println!("bestmove {best_move}"),
+    }
+}
+
+/// The state of the engine between commands
+struct Engine {
+    /// The search, while it isn't running
+    search: Option<Search>,
+    /// The thread running a search, returning the search once it finishes
+    running: Option<JoinHandle<Search>>,
+    stop_signal: StopSignal,
+    ponder_signal: PonderSignal,
+    /// The game since the position was set up, searched with the positions before the current one
+    game: Game,
+    chess960: bool,
+}
+
+impl Engine {
+    /// Create an engine at the starting position
+    fn new() -> Self {
+        let search = Search::new(DEFAULT_HASH_MEGABYTES);
+        let stop_signal = search.stop_signal();
+        let ponder_signal = search.ponder_signal();
+
+        Self {
+            search: Some(search),
+            running: None,
+            stop_signal,
+            ponder_signal,
+            game: Game::default(),
+            chess960: false,
+        }
+    }
+
+    /// Wait for any running search to finish, getting the idle search
+    fn search(&mut self) -> &mut Search {
+        if let Some(running) = self.running.take() {
+            self.search = Some(running.join().expect("search thread panicked"));
+        }
+
+        self.search.as_mut().expect("search is idle")
+    }
+
+    /// Stop any running search, waiting for it to print its best move
+    fn stop(&mut self) {
+        if self.running.is_some() {
+            self.stop_signal.stop();
+            self.search();
+        }
+    }
+
+    /// Handle a `setoption name <name> [value <value>]` command
+    fn set_option(&mut self, arguments: &str) -> Result<(), String> {
+        let arguments = arguments
+            .strip_prefix("name ")
+            .ok_or("missing option name")?;
+        let (name, value) = arguments
+            .split_once(" value ")
+            .map_or((arguments, ""), |(name, value)| (name, value));
+        let parse_spin = |value: &str, max: usize| {
+            value
+                .parse::<usize>()
+                .map(|value| value.clamp(1, max))
+                .map_err(|_| format!("invalid value `{value}` for `{name}`"))
+        };
+
+        match name.trim().to_ascii_lowercase().as_str() {
+            "hash" => {
+                let megabytes = parse_spin(value, MAX_HASH_MEGABYTES)?;
+                self.search().set_hash(megabytes);
+            }
+            "threads" => {
+                let threads = parse_spin(value, MAX_THREADS)?;
+                self.search().set_threads(threads);
+            }
+            "multipv" => {
+                let multipv = parse_spin(value, MAX_MULTIPV)?;
+                self.search().set_multipv(multipv);
+            }
+            "syzygypath" => {
+                let tablebase = if value.is_empty() || value == "<empty>" {
+                    None
+                } else {
+                    let tablebase = SyzygyTablebase::open(value)
+                        .map_err(|error| format!("unable to open tablebase `{value}`: {error}"))?;
+                    Some(Arc::new(tablebase))
+                };
+                self.search().set_tablebase(tablebase);
+            }
+            #[cfg(feature = "nnue")]
+            "evalfile" => {
+                let network = if value.is_empty() || value == "<empty>" {
+                    None
+                } else {
+                    Some(load_network(value)?)
+                };
+                self.search().set_network(network);
+            }
+            "move overhead" => {
+                let milliseconds = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid value `{value}` for `{name}`"))?;
+                self.search()
+                    .set_move_overhead(Duration::from_millis(milliseconds.min(MAX_MOVE_OVERHEAD)));
+            }
+            "uci_chess960" => self.chess960 = value.trim() == "true",
+            // Whether to ponder is up to the interface, which sends `go ponder`
+            "ponder" => {}
+            "clear hash" => self.search().clear(),
+            option => self.set_pruning_option(option, value)?,
+        }
+
+        Ok(())
+    }
+
+    /// Handle a `position [startpos | fen <fen>] [moves <move>...]` command
+    fn set_position(&mut self, arguments: &str) -> Result<(), String> {
+        let (setup, moves) = arguments
+            .split_once("moves")
+            .map_or((arguments, ""), |(setup, moves)| (setup, moves));
+        let fen = match setup.trim() {
+            "startpos" => STARTPOS,
+            setup => setup
+                .strip_prefix("fen")
+                .ok_or_else(|| format!("invalid position `{setup}`"))?
+                .trim(),
+        };
+        let mut game =
+            Game::from_fen(fen).map_err(|error| format!("invalid position `{fen}`: {error:?}"))?;
+
+        for written in moves.split_whitespace() {
+            game.position()
+                .parse_uci(written, self.chess960)
+                .and_then(|chess_move| game.push(chess_move).ok())
+                .ok_or_else(|| format!("illegal move `{written}`"))?;
+        }
+        self.game = game;
+
+        Ok(())
+    }
+
+    /// Handle setting one of the [pruning options](PRUNING_OPTIONS)
+    fn set_pruning_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let enabled = match value.trim() {
+            "true" => true,
+            "false" => false,
+            _ => return Err(format!("invalid value `{value}` for `{name}`")),
+        };
+        let mut pruning = self.search().pruning();
+        match name {
+            "nullmovepruning" => pruning.null_move = enabled,
+            "latemovereductions" => pruning.late_move_reductions = enabled,
+            "reversefutilitypruning" => pruning.reverse_futility = enabled,
+            "futilitypruning" => pruning.futility = enabled,
+            "razoring" => pruning.razoring = enabled,
+            "latemovepruning" => pruning.late_move_pruning = enabled,
+            _ => return Err(format!("unknown option `{name}`")),
+        }
+        self.search().set_pruning(pruning);
+
+        Ok(())
+    }
+
+    /// Handle a `go` command with any of `searchmoves`, `depth`, `nodes`, `mate`, `movetime`, `wtime`, `btime`,
+    /// `winc`, `binc`, `movestogo`, `infinite` and `ponder`, starting a search on another thread
+    fn go(&mut self, arguments: &str) -> Result<(), String> {
+        let mut limits = SearchLimits::default();
+        let mut infinite = false;
+        let mut ponder = false;
+        let mut arguments = arguments.split_whitespace().peekable();
+        while let Some(argument) = arguments.next() {
+            let mut value = || {
+                let value = arguments
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{argument}`"))?;
+                value
+                    .parse::<i64>()
+                    .map_err(|_| format!("invalid value `{value}` for `{argument}`"))
+            };
+            // Some interfaces send negative times once a player has overstepped, treat them as none left
+            let mut milliseconds =
+                || value().map(|value| Duration::from_millis(u64::try_from(value).unwrap_or(0)));
+            match argument {
+                "depth" => {
+                    let depth = u8::try_from(value()?.max(1)).unwrap_or(u8::MAX);
+                    limits.depth = Some(PlyCount::new(depth));
+                }
+                "nodes" => {
+                    limits.nodes = Some(NodeCount::new(u64::try_from(value()?).unwrap_or(0)))
+                }
+                "mate" => limits.mate = Some(u8::try_from(value()?.max(1)).unwrap_or(u8::MAX)),
+                "searchmoves" => {
+                    // The moves run until the next argument, which isn't a move
+                    while let Some(written) = arguments.next_if(|written| {
+                        self.game
+                            .position()
+                            .parse_uci(written, self.chess960)
+                            .is_some()
+                    }) {
+                        limits
+                            .search_moves
+                            .extend(self.game.position().parse_uci(written, self.chess960));
+                    }
+                }
+                "movetime" => limits.movetime = Some(milliseconds()?),
+                "wtime" => limits.time_left[PlayerColor::White] = Some(milliseconds()?),
+                "btime" => limits.time_left[PlayerColor::Black] = Some(milliseconds()?),
+                "winc" => limits.increment[PlayerColor::White] = milliseconds()?,
+                "binc" => limits.increment[PlayerColor::Black] = milliseconds()?,
+                "movestogo" => limits.moves_to_go = u32::try_from(value()?).ok(),
+                "infinite" => infinite = true,
+                "ponder" => ponder = true,
+                _ => {}
+            }
+        }
+
+        let mut search = self.search.take().ok_or("search is already running")?;
+        let stop_signal = self.stop_signal.clone();
+        let ponder_signal = self.ponder_signal.clone();
+        let position = self.game.position().clone();
+        let history = self.game.history().to_vec();
+        let chess960 = self.chess960;
+        stop_signal.reset();
+        if ponder {
+            ponder_signal.start();
+        } else {
+            ponder_signal.hit();
+        }
+        self.running = Some(thread::spawn(move || {
+            let result = search.search_with_progress(&position, &history, limits, |result| {
+                print_info(result, chess960);
+            });
+            // An infinite search (or one still pondering) only reports its best move once it has been told to stop
+            while (infinite || ponder_signal.is_pondering()) && !stop_signal.is_stopped() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            print_best_move(&result, chess960);
+
+            search
+        }));
+
+        Ok(())
+    }
+
+    /// Handle a single command, getting whether the engine should quit
+    fn handle(&mut self, line: &str) -> Result<bool, String> {
+        let (command, arguments) = line
+            .trim()
+            .split_once(' ')
+            .map_or((line.trim(), ""), |(command, arguments)| {
+                (command, arguments.trim())
+            });
+
+        match command {
+            "uci" => {
+                println!("id name Thermite {}", env!("CARGO_PKG_VERSION"));
+                println!("id author {}", env!("CARGO_PKG_AUTHORS"));
+                println!(
+                    "option name Hash type spin default {DEFAULT_HASH_MEGABYTES} min 1 max {MAX_HASH_MEGABYTES}"
+                );
+                println!("option name Threads type spin default 1 min 1 max {MAX_THREADS}");
+                println!("option name MultiPV type spin default 1 min 1 max {MAX_MULTIPV}");
+                println!(
+                    "option name Move Overhead type spin default {DEFAULT_MOVE_OVERHEAD_MILLISECONDS} min 0 max {MAX_MOVE_OVERHEAD}"
+                );
+                println!("option name SyzygyPath type string default <empty>");
+                #[cfg(feature = "nnue")]
+                println!("option name EvalFile type string default <empty>");
+                println!("option name Ponder type check default false");
+                println!("option name UCI_Chess960 type check default false");
+                println!("option name Clear Hash type button");
+                for option in PRUNING_OPTIONS {
+                    println!("option name {option} type check default true");
+                }
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "setoption" => {
+                self.stop();
+                self.set_option(arguments)?;
+            }
+            "ucinewgame" => {
+                self.stop();
+                self.search().clear();
+            }
+            "position" => self.set_position(arguments)?,
+            "go" => {
+                self.stop();
+                self.go(arguments)?;
+            }
+            "ponderhit" => self.ponder_signal.hit(),
+            "stop" => self.stop(),
+            "quit" => {
+                self.stop();
+                return Ok(true);
+            }
+            "" => {}
+            _ => return Err(format!("unknown command `{command}`")),
+        }
+
+        Ok(false)
+    }
+}
+
+/// Speak the Universal Chess Interface over stdin and stdout until `quit` (or the end of input)
+///
+/// Invalid commands are reported as `info string` lines rather than ending the session. Interfaces that open with
+/// `xboard` instead are handed over to the [CECP mode](xboard::run).
+///
+/// # Errors
+/// If stdin cannot be read
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut engine = Engine::new();
+    let mut lines = io::stdin().lock().lines();
+    while let Some(line) = lines.next() {
+        let line = line.map_err(|error| format!("unable to read stdin: {error}"))?;
+        if line.trim() == "xboard" {
+            // The CECP mode reads stdin from its own thread
+            drop(lines);
+            engine.stop();
+            return xboard::run(args);
+        }
+        match engine.handle(&line) {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            Err(error) => println!("info string error: {error}"),
+        }
+    }
+    engine.stop();
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use thermite_core::game::Game;
+
+/// How long an engine has to answer `uci` and `isready`
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long an engine has to exit after being told to quit before it's killed
+const QUIT_TIMEOUT: Duration = Duration::from_secs(1);
+/// The centipawn score reported for a forced mate, less the moves it takes
+const MATE_CENTIPAWNS: i32 = 100_000;
+
+/// Why an engine failed to answer
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EngineError {
+    /// The engine didn't answer before its deadline
+    TimedOut,
+    /// The engine exited, or its input or output was closed
+    Disconnected,
+}
+
+impl Display for EngineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::TimedOut => "timed out",
+            Self::Disconnected => "disconnected",
+        })
+    }
+}
+
+/// The move an engine chose, as written in its `bestmove` line
+pub struct EngineMove {
+    /// The move in UCI notation, not yet checked to be legal
+    pub best_move: String,
+    /// The last score the engine reported, in centipawns from its own point of view
+    pub score: Option<i32>,
+    /// How long the engine took to answer
+    pub elapsed: Duration,
+}
+
+/// Parse the score of an `info` line in centipawns, with mates as a large score less the moves to mate
+fn parse_score(line: &str) -> Option<i32> {
+    let mut tokens = line
+        .split_whitespace()
+        .skip_while(|&token| token != "score");
+    tokens.next()?;
+    let kind = tokens.next()?;
+    let value = tokens.next()?.parse::<i32>().ok()?;
+
+    match kind {
+        "cp" => Some(value),
+        "mate" if value > 0 => Some(MATE_CENTIPAWNS - value),
+        "mate" => Some(-MATE_CENTIPAWNS - value),
+        _ => None,
+    }
+}
+
+/// A UCI engine running as a child process, its output read on its own thread
+pub struct UciEngine {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl UciEngine {
+    /// Start an engine, wait for it to finish its `uci` handshake and set its options
+    ///
+    /// # Errors
+    /// If the engine cannot be started or doesn't answer the handshake
+    pub fn start(command: &str, options: &[(String, String)]) -> Result<Self, String> {
+        let mut child = Command::new(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("unable to start `{command}`: {error}"))?;
+        let stdin = child.stdin.take().ok_or("engine stdin unavailable")?;
+        let stdout = child.stdout.take().ok_or("engine stdout unavailable")?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Self {
+            name: command.to_string(),
+            child,
+            stdin,
+            lines,
+        };
+        let handshake = |error: EngineError| format!("`{command}` {error} during the handshake");
+        engine.send("uci").map_err(handshake)?;
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        loop {
+            let line = engine.receive(deadline).map_err(handshake)?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().to_string();
+            } else if line.trim() == "uciok" {
+                break;
+            }
+        }
+        for (name, value) in options {
+            engine
+                .send(&format!("setoption name {name} value {value}"))
+                .map_err(handshake)?;
+        }
+        engine.ready().map_err(handshake)?;
+
+        Ok(engine)
+    }
+
+    /// The name the engine gave itself, or its command if it didn't
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the engine's process hasn't exited
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Send a single command
+    fn send(&mut self, command: &str) -> Result<(), EngineError> {
+        writeln!(self.stdin, "{command}")
+            .and_then(|()| self.stdin.flush())
+            .map_err(|_| EngineError::Disconnected)
+    }
+
+    /// Wait for the next line of output until a deadline
+    fn receive(&self, deadline: Instant) -> Result<String, EngineError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.lines
+            .recv_timeout(timeout)
+            .map_err(|error| match error {
+                RecvTimeoutError::Timeout => EngineError::TimedOut,
+                RecvTimeoutError::Disconnected => EngineError::Disconnected,
+            })
+    }
+
+    /// Wait for the engine to answer `isready`, discarding anything it wrote before
+    fn ready(&mut self) -> Result<(), EngineError> {
+        self.send("isready")?;
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        while self.receive(deadline)?.trim() != "readyok" {}
+
+        Ok(())
+    }
+
+    /// Tell the engine a new game is starting and wait for it to be ready
+    ///
+    /// # Errors
+    /// If the engine doesn't answer in time or has exited
+    pub fn new_game(&mut self) -> Result<(), EngineError> {
+        self.send("ucinewgame")?;
+        self.ready()
+    }
+
+    /// Send the position of a game with a `go` command, and wait up to a timeout for the move the engine chooses
+    ///
+    /// An engine that times out is told to stop, and the move it then sends is discarded.
+    ///
+    /// # Errors
+    /// If the engine doesn't answer before the timeout or has exited
+    pub fn go(
+        &mut self,
+        game: &Game,
+        go: &str,
+        timeout: Duration,
+    ) -> Result<EngineMove, EngineError> {
+        let moves = game
+            .moves()
+            .map(|chess_move| chess_move.to_uci(false))
+            .collect::<Vec<_>>();
+        let position = if moves.is_empty() {
+            format!("position fen {}", game.starting_fen())
+        } else {
+            format!(
+                "position fen {} moves {}",
+                game.starting_fen(),
+                moves.join(" ")
+            )
+        };
+        self.send(&position)?;
+        self.send(go)?;
+
+        let start = Instant::now();
+        let deadline = start + timeout;
+        let mut score = None;
+        loop {
+            let line = match self.receive(deadline) {
+                Ok(line) => line,
+                Err(EngineError::TimedOut) => {
+                    self.stop();
+                    return Err(EngineError::TimedOut);
+                }
+                Err(error) => return Err(error),
+            };
+            let elapsed = start.elapsed();
+            if line.starts_with("info ") {
+                score = parse_score(&line).or(score);
+            } else if let Some(rest) = line.strip_prefix("bestmove") {
+                let best_move = rest.split_whitespace().next().unwrap_or_default();
+
+                return Ok(EngineMove {
+                    best_move: best_move.to_string(),
+                    score,
+                    elapsed,
+                });
+            }
+        }
+    }
+
+    /// Stop a search that ran too long, discarding its `bestmove` so it isn't taken as the answer to the next one
+    fn stop(&mut self) {
+        if self.send("stop").is_err() {
+            return;
+        }
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        while let Ok(line) = self.receive(deadline) {
+            if line.starts_with("bestmove") {
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + QUIT_TIMEOUT;
+        while Instant::now() < deadline {
+            if matches!(self.child.try_wait(), Ok(Some(_))) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
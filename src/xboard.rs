@@ -0,0 +1,456 @@
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use thermite_core::evaluation::Score;
+use thermite_core::game::{Game, GameOutcome};
+use thermite_core::player_color::PlayerColor;
+use thermite_core::ply_count::PlyCount;
+use thermite_core::search::{
+    Search, SearchLimits, SearchResult, StopSignal, DEFAULT_HASH_MEGABYTES, MAX_THREADS,
+};
+
+/// The score CECP reports a mate in a single move as, with each further move to mate adding one
+const MATE_SCORE: i64 = 100_000;
+
+/// Something the engine has to react to, either a command from the interface or a search finishing
+enum Event {
+    Command(String),
+    /// Stdin couldn't be read
+    Unreadable(String),
+    /// A search finished, with the generation it was started in
+    Finished(u64, SearchResult),
+}
+
+/// What a running search is for
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Thinking {
+    /// Choosing the engine's move, which is played once the search finishes
+    Move,
+    /// Analysing the position for the interface, without playing a move
+    Analysis,
+}
+
+/// The time control set with `level` and `st`
+#[derive(Copy, Clone, Default, Debug)]
+struct TimeControl {
+    /// How many moves have to be made each session, sudden death if `None`
+    moves_per_session: Option<u32>,
+    /// The time each player starts a game with, `None` without a `level`
+    base: Option<Duration>,
+    /// The time added to a player's clock after each of their moves
+    increment: Duration,
+    /// A fixed time to spend on every move
+    movetime: Option<Duration>,
+}
+
+/// Format a score as CECP's centipawns, where mating in `N` moves is `100000 + N` and being mated `-100000 - N`
+fn format_score(score: Score) -> i64 {
+    match score {
+        Score::Stalemate => 0,
+        Score::Approximate(evaluation) => i64::from(evaluation.centipawns()),
+        Score::Mating(plies) => MATE_SCORE + i64::from(u8::from(plies).div_ceil(2)),
+        Score::Mated(plies) => -MATE_SCORE - i64::from(u8::from(plies) / 2),
+    }
+}
+
+/// Print the thinking output (`<depth> <score> <centiseconds> <nodes> <pv>`) for a line of a completed iteration
+fn print_thinking(result: &SearchResult) {
+    let principal_variation = result
+        .principal_variation
+        .iter()
+        .map(|chess_move| chess_move.to_uci(false))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    println!(
+        "{} {} {} {} {principal_variation}",
+        result.depth,
+        format_score(result.score),
+        result.elapsed.as_millis() / 10,
+        result.nodes.as_ref(),
+    );
+}
+
+/// Print the result of a game that has ended, with CECP's comment explaining why
+fn print_outcome(outcome: GameOutcome) {
+    let reason = match outcome {
+        GameOutcome::Checkmate(PlayerColor::White) => "White mates",
+        GameOutcome::Checkmate(PlayerColor::Black) => "Black mates",
+        GameOutcome::Stalemate => "Stalemate",
+        GameOutcome::FiftyMoveRule => "Draw by fifty move rule",
+        GameOutcome::ThreefoldRepetition => "Draw by repetition",
+        GameOutcome::InsufficientMaterial => "Draw by insufficient material",
+    };
+
+    println!("{} {{{reason}}}", outcome.result());
+}
+
+/// Parse a `level <moves> <base> <increment>` command, where the base is in minutes (or `minutes:seconds`) and the
+/// increment in seconds
+fn parse_level(arguments: &str) -> Result<TimeControl, String> {
+    let invalid = || format!("invalid level `{arguments}`");
+    let [moves, base, increment] = arguments
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| invalid())?;
+    let moves = moves.parse::<u32>().map_err(|_| invalid())?;
+    let (minutes, seconds) = base.split_once(':').unwrap_or((base, "0"));
+    let minutes = minutes.parse::<u64>().map_err(|_| invalid())?;
+    let seconds = seconds.parse::<u64>().map_err(|_| invalid())?;
+    let increment = increment
+        .parse::<f64>()
+        .ok()
+        .and_then(|increment| Duration::try_from_secs_f64(increment).ok())
+        .ok_or_else(invalid)?;
+
+    Ok(TimeControl {
+        moves_per_session: (moves > 0).then_some(moves),
+        base: Some(Duration::from_secs(minutes * 60 + seconds)),
+        increment,
+        movetime: None,
+    })
+}
+
+/// Parse a time in centiseconds, as sent by `time` and `otim`, treating a negative time as none left
+fn parse_centiseconds(arguments: &str) -> Result<Duration, String> {
+    let centiseconds = arguments
+        .parse::<i64>()
+        .map_err(|_| format!("invalid time `{arguments}`"))?;
+
+    Ok(Duration::from_millis(
+        u64::try_from(centiseconds).unwrap_or(0) * 10,
+    ))
+}
+
+/// The state of the engine between events
+struct Engine {
+    /// The search, while it isn't running
+    search: Option<Search>,
+    /// The thread running a search, returning the search once it finishes
+    running: Option<JoinHandle<Search>>,
+    /// What the running search is for
+    thinking: Option<Thinking>,
+    /// Counts the searches that were stopped early, so the results they still send can be ignored
+    generation: u64,
+    stop_signal: StopSignal,
+    events: Sender<Event>,
+    /// The game since it started (or the board was set up)
+    game: Game,
+    /// The player the engine moves for, `None` in force mode
+    engine_player: Option<PlayerColor>,
+    analyzing: bool,
+    post: bool,
+    time_control: TimeControl,
+    /// The time left on the engine's clock, from `time`
+    engine_time: Option<Duration>,
+    /// The time left on the opponent's clock, from `otim`
+    opponent_time: Option<Duration>,
+    /// The deepest iteration to search, from `sd`
+    depth: Option<PlyCount>,
+}
+
+impl Engine {
+    /// Create an engine at the starting position, playing black, that sends its finished searches as events
+    fn new(events: Sender<Event>) -> Self {
+        let search = Search::new(DEFAULT_HASH_MEGABYTES);
+        let stop_signal = search.stop_signal();
+
+        Self {
+            search: Some(search),
+            running: None,
+            thinking: None,
+            generation: 0,
+            stop_signal,
+            events,
+            game: Game::default(),
+            engine_player: Some(PlayerColor::Black),
+            analyzing: false,
+            post: false,
+            time_control: TimeControl::default(),
+            engine_time: None,
+            opponent_time: None,
+            depth: None,
+        }
+    }
+
+    /// Stop any running search without acting on its result, getting the idle search
+    fn abort(&mut self) -> &mut Search {
+        if let Some(running) = self.running.take() {
+            self.stop_signal.stop();
+            self.search = Some(running.join().expect("search thread panicked"));
+            self.generation += 1;
+        }
+        self.thinking = None;
+
+        self.search.as_mut().expect("search is idle")
+    }
+
+    /// The limits for choosing the engine's move, from the time control, clocks and depth limit
+    fn limits(&self, player: PlayerColor) -> SearchLimits {
+        let mut limits = SearchLimits {
+            depth: self.depth,
+            movetime: self.time_control.movetime,
+            ..SearchLimits::default()
+        };
+        if limits.movetime.is_none() {
+            limits.time_left[player] = self.engine_time;
+            limits.time_left[player.switch()] = self.opponent_time;
+            limits.increment[player] = self.time_control.increment;
+            limits.increment[player.switch()] = self.time_control.increment;
+            // Each player has made half of the moves since the start
+            limits.moves_to_go = self.time_control.moves_per_session.map(|moves| {
+                let made = u32::try_from(self.game.ply() / 2).unwrap_or(u32::MAX);
+                moves - made % moves
+            });
+        }
+
+        limits
+    }
+
+    /// Start whatever the engine should be doing in the current position, analysing it, announcing the result of a
+    /// game that's over, or thinking about its move when it's the engine's turn
+    fn think(&mut self) {
+        let thinking = if self.analyzing {
+            Thinking::Analysis
+        } else if self.engine_player == Some(self.game.position().player_to_move()) {
+            if let Some(outcome) = self.game.outcome() {
+                print_outcome(outcome);
+                return;
+            }
+            Thinking::Move
+        } else {
+            return;
+        };
+        let limits = match thinking {
+            Thinking::Move => self.limits(self.game.position().player_to_move()),
+            Thinking::Analysis => SearchLimits::default(),
+        };
+
+        self.abort();
+        let mut search = self.search.take().expect("search is idle");
+        let position = self.game.position().clone();
+        let history = self.game.history().to_vec();
+        let events = self.events.clone();
+        let generation = self.generation;
+        let post = self.post || thinking == Thinking::Analysis;
+        self.stop_signal.reset();
+        self.thinking = Some(thinking);
+        self.running = Some(thread::spawn(move || {
+            let result = search.search_with_progress(&position, &history, limits, |result| {
+                if post {
+                    print_thinking(result);
+                }
+            });
+            // The engine has quit if nothing is listening any more
+            let _ = events.send(Event::Finished(generation, result));
+
+            search
+        }));
+    }
+
+    /// Analyse the position again after it changed, if the engine is analysing
+    ///
+    /// Unlike a move, changing the position this way never has the engine reply with a move of its own.
+    fn restart_analysis(&mut self) {
+        if self.analyzing {
+            self.think();
+        }
+    }
+
+    /// Handle a search finishing, playing its best move if it was choosing the engine's move
+    fn finish(&mut self, generation: u64, result: &SearchResult) {
+        // Results of searches that were stopped early are stale
+        if generation != self.generation {
+            return;
+        }
+        let thinking = self.thinking;
+        self.abort();
+
+        if thinking == Some(Thinking::Move) {
+            if let Some(best_move) = result.best_move {
+                println!("move {}", best_move.to_uci(false));
+                self.game.push_legal(best_move);
+                if let Some(outcome) = self.game.outcome() {
+                    print_outcome(outcome);
+                }
+            }
+        }
+    }
+
+    /// Handle a `usermove <move>` command, making the move and replying when it's the engine's turn
+    fn user_move(&mut self, written: &str) {
+        self.abort();
+        let Some(chess_move) = self.game.position().parse_uci(written, false) else {
+            println!("Illegal move: {written}");
+            return;
+        };
+        self.game.push_legal(chess_move);
+        self.think();
+    }
+
+    /// Handle a command setting the time control or clocks, `level`, `st`, `sd`, `time` or `otim`
+    fn set_time(&mut self, command: &str, arguments: &str) -> Result<(), String> {
+        match command {
+            "level" => {
+                self.time_control = parse_level(arguments)?;
+                self.engine_time = self.time_control.base;
+                self.opponent_time = self.time_control.base;
+            }
+            "st" => {
+                let seconds = arguments
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid time `{arguments}`"))?;
+                self.time_control.movetime = Some(Duration::from_secs(seconds));
+            }
+            "sd" => {
+                let depth = arguments
+                    .parse::<u8>()
+                    .map_err(|_| format!("invalid depth `{arguments}`"))?;
+                self.depth = Some(PlyCount::new(depth.max(1)));
+            }
+            "time" => self.engine_time = Some(parse_centiseconds(arguments)?),
+            "otim" => self.opponent_time = Some(parse_centiseconds(arguments)?),
+            _ => return Err(format!("unknown command `{command}`")),
+        }
+
+        Ok(())
+    }
+
+    /// Handle a `new` command, starting a new game with the engine playing black
+    fn new_game(&mut self) {
+        self.abort().clear();
+        self.game = Game::default();
+        self.engine_player = Some(PlayerColor::Black);
+        self.depth = None;
+        self.engine_time = self.time_control.base;
+        self.opponent_time = self.time_control.base;
+        self.think();
+    }
+
+    /// Handle a single command, getting whether the engine should quit
+    fn handle(&mut self, line: &str) -> Result<bool, String> {
+        let (command, arguments) = line
+            .trim()
+            .split_once(' ')
+            .map_or((line.trim(), ""), |(command, arguments)| {
+                (command, arguments.trim())
+            });
+
+        match command {
+            "protover" => {
+                println!(
+                    "feature myname=\"Thermite {}\" ping=1 setboard=1 usermove=1 analyze=1 colors=0 san=0 time=1 \
+                     draw=0 sigint=0 sigterm=0 reuse=1 playother=0 memory=1 smp=1 variants=\"normal\" done=1",
+                    env!("CARGO_PKG_VERSION")
+                );
+            }
+            "new" => self.new_game(),
+            "setboard" => {
+                let game = Game::from_fen(arguments)
+                    .map_err(|error| format!("invalid position `{arguments}`: {error:?}"))?;
+                self.abort();
+                self.game = game;
+                self.restart_analysis();
+            }
+            "usermove" => self.user_move(arguments),
+            "go" => {
+                self.engine_player = Some(self.game.position().player_to_move());
+                self.think();
+            }
+            "force" | "result" => {
+                self.abort();
+                self.engine_player = None;
+            }
+            "undo" | "remove" => {
+                self.abort();
+                for _ in 0..if command == "undo" { 1 } else { 2 } {
+                    self.game.pop();
+                }
+                self.restart_analysis();
+            }
+            "level" | "st" | "sd" | "time" | "otim" => self.set_time(command, arguments)?,
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "analyze" => {
+                self.analyzing = true;
+                self.think();
+            }
+            "exit" => {
+                self.abort();
+                self.analyzing = false;
+            }
+            "?" => {
+                if self.thinking == Some(Thinking::Move) {
+                    self.stop_signal.stop();
+                }
+            }
+            "ping" => println!("pong {arguments}"),
+            "memory" => {
+                let megabytes = arguments
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid memory `{arguments}`"))?;
+                self.abort().set_hash(megabytes.max(1));
+            }
+            "cores" => {
+                let threads = arguments
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid cores `{arguments}`"))?;
+                self.abort().set_threads(threads.clamp(1, MAX_THREADS));
+            }
+            "quit" => {
+                self.abort();
+                return Ok(true);
+            }
+            // Commands that need no reply, including those for features the engine doesn't claim
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer"
+            | "name" | "rating" | "ics" | "." | "" => {}
+            _ => return Err("unknown command".to_string()),
+        }
+
+        Ok(false)
+    }
+}
+
+/// Speak the Chess Engine Communication Protocol (`xboard`) over stdin and stdout until `quit` (or the end of input)
+///
+/// Commands are read on their own thread, so the engine can react to them while it's thinking. Invalid commands are
+/// reported as `Error` lines rather than ending the session.
+///
+/// # Errors
+/// If stdin cannot be read
+pub fn run(_args: impl Iterator<Item = String>) -> Result<(), String> {
+    let (events, received) = mpsc::channel();
+    let commands = events.clone();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let event = line.map_or_else(
+                |error| Event::Unreadable(format!("unable to read stdin: {error}")),
+                Event::Command,
+            );
+            if commands.send(event).is_err() {
+                return;
+            }
+        }
+        let _ = commands.send(Event::Command("quit".to_string()));
+    });
+
+    let mut engine = Engine::new(events);
+    for event in received {
+        match event {
+            Event::Command(line) => match engine.handle(&line) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(error) => println!("Error ({error}): {}", line.trim()),
+            },
+            Event::Finished(generation, result) => engine.finish(generation, &result),
+            Event::Unreadable(error) => {
+                engine.abort();
+                return Err(error);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -66,16 +66,38 @@ static ROOK_OCCUPANCY_MASK: EnumMap<Square, BoardMask> = EnumMap::from_array([
 ]);
 
 impl BoardMask {
-    fn pdep(self, occupancy_mask: Self) -> Self {
-        // Self(Pdep::pdep(self.0, occupancy_mask.0))
-        todo!("pdep")
+    /// Parallel bit deposit, scatter the low bits of `self` into the set bits of `occupancy_mask`
+    const fn pdep(self, occupancy_mask: Self) -> Self {
+        let mut mask = occupancy_mask.0;
+        let mut deposited = 0;
+        let mut bit = 1;
+        while mask != 0 {
+            let lowest = mask & mask.wrapping_neg();
+            if self.0 & bit != 0 {
+                deposited |= lowest;
+            }
+            mask ^= lowest;
+            bit <<= 1;
+        }
+
+        Self(deposited)
     }
-}
 
-impl BoardMask {
-    fn pext(self, occupancy_mask: Self) -> Self {
-        // Self(Pext::pext(self.0, occupancy_mask.0))
-        todo!("pext")
+    /// Parallel bit extract, gather the bits of `self` under `occupancy_mask` into the low bits
+    const fn pext(self, occupancy_mask: Self) -> Self {
+        let mut mask = occupancy_mask.0;
+        let mut extracted = 0;
+        let mut bit = 1;
+        while mask != 0 {
+            let lowest = mask & mask.wrapping_neg();
+            if self.0 & lowest != 0 {
+                extracted |= bit;
+            }
+            mask ^= lowest;
+            bit <<= 1;
+        }
+
+        Self(extracted)
     }
 }
 
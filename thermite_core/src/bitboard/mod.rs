@@ -3,6 +3,7 @@ mod lines;
 mod shifts;
 
 use crate::square::{File, Rank, Square};
+use core::fmt::{Display, Formatter};
 use derive_more::{
     AsRef, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Constructor, LowerHex,
     Not, Shl, ShlAssign, Shr, ShrAssign, UpperHex,
@@ -58,14 +59,14 @@ impl BoardMask {
     ]);
     /// Mask of each file
     pub const FILES: EnumMap<File, Self> = EnumMap::from_array([
-        Self(0x8080_8080_8080_8080),
-        Self(0x4040_4040_4040_4040),
-        Self(0x2020_2020_2020_2020),
-        Self(0x1010_1010_1010_1010),
-        Self(0x0808_0808_0808_0808),
-        Self(0x0404_0404_0404_0404),
-        Self(0x0202_0202_0202_0202),
         Self(0x0101_0101_0101_0101),
+        Self(0x0202_0202_0202_0202),
+        Self(0x0404_0404_0404_0404),
+        Self(0x0808_0808_0808_0808),
+        Self(0x1010_1010_1010_1010),
+        Self(0x2020_2020_2020_2020),
+        Self(0x4040_4040_4040_4040),
+        Self(0x8080_8080_8080_8080),
     ]);
 
     /// If a bit is set, return that [`Square`](Square) and unset the bit
@@ -92,6 +93,31 @@ impl BoardMask {
     }
 }
 
+impl Display for BoardMask {
+    /// Draw the mask as an 8x8 grid from white's side, with set squares as `X` and rank and file labels
+    ///
+    /// ```
+    /// use thermite_core::bitboard::BoardMask;
+    /// use thermite_core::square::Square;
+    ///
+    /// let mask = Square::E4.to_mask() | Square::H8.to_mask();
+    /// assert_eq!(mask.to_string().lines().next(), Some("8 . . . . . . . X"));
+    /// assert_eq!(mask.to_string().lines().nth(4), Some("4 . . . . X . . ."));
+    /// ```
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for rank in (0..8u8).rev() {
+            write!(f, "{}", rank + 1)?;
+            for file in 0..8 {
+                let is_set = self.0 & (1 << (rank * 8 + file)) != 0;
+                write!(f, " {}", if is_set { 'X' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "  a b c d e f g h")
+    }
+}
+
 /// A [`bitboard`](BoardMask) to square iterator container that will emit active squares from the mask (set bits).
 #[derive(Clone, Debug)]
 #[must_use]
@@ -125,7 +151,7 @@ impl ExactSizeIterator for MaskSquareIterator {}
 #[cfg(test)]
 mod test {
     use crate::bitboard::BoardMask;
-    use crate::square::{Square, Square::*};
+    use crate::square::{File, Square, Square::*};
     use alloc::vec::Vec;
     use core::ops::Not;
 
@@ -135,24 +161,51 @@ mod test {
     fn is_empty_works() {
         assert!(BoardMask::EMPTY.is_empty());
         assert!(!BoardMask::EMPTY.not().is_empty());
-        assert!(!BoardMask::new(0x12300).is_empty());
-        assert!(!BoardMask::new(0x8400400004000).is_empty());
-        assert!(!BoardMask::new(0x22000812).is_empty());
+        assert!(!BoardMask::new(0x0001_2300).is_empty());
+        assert!(!BoardMask::new(0x0008_4004_0000_4000).is_empty());
+        assert!(!BoardMask::new(0x2200_0812).is_empty());
     }
 
     #[test]
     fn num_squares_works() {
         assert_eq!(BoardMask::EMPTY.num_squares(), 0);
         assert_eq!(BoardMask::EMPTY.not().num_squares(), 64);
-        assert_eq!(BoardMask::new(0x12300).num_squares(), 4);
-        assert_eq!(BoardMask::new(0x8400400004000).num_squares(), 4);
-        assert_eq!(BoardMask::new(0x22000812).num_squares(), 5);
+        assert_eq!(BoardMask::new(0x0001_2300).num_squares(), 4);
+        assert_eq!(BoardMask::new(0x0008_4004_0000_4000).num_squares(), 4);
+        assert_eq!(BoardMask::new(0x2200_0812).num_squares(), 5);
+    }
+
+    #[test_case(File::A, &[A1, A2, A3, A4, A5, A6, A7, A8])]
+    #[test_case(File::D, &[D1, D2, D3, D4, D5, D6, D7, D8])]
+    #[test_case(File::H, &[H1, H2, H3, H4, H5, H6, H7, H8])]
+    fn files_works(file: File, expected: &[Square]) {
+        assert_eq!(
+            BoardMask::FILES[file].into_iter().collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn display_draws_a_grid() {
+        let mask = A1.to_mask() | C3.to_mask() | H8.to_mask();
+        assert_eq!(
+            mask.to_string(),
+            "8 . . . . . . . X\n\
+             7 . . . . . . . .\n\
+             6 . . . . . . . .\n\
+             5 . . . . . . . .\n\
+             4 . . . . . . . .\n\
+             3 . . X . . . . .\n\
+             2 . . . . . . . .\n\
+             1 X . . . . . . .\n\
+             \x20 a b c d e f g h\n"
+        );
     }
 
     #[test_case(0x0, &[])]
-    #[test_case(0x400400000, &[G3, C5])]
-    #[test_case(0x22000812, &[B1, E1, D2, B4, F4])]
-    #[test_case(0x8400400004000, &[G2, C5, G6, D7])]
+    #[test_case(0x0004_0040_0000, &[G3, C5])]
+    #[test_case(0x2200_0812, &[B1, E1, D2, B4, F4])]
+    #[test_case(0x0008_4004_0000_4000, &[G2, C5, G6, D7])]
     fn into_iter_works(mask: u64, expected: &[Square]) {
         assert_eq!(
             BoardMask::new(mask).into_iter().collect::<Vec<_>>(),
@@ -1,4 +1,3 @@
-use crate::bitboard::BoardMask;
 use crate::chess_move::castle::Castle;
 use crate::player_color::PlayerColor;
 use crate::square::Square;
@@ -7,7 +6,7 @@ use core::str::FromStr;
 use enum_iterator::Sequence;
 use enum_map::{Enum, EnumMap};
 
-/// The square that the king for a given side moves from when castling in a given direction
+/// The square that the king for a given side moves from when castling in standard chess
 pub const KING_FROM_SQUARES: EnumMap<PlayerColor, Square> =
     EnumMap::from_array([Square::E1, Square::E8]);
 
@@ -18,7 +17,7 @@ pub const KING_TO_SQUARES: EnumMap<CastleDirection, EnumMap<PlayerColor, Square>
         EnumMap::from_array([Square::C1, Square::C8]),
     ]);
 
-/// The square that the rook for a given side moves from when castling in a given direction
+/// The square that the rook for a given side moves from when castling in a given direction in standard chess
 pub const ROOK_FROM_SQUARES: EnumMap<CastleDirection, EnumMap<PlayerColor, Square>> =
     EnumMap::from_array([
         EnumMap::from_array([Square::H1, Square::H8]),
@@ -32,19 +31,75 @@ pub const ROOK_TO_SQUARES: EnumMap<CastleDirection, EnumMap<PlayerColor, Square>
         EnumMap::from_array([Square::D1, Square::D8]),
     ]);
 
-/// A mask of the squares that must be empty to castle
-pub const UNOCCUPIED_SQUARES: EnumMap<CastleDirection, EnumMap<PlayerColor, BoardMask>> =
-    EnumMap::from_array([
-        EnumMap::from_array([BoardMask(0x60), BoardMask(0x6000_0000_0000_0000)]),
-        EnumMap::from_array([BoardMask(0xe), BoardMask(0x0e00_0000_0000_0000)]),
-    ]);
+/// The squares the kings and rooks of a position castle from
+///
+/// In standard chess these are always the [king's](KING_FROM_SQUARES) and [rooks'](ROOK_FROM_SQUARES) starting
+/// squares, but in Chess960 they depend on the starting position. Wherever they start, the king and rook always end
+/// on the same squares as in standard chess.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct CastleSquares {
+    king_from: EnumMap<PlayerColor, Square>,
+    rook_from: EnumMap<CastleDirection, EnumMap<PlayerColor, Square>>,
+}
 
-/// A mask of the squares that cannot be attacked to castle
-pub const UNATTACKED_SQUARES: EnumMap<CastleDirection, EnumMap<PlayerColor, BoardMask>> =
-    EnumMap::from_array([
-        EnumMap::from_array([BoardMask(0x60), BoardMask(0x6000_0000_0000_0000)]),
-        EnumMap::from_array([BoardMask(0xc), BoardMask(0x0c00_0000_0000_0000)]),
-    ]);
+impl Default for CastleSquares {
+    fn default() -> Self {
+        Self {
+            king_from: KING_FROM_SQUARES,
+            rook_from: ROOK_FROM_SQUARES,
+        }
+    }
+}
+
+impl CastleSquares {
+    /// Set the square the king of a player castles from
+    #[must_use]
+    pub fn with_king_from(mut self, player: PlayerColor, square: Square) -> Self {
+        self.king_from[player] = square;
+        self
+    }
+
+    /// Set the square the rook of a player castles from in a direction
+    #[must_use]
+    pub fn with_rook_from(
+        mut self,
+        direction: CastleDirection,
+        player: PlayerColor,
+        square: Square,
+    ) -> Self {
+        self.rook_from[direction][player] = square;
+        self
+    }
+
+    /// The square the king of a player castles from
+    #[must_use]
+    pub fn king_from(&self, player: PlayerColor) -> Square {
+        self.king_from[player]
+    }
+
+    /// The square the rook of a player castles from in a direction
+    #[must_use]
+    pub fn rook_from(&self, direction: CastleDirection, player: PlayerColor) -> Square {
+        self.rook_from[direction][player]
+    }
+
+    /// If the squares are those of standard chess
+    ///
+    /// ```
+    /// use thermite_core::castles::{CastleDirection, CastleSquares};
+    /// use thermite_core::player_color::PlayerColor;
+    /// use thermite_core::square::Square;
+    ///
+    /// assert!(CastleSquares::default().is_standard());
+    /// assert!(!CastleSquares::default()
+    ///     .with_rook_from(CastleDirection::KingSide, PlayerColor::White, Square::G1)
+    ///     .is_standard());
+    /// ```
+    #[must_use]
+    pub fn is_standard(&self) -> bool {
+        *self == Self::default()
+    }
+}
 
 /// The direction to castle in for either side
 #[derive(Enum, Sequence, Copy, Clone, Eq, PartialEq, Debug, Hash)]
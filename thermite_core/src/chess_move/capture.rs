@@ -1,5 +1,5 @@
 use crate::chess_move::quiet::Quiet;
-use crate::pieces::NonKingPieceType;
+use crate::pieces::{NonKingPieceType, OwnedPiece};
 use crate::square::Square;
 
 /// A valid capture move
@@ -32,6 +32,12 @@ impl Capture {
         self.quiet.to()
     }
 
+    /// Get the [piece](OwnedPiece) doing the capturing
+    #[must_use]
+    pub const fn piece(&self) -> OwnedPiece {
+        self.quiet.piece()
+    }
+
     /// Get the [piece](NonKingPieceType) being captured
     #[must_use]
     pub const fn captured_piece(&self) -> NonKingPieceType {
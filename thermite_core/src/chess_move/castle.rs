@@ -1,10 +1,7 @@
 use crate::bitboard::BoardMask;
 use crate::castles::{
-    CastleDirection, CastleRights, KING_FROM_SQUARES, KING_TO_SQUARES, ROOK_FROM_SQUARES,
-    ROOK_TO_SQUARES, UNATTACKED_SQUARES, UNOCCUPIED_SQUARES,
+    CastleDirection, CastleRights, CastleSquares, KING_TO_SQUARES, ROOK_TO_SQUARES,
 };
-use crate::chess_move::quiet::Quiet;
-use crate::pieces::{Piece, PieceType};
 use crate::player_color::PlayerColor;
 use crate::square::Square;
 use enum_iterator::all;
@@ -17,60 +14,57 @@ pub struct Castle {
     player: PlayerColor,
     /// The direction the king is castling towards
     direction: CastleDirection,
+    /// The square the king starts on
+    king_from: Square,
+    /// The square the rook starts on
+    rook_from: Square,
 }
 
 impl Castle {
     #[cfg(test)]
-    /// Create a new castle inner move for a [player](PlayerColor) in a given [`CastleDirection`]
-    pub(crate) const fn new(player: PlayerColor, direction: CastleDirection) -> Self {
-        Self { player, direction }
+    /// Create a new standard chess castle inner move for a [player](PlayerColor) in a given [`CastleDirection`]
+    pub(crate) fn new(player: PlayerColor, direction: CastleDirection) -> Self {
+        Self::with_squares(player, direction, CastleSquares::default())
     }
-    /// Get the [king](PieceType::King)'s original [`Square`]
+
+    /// Create a castle move for a [player](PlayerColor) in a given [`CastleDirection`] from a position's [`CastleSquares`]
+    pub(crate) fn with_squares(
+        player: PlayerColor,
+        direction: CastleDirection,
+        castle_squares: CastleSquares,
+    ) -> Self {
+        Self {
+            player,
+            direction,
+            king_from: castle_squares.king_from(player),
+            rook_from: castle_squares.rook_from(direction, player),
+        }
+    }
+
+    /// Get the [king](crate::pieces::PieceType::King)'s original [`Square`]
     #[must_use]
-    pub fn king_from(&self) -> Square {
-        KING_FROM_SQUARES[self.player]
+    pub const fn king_from(&self) -> Square {
+        self.king_from
     }
 
-    /// Get the [king](PieceType::King)'s destination [`Square`]
+    /// Get the [king](crate::pieces::PieceType::King)'s destination [`Square`]
     #[must_use]
     pub fn king_to(&self) -> Square {
         KING_TO_SQUARES[self.direction][self.player]
     }
 
-    /// Get the [rook](PieceType::Rook)'s original [`Square`]
+    /// Get the [rook](crate::pieces::PieceType::Rook)'s original [`Square`]
     #[must_use]
-    pub fn rook_from(&self) -> Square {
-        ROOK_FROM_SQUARES[self.direction][self.player]
+    pub const fn rook_from(&self) -> Square {
+        self.rook_from
     }
 
-    /// Get the [rook](PieceType::Rook)'s destination [`Square`]
+    /// Get the [rook](crate::pieces::PieceType::Rook)'s destination [`Square`]
     #[must_use]
     pub fn rook_to(&self) -> Square {
         ROOK_TO_SQUARES[self.direction][self.player]
     }
 
-    /// The [rook](PieceType::Rook)'s [move](Quiet)
-    #[must_use]
-    pub(crate) fn rook_quiet(self) -> Quiet {
-        Quiet::new(
-            self.rook_from(),
-            self.rook_to(),
-            PieceType::Rook.owned_by(self.player()),
-        )
-        .expect("rook quiet from and to squares are different")
-    }
-
-    /// The [king](PieceType::King)'s [move](Quiet)
-    #[must_use]
-    pub(crate) fn king_quiet(self) -> Quiet {
-        Quiet::new(
-            self.king_from(),
-            self.king_to(),
-            PieceType::King.owned_by(self.player()),
-        )
-        .expect("king quiet from and to squares are different")
-    }
-
     /// Get the [player](PlayerColor) doing the castling
     #[must_use]
     pub const fn player(&self) -> PlayerColor {
@@ -83,73 +77,61 @@ impl Castle {
         self.direction
     }
 
+    /// The [mask](BoardMask) of the squares the king passes through, including its destination but not its origin
+    fn king_path(self) -> BoardMask {
+        (BoardMask::line_between(self.king_from, self.king_to()) | self.king_to().to_mask())
+            & !self.king_from.to_mask()
+    }
+
     /// Get the [mask](BoardMask) for [squares](Square) that cannot be attacked in order to [castle](Castle)
     pub fn unattacked_mask(&self) -> BoardMask {
-        UNATTACKED_SQUARES[self.direction()][self.player()]
+        self.king_path()
     }
 
     /// Get the [mask](BoardMask) for [squares](Square) that cannot be occupied in order to [castle](Castle)
+    ///
+    /// Every square the king and rook pass through or land on, other than the squares the two start on.
     pub fn unoccupied_mask(&self) -> BoardMask {
-        UNOCCUPIED_SQUARES[self.direction()][self.player()]
+        let rook_path =
+            BoardMask::line_between(self.rook_from, self.rook_to()) | self.rook_to().to_mask();
+
+        (self.king_path() | rook_path) & !(self.king_from.to_mask() | self.rook_from.to_mask())
     }
 
     /// Get the [rights](CastleRights) required to do this castle move
     #[must_use]
     pub const fn required_rights(&self) -> CastleRights {
-        match self {
-            Self {
-                player: PlayerColor::White,
-                direction: CastleDirection::KingSide,
-            } => CastleRights::WhiteKing,
-            Self {
-                player: PlayerColor::White,
-                direction: CastleDirection::QueenSide,
-            } => CastleRights::WhiteQueen,
-            Self {
-                player: PlayerColor::Black,
-                direction: CastleDirection::KingSide,
-            } => CastleRights::BlackKing,
-            Self {
-                player: PlayerColor::Black,
-                direction: CastleDirection::QueenSide,
-            } => CastleRights::BlackQueen,
+        match (self.player, self.direction) {
+            (PlayerColor::White, CastleDirection::KingSide) => CastleRights::WhiteKing,
+            (PlayerColor::White, CastleDirection::QueenSide) => CastleRights::WhiteQueen,
+            (PlayerColor::Black, CastleDirection::KingSide) => CastleRights::BlackKing,
+            (PlayerColor::Black, CastleDirection::QueenSide) => CastleRights::BlackQueen,
         }
     }
 
-    /// Get all possible castle moves
-    pub(crate) fn all() -> impl Iterator<Item = Self> {
-        all::<PlayerColor>().flat_map(Self::all_for_player)
-    }
-
-    /// Get all possible castle moves for a [player](PlayerColor)
-    pub(crate) fn all_for_player(player: PlayerColor) -> impl Iterator<Item = Self> {
-        all::<CastleDirection>().map(move |direction| Self { player, direction })
+    /// Get all possible castle moves of a position
+    pub(crate) fn all(castle_squares: CastleSquares) -> impl Iterator<Item = Self> {
+        all::<PlayerColor>().flat_map(move |player| Self::all_for_player(player, castle_squares))
     }
-}
-
-/// Container for the pair of quiet moves (king and rook) required to castle
-pub(crate) struct CastleQuietMoves {
-    pub(crate) rook_quiet: Quiet,
-    pub(crate) king_quiet: Quiet,
-}
 
-impl CastleQuietMoves {
-    /// Get the quiet moves for a [`Castle`] move
-    #[must_use]
-    pub fn new(castle: Castle) -> Self {
-        Self {
-            rook_quiet: castle.rook_quiet(),
-            king_quiet: castle.king_quiet(),
-        }
+    /// Get all possible castle moves for a [player](PlayerColor) of a position
+    pub(crate) fn all_for_player(
+        player: PlayerColor,
+        castle_squares: CastleSquares,
+    ) -> impl Iterator<Item = Self> {
+        all::<CastleDirection>()
+            .map(move |direction| Self::with_squares(player, direction, castle_squares))
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::bitboard::BoardMask;
-    use crate::castles::CastleDirection::{KingSide, QueenSide};
+    use crate::castles::CastleDirection::{self, KingSide, QueenSide};
+    use crate::castles::CastleSquares;
     use crate::chess_move::castle::Castle;
     use crate::player_color::PlayerColor::{Black, White};
+    use crate::square::Square::{self, *};
 
     use test_case::test_case;
 
@@ -168,4 +150,25 @@ mod test {
     fn unoccupied_mask_works(castle: Castle, expected_mask: BoardMask) {
         assert_eq!(castle.unoccupied_mask(), expected_mask);
     }
+
+    #[test_case(KingSide, F1, G1, BoardMask::EMPTY, BoardMask::new(0x40))]
+    #[test_case(KingSide, G1, H1, BoardMask::new(0x20), BoardMask::EMPTY)]
+    #[test_case(QueenSide, B1, A1, BoardMask::new(0xC), BoardMask::new(0x4))]
+    #[test_case(QueenSide, C1, B1, BoardMask::new(0x8), BoardMask::EMPTY)]
+    #[test_case(QueenSide, C1, D1, BoardMask::EMPTY, BoardMask::EMPTY)]
+    #[test_case(QueenSide, F1, B1, BoardMask::new(0x1C), BoardMask::new(0x1C))]
+    fn chess960_masks_work(
+        direction: CastleDirection,
+        king_from: Square,
+        rook_from: Square,
+        expected_unoccupied: BoardMask,
+        expected_unattacked: BoardMask,
+    ) {
+        let castle_squares = CastleSquares::default()
+            .with_king_from(White, king_from)
+            .with_rook_from(direction, White, rook_from);
+        let castle = Castle::with_squares(White, direction, castle_squares);
+        assert_eq!(castle.unoccupied_mask(), expected_unoccupied);
+        assert_eq!(castle.unattacked_mask(), expected_unattacked);
+    }
 }
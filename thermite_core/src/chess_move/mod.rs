@@ -1,6 +1,8 @@
 use crate::chess_move::promotion::Promotion;
-use crate::pieces::Piece;
+use crate::pieces::{Piece, PieceType, PromotablePieceType};
 use crate::square::Square;
+use alloc::format;
+use alloc::string::{String, ToString};
 use capture::Capture;
 use castle::Castle;
 use core::fmt::{Display, Formatter};
@@ -43,6 +45,89 @@ pub enum ChessMove {
     PromotingCapture(PromotingCapture),
 }
 
+impl ChessMove {
+    /// The [`Square`] the moving piece (the king, when castling) starts on
+    #[must_use]
+    pub fn from_square(&self) -> Square {
+        match *self {
+            Self::Quiet(quiet) => quiet.from(),
+            Self::Capture(capture) => capture.from(),
+            Self::DoublePawnPush(pawn_push) => Square::from(pawn_push.from()),
+            Self::EnPassantCapture(capture) => Square::from(capture.from()),
+            Self::Castle(castle) => castle.king_from(),
+            Self::Promotion(promotion) => Square::from(promotion.from()),
+            Self::PromotingCapture(promoting_capture) => {
+                Square::from(promoting_capture.promotion().from())
+            }
+        }
+    }
+
+    /// The [`Square`] the moving piece (the king, when castling) ends on
+    #[must_use]
+    pub fn to_square(&self) -> Square {
+        match *self {
+            Self::Quiet(quiet) => quiet.to(),
+            Self::Capture(capture) => capture.to(),
+            Self::DoublePawnPush(pawn_push) => Square::from(pawn_push.to()),
+            Self::EnPassantCapture(capture) => Square::from(capture.to()),
+            Self::Castle(castle) => castle.king_to(),
+            Self::Promotion(promotion) => Square::from(promotion.to()),
+            Self::PromotingCapture(promoting_capture) => {
+                Square::from(promoting_capture.promotion().to())
+            }
+        }
+    }
+
+    /// The [`PieceType`] being moved (the king, when castling)
+    #[must_use]
+    pub const fn moved_piece(&self) -> PieceType {
+        match *self {
+            Self::Quiet(quiet) => quiet.piece().piece,
+            Self::Capture(capture) => capture.piece().piece,
+            Self::Castle(_) => PieceType::King,
+            Self::DoublePawnPush(_)
+            | Self::EnPassantCapture(_)
+            | Self::Promotion(_)
+            | Self::PromotingCapture(_) => PieceType::Pawn,
+        }
+    }
+
+    /// Whether or not the move captures a piece
+    #[must_use]
+    pub const fn is_capture(&self) -> bool {
+        matches!(
+            self,
+            Self::Capture(_) | Self::EnPassantCapture(_) | Self::PromotingCapture(_)
+        )
+    }
+
+    /// The [`PromotablePieceType`] a pawn is promoted to, if this is a promoting move
+    #[must_use]
+    pub const fn promotion_piece(&self) -> Option<PromotablePieceType> {
+        match *self {
+            Self::Promotion(promotion) => Some(promotion.piece),
+            Self::PromotingCapture(promoting_capture) => Some(promoting_capture.promotion().piece),
+            _ => None,
+        }
+    }
+}
+
+impl ChessMove {
+    /// Write the move in UCI's long algebraic notation
+    ///
+    /// Castles are written as the king moving to its destination, or under `UCI_Chess960` as the king taking its own
+    /// rook (as in Chess960 the king may otherwise castle onto a square it could also just move to).
+    #[must_use]
+    pub fn to_uci(&self, chess960: bool) -> String {
+        match *self {
+            Self::Castle(castle) if chess960 => {
+                format!("{}{}", castle.king_from(), castle.rook_from())
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
 impl Display for ChessMove {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match *self {
@@ -0,0 +1,154 @@
+use crate::pieces::{NonKingPieceType, Piece};
+use crate::player_color::PlayerColor;
+use crate::zobrist::MaterialHash;
+use alloc::vec;
+use alloc::vec::Vec;
+use enum_iterator::all;
+use enum_map::EnumMap;
+use once_cell::sync::Lazy;
+
+/// The most pawns a side can have
+const MAX_PAWNS: u8 = 8;
+
+/// An ending with known theory, evaluated (or scaled) by dedicated knowledge rather than the general evaluation
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Endgame {
+    /// King and pawn against king, looked up in a bitbase
+    Kpk,
+    /// King, bishop and knight against king, driving the king to a corner of the bishop's color
+    Kbnk,
+    /// King and queen against king, driving the king to any edge
+    Kqk,
+    /// King and rook against king, driving the king to any edge
+    Krk,
+    /// King and rook against king and pawn, won unless the pawn is far advanced and supported
+    Krkp,
+    /// King, bishop and pawns against king, drawn when the pawns are rook pawns promoting on the wrong color
+    Kbpk,
+    /// Bishops of opposite colors (and pawns) on each side, which are hard to win even a few pawns up
+    OppositeBishops,
+}
+
+impl Endgame {
+    /// Whether the ending only scales the general evaluation, rather than replacing it
+    #[must_use]
+    pub const fn is_scaling(self) -> bool {
+        matches!(self, Self::Kbpk | Self::OppositeBishops)
+    }
+
+    /// Find the ending for a [`MaterialHash`], along with the [player](PlayerColor) with the stronger material
+    ///
+    /// ```
+    /// use thermite_core::evaluation::Endgame;
+    /// use thermite_core::fen;
+    /// use thermite_core::player_color::PlayerColor;
+    ///
+    /// let position = fen!("8/8/3k4/8/8/8/2r5/1K6 w - - 0 1");
+    /// assert_eq!(Endgame::lookup(position.material_hash()), Some((Endgame::Krk, PlayerColor::Black)));
+    /// assert_eq!(Endgame::lookup(fen!("4k3/8/8/8/8/8/8/2R1K1R1 w - - 0 1").material_hash()), None);
+    /// ```
+    #[must_use]
+    pub fn lookup(material_hash: MaterialHash) -> Option<(Self, PlayerColor)> {
+        let key = *material_hash.as_ref();
+        SIGNATURES
+            .binary_search_by_key(&key, |&(signature, _, _)| signature)
+            .ok()
+            .map(|index| (SIGNATURES[index].1, SIGNATURES[index].2))
+    }
+
+    /// The piece counts (stronger side, weaker side) of every signature of the ending
+    fn signatures(self) -> Vec<[EnumMap<NonKingPieceType, u8>; 2]> {
+        let counts = |pawns: u8, knights: u8, bishops: u8, rooks: u8, queens: u8| {
+            EnumMap::from_array([pawns, knights, bishops, rooks, queens])
+        };
+        let bare_king = counts(0, 0, 0, 0, 0);
+
+        match self {
+            Self::Kpk => vec![[counts(1, 0, 0, 0, 0), bare_king]],
+            Self::Kbnk => vec![[counts(0, 1, 1, 0, 0), bare_king]],
+            Self::Kqk => vec![[counts(0, 0, 0, 0, 1), bare_king]],
+            Self::Krk => vec![[counts(0, 0, 0, 1, 0), bare_king]],
+            Self::Krkp => vec![[counts(0, 0, 0, 1, 0), counts(1, 0, 0, 0, 0)]],
+            Self::Kbpk => (1..=MAX_PAWNS)
+                .map(|pawns| [counts(pawns, 0, 1, 0, 0), bare_king])
+                .collect(),
+            Self::OppositeBishops => (0..=MAX_PAWNS)
+                .flat_map(|pawns| {
+                    // The side with more pawns is the stronger one
+                    (0..=pawns).map(move |other_pawns| {
+                        [counts(pawns, 0, 1, 0, 0), counts(other_pawns, 0, 1, 0, 0)]
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Get the [`MaterialHash`] of the piece counts for each [player](PlayerColor)
+fn signature_hash(counts: EnumMap<PlayerColor, EnumMap<NonKingPieceType, u8>>) -> MaterialHash {
+    let mut material_hash = MaterialHash::default();
+    for (player, player_counts) in counts {
+        for (piece, count) in player_counts {
+            for index in 0..count {
+                material_hash.toggle_piece_count(piece.owned_by(player), index);
+            }
+        }
+    }
+
+    material_hash
+}
+
+/// Every known ending's material signature (for both stronger players) sorted by hash, for a binary search
+static SIGNATURES: Lazy<Vec<(u64, Endgame, PlayerColor)>> = Lazy::new(|| {
+    let endgames = [
+        Endgame::Kpk,
+        Endgame::Kbnk,
+        Endgame::Kqk,
+        Endgame::Krk,
+        Endgame::Krkp,
+        Endgame::Kbpk,
+        Endgame::OppositeBishops,
+    ];
+
+    let mut signatures = Vec::new();
+    for endgame in endgames {
+        for [strong, weak] in endgame.signatures() {
+            for strong_player in all::<PlayerColor>() {
+                let mut counts = EnumMap::default();
+                counts[strong_player] = strong;
+                counts[strong_player.switch()] = weak;
+                signatures.push((*signature_hash(counts).as_ref(), endgame, strong_player));
+            }
+        }
+    }
+    signatures.sort_by_key(|&(hash, _, _)| hash);
+    // Symmetric signatures (like opposite bishops with equal pawns) are found for both players, keep the first
+    signatures.dedup_by_key(|&mut (hash, _, _)| hash);
+
+    signatures
+});
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fen;
+    use test_case::test_case;
+
+    #[test_case("8/8/8/4k3/8/8/4P3/4K3 w - - 0 1", Some((Endgame::Kpk, PlayerColor::White)))]
+    #[test_case("8/4p3/8/4k3/8/8/8/4K3 b - - 0 1", Some((Endgame::Kpk, PlayerColor::Black)))]
+    #[test_case("8/8/8/4k3/8/8/8/2BNK3 w - - 0 1", Some((Endgame::Kbnk, PlayerColor::White)))]
+    #[test_case("8/8/8/4k3/8/8/8/3QK3 w - - 0 1", Some((Endgame::Kqk, PlayerColor::White)))]
+    #[test_case("8/8/8/4k3/8/8/1p6/3RK3 w - - 0 1", Some((Endgame::Krkp, PlayerColor::White)))]
+    #[test_case("8/8/8/4k3/8/P7/P7/2B1K3 w - - 0 1", Some((Endgame::Kbpk, PlayerColor::White)))]
+    #[test_case("2b5/5p2/8/4k3/8/P7/P7/2B1K3 w - - 0 1", Some((Endgame::OppositeBishops, PlayerColor::White)))]
+    #[test_case("8/8/8/4k3/8/8/8/3QK1N1 w - - 0 1", None)]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", None)]
+    fn lookup_works(fen: &str, expected: Option<(Endgame, PlayerColor)>) {
+        assert_eq!(Endgame::lookup(fen!(fen).material_hash()), expected);
+    }
+
+    #[test]
+    fn signatures_are_unique() {
+        assert_eq!(SIGNATURES.len(), 2 * (5 + MAX_PAWNS as usize) + 81);
+    }
+}
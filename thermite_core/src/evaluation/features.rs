@@ -0,0 +1,58 @@
+use crate::evaluation::{
+    EvaluationParameter, EvaluationParameters, GamePhase, PawnEvaluation, TaperedEvaluation,
+};
+use alloc::vec::Vec;
+
+/// The sparse, linear breakdown of a static evaluation into how many times each [parameter](EvaluationParameter) applies
+///
+/// Coefficients are the difference between [white](crate::player_color::PlayerColor::White)'s and
+/// [black](crate::player_color::PlayerColor::Black)'s counts, so evaluating the features against a set of
+/// [parameters](EvaluationParameters) gives the blended evaluation from white's perspective.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EvaluationFeatures {
+    pub(crate) coefficients: Vec<(EvaluationParameter, i16)>,
+    pub(crate) phase: GamePhase,
+    pub(crate) scale_factor: f32,
+}
+
+impl EvaluationFeatures {
+    /// Get every [parameter](EvaluationParameter) with a non-zero coefficient along with that coefficient
+    #[must_use]
+    pub fn coefficients(&self) -> &[(EvaluationParameter, i16)] {
+        &self.coefficients
+    }
+
+    /// Get the [`GamePhase`] used to blend the mid-game and end-game evaluations
+    #[must_use]
+    pub const fn phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    /// Get the fraction of the blended evaluation kept, below 1.0 in [endings](crate::evaluation::Endgame) that are harder to win than their material suggests
+    #[must_use]
+    pub const fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Evaluate the features with a set of [parameters](EvaluationParameters), from [white](crate::player_color::PlayerColor::White)'s perspective
+    ///
+    /// ```
+    /// use thermite_core::evaluation::EvaluationParameters;
+    /// use thermite_core::fen;
+    /// use thermite_core::player_color::PlayerColor;
+    ///
+    /// let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    /// let evaluation = position.evaluation_features().evaluate(&EvaluationParameters::default());
+    /// let expected = position.evaluate_with_trace().evaluation_for(PlayerColor::White);
+    /// assert!((*evaluation - *expected).abs() < 0.001);
+    /// ```
+    #[must_use]
+    pub fn evaluate(&self, parameters: &EvaluationParameters) -> PawnEvaluation {
+        self.coefficients
+            .iter()
+            .map(|&(parameter, coefficient)| parameters[parameter] * f32::from(coefficient))
+            .fold(TaperedEvaluation::default(), |total, value| total + value)
+            .taper(self.phase)
+            * self.scale_factor
+    }
+}
@@ -0,0 +1,45 @@
+use crate::pieces::NonKingPieceType;
+use derive_more::{AsRef, Display, Into};
+use enum_map::EnumMap;
+
+/// How far a game has progressed from the opening towards the endgame, based on the non-pawn material left on the board
+///
+/// A full board is [`GamePhase::MAX`] (mid-game) and bare kings and pawns is [`GamePhase::MIN`] (end-game).
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Ord, PartialOrd, Hash, AsRef, Into, Display)]
+pub struct GamePhase(u8);
+
+/// How much each [piece](NonKingPieceType) contributes to the [`GamePhase`]
+const PHASE_WEIGHTS: EnumMap<NonKingPieceType, u8> = EnumMap::from_array([0, 1, 1, 2, 4]);
+
+impl GamePhase {
+    /// The end-game phase, when no pieces other than pawns and kings remain
+    pub const MIN: Self = Self(0);
+    /// The mid-game phase, when all of the starting pieces remain (or more with promotions)
+    pub const MAX: Self = Self(24);
+
+    /// Get the phase for a given number of each [piece](NonKingPieceType) on the board (for both players)
+    ///
+    /// ```
+    /// use enum_map::EnumMap;
+    /// use thermite_core::evaluation::GamePhase;
+    ///
+    /// assert_eq!(GamePhase::from_piece_counts(EnumMap::from_array([16, 4, 4, 4, 2])), GamePhase::MAX);
+    /// assert_eq!(GamePhase::from_piece_counts(EnumMap::from_array([16, 0, 0, 0, 0])), GamePhase::MIN);
+    /// assert!(GamePhase::from_piece_counts(EnumMap::from_array([9, 0, 0, 4, 2])) < GamePhase::MAX);
+    /// ```
+    #[must_use]
+    pub fn from_piece_counts(piece_counts: EnumMap<NonKingPieceType, u8>) -> Self {
+        let phase = piece_counts
+            .iter()
+            .map(|(piece, &count)| PHASE_WEIGHTS[piece].saturating_mul(count))
+            .fold(0u8, u8::saturating_add);
+
+        Self(phase.min(Self::MAX.0))
+    }
+
+    /// The fraction (0.0 for [end-game](Self::MIN) to 1.0 for [mid-game](Self::MAX)) of the game that is mid-game
+    #[must_use]
+    pub fn mid_game_weight(self) -> f32 {
+        f32::from(self.0) / f32::from(Self::MAX.0)
+    }
+}
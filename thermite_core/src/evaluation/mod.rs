@@ -1,5 +1,17 @@
+mod endgame;
+mod features;
+mod game_phase;
+mod parameter;
 mod pawn_evaluation;
 mod score;
+mod tapered_evaluation;
+mod trace;
 
+pub use endgame::Endgame;
+pub use features::EvaluationFeatures;
+pub use game_phase::GamePhase;
+pub use parameter::{EvaluationParameter, EvaluationParameters};
 pub use pawn_evaluation::PawnEvaluation;
 pub use score::Score;
+pub use tapered_evaluation::TaperedEvaluation;
+pub use trace::{EvaluationTerm, EvaluationTrace};
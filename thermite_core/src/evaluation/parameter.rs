@@ -0,0 +1,252 @@
+use crate::evaluation::{EvaluationTerm, TaperedEvaluation};
+use crate::pieces::{NonKingPieceType, PieceType, PromotablePieceType};
+use crate::square::{File, Rank, Square};
+use core::fmt::{Display, Formatter};
+use core::ops::{Index, IndexMut};
+use enum_iterator::all;
+use enum_map::Enum;
+
+/// A single tunable weight of the handcrafted evaluation
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EvaluationParameter {
+    /// The material value of a [piece](NonKingPieceType), shared between the mid-game and end-game
+    PieceValue(NonKingPieceType),
+    /// The bonus for a [piece](PieceType) standing on a [square](Square), from [white](crate::player_color::PlayerColor::White)'s perspective
+    PieceSquare(PieceType, Square),
+    /// The penalty for each extra pawn stacked on a file
+    DoubledPawn,
+    /// The penalty for each pawn without any friendly pawns on the neighbouring files
+    IsolatedPawn,
+    /// The bonus for a passed pawn on a [rank](Rank) relative to its owner
+    PassedPawn(Rank),
+    /// The bonus for each square a [piece](PromotablePieceType) can safely move to beyond its baseline
+    Mobility(PromotablePieceType),
+    /// The bonus for each friendly pawn sheltering the king
+    PawnShield,
+    /// The penalty for each square around the king attacked by an opposing [piece](PromotablePieceType)
+    KingZoneAttack(PromotablePieceType),
+}
+
+impl EvaluationParameter {
+    /// The total number of distinct parameters
+    pub const COUNT: usize = NonKingPieceType::LENGTH
+        + PieceType::LENGTH * Square::LENGTH
+        + 2
+        + Rank::LENGTH
+        + PromotablePieceType::LENGTH
+        + 1
+        + PromotablePieceType::LENGTH;
+
+    /// Get every parameter, in [index](Self::index) order
+    pub fn all() -> impl Iterator<Item = Self> {
+        all::<NonKingPieceType>()
+            .map(Self::PieceValue)
+            .chain(all::<PieceType>().flat_map(|piece| {
+                all::<Square>().map(move |square| Self::PieceSquare(piece, square))
+            }))
+            .chain([Self::DoubledPawn, Self::IsolatedPawn])
+            .chain(
+                (0..Rank::LENGTH)
+                    .map(Rank::from_usize)
+                    .map(Self::PassedPawn),
+            )
+            .chain(all::<PromotablePieceType>().map(Self::Mobility))
+            .chain([Self::PawnShield])
+            .chain(all::<PromotablePieceType>().map(Self::KingZoneAttack))
+    }
+
+    /// Get the dense index of the parameter, from `0` up to [`COUNT`](Self::COUNT)
+    ///
+    /// ```
+    /// use thermite_core::evaluation::EvaluationParameter;
+    ///
+    /// for (index, parameter) in EvaluationParameter::all().enumerate() {
+    ///     assert_eq!(parameter.index(), index);
+    /// }
+    /// assert_eq!(EvaluationParameter::all().count(), EvaluationParameter::COUNT);
+    /// ```
+    #[must_use]
+    pub fn index(self) -> usize {
+        const PIECE_SQUARES_OFFSET: usize = NonKingPieceType::LENGTH;
+        const DOUBLED_PAWN_OFFSET: usize =
+            PIECE_SQUARES_OFFSET + PieceType::LENGTH * Square::LENGTH;
+        const PASSED_PAWN_OFFSET: usize = DOUBLED_PAWN_OFFSET + 2;
+        const MOBILITY_OFFSET: usize = PASSED_PAWN_OFFSET + Rank::LENGTH;
+        const PAWN_SHIELD_OFFSET: usize = MOBILITY_OFFSET + PromotablePieceType::LENGTH;
+        const KING_ZONE_ATTACK_OFFSET: usize = PAWN_SHIELD_OFFSET + 1;
+
+        match self {
+            Self::PieceValue(piece) => piece.into_usize(),
+            Self::PieceSquare(piece, square) => {
+                PIECE_SQUARES_OFFSET + piece.into_usize() * Square::LENGTH + square as usize
+            }
+            Self::DoubledPawn => DOUBLED_PAWN_OFFSET,
+            Self::IsolatedPawn => DOUBLED_PAWN_OFFSET + 1,
+            Self::PassedPawn(rank) => PASSED_PAWN_OFFSET + rank as usize,
+            Self::Mobility(piece) => MOBILITY_OFFSET + piece.into_usize(),
+            Self::PawnShield => PAWN_SHIELD_OFFSET,
+            Self::KingZoneAttack(piece) => KING_ZONE_ATTACK_OFFSET + piece.into_usize(),
+        }
+    }
+
+    /// Get the [term](EvaluationTerm) the parameter contributes to
+    #[must_use]
+    pub const fn term(self) -> EvaluationTerm {
+        match self {
+            Self::PieceValue(_) => EvaluationTerm::Material,
+            Self::PieceSquare(_, _) => EvaluationTerm::PieceSquares,
+            Self::DoubledPawn | Self::IsolatedPawn | Self::PassedPawn(_) => EvaluationTerm::Pawns,
+            Self::Mobility(_) => EvaluationTerm::Mobility,
+            Self::PawnShield | Self::KingZoneAttack(_) => EvaluationTerm::KingSafety,
+        }
+    }
+
+    /// If the parameter has separate mid-game and end-game weights, otherwise both phases share the same weight
+    #[must_use]
+    pub const fn is_tapered(self) -> bool {
+        !matches!(self, Self::PieceValue(_))
+    }
+}
+
+/// A full set of [evaluation weights](EvaluationParameter), defaulting to the weights currently used by the engine
+#[derive(Clone, PartialEq, Debug)]
+pub struct EvaluationParameters([TaperedEvaluation; EvaluationParameter::COUNT]);
+
+impl Default for EvaluationParameters {
+    fn default() -> Self {
+        let mut parameters = Self([TaperedEvaluation::default(); EvaluationParameter::COUNT]);
+        for parameter in EvaluationParameter::all() {
+            parameters[parameter] = parameter.value();
+        }
+
+        parameters
+    }
+}
+
+impl Index<EvaluationParameter> for EvaluationParameters {
+    type Output = TaperedEvaluation;
+
+    fn index(&self, parameter: EvaluationParameter) -> &Self::Output {
+        &self.0[parameter.index()]
+    }
+}
+
+impl IndexMut<EvaluationParameter> for EvaluationParameters {
+    fn index_mut(&mut self, parameter: EvaluationParameter) -> &mut Self::Output {
+        &mut self.0[parameter.index()]
+    }
+}
+
+impl EvaluationParameters {
+    /// Write a single (mid-game, end-game) centipawn pair
+    fn write_pair(
+        &self,
+        f: &mut Formatter<'_>,
+        parameter: EvaluationParameter,
+    ) -> core::fmt::Result {
+        let TaperedEvaluation { mid_game, end_game } = self[parameter];
+        write!(
+            f,
+            "({}, {})",
+            mid_game.to_centipawns(),
+            end_game.to_centipawns()
+        )
+    }
+
+    /// Write a piece-square table for a single phase, in the rank 8 first layout of the source tables
+    fn write_piece_squares(
+        &self,
+        f: &mut Formatter<'_>,
+        name: &str,
+        phase: fn(TaperedEvaluation) -> i16,
+    ) -> core::fmt::Result {
+        writeln!(f, "#[rustfmt::skip]")?;
+        writeln!(
+            f,
+            "pub const {name}: EnumMap<PieceType, [i16; Square::LENGTH]> = EnumMap::from_array(["
+        )?;
+        for piece in all::<PieceType>() {
+            writeln!(f, "    // {piece:?}")?;
+            writeln!(f, "    [")?;
+            for rank in (0..Rank::LENGTH).rev() {
+                write!(f, "       ")?;
+                for file in 0..File::LENGTH {
+                    let square = Square::try_from((rank * File::LENGTH + file) as u8)
+                        .expect("rank and file are within the board");
+                    write!(
+                        f,
+                        " {:>3},",
+                        phase(self[EvaluationParameter::PieceSquare(piece, square)])
+                    )?;
+                }
+                writeln!(f)?;
+            }
+            writeln!(f, "    ],")?;
+        }
+        writeln!(f, "]);")
+    }
+}
+
+impl Display for EvaluationParameters {
+    /// Format the parameters as the Rust constants they are declared as in the evaluation source
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "pub(super) static PIECE_VALUES: Lazy<EnumMap<NonKingPieceType, PawnEvaluation>> = Lazy::new(|| {{")?;
+        writeln!(f, "    EnumMap::from_array([")?;
+        for piece in all::<NonKingPieceType>() {
+            let value = self[EvaluationParameter::PieceValue(piece)].mid_game;
+            writeln!(f, "        PawnEvaluation({:.2}), // {piece:?}", *value)?;
+        }
+        writeln!(f, "    ])")?;
+        writeln!(f, "}});")?;
+        writeln!(f)?;
+        self.write_piece_squares(f, "MID_GAME_PIECE_SQUARES", |value| {
+            value.mid_game.to_centipawns()
+        })?;
+        writeln!(f)?;
+        self.write_piece_squares(f, "END_GAME_PIECE_SQUARES", |value| {
+            value.end_game.to_centipawns()
+        })?;
+        writeln!(f)?;
+        write!(f, "const DOUBLED_PAWN: (i16, i16) = ")?;
+        self.write_pair(f, EvaluationParameter::DoubledPawn)?;
+        writeln!(f, ";")?;
+        write!(f, "const ISOLATED_PAWN: (i16, i16) = ")?;
+        self.write_pair(f, EvaluationParameter::IsolatedPawn)?;
+        writeln!(f, ";")?;
+        writeln!(f, "#[rustfmt::skip]")?;
+        writeln!(f, "const PASSED_PAWN: [(i16, i16); Rank::LENGTH] = [")?;
+        write!(f, "   ")?;
+        for rank in (0..Rank::LENGTH).map(Rank::from_usize) {
+            write!(f, " ")?;
+            self.write_pair(f, EvaluationParameter::PassedPawn(rank))?;
+            write!(f, ",")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "];")?;
+        write!(
+            f,
+            "const MOBILITY: EnumMap<PromotablePieceType, (i16, i16)> =\n    EnumMap::from_array(["
+        )?;
+        for (index, piece) in all::<PromotablePieceType>().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            self.write_pair(f, EvaluationParameter::Mobility(piece))?;
+        }
+        writeln!(f, "]);")?;
+        write!(f, "const PAWN_SHIELD: (i16, i16) = ")?;
+        self.write_pair(f, EvaluationParameter::PawnShield)?;
+        writeln!(f, ";")?;
+        write!(
+            f,
+            "const KING_ZONE_ATTACK: EnumMap<PromotablePieceType, (i16, i16)> =\n    EnumMap::from_array(["
+        )?;
+        for (index, piece) in all::<PromotablePieceType>().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            self.write_pair(f, EvaluationParameter::KingZoneAttack(piece))?;
+        }
+        write!(f, "]);")
+    }
+}
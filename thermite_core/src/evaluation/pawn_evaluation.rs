@@ -8,6 +8,7 @@ use derive_more::{
     Copy,
     Clone,
     Debug,
+    Default,
     PartialOrd,
     Constructor,
     Neg,
@@ -45,6 +46,38 @@ impl PawnEvaluation {
         }
     }
 
+    /// Create an evaluation from a number of centi-pawns (1/100th of a pawn)
+    ///
+    /// ```
+    /// use thermite_core::evaluation::PawnEvaluation;
+    /// assert_eq!(PawnEvaluation::from_centipawns(100), PawnEvaluation::new(1.0));
+    /// assert_eq!(PawnEvaluation::from_centipawns(-25), PawnEvaluation::new(-0.25));
+    /// ```
+    #[must_use]
+    pub fn from_centipawns(centipawns: i16) -> Self {
+        Self(f32::from(centipawns) / 100.0)
+    }
+
+    /// Round the evaluation to the nearest number of centi-pawns (1/100th of a pawn)
+    ///
+    /// ```
+    /// use thermite_core::evaluation::PawnEvaluation;
+    /// assert_eq!(PawnEvaluation::new(1.0).to_centipawns(), 100);
+    /// assert_eq!(PawnEvaluation::new(-0.256).to_centipawns(), -26);
+    /// assert_eq!(PawnEvaluation::new(0.004).to_centipawns(), 0);
+    /// ```
+    #[must_use]
+    pub fn to_centipawns(self) -> i16 {
+        let centipawns = self.0 * 100.0;
+        let rounding = if centipawns.is_sign_negative() {
+            -0.5
+        } else {
+            0.5
+        };
+
+        (centipawns + rounding) as i16
+    }
+
     /// Get the rounded centi-pawn (1/100th of a pawn is 1 centi-pawn) representation
     #[must_use]
     pub fn centipawns(&self) -> i32 {
@@ -55,7 +88,7 @@ impl PawnEvaluation {
 impl PartialEq for PawnEvaluation {
     fn eq(&self, other: &Self) -> bool {
         // f32::EPSILON.sqrt()
-        const EPSILON: PawnEvaluation = Self(0.000_345_266_98);
+        const EPSILON: PawnEvaluation = PawnEvaluation(0.000_345_266_98);
         (*self - *other).abs() <= EPSILON
     }
 }
@@ -0,0 +1,49 @@
+use crate::evaluation::{GamePhase, PawnEvaluation};
+use derive_more::{Add, AddAssign, Constructor, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// A pair of [evaluations](PawnEvaluation), one for the mid-game and one for the end-game, to be blended by [`GamePhase`]
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Debug,
+    Default,
+    Constructor,
+    Neg,
+    Add,
+    AddAssign,
+    Sub,
+    SubAssign,
+    Mul,
+    MulAssign,
+)]
+pub struct TaperedEvaluation {
+    /// The evaluation while most of the pieces are still on the board
+    pub mid_game: PawnEvaluation,
+    /// The evaluation once most of the pieces have been traded off
+    pub end_game: PawnEvaluation,
+}
+
+impl TaperedEvaluation {
+    /// Create an evaluation with the same value for both the mid-game and end-game
+    #[must_use]
+    pub const fn flat(evaluation: PawnEvaluation) -> Self {
+        Self::new(evaluation, evaluation)
+    }
+
+    /// Linearly interpolate between the mid-game and end-game evaluations for a given [`GamePhase`]
+    ///
+    /// ```
+    /// use thermite_core::evaluation::{GamePhase, PawnEvaluation, TaperedEvaluation};
+    ///
+    /// let evaluation = TaperedEvaluation::new(PawnEvaluation::new(1.0), PawnEvaluation::new(3.0));
+    /// assert_eq!(evaluation.taper(GamePhase::MAX), PawnEvaluation::new(1.0));
+    /// assert_eq!(evaluation.taper(GamePhase::MIN), PawnEvaluation::new(3.0));
+    /// ```
+    #[must_use]
+    pub fn taper(self, phase: GamePhase) -> PawnEvaluation {
+        let mid_game_weight = phase.mid_game_weight();
+
+        self.mid_game * mid_game_weight + self.end_game * (1.0 - mid_game_weight)
+    }
+}
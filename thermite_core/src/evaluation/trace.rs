@@ -0,0 +1,132 @@
+use crate::evaluation::{GamePhase, PawnEvaluation, TaperedEvaluation};
+use crate::player_color::PlayerColor;
+use core::fmt::{Display, Formatter};
+use enum_iterator::{all, Sequence};
+use enum_map::{Enum, EnumMap};
+
+/// A single component of the handcrafted evaluation
+#[derive(Enum, Sequence, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum EvaluationTerm {
+    /// The sum of the value of every non-king piece
+    Material,
+    /// Bonuses and penalties for where on the board each piece stands
+    PieceSquares,
+    /// Pawn structure, doubled and isolated pawns as well as passed pawns
+    Pawns,
+    /// The number of squares each piece can safely move to
+    Mobility,
+    /// The pawn shield in front of the king and the enemy pieces attacking the squares around it
+    KingSafety,
+}
+
+impl Display for EvaluationTerm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Self::Material => "Material",
+            Self::PieceSquares => "Piece squares",
+            Self::Pawns => "Pawns",
+            Self::Mobility => "Mobility",
+            Self::KingSafety => "King safety",
+        };
+
+        f.pad(name)
+    }
+}
+
+/// A breakdown of a static evaluation by [term](EvaluationTerm) and [player](PlayerColor), along with the [`GamePhase`] used to blend them
+#[derive(Clone, PartialEq, Debug)]
+pub struct EvaluationTrace {
+    pub(crate) terms: EnumMap<EvaluationTerm, EnumMap<PlayerColor, TaperedEvaluation>>,
+    pub(crate) phase: GamePhase,
+    pub(crate) scale_factor: f32,
+}
+
+impl EvaluationTrace {
+    /// Get the contribution of a single [term](EvaluationTerm) for a [player](PlayerColor), from that player's perspective
+    #[must_use]
+    pub fn term(&self, term: EvaluationTerm, player: PlayerColor) -> TaperedEvaluation {
+        self.terms[term][player]
+    }
+
+    /// Get the net contribution of a single [term](EvaluationTerm), from [white](PlayerColor::White)'s perspective
+    #[must_use]
+    pub fn term_total(&self, term: EvaluationTerm) -> TaperedEvaluation {
+        self.terms[term][PlayerColor::White] - self.terms[term][PlayerColor::Black]
+    }
+
+    /// Get the [`GamePhase`] used to blend the mid-game and end-game evaluations
+    #[must_use]
+    pub const fn phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    /// Get the fraction of the blended evaluation kept, below 1.0 in [endings](crate::evaluation::Endgame) that are harder to win than their material suggests
+    #[must_use]
+    pub const fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Get the sum of every [term](EvaluationTerm), from [white](PlayerColor::White)'s perspective, before being blended
+    #[must_use]
+    pub fn total(&self) -> TaperedEvaluation {
+        all::<EvaluationTerm>()
+            .map(|term| self.term_total(term))
+            .fold(TaperedEvaluation::default(), |total, term| total + term)
+    }
+
+    /// Get the final blended [evaluation](PawnEvaluation) from a given [player](PlayerColor)'s perspective
+    #[must_use]
+    pub fn evaluation_for(&self, player: PlayerColor) -> PawnEvaluation {
+        let white_evaluation = self.total().taper(self.phase) * self.scale_factor;
+        match player {
+            PlayerColor::White => white_evaluation,
+            PlayerColor::Black => -white_evaluation,
+        }
+    }
+}
+
+impl Display for EvaluationTrace {
+    /// Format the trace as a table of each term, similar to Stockfish's `eval` command
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        const SEPARATOR: &str =
+            " --------------+-----------------+-----------------+-----------------";
+        fn write_pair(f: &mut Formatter<'_>, evaluation: TaperedEvaluation) -> core::fmt::Result {
+            write!(
+                f,
+                " {:>+7.2} {:>+7.2} ",
+                *evaluation.mid_game, *evaluation.end_game
+            )
+        }
+
+        writeln!(
+            f,
+            "          Term |      White      |      Black      |      Total"
+        )?;
+        writeln!(
+            f,
+            "               |      MG      EG |      MG      EG |      MG      EG"
+        )?;
+        writeln!(f, "{SEPARATOR}")?;
+        for term in all::<EvaluationTerm>() {
+            write!(f, " {term:>13} |")?;
+            write_pair(f, self.term(term, PlayerColor::White))?;
+            write!(f, "|")?;
+            write_pair(f, self.term(term, PlayerColor::Black))?;
+            write!(f, "|")?;
+            write_pair(f, self.term_total(term))?;
+            writeln!(f)?;
+        }
+        writeln!(f, "{SEPARATOR}")?;
+        write!(f, " {:>13} |                 |                 |", "Total")?;
+        write_pair(f, self.total())?;
+        writeln!(f)?;
+        writeln!(f)?;
+        writeln!(f, "Phase: {}/{}", self.phase, GamePhase::MAX)?;
+        writeln!(f, "Scale factor: {:.2}", self.scale_factor)?;
+        write!(
+            f,
+            "Final evaluation: {:+.2} (white side)",
+            *self.evaluation_for(PlayerColor::White)
+        )
+    }
+}
@@ -0,0 +1,519 @@
+use crate::chess_move::ChessMove;
+use crate::half_move_clock::HALF_MOVE_LIMIT;
+use crate::pgn::GameResult;
+use crate::pieces::NonKingPieceType;
+use crate::player_color::PlayerColor;
+use crate::ply_count::PlyCount;
+use crate::position::{
+    FenParseError, IllegalPosition, LegalPosition, LegalPositionState, PositionBuilder,
+};
+use crate::square::Square;
+use crate::zobrist::ZobristHash;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The FEN of the standard starting position
+const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// A move that isn't legal in the position it would be played in
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct IllegalMove(pub ChessMove);
+
+/// Why a FEN can't start a [`Game`]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum InvalidStartingPosition {
+    /// The FEN can't be parsed
+    InvalidFen(FenParseError),
+    /// The FEN isn't a legal position
+    IllegalPosition(IllegalPosition),
+}
+
+/// How a game ended
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GameOutcome {
+    /// The player to move is in check without any legal moves, so the other player wins
+    Checkmate(PlayerColor),
+    /// The player to move isn't in check but has no legal moves
+    Stalemate,
+    /// A hundred plies have passed without a capture or pawn move
+    FiftyMoveRule,
+    /// The same position has been reached three times
+    ThreefoldRepetition,
+    /// Neither player has the material left to checkmate
+    InsufficientMaterial,
+}
+
+impl GameOutcome {
+    /// The player who won, `None` for a draw
+    #[must_use]
+    pub const fn winner(self) -> Option<PlayerColor> {
+        match self {
+            Self::Checkmate(winner) => Some(winner),
+            _ => None,
+        }
+    }
+
+    /// The result of a game that ended this way
+    #[must_use]
+    pub const fn result(self) -> GameResult {
+        match self.winner() {
+            Some(PlayerColor::White) => GameResult::WhiteWins,
+            Some(PlayerColor::Black) => GameResult::BlackWins,
+            None => GameResult::Draw,
+        }
+    }
+}
+
+/// Whether neither player has the material left to checkmate, with only kings and either a single minor piece or
+/// bishops that are all on the same colored squares
+fn is_insufficient_material(position: &LegalPosition) -> bool {
+    let has_major_or_pawn = [
+        NonKingPieceType::Pawn,
+        NonKingPieceType::Rook,
+        NonKingPieceType::Queen,
+    ]
+    .into_iter()
+    .any(|piece| !position.piece_mask(piece).is_empty());
+    if has_major_or_pawn {
+        return false;
+    }
+
+    let knights = position.piece_mask(NonKingPieceType::Knight);
+    let bishops = position.piece_mask(NonKingPieceType::Bishop);
+    if knights.num_squares() + bishops.num_squares() <= 1 {
+        return true;
+    }
+
+    let mut bishop_colors = bishops.into_iter().map(Square::is_light);
+    knights.is_empty()
+        && bishop_colors
+            .next()
+            .is_some_and(|first| bishop_colors.all(|light| light == first))
+}
+
+/// A game of chess played move by move from a starting position
+///
+/// Owns the current position along with the moves played to reach it, and the state each was made from, so any of
+/// them can be taken back. Moves taken back are kept to replay, letting the game jump to any ply, until a different
+/// move is played in their place.
+///
+/// ```
+/// use thermite_core::game::{Game, GameOutcome};
+/// use thermite_core::player_color::PlayerColor;
+///
+/// let mut game = Game::default();
+/// for san in ["f3", "e5", "g4", "Qh4#"] {
+///     let chess_move = game.position().parse_san(san).unwrap();
+///     game.push(chess_move).unwrap();
+/// }
+/// assert_eq!(game.outcome(), Some(GameOutcome::Checkmate(PlayerColor::Black)));
+///
+/// assert!(game.go_to_ply(1));
+/// assert_eq!(game.outcome(), None);
+/// assert_eq!(game.fullmove_number(), 1);
+/// assert!(game.go_to_ply(4));
+/// assert_eq!(game.fullmove_number(), 3);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Game {
+    starting_fen: String,
+    starting_fullmove: u16,
+    starting_position: LegalPosition,
+    position: LegalPosition,
+    /// The moves played to reach the current position, with the state each was made from to take it back
+    played: Vec<(ChessMove, LegalPositionState)>,
+    /// The moves taken back from the end of the game, the next to replay last
+    taken_back: Vec<ChessMove>,
+    /// The hash of the starting position and of the position after each move played, to detect repetitions
+    hashes: Vec<ZobristHash>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::from_fen(STARTING_FEN).expect("starting position FEN is valid")
+    }
+}
+
+impl Game {
+    /// Start a game from a FEN, keeping its fullmove number (1 if it's missing)
+    ///
+    /// # Errors
+    /// If the FEN can't be parsed or isn't a legal position
+    pub fn from_fen(fen: &str) -> Result<Self, InvalidStartingPosition> {
+        let builder = fen
+            .parse::<PositionBuilder>()
+            .map_err(InvalidStartingPosition::InvalidFen)?;
+        let position =
+            LegalPosition::try_from(builder).map_err(InvalidStartingPosition::IllegalPosition)?;
+        let starting_fullmove = fen
+            .split_whitespace()
+            .nth(5)
+            .and_then(|fullmove| fullmove.parse().ok())
+            .unwrap_or(1)
+            .max(1);
+
+        Ok(Self {
+            starting_fen: fen.trim().to_string(),
+            starting_fullmove,
+            hashes: alloc::vec![position.hash()],
+            starting_position: position.clone(),
+            position,
+            played: Vec::new(),
+            taken_back: Vec::new(),
+        })
+    }
+
+    /// The FEN the game started from
+    #[must_use]
+    pub fn starting_fen(&self) -> &str {
+        &self.starting_fen
+    }
+
+    /// The fullmove number of the position the game started from
+    #[must_use]
+    pub const fn starting_fullmove(&self) -> u16 {
+        self.starting_fullmove
+    }
+
+    /// The position the game started from
+    #[must_use]
+    pub const fn starting_position(&self) -> &LegalPosition {
+        &self.starting_position
+    }
+
+    /// The current position, after every move played
+    #[must_use]
+    pub const fn position(&self) -> &LegalPosition {
+        &self.position
+    }
+
+    /// How many plies have been played to reach the current position
+    #[must_use]
+    pub const fn ply(&self) -> usize {
+        self.played.len()
+    }
+
+    /// The last ply the game can [go to](Self::go_to_ply), including the moves taken back
+    #[must_use]
+    pub const fn last_ply(&self) -> usize {
+        self.played.len() + self.taken_back.len()
+    }
+
+    /// The fullmove number of the current position, starting from the game's fullmove number and increasing after
+    /// each of black's moves
+    #[must_use]
+    pub fn fullmove_number(&self) -> u16 {
+        let moves = match self.starting_position.player_to_move() {
+            PlayerColor::White => self.ply() / 2,
+            PlayerColor::Black => self.ply().div_ceil(2),
+        };
+
+        self.starting_fullmove
+            .saturating_add(u16::try_from(moves).unwrap_or(u16::MAX))
+    }
+
+    /// The moves played to reach the current position, in order
+    #[must_use]
+    pub fn moves(&self) -> impl ExactSizeIterator<Item = ChessMove> + '_ {
+        self.played.iter().map(|&(chess_move, _)| chess_move)
+    }
+
+    /// The last move played, if any
+    #[must_use]
+    pub fn last_move(&self) -> Option<ChessMove> {
+        self.played.last().map(|&(chess_move, _)| chess_move)
+    }
+
+    /// Make a move without checking it's legal
+    fn make_move(&mut self, chess_move: ChessMove) {
+        let state = self.position.make_move(chess_move);
+        self.played.push((chess_move, state));
+        self.hashes.push(self.position.hash());
+    }
+
+    /// Play a move in the current position
+    ///
+    /// Replaying the move that was taken back keeps the rest of the moves taken back after it, any other move
+    /// replaces them.
+    ///
+    /// # Errors
+    /// If the move isn't legal in the current position
+    pub fn push(&mut self, chess_move: ChessMove) -> Result<(), IllegalMove> {
+        if !self.position.generate_legal_moves().contains(&chess_move) {
+            return Err(IllegalMove(chess_move));
+        }
+        self.push_legal(chess_move);
+
+        Ok(())
+    }
+
+    /// Play a move already known to be legal in the current position (as one generated, parsed or found by a
+    /// search), like [`push`](Self::push) without generating the legal moves to check it
+    ///
+    /// The move is only checked in debug builds, playing an illegal move otherwise corrupts the game.
+    pub fn push_legal(&mut self, chess_move: ChessMove) {
+        debug_assert!(
+            self.position.generate_legal_moves().contains(&chess_move),
+            "{chess_move} is illegal in {}",
+            self.position
+        );
+        if self.taken_back.last() == Some(&chess_move) {
+            self.taken_back.pop();
+        } else {
+            self.taken_back.clear();
+        }
+        self.make_move(chess_move);
+    }
+
+    /// Take back the last move played, getting it, or `None` at the start of the game
+    ///
+    /// The move is kept to replay with [`go_to_ply`](Self::go_to_ply) until a different move is pushed.
+    pub fn pop(&mut self) -> Option<ChessMove> {
+        let (chess_move, state) = self.played.pop()?;
+        self.position.unmake_move(chess_move, state);
+        self.hashes.pop();
+        self.taken_back.push(chess_move);
+
+        Some(chess_move)
+    }
+
+    /// Take back or replay moves to reach the position after a number of plies, getting whether it's within the
+    /// [last ply](Self::last_ply)
+    pub fn go_to_ply(&mut self, ply: usize) -> bool {
+        if ply > self.last_ply() {
+            return false;
+        }
+        while self.ply() > ply {
+            self.pop();
+        }
+        while self.ply() < ply {
+            let Some(chess_move) = self.taken_back.pop() else {
+                break;
+            };
+            self.make_move(chess_move);
+        }
+
+        true
+    }
+
+    /// The hashes of the positions before the current one, oldest first, for a search to detect repetitions of them
+    #[must_use]
+    pub fn history(&self) -> &[ZobristHash] {
+        &self.hashes[..self.hashes.len() - 1]
+    }
+
+    /// How many times the current position has been reached, counting only the positions since the last capture or
+    /// pawn move with the same player to move
+    #[must_use]
+    pub fn repetitions(&self) -> usize {
+        let halfmove_clock = usize::from(u8::from(PlyCount::from(self.position.halfmove_clock())));
+        let hash = self.position.hash();
+
+        self.hashes
+            .iter()
+            .rev()
+            .take(halfmove_clock + 1)
+            .step_by(2)
+            .filter(|&&previous| previous == hash)
+            .count()
+    }
+
+    /// How the game has ended in the current position, `None` if it's still going
+    ///
+    /// Checkmate and stalemate take precedence over the draws claimable by the fifty-move rule and threefold
+    /// repetition.
+    #[must_use]
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        if self.position.generate_legal_moves().is_empty() {
+            return Some(if self.position.in_check() {
+                GameOutcome::Checkmate(self.position.player_to_move().switch())
+            } else {
+                GameOutcome::Stalemate
+            });
+        }
+
+        let halfmove_clock = usize::from(u8::from(PlyCount::from(self.position.halfmove_clock())));
+        if halfmove_clock >= HALF_MOVE_LIMIT {
+            Some(GameOutcome::FiftyMoveRule)
+        } else if self.repetitions() >= 3 {
+            Some(GameOutcome::ThreefoldRepetition)
+        } else if is_insufficient_material(&self.position) {
+            Some(GameOutcome::InsufficientMaterial)
+        } else {
+            None
+        }
+    }
+
+    /// The result of the game in the current position, [unknown](GameResult::Unknown) while it's still going
+    #[must_use]
+    pub fn result(&self) -> GameResult {
+        self.outcome()
+            .map_or(GameResult::Unknown, GameOutcome::result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chess_move::ChessMove;
+    use crate::game::{Game, GameOutcome, IllegalMove, InvalidStartingPosition};
+    use crate::pgn::GameResult;
+    use crate::player_color::PlayerColor;
+    use alloc::vec::Vec;
+    use test_case::test_case;
+
+    /// Play moves written in SAN
+    fn play(game: &mut Game, moves: &[&str]) {
+        for san in moves {
+            let chess_move = game.position().parse_san(san).unwrap();
+            game.push(chess_move).unwrap();
+        }
+    }
+
+    #[test]
+    fn push_and_pop_restore_the_position() {
+        let mut game = Game::default();
+        let start = game.position().clone();
+        play(&mut game, &["e4", "d5", "exd5", "Qxd5", "Nc3"]);
+        let moves = game.moves().collect::<Vec<_>>();
+        assert_eq!(moves.len(), 5);
+        assert_eq!(game.last_move(), moves.last().copied());
+
+        for expected in moves.iter().rev() {
+            assert_eq!(game.pop(), Some(*expected));
+        }
+        assert_eq!(game.pop(), None);
+        assert_eq!(game.position(), &start);
+        assert_eq!(game.last_ply(), 5);
+    }
+
+    #[test]
+    fn go_to_ply_replays_moves_taken_back() {
+        let mut game = Game::default();
+        play(&mut game, &["e4", "e5", "Nf3", "Nc6"]);
+        let end = game.position().clone();
+
+        assert!(game.go_to_ply(1));
+        assert_eq!(game.ply(), 1);
+        assert!(!game.go_to_ply(5));
+        assert!(game.go_to_ply(4));
+        assert_eq!(game.position(), &end);
+
+        // Replaying the next move keeps the rest, a different move replaces them
+        game.go_to_ply(2);
+        play(&mut game, &["Nf3"]);
+        assert_eq!(game.last_ply(), 4);
+        play(&mut game, &["Nf6"]);
+        assert_eq!(game.last_ply(), 4);
+        game.go_to_ply(2);
+        play(&mut game, &["Bc4"]);
+        assert_eq!(game.last_ply(), 3);
+    }
+
+    #[test]
+    fn push_rejects_illegal_moves() {
+        let mut game = Game::default();
+        let mut other = Game::default();
+        play(&mut other, &["e4"]);
+        let illegal: ChessMove = other.position().parse_san("e5").unwrap();
+        let moves = game.moves().len();
+
+        assert_eq!(game.push(illegal), Err(IllegalMove(illegal)));
+        assert_eq!(game.moves().len(), moves);
+    }
+
+    #[test]
+    fn push_legal_plays_moves() {
+        let mut game = Game::default();
+        let chess_move = game.position().parse_san("e4").unwrap();
+        game.push_legal(chess_move);
+
+        assert_eq!(game.last_move(), Some(chess_move));
+        assert_eq!(game.history().len(), 1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "is illegal"]
+    fn push_legal_checks_moves_in_debug_builds() {
+        let mut game = Game::default();
+        let mut other = Game::default();
+        play(&mut other, &["e4"]);
+        game.push_legal(other.position().parse_san("e5").unwrap());
+    }
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0, 1)]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 3, 2)]
+    #[test_case("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 12", 0, 12; "black to move")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 12", 1, 13; "after black moves")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -", 2, 2; "missing fullmove")]
+    fn fullmove_number_works(fen: &str, plies: usize, expected: u16) {
+        let mut game = Game::from_fen(fen).unwrap();
+        for _ in 0..plies {
+            let chess_move = game.position().generate_legal_moves()[0];
+            game.push(chess_move).unwrap();
+        }
+
+        assert_eq!(game.starting_fen(), fen);
+        assert_eq!(game.fullmove_number(), expected);
+    }
+
+    #[test]
+    fn from_fen_rejects_invalid_positions() {
+        assert!(matches!(
+            Game::from_fen("not a fen"),
+            Err(InvalidStartingPosition::InvalidFen(_))
+        ));
+    }
+
+    #[test_case("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3", Some(GameOutcome::Checkmate(PlayerColor::Black)); "checkmate")]
+    #[test_case("k7/8/1QK5/8/8/8/8/8 b - - 0 1", Some(GameOutcome::Stalemate); "stalemate")]
+    #[test_case("k7/8/2K5/8/8/8/8/7R b - - 100 80", Some(GameOutcome::FiftyMoveRule); "fifty moves")]
+    #[test_case("k7/8/2K5/8/8/8/8/7N b - - 0 1", Some(GameOutcome::InsufficientMaterial); "lone knight")]
+    #[test_case("k7/8/2K5/8/8/8/8/B1b5 b - - 0 1", Some(GameOutcome::InsufficientMaterial); "same colored bishops")]
+    #[test_case("k7/8/2K5/8/8/8/8/1Bb5 b - - 0 1", None; "opposite colored bishops")]
+    #[test_case("k7/8/2K5/8/8/8/8/1N1N4 b - - 0 1", None; "two knights")]
+    #[test_case("k7/8/2K5/8/8/8/8/7R b - - 0 1", None; "still going")]
+    fn outcome_works(fen: &str, expected: Option<GameOutcome>) {
+        let game = Game::from_fen(fen).unwrap();
+
+        assert_eq!(game.outcome(), expected);
+        assert_eq!(
+            game.result(),
+            expected.map_or(GameResult::Unknown, GameOutcome::result)
+        );
+    }
+
+    #[test]
+    fn detects_threefold_repetition() {
+        let mut game = Game::default();
+        play(
+            &mut game,
+            &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"],
+        );
+        assert_eq!(game.repetitions(), 2);
+        assert_eq!(game.outcome(), None);
+
+        play(&mut game, &["Ng8"]);
+        assert_eq!(game.repetitions(), 3);
+        assert_eq!(game.outcome(), Some(GameOutcome::ThreefoldRepetition));
+        assert_eq!(game.result(), GameResult::Draw);
+
+        game.pop();
+        assert_eq!(game.outcome(), None);
+    }
+
+    #[test]
+    fn history_holds_the_positions_before_the_current_one() {
+        let mut game = Game::default();
+        assert!(game.history().is_empty());
+
+        let start = game.position().hash();
+        play(&mut game, &["Nf3", "Nf6"]);
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.history()[0], start);
+        assert!(!game.history().contains(&game.position().hash()));
+
+        play(&mut game, &["Ng1", "Ng8"]);
+        assert_eq!(game.history()[0], game.position().hash());
+    }
+}
@@ -100,7 +100,7 @@ mod test {
         let mut input = HalfMoveClock::new(PlyCount::new(input)).expect("invalid test input");
         let expected =
             expected.map(|n| HalfMoveClock::new(PlyCount::new(n)).expect("invalid test setup"));
-        assert_eq!(input.increment().map(|_| input), expected);
+        assert_eq!(input.increment().map(|()| input), expected);
     }
 
     #[test]
@@ -10,7 +10,9 @@
     clippy::module_name_repetitions,
     clippy::cast_possible_truncation,
     clippy::cast_possible_wrap,
-    clippy::cast_sign_loss
+    clippy::cast_sign_loss,
+    clippy::multiple_crate_versions,
+    clippy::non_std_lazy_statics
 )]
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -27,10 +29,17 @@ pub mod chess_move;
 pub mod direction;
 /// The checkmate/checkmated plies or the approximate material/positional advantage for a given side
 pub mod evaluation;
+/// A game of chess played move by move, with its moves to take back and replay and how it ended
+pub mod game;
 /// A clock for keeping track of half moves without a capture or pawn push before a draw
 pub mod half_move_clock;
+/// Efficiently updatable neural network evaluation
+#[cfg(feature = "nnue")]
+pub mod nnue;
 /// A counter for keeping track of visited chess positions
 pub mod node_count;
+/// Reading and writing games in Portable Game Notation
+pub mod pgn;
 /// A counter for the sum of the number of a piece on a board
 pub mod piece_count;
 /// A piece that can be placed on the board
@@ -39,9 +48,23 @@ pub mod pieces;
 pub mod player_color;
 /// The depth or number of single moves deep into a game
 pub mod ply_count;
+/// Polyglot position keys and opening books
+pub mod polyglot;
 /// The total representation of a single legal state of a game of chess and its internal logic
 pub mod position;
+/// Finding the best move of a position with an alpha-beta search
+#[cfg(feature = "std")]
+pub mod search;
+/// Elo estimates and sequential probability ratio tests for the results of engine matches
+#[cfg(feature = "std")]
+pub mod sprt;
 /// A single tile on a board where a piece can be placed
 pub mod square;
+/// Syzygy endgame tablebase probing
+#[cfg(feature = "std")]
+pub mod tablebase;
+/// Texel-style fitting of the evaluation parameters to the results of labelled positions
+#[cfg(feature = "std")]
+pub mod tuning;
 /// Board transposition hashing (if a position is identical in terms of play but could be arrived at via different moves)
 pub mod zobrist;
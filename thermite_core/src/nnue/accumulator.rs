@@ -0,0 +1,113 @@
+use crate::evaluation::PawnEvaluation;
+use crate::nnue::{simd, Network};
+use crate::pieces::PlacedPiece;
+use crate::player_color::PlayerColor;
+use crate::square::Square;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use enum_map::EnumMap;
+
+/// The hidden layer of a [`Network`] for both [perspectives](PlayerColor), kept up to date as the board changes
+#[derive(Clone)]
+pub struct Accumulator {
+    network: Arc<Network>,
+    values: EnumMap<PlayerColor, Vec<i16>>,
+}
+
+impl PartialEq for Accumulator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.network, &other.network) && self.values == other.values
+    }
+}
+
+impl Debug for Accumulator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Accumulator")
+            .field("network", &self.network)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Accumulator {
+    /// Create an accumulator with no active features (every neuron at its bias)
+    #[must_use]
+    pub fn new(network: Arc<Network>) -> Self {
+        let biases = network.feature_biases().to_vec();
+
+        Self {
+            network,
+            values: EnumMap::from_fn(|_| biases.clone()),
+        }
+    }
+
+    /// Get the [`Network`] the accumulator belongs to
+    #[must_use]
+    pub const fn network(&self) -> &Arc<Network> {
+        &self.network
+    }
+
+    /// Recompute a [perspective](PlayerColor) from scratch given every [piece](PlacedPiece) on the board
+    pub fn refresh(
+        &mut self,
+        perspective: PlayerColor,
+        king_square: Square,
+        pieces: impl Iterator<Item = PlacedPiece>,
+    ) {
+        let values = &mut self.values[perspective];
+        values.copy_from_slice(self.network.feature_biases());
+        for placed_piece in pieces {
+            let feature = Network::feature_index(perspective, king_square, placed_piece);
+            simd::add_assign(values, self.network.feature_weights(feature));
+        }
+    }
+
+    /// Activate a [piece](PlacedPiece)'s feature for a [perspective](PlayerColor) with its king on a [square](Square)
+    pub fn add_piece(
+        &mut self,
+        perspective: PlayerColor,
+        king_square: Square,
+        placed_piece: PlacedPiece,
+    ) {
+        let feature = Network::feature_index(perspective, king_square, placed_piece);
+        simd::add_assign(
+            &mut self.values[perspective],
+            self.network.feature_weights(feature),
+        );
+    }
+
+    /// Deactivate a [piece](PlacedPiece)'s feature for a [perspective](PlayerColor) with its king on a [square](Square)
+    pub fn remove_piece(
+        &mut self,
+        perspective: PlayerColor,
+        king_square: Square,
+        placed_piece: PlacedPiece,
+    ) {
+        let feature = Network::feature_index(perspective, king_square, placed_piece);
+        simd::sub_assign(
+            &mut self.values[perspective],
+            self.network.feature_weights(feature),
+        );
+    }
+
+    /// Move a [piece](PlacedPiece)'s feature to another [square](Square) for a [perspective](PlayerColor) with its king on a [square](Square)
+    pub fn move_piece(
+        &mut self,
+        perspective: PlayerColor,
+        king_square: Square,
+        from: PlacedPiece,
+        to: Square,
+    ) {
+        self.remove_piece(perspective, king_square, from);
+        self.add_piece(perspective, king_square, from.owned_piece.placed_on(to));
+    }
+
+    /// Evaluate the position from the perspective of the [player to move](PlayerColor)
+    #[must_use]
+    pub fn evaluate(&self, player_to_move: PlayerColor) -> PawnEvaluation {
+        self.network.evaluate(
+            &self.values[player_to_move],
+            &self.values[player_to_move.switch()],
+        )
+    }
+}
@@ -0,0 +1,16 @@
+//! A simple perspective network with `HalfKA` input features.
+//!
+//! Each (king square, colored piece, square) feature feeds a single hidden layer for each side,
+//! the clipped activations of both sides (side to move first) then feed a single output.
+//!
+//! The hidden layer is kept as an [`Accumulator`] inside the [position](crate::position::LegalPosition) and updated
+//! incrementally as pieces are added, removed and moved, only being refreshed from scratch when a king moves.
+
+mod accumulator;
+mod network;
+mod simd;
+
+pub use accumulator::Accumulator;
+#[cfg(test)]
+pub(crate) use network::test::random_network_bytes;
+pub use network::{Network, NetworkLoadError};
@@ -0,0 +1,238 @@
+use crate::evaluation::PawnEvaluation;
+use crate::nnue::simd::{self, LANES};
+use crate::pieces::{PieceType, PlacedPiece};
+use crate::player_color::PlayerColor;
+use crate::square::Square;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use enum_map::Enum;
+
+/// The identifier at the start of every network file
+const MAGIC: [u8; 4] = *b"THNN";
+/// The only supported version of the network file layout
+const VERSION: u32 = 1;
+/// The number of bytes before the weights (magic, version and hidden layer size)
+const HEADER_SIZE: usize = 12;
+
+/// Errors that can occur while loading a [`Network`] from bytes
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum NetworkLoadError {
+    /// The data doesn't start with the expected `THNN` magic
+    InvalidMagic,
+    /// The network was written with a layout version that isn't supported
+    UnsupportedVersion(u32),
+    /// The hidden layer size is zero or not a multiple of the SIMD lane count
+    InvalidHiddenSize(u32),
+    /// The data is not the length required by the hidden layer size
+    UnexpectedLength {
+        /// The number of bytes required for the declared hidden layer size
+        expected: usize,
+        /// The number of bytes provided
+        actual: usize,
+    },
+}
+
+/// The quantized weights of an NNUE evaluation network
+///
+/// The file layout (all little-endian) is the `THNN` magic, a `u32` version, a `u32` hidden layer size `H`, then
+/// `Network::INPUTS * H` `i16` feature weights (feature major), `H` `i16` feature biases, `2 * H` `i16` output weights
+/// (side to move first) and finally an `i32` output bias.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Network {
+    hidden_size: usize,
+    feature_weights: Vec<i16>,
+    feature_biases: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i32,
+}
+
+impl Debug for Network {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Network")
+            .field("hidden_size", &self.hidden_size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Read a sequence of little-endian `i16`s
+fn read_i16s(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect()
+}
+
+impl Network {
+    /// The number of input features per perspective: every (king square, colored piece, square) combination
+    pub const INPUTS: usize =
+        Square::LENGTH * PlayerColor::LENGTH * PieceType::LENGTH * Square::LENGTH;
+    /// The quantization of the hidden layer, activations are clipped to `0..=ACTIVATION_RANGE`
+    pub const ACTIVATION_RANGE: i16 = 255;
+    /// The quantization of the output weights
+    pub const OUTPUT_QUANTIZATION: i32 = 64;
+    /// The number of centipawns a raw (de-quantized) output of 1.0 is worth
+    pub const EVALUATION_SCALE: i32 = 400;
+
+    /// Load a network from the bytes of a network file
+    ///
+    /// # Errors
+    /// If the header is invalid or the length of the data doesn't match the header's hidden layer size
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NetworkLoadError> {
+        if bytes.get(..4) != Some(&MAGIC) {
+            return Err(NetworkLoadError::InvalidMagic);
+        }
+        let read_u32 = |offset: usize| {
+            bytes
+                .get(offset..offset + 4)
+                .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        };
+        let version = read_u32(4).ok_or(NetworkLoadError::InvalidMagic)?;
+        if version != VERSION {
+            return Err(NetworkLoadError::UnsupportedVersion(version));
+        }
+        let declared_hidden_size = read_u32(8).ok_or(NetworkLoadError::InvalidMagic)?;
+        let hidden_size = declared_hidden_size as usize;
+        if hidden_size == 0 || !hidden_size.is_multiple_of(LANES) {
+            return Err(NetworkLoadError::InvalidHiddenSize(declared_hidden_size));
+        }
+
+        let feature_weights_size = Self::INPUTS * hidden_size * 2;
+        let feature_biases_size = hidden_size * 2;
+        let output_weights_size = hidden_size * 2 * 2;
+        let expected =
+            HEADER_SIZE + feature_weights_size + feature_biases_size + output_weights_size + 4;
+        if bytes.len() != expected {
+            return Err(NetworkLoadError::UnexpectedLength {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+
+        let (feature_weights, rest) = bytes[HEADER_SIZE..].split_at(feature_weights_size);
+        let (feature_biases, rest) = rest.split_at(feature_biases_size);
+        let (output_weights, output_bias) = rest.split_at(output_weights_size);
+
+        Ok(Self {
+            hidden_size,
+            feature_weights: read_i16s(feature_weights),
+            feature_biases: read_i16s(feature_biases),
+            output_weights: read_i16s(output_weights),
+            output_bias: i32::from_le_bytes([
+                output_bias[0],
+                output_bias[1],
+                output_bias[2],
+                output_bias[3],
+            ]),
+        })
+    }
+
+    /// Get the number of neurons in the hidden layer (for each perspective)
+    #[must_use]
+    pub const fn hidden_size(&self) -> usize {
+        self.hidden_size
+    }
+
+    /// Get the index of the input feature for a [piece](PlacedPiece) as seen by a [perspective](PlayerColor) with its king on a [square](Square).
+    /// Black's perspective is flipped vertically so both sides see themselves at the bottom of the board.
+    pub(crate) fn feature_index(
+        perspective: PlayerColor,
+        king_square: Square,
+        placed_piece: PlacedPiece,
+    ) -> usize {
+        let orient = |square: Square| match perspective {
+            PlayerColor::White => square as usize,
+            PlayerColor::Black => square as usize ^ Square::A8 as usize,
+        };
+        let PlacedPiece {
+            owned_piece,
+            square,
+        } = placed_piece;
+        let relative_player = usize::from(owned_piece.player != perspective);
+        let piece_index = relative_player * PieceType::LENGTH + owned_piece.piece.into_usize();
+
+        (orient(king_square) * PlayerColor::LENGTH * PieceType::LENGTH + piece_index)
+            * Square::LENGTH
+            + orient(square)
+    }
+
+    /// Get the hidden layer weights of a single input feature
+    pub(crate) fn feature_weights(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden_size..(feature + 1) * self.hidden_size]
+    }
+
+    /// Get the hidden layer biases, the starting point of every accumulator
+    pub(crate) fn feature_biases(&self) -> &[i16] {
+        &self.feature_biases
+    }
+
+    /// Run the output layer over the hidden layer of the side to move and their opponent
+    pub(crate) fn evaluate(&self, player_to_move: &[i16], opponent: &[i16]) -> PawnEvaluation {
+        let (player_weights, opponent_weights) = self.output_weights.split_at(self.hidden_size);
+        let output = simd::clipped_relu_dot(player_to_move, player_weights, Self::ACTIVATION_RANGE)
+            + simd::clipped_relu_dot(opponent, opponent_weights, Self::ACTIVATION_RANGE)
+            + self.output_bias;
+        let centipawns = i64::from(output) * i64::from(Self::EVALUATION_SCALE)
+            / (i64::from(Self::ACTIVATION_RANGE) * i64::from(Self::OUTPUT_QUANTIZATION));
+
+        PawnEvaluation::from_centipawns(
+            centipawns.clamp(i64::from(i16::MIN), i64::from(i16::MAX)) as i16
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use crate::nnue::{Network, NetworkLoadError};
+    use alloc::vec::Vec;
+
+    /// Build the bytes of a network file with small random weights
+    pub fn random_network_bytes(hidden_size: u32) -> Vec<u8> {
+        let hidden_size_usize = hidden_size as usize;
+        let mut bytes = Vec::from(*b"THNN");
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(hidden_size.to_le_bytes());
+        let weights = Network::INPUTS * hidden_size_usize + hidden_size_usize * 3;
+        for _ in 0..weights {
+            bytes.extend(fastrand::i16(-64..64).to_le_bytes());
+        }
+        bytes.extend(fastrand::i32(-1000..1000).to_le_bytes());
+
+        bytes
+    }
+
+    #[test]
+    fn from_bytes_works() {
+        let network = Network::from_bytes(&random_network_bytes(16)).unwrap();
+        assert_eq!(network.hidden_size(), 16);
+        assert_eq!(network.feature_biases().len(), 16);
+        assert_eq!(network.feature_weights(Network::INPUTS - 1).len(), 16);
+    }
+
+    #[test]
+    fn from_bytes_errors() {
+        let bytes = random_network_bytes(8);
+        assert_eq!(
+            Network::from_bytes(b"nope"),
+            Err(NetworkLoadError::InvalidMagic)
+        );
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 2;
+        assert_eq!(
+            Network::from_bytes(&wrong_version),
+            Err(NetworkLoadError::UnsupportedVersion(2))
+        );
+        let mut wrong_hidden_size = bytes.clone();
+        wrong_hidden_size[8] = 7;
+        assert_eq!(
+            Network::from_bytes(&wrong_hidden_size),
+            Err(NetworkLoadError::InvalidHiddenSize(7))
+        );
+        assert_eq!(
+            Network::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(NetworkLoadError::UnexpectedLength {
+                expected: bytes.len(),
+                actual: bytes.len() - 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! The vectorized kernels used for updating the accumulator and running the output layer.
+//! Uses SSE2 on `x86_64` (which every `x86_64` CPU supports), falling back to plain loops elsewhere.
+
+/// The number of 16-bit lanes in a single vector, the hidden layer size must be a multiple of this
+pub const LANES: usize = 8;
+
+/// Portable implementations of each kernel (only used to check the vectorized kernels against on `x86_64`)
+#[cfg_attr(target_arch = "x86_64", allow(dead_code))]
+pub mod scalar {
+    /// Add a feature's weights into the accumulator
+    pub fn add_assign(accumulator: &mut [i16], weights: &[i16]) {
+        for (value, &weight) in accumulator.iter_mut().zip(weights) {
+            *value = value.wrapping_add(weight);
+        }
+    }
+
+    /// Subtract a feature's weights from the accumulator
+    pub fn sub_assign(accumulator: &mut [i16], weights: &[i16]) {
+        for (value, &weight) in accumulator.iter_mut().zip(weights) {
+            *value = value.wrapping_sub(weight);
+        }
+    }
+
+    /// Clamp each value between `0` and `max` and take the dot product with the weights
+    pub fn clipped_relu_dot(values: &[i16], weights: &[i16], max: i16) -> i32 {
+        values
+            .iter()
+            .zip(weights)
+            .map(|(&value, &weight)| i32::from(value.clamp(0, max)) * i32::from(weight))
+            .sum()
+    }
+}
+
+/// SSE2 implementations of each kernel
+#[cfg(target_arch = "x86_64")]
+pub mod sse2 {
+    use super::LANES;
+    use core::arch::x86_64::{
+        __m128i, _mm_add_epi16, _mm_add_epi32, _mm_cvtsi128_si32, _mm_loadu_si128, _mm_madd_epi16,
+        _mm_max_epi16, _mm_min_epi16, _mm_set1_epi16, _mm_setzero_si128, _mm_shuffle_epi32,
+        _mm_storeu_si128, _mm_sub_epi16,
+    };
+
+    /// Apply a lane-wise operation to the accumulator and the weights, storing the result in the accumulator
+    fn apply(accumulator: &mut [i16], weights: &[i16], operation: fn(__m128i, __m128i) -> __m128i) {
+        assert_eq!(accumulator.len(), weights.len());
+        assert_eq!(accumulator.len() % LANES, 0);
+        for (values, weights) in accumulator
+            .chunks_exact_mut(LANES)
+            .zip(weights.chunks_exact(LANES))
+        {
+            // SAFETY: each chunk is exactly `LANES` (128 bits) of `i16`, and unaligned loads/stores are used
+            unsafe {
+                let sum = operation(
+                    _mm_loadu_si128(values.as_ptr().cast()),
+                    _mm_loadu_si128(weights.as_ptr().cast()),
+                );
+                _mm_storeu_si128(values.as_mut_ptr().cast(), sum);
+            }
+        }
+    }
+
+    /// Add a feature's weights into the accumulator
+    pub fn add_assign(accumulator: &mut [i16], weights: &[i16]) {
+        // SAFETY: SSE2 is part of the `x86_64` baseline
+        apply(accumulator, weights, |a, b| unsafe { _mm_add_epi16(a, b) });
+    }
+
+    /// Subtract a feature's weights from the accumulator
+    pub fn sub_assign(accumulator: &mut [i16], weights: &[i16]) {
+        // SAFETY: SSE2 is part of the `x86_64` baseline
+        apply(accumulator, weights, |a, b| unsafe { _mm_sub_epi16(a, b) });
+    }
+
+    /// Clamp each value between `0` and `max` and take the dot product with the weights
+    pub fn clipped_relu_dot(values: &[i16], weights: &[i16], max: i16) -> i32 {
+        assert_eq!(values.len(), weights.len());
+        assert_eq!(values.len() % LANES, 0);
+        // SAFETY: each chunk is exactly `LANES` (128 bits) of `i16`, unaligned loads are used and SSE2 is part of the `x86_64` baseline
+        unsafe {
+            let zero = _mm_setzero_si128();
+            let max = _mm_set1_epi16(max);
+            let mut sum = _mm_setzero_si128();
+            for (values, weights) in values.chunks_exact(LANES).zip(weights.chunks_exact(LANES)) {
+                let values = _mm_loadu_si128(values.as_ptr().cast());
+                let clipped = _mm_min_epi16(_mm_max_epi16(values, zero), max);
+                let weights = _mm_loadu_si128(weights.as_ptr().cast());
+                sum = _mm_add_epi32(sum, _mm_madd_epi16(clipped, weights));
+            }
+            // Horizontally add the four 32-bit lanes
+            let sum = _mm_add_epi32(sum, _mm_shuffle_epi32::<0b0100_1110>(sum));
+            let sum = _mm_add_epi32(sum, _mm_shuffle_epi32::<0b1011_0001>(sum));
+
+            _mm_cvtsi128_si32(sum)
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub use sse2::{add_assign, clipped_relu_dot, sub_assign};
+
+#[cfg(not(target_arch = "x86_64"))]
+pub use scalar::{add_assign, clipped_relu_dot, sub_assign};
+
+#[cfg(test)]
+mod test {
+    use crate::nnue::simd::{scalar, LANES};
+    use alloc::vec::Vec;
+
+    fn random_values(length: usize) -> Vec<i16> {
+        (0..length).map(|_| fastrand::i16(-600..600)).collect()
+    }
+
+    #[test]
+    fn kernels_match_scalar() {
+        for _ in 0..100 {
+            let length = LANES * fastrand::usize(1..8);
+            let accumulator = random_values(length);
+            let weights = random_values(length);
+
+            let mut expected = accumulator.clone();
+            scalar::add_assign(&mut expected, &weights);
+            let mut actual = accumulator.clone();
+            super::add_assign(&mut actual, &weights);
+            assert_eq!(actual, expected);
+
+            scalar::sub_assign(&mut expected, &weights);
+            super::sub_assign(&mut actual, &weights);
+            assert_eq!(actual, expected);
+            assert_eq!(actual, accumulator);
+
+            assert_eq!(
+                super::clipped_relu_dot(&accumulator, &weights, 255),
+                scalar::clipped_relu_dot(&accumulator, &weights, 255)
+            );
+        }
+    }
+}
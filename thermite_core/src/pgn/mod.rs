@@ -0,0 +1,304 @@
+//! Reading and writing games in Portable Game Notation (PGN).
+//!
+//! A game is made up of its tag pairs, a mainline of moves (each with its own NAGs, comments and variations) and the
+//! game's result. Moves are stored as [`ChessMove`]s, resolved from (and written back to) standard algebraic notation
+//! against the position they're played in.
+
+mod reader;
+mod writer;
+
+pub use crate::game::IllegalMove;
+pub use reader::{PgnError, PgnErrorKind, PgnReader};
+
+use crate::chess_move::ChessMove;
+use crate::position::{LegalPosition, PositionBuilder};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+/// The FEN of the standard starting position
+const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The tags every game has, in the order they're exported
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// The result of a game, as written at the end of its movetext
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum GameResult {
+    /// White won (`1-0`)
+    WhiteWins,
+    /// Black won (`0-1`)
+    BlackWins,
+    /// The game was drawn (`1/2-1/2`)
+    Draw,
+    /// The game is still going, was abandoned or the result is unknown (`*`)
+    #[default]
+    Unknown,
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::WhiteWins => "1-0",
+            Self::BlackWins => "0-1",
+            Self::Draw => "1/2-1/2",
+            Self::Unknown => "*",
+        })
+    }
+}
+
+/// The error for a string that isn't a [`GameResult`]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct IllegalGameResult;
+
+impl FromStr for GameResult {
+    type Err = IllegalGameResult;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(Self::WhiteWins),
+            "0-1" => Ok(Self::BlackWins),
+            "1/2-1/2" => Ok(Self::Draw),
+            "*" => Ok(Self::Unknown),
+            _ => Err(IllegalGameResult),
+        }
+    }
+}
+
+/// A single move of a [line](PgnLine), with its annotations and the variations that could have been played instead
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PgnMove {
+    /// The move played
+    pub chess_move: ChessMove,
+    /// The numeric annotation glyphs (`$1` for a good move, `$2` for a mistake, etc) of the move
+    pub nags: Vec<u8>,
+    /// The comments following the move
+    pub comments: Vec<String>,
+    /// Alternative lines starting from the same position as this move, played instead of it
+    pub variations: Vec<PgnLine>,
+}
+
+impl PgnMove {
+    /// Create a move without any annotations or variations
+    #[must_use]
+    pub const fn new(chess_move: ChessMove) -> Self {
+        Self {
+            chess_move,
+            nags: Vec::new(),
+            comments: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+}
+
+/// A sequence of moves, either the game's mainline or a variation
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct PgnLine {
+    /// The comments before the first move of the line
+    pub comments: Vec<String>,
+    /// The moves of the line in the order they're played
+    pub moves: Vec<PgnMove>,
+}
+
+/// A single game of chess, as read from (or written to) PGN
+#[derive(Clone, PartialEq, Debug)]
+pub struct PgnGame {
+    tags: Vec<(String, String)>,
+    starting_position: LegalPosition,
+    starting_fullmove: u16,
+    mainline: PgnLine,
+    result: GameResult,
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        let starting_position = LegalPosition::try_from(
+            STARTING_FEN
+                .parse::<PositionBuilder>()
+                .expect("starting position FEN is valid"),
+        )
+        .expect("starting position is legal");
+
+        Self {
+            tags: Vec::new(),
+            starting_position,
+            starting_fullmove: 1,
+            mainline: PgnLine::default(),
+            result: GameResult::default(),
+        }
+    }
+}
+
+impl PgnGame {
+    /// Create a game starting from a FEN, setting its `SetUp` and `FEN` tags
+    ///
+    /// # Errors
+    /// If the FEN can't be parsed or isn't a legal position
+    pub fn from_fen(fen: &str) -> Result<Self, PgnErrorKind> {
+        let mut game = Self::default();
+        game.set_tag("SetUp", "1");
+        game.set_tag("FEN", fen);
+        game.reset_starting_position()?;
+
+        Ok(game)
+    }
+
+    /// Create a game from the standard starting position playing a list of moves
+    ///
+    /// ```
+    /// use thermite_core::pgn::PgnGame;
+    ///
+    /// let e4 = PgnGame::default().final_position().parse_san("e4").unwrap();
+    /// let game = PgnGame::from_moves([e4]).unwrap();
+    /// assert!(game.to_string().ends_with("\n1. e4 *\n"));
+    /// ```
+    ///
+    /// # Errors
+    /// If any of the moves aren't legal when they're played
+    pub fn from_moves(moves: impl IntoIterator<Item = ChessMove>) -> Result<Self, IllegalMove> {
+        let mut game = Self::default();
+        game.push_moves(moves)?;
+
+        Ok(game)
+    }
+
+    /// Every tag pair of the game, in the order they were read or set
+    #[must_use]
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// Get the value of a tag, if it's set
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set the value of a tag, replacing any existing value
+    ///
+    /// Setting the `FEN` tag doesn't change the starting position of a game that's already been read.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Parse the starting position (and fullmove number) from the `FEN` tag, or the standard starting position without one
+    fn reset_starting_position(&mut self) -> Result<(), PgnErrorKind> {
+        let fen = self.tag("FEN").unwrap_or(STARTING_FEN);
+        let builder = fen
+            .parse::<PositionBuilder>()
+            .map_err(PgnErrorKind::InvalidFen)?;
+        self.starting_fullmove = fen
+            .split_whitespace()
+            .nth(5)
+            .and_then(|fullmove| fullmove.parse().ok())
+            .unwrap_or(1)
+            .max(1);
+        self.starting_position =
+            LegalPosition::try_from(builder).map_err(PgnErrorKind::IllegalPosition)?;
+
+        Ok(())
+    }
+
+    /// The result of the game
+    #[must_use]
+    pub const fn result(&self) -> GameResult {
+        self.result
+    }
+
+    /// Set the result of the game, which is also written as its `Result` tag
+    pub const fn set_result(&mut self, result: GameResult) {
+        self.result = result;
+    }
+
+    /// The position the game starts from
+    #[must_use]
+    pub const fn starting_position(&self) -> &LegalPosition {
+        &self.starting_position
+    }
+
+    /// The mainline of the game, with its annotations and variations
+    #[must_use]
+    pub const fn mainline(&self) -> &PgnLine {
+        &self.mainline
+    }
+
+    /// The moves of the mainline
+    pub fn moves(&self) -> impl Iterator<Item = ChessMove> + '_ {
+        self.mainline
+            .moves
+            .iter()
+            .map(|pgn_move| pgn_move.chess_move)
+    }
+
+    /// Replay the mainline, getting the starting position followed by the position after each move
+    #[must_use]
+    pub fn positions(&self) -> Vec<LegalPosition> {
+        let mut position = self.starting_position.clone();
+        let mut positions = Vec::with_capacity(self.mainline.moves.len() + 1);
+        positions.push(position.clone());
+        for chess_move in self.moves() {
+            position.make_move(chess_move);
+            positions.push(position.clone());
+        }
+
+        positions
+    }
+
+    /// The position at the end of the mainline
+    #[must_use]
+    pub fn final_position(&self) -> LegalPosition {
+        let mut position = self.starting_position.clone();
+        for chess_move in self.moves() {
+            position.make_move(chess_move);
+        }
+
+        position
+    }
+
+    /// Play a move at the end of the mainline
+    ///
+    /// # Errors
+    /// If the move isn't legal at the end of the mainline
+    pub fn push_move(&mut self, chess_move: ChessMove) -> Result<(), IllegalMove> {
+        self.push_moves([chess_move])
+    }
+
+    /// Play several moves at the end of the mainline, keeping those played before any illegal move
+    ///
+    /// # Errors
+    /// If any of the moves aren't legal when they're played
+    pub fn push_moves(
+        &mut self,
+        moves: impl IntoIterator<Item = ChessMove>,
+    ) -> Result<(), IllegalMove> {
+        let mut position = self.final_position();
+        for chess_move in moves {
+            if !position.generate_legal_moves().contains(&chess_move) {
+                return Err(IllegalMove(chess_move));
+            }
+            position.make_move(chess_move);
+            self.mainline.moves.push(PgnMove::new(chess_move));
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for PgnGame {
+    type Err = PgnError;
+
+    /// Parse the first game of a PGN string
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PgnReader::new(s)
+            .next()
+            .unwrap_or_else(|| Err(PgnError::new(1, 1, PgnErrorKind::MissingGame)))
+    }
+}
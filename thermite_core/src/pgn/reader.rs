@@ -0,0 +1,624 @@
+use crate::chess_move::ChessMove;
+use crate::pgn::{GameResult, PgnGame, PgnLine, PgnMove};
+use crate::position::{FenParseError, IllegalPosition, LegalPosition, SanParseError};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::iter::Peekable;
+use core::str::Chars;
+
+/// What went wrong while reading a PGN game
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum PgnErrorKind {
+    /// A char that can't start any PGN token
+    UnexpectedChar(char),
+    /// A string that's missing its closing quote
+    UnterminatedString,
+    /// A brace comment that's missing its closing brace
+    UnterminatedComment,
+    /// A `$` that isn't followed by a number from 0 to 255
+    InvalidNag,
+    /// A tag pair that isn't a name and a string value in brackets
+    MalformedTag,
+    /// A token that can't appear where it does, like a NAG before any moves or a result inside a variation
+    UnexpectedToken,
+    /// A variation that's missing its closing parenthesis
+    UnterminatedVariation,
+    /// Movetext that doesn't end with a result (`1-0`, `0-1`, `1/2-1/2` or `*`)
+    MissingResult,
+    /// The input doesn't contain any games
+    MissingGame,
+    /// The `FEN` tag can't be parsed
+    InvalidFen(FenParseError),
+    /// The `FEN` tag isn't a legal position
+    IllegalPosition(IllegalPosition),
+    /// A move that can't be parsed, or isn't legal, in the position it's played in
+    InvalidMove(String, SanParseError),
+}
+
+/// An error reading a PGN game, along with where in the input it happened
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PgnError {
+    line: usize,
+    column: usize,
+    kind: PgnErrorKind,
+}
+
+impl PgnError {
+    /// Create an error at a (1-based) line and column of the input
+    pub(crate) const fn new(line: usize, column: usize, kind: PgnErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+
+    /// The line the error happened on, starting from 1
+    #[must_use]
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column (in chars) the error happened at, starting from 1
+    #[must_use]
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+
+    /// What went wrong
+    #[must_use]
+    pub const fn kind(&self) -> &PgnErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:?} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+/// A single PGN token
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Token {
+    /// A quoted string, with its escapes resolved
+    String(String),
+    /// A move, move number, result or tag name
+    Symbol(String),
+    /// A numeric annotation glyph (`$1`)
+    Nag(u8),
+    /// A brace (`{}`) or rest of line (`;`) comment
+    Comment(String),
+    /// `.` following a move number
+    Period,
+    /// `*` for an unknown result
+    Asterisk,
+    /// `[` starting a tag pair
+    LeftBracket,
+    /// `]` ending a tag pair
+    RightBracket,
+    /// `(` starting a variation
+    LeftParenthesis,
+    /// `)` ending a variation
+    RightParenthesis,
+}
+
+/// Splits PGN text into [`Token`]s, keeping track of where each one starts
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+/// A [`Token`] and its (line, column)
+type Located<T> = (T, usize, usize);
+
+/// Whether a char can continue a symbol, including the move suffix annotations (`!`, `?`)
+const fn is_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '#' | '=' | ':' | '-' | '/' | '!' | '?')
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    const fn error(&self, kind: PgnErrorKind) -> PgnError {
+        PgnError::new(self.line, self.column, kind)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    /// Skip the rest of the current line, returning what was skipped
+    fn rest_of_line(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.chars.next_if(|&c| c != '\n') {
+            self.column += 1;
+            text.push(c);
+        }
+
+        text
+    }
+
+    /// Skip whitespace and `%` escaped lines
+    fn skip_whitespace(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '%' && self.column == 1 {
+                self.rest_of_line();
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Get the next token, or `None` at the end of the input
+    fn next_token(&mut self) -> Result<Option<Located<Token>>, PgnError> {
+        self.skip_whitespace();
+        let (line, column) = (self.line, self.column);
+        let Some(c) = self.bump() else {
+            return Ok(None);
+        };
+
+        let token = match c {
+            '.' => Token::Period,
+            '*' => Token::Asterisk,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '(' => Token::LeftParenthesis,
+            ')' => Token::RightParenthesis,
+            ';' => Token::Comment(String::from(self.rest_of_line().trim())),
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match self.bump() {
+                        Some('}') => break,
+                        Some(c) => comment.push(c),
+                        None => {
+                            return Err(PgnError::new(
+                                line,
+                                column,
+                                PgnErrorKind::UnterminatedComment,
+                            ))
+                        }
+                    }
+                }
+                Token::Comment(comment.split_whitespace().collect::<Vec<_>>().join(" "))
+            }
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some('\\') if matches!(self.chars.peek(), Some('"' | '\\')) => {
+                            string.extend(self.bump());
+                        }
+                        Some('\n') | None => {
+                            return Err(PgnError::new(
+                                line,
+                                column,
+                                PgnErrorKind::UnterminatedString,
+                            ))
+                        }
+                        Some(c) => string.push(c),
+                    }
+                }
+                Token::String(string)
+            }
+            '$' => {
+                let mut digits = String::new();
+                while let Some(&digit) = self.chars.peek().filter(|c| c.is_ascii_digit()) {
+                    self.bump();
+                    digits.push(digit);
+                }
+                Token::Nag(
+                    digits
+                        .parse()
+                        .map_err(|_| PgnError::new(line, column, PgnErrorKind::InvalidNag))?,
+                )
+            }
+            c if c.is_ascii_alphanumeric() => {
+                let mut symbol = String::from(c);
+                while let Some(&c) = self.chars.peek().filter(|&&c| is_symbol_char(c)) {
+                    self.bump();
+                    symbol.push(c);
+                }
+                Token::Symbol(symbol)
+            }
+            c => return Err(PgnError::new(line, column, PgnErrorKind::UnexpectedChar(c))),
+        };
+
+        Ok(Some((token, line, column)))
+    }
+}
+
+/// Split the suffix annotation (`!`, `?`, `!!`, `??`, `!?` or `?!`) from a move, as its equivalent NAG
+fn split_suffix_annotation(san: &str) -> (&str, Option<u8>) {
+    let annotations = [
+        ("!!", 3),
+        ("??", 4),
+        ("!?", 5),
+        ("?!", 6),
+        ("!", 1),
+        ("?", 2),
+    ];
+
+    annotations
+        .iter()
+        .find_map(|&(suffix, nag)| san.strip_suffix(suffix).map(|san| (san, Some(nag))))
+        .unwrap_or((san, None))
+}
+
+/// Reads every game from PGN text, one at a time
+///
+/// ```
+/// use thermite_core::pgn::{GameResult, PgnReader};
+///
+/// let pgn = r#"[Event "First"]
+///
+/// 1. e4 e5 2. Nf3 {Developing} Nc6 (2... d6 $6) 1-0
+///
+/// [Event "Second"]
+///
+/// 1. d4 *
+/// "#;
+/// let games: Vec<_> = PgnReader::new(pgn).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(games.len(), 2);
+/// assert_eq!(games[0].tag("Event"), Some("First"));
+/// assert_eq!(games[0].moves().count(), 4);
+/// assert_eq!(games[0].result(), GameResult::WhiteWins);
+/// assert_eq!(games[1].result(), GameResult::Unknown);
+/// ```
+pub struct PgnReader<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Located<Token>>,
+    finished: bool,
+}
+
+impl<'a> PgnReader<'a> {
+    /// Create a reader for the games of some PGN text
+    #[must_use]
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            lexer: Lexer::new(text),
+            peeked: None,
+            finished: false,
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Located<Token>>, PgnError> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.lexer.next_token(),
+        }
+    }
+
+    fn peek_token(&mut self) -> Result<Option<&Token>, PgnError> {
+        if self.peeked.is_none() {
+            self.peeked = self.lexer.next_token()?;
+        }
+
+        Ok(self.peeked.as_ref().map(|(token, _, _)| token))
+    }
+
+    /// Read a `[Name "Value"]` tag pair, after its opening bracket
+    fn read_tag(&mut self, line: usize, column: usize) -> Result<(String, String), PgnError> {
+        let malformed = PgnError::new(line, column, PgnErrorKind::MalformedTag);
+        let Some((Token::Symbol(name), _, _)) = self.next_token()? else {
+            return Err(malformed);
+        };
+        let Some((Token::String(value), _, _)) = self.next_token()? else {
+            return Err(malformed);
+        };
+        let Some((Token::RightBracket, _, _)) = self.next_token()? else {
+            return Err(malformed);
+        };
+
+        Ok((name, value))
+    }
+
+    /// Read a line of moves played from a position, up to the end of the variation or the game's result
+    ///
+    /// The position is restored once the line has been read. Only the mainline ends with a result.
+    fn read_line(
+        &mut self,
+        position: &mut LegalPosition,
+        variation_start: Option<(usize, usize)>,
+    ) -> Result<(PgnLine, Option<GameResult>), PgnError> {
+        let mut line = PgnLine::default();
+        let mut played = Vec::new();
+        let unexpected = |line, column| PgnError::new(line, column, PgnErrorKind::UnexpectedToken);
+
+        let result = loop {
+            let Some((token, token_line, token_column)) = self.next_token()? else {
+                break Err(match variation_start {
+                    Some((line, column)) => {
+                        PgnError::new(line, column, PgnErrorKind::UnterminatedVariation)
+                    }
+                    None => self.lexer.error(PgnErrorKind::MissingResult),
+                });
+            };
+
+            match token {
+                Token::Period => {}
+                Token::Comment(comment) => match line.moves.last_mut() {
+                    Some(last) => last.comments.push(comment),
+                    None => line.comments.push(comment),
+                },
+                Token::Nag(nag) => match line.moves.last_mut() {
+                    Some(last) => last.nags.push(nag),
+                    None => break Err(unexpected(token_line, token_column)),
+                },
+                Token::Asterisk if variation_start.is_none() => {
+                    break Ok(Some(GameResult::Unknown))
+                }
+                Token::RightParenthesis if variation_start.is_some() => break Ok(None),
+                Token::LeftBracket if variation_start.is_none() => {
+                    break Err(PgnError::new(
+                        token_line,
+                        token_column,
+                        PgnErrorKind::MissingResult,
+                    ))
+                }
+                Token::LeftParenthesis => {
+                    let Some((last_move, last_state)) = played.pop() else {
+                        break Err(unexpected(token_line, token_column));
+                    };
+                    position.unmake_move(last_move, last_state);
+                    let variation = self.read_line(position, Some((token_line, token_column)));
+                    played.push((last_move, position.make_move(last_move)));
+                    match variation {
+                        Ok((variation, _)) => line
+                            .moves
+                            .last_mut()
+                            .expect("a move was played before the variation")
+                            .variations
+                            .push(variation),
+                        Err(error) => break Err(error),
+                    }
+                }
+                Token::Symbol(symbol) => {
+                    if let Ok(result) = symbol.parse::<GameResult>() {
+                        if variation_start.is_none() {
+                            break Ok(Some(result));
+                        }
+                        break Err(unexpected(token_line, token_column));
+                    }
+                    // Move numbers are only a guide, the position knows whose move it is
+                    if symbol.bytes().all(|c| c.is_ascii_digit()) {
+                        continue;
+                    }
+
+                    let (san, annotation) = split_suffix_annotation(&symbol);
+                    let chess_move: ChessMove = match position.parse_san(san) {
+                        Ok(chess_move) => chess_move,
+                        Err(error) => {
+                            break Err(PgnError::new(
+                                token_line,
+                                token_column,
+                                PgnErrorKind::InvalidMove(symbol, error),
+                            ))
+                        }
+                    };
+                    let mut pgn_move = PgnMove::new(chess_move);
+                    pgn_move.nags.extend(annotation);
+                    line.moves.push(pgn_move);
+                    played.push((chess_move, position.make_move(chess_move)));
+                }
+                _ => break Err(unexpected(token_line, token_column)),
+            }
+        };
+
+        for (chess_move, state) in played.into_iter().rev() {
+            position.unmake_move(chess_move, state);
+        }
+
+        result.map(|result| (line, result))
+    }
+
+    /// Read the next game, or `None` once there are no more games
+    fn read_game(&mut self) -> Result<Option<PgnGame>, PgnError> {
+        if self.peek_token()?.is_none() {
+            return Ok(None);
+        }
+
+        let mut game = PgnGame::default();
+        let mut fen_location = (self.lexer.line, self.lexer.column);
+        while matches!(self.peek_token()?, Some(Token::LeftBracket)) {
+            let (_, line, column) = self.next_token()?.expect("peeked a bracket");
+            let (name, value) = self.read_tag(line, column)?;
+            if name == "FEN" {
+                fen_location = (line, column);
+            }
+            game.set_tag(&name, &value);
+        }
+        game.reset_starting_position()
+            .map_err(|kind| PgnError::new(fen_location.0, fen_location.1, kind))?;
+
+        let mut position = game.starting_position.clone();
+        let (mainline, result) = self.read_line(&mut position, None)?;
+        game.mainline = mainline;
+        game.result = result.unwrap_or_default();
+
+        Ok(Some(game))
+    }
+}
+
+impl Iterator for PgnReader<'_> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let game = self.read_game().transpose();
+        // The rest of the input can't be trusted after an error
+        self.finished = !matches!(game, Some(Ok(_)));
+
+        game
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fen;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use test_case::test_case;
+
+    const GAME: &str = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+[Annotator "Someone \"Quoted\""]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
+23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
+hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
+35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
+Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+"#;
+
+    #[test]
+    fn reads_tags() {
+        let game: PgnGame = GAME.parse().unwrap();
+
+        assert_eq!(game.tag("Event"), Some("F/S Return Match"));
+        assert_eq!(game.tag("White"), Some("Fischer, Robert J."));
+        assert_eq!(game.tag("Annotator"), Some("Someone \"Quoted\""));
+        assert_eq!(game.tag("ECO"), None);
+        assert_eq!(game.tags().len(), 8);
+        assert_eq!(game.result(), GameResult::Draw);
+    }
+
+    #[test]
+    fn replays_mainline() {
+        let game: PgnGame = GAME.parse().unwrap();
+        let positions = game.positions();
+
+        assert_eq!(game.moves().count(), 85);
+        assert_eq!(positions.len(), 86);
+        assert_eq!(
+            game.mainline().moves[4].comments,
+            vec!["This opening is called the Ruy Lopez.".to_string()]
+        );
+        assert_eq!(
+            positions.last().unwrap().hash(),
+            fen!("8/8/4R1p1/2k3p1/1p4P1/1P1b1P2/3K1n2/8 b - - 2 43").hash()
+        );
+        assert_eq!(positions.last(), Some(&game.final_position()));
+    }
+
+    #[test]
+    fn reads_annotations_and_nested_variations() {
+        let pgn = "{Start} 1. e4 $1 e5!? (1... c5 {Sicilian} 2. Nf3 (2. c3) d6) (1... e6) 2. Nf3 ; rest of line\n *";
+        let game: PgnGame = pgn.parse().unwrap();
+        let mainline = game.mainline();
+
+        assert_eq!(mainline.comments, vec!["Start".to_string()]);
+        assert_eq!(mainline.moves.len(), 3);
+        assert_eq!(mainline.moves[0].nags, vec![1]);
+        assert_eq!(mainline.moves[1].nags, vec![5]);
+        assert_eq!(mainline.moves[2].comments, vec!["rest of line".to_string()]);
+
+        let variations = &mainline.moves[1].variations;
+        assert_eq!(variations.len(), 2);
+        assert_eq!(variations[0].moves.len(), 3);
+        assert_eq!(variations[0].moves[0].chess_move.to_string(), "c7c5");
+        assert_eq!(
+            variations[0].moves[0].comments,
+            vec!["Sicilian".to_string()]
+        );
+        assert_eq!(
+            variations[0].moves[1].variations[0].moves[0]
+                .chess_move
+                .to_string(),
+            "c2c3"
+        );
+        assert_eq!(variations[1].moves[0].chess_move.to_string(), "e7e6");
+        assert_eq!(game.result(), GameResult::Unknown);
+    }
+
+    #[test]
+    fn reads_multiple_games() {
+        let pgn = "[Event \"A\"]\n\n1. e4 1-0\n\n[Event \"B\"]\n\n1. d4 d5 0-1\n\n\n%escaped line\n1. c4 *\n";
+        let games = PgnReader::new(pgn).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[0].tag("Event"), Some("A"));
+        assert_eq!(games[0].result(), GameResult::WhiteWins);
+        assert_eq!(games[1].tag("Event"), Some("B"));
+        assert_eq!(games[1].moves().count(), 2);
+        assert_eq!(games[1].result(), GameResult::BlackWins);
+        assert_eq!(games[2].tags(), &[]);
+        assert_eq!(games[2].result(), GameResult::Unknown);
+    }
+
+    #[test]
+    fn reads_setup_position() {
+        let pgn = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n\n12... Kd7 13. e4 *";
+        let game: PgnGame = pgn.parse().unwrap();
+
+        assert_eq!(
+            game.starting_position(),
+            &fen!("4k3/8/8/8/8/8/4P3/4K3 b - - 0 12")
+        );
+        assert_eq!(game.moves().count(), 2);
+    }
+
+    #[test_case("1. e4 e5", 1, 9, PgnErrorKind::MissingResult; "missing result at end")]
+    #[test_case("1. e4\n\n[Event \"?\"]\n1. d4 *", 3, 1, PgnErrorKind::MissingResult; "missing result before tags")]
+    #[test_case("1. e4 e4 *", 1, 7, PgnErrorKind::InvalidMove("e4".to_string(), SanParseError::IllegalMove); "illegal move")]
+    #[test_case("1. e4\n  2. Zz9 *", 2, 6, PgnErrorKind::InvalidMove("Zz9".to_string(), SanParseError::InvalidSyntax); "invalid move")]
+    #[test_case("[Event \"?\"\n\n1. e4 *", 1, 1, PgnErrorKind::MalformedTag; "unclosed tag")]
+    #[test_case("[Event ?]\n\n1. e4 *", 1, 8, PgnErrorKind::UnexpectedChar('?'); "unquoted tag")]
+    #[test_case("[Event \"?\n\n1. e4 *", 1, 8, PgnErrorKind::UnterminatedString; "unterminated string")]
+    #[test_case("1. e4 {never closed *", 1, 7, PgnErrorKind::UnterminatedComment; "unterminated comment")]
+    #[test_case("1. e4 (1. d4", 1, 7, PgnErrorKind::UnterminatedVariation; "unterminated variation")]
+    #[test_case("(1. d4) 1. e4 *", 1, 1, PgnErrorKind::UnexpectedToken; "variation before a move")]
+    #[test_case("1. e4 e5) *", 1, 9, PgnErrorKind::UnexpectedToken; "unopened variation")]
+    #[test_case("1. e4 (1. d4 1-0) *", 1, 14, PgnErrorKind::UnexpectedToken; "result in variation")]
+    #[test_case("$1 1. e4 *", 1, 1, PgnErrorKind::UnexpectedToken; "nag before a move")]
+    #[test_case("1. e4 $256 *", 1, 7, PgnErrorKind::InvalidNag; "nag out of range")]
+    #[test_case("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n*", 1, 1, PgnErrorKind::IllegalPosition(IllegalPosition::MissingKing(crate::player_color::PlayerColor::White)); "fen without kings")]
+    #[test_case("", 1, 1, PgnErrorKind::MissingGame; "empty")]
+    fn read_errors(pgn: &str, line: usize, column: usize, kind: PgnErrorKind) {
+        assert_eq!(
+            pgn.parse::<PgnGame>(),
+            Err(PgnError::new(line, column, kind))
+        );
+    }
+
+    #[test]
+    fn stops_after_an_error() {
+        let mut reader = PgnReader::new("1. e4 *\n1. e5 *\n1. d4 *");
+
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(reader.next().unwrap().unwrap_err().line(), 2);
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::pgn::{PgnGame, PgnLine, SEVEN_TAG_ROSTER};
+use crate::player_color::PlayerColor;
+use crate::position::LegalPosition;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// The longest a line of exported movetext can be
+const MAX_LINE_LENGTH: usize = 79;
+
+/// Escape the quotes and backslashes of a tag value
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The value exported for a seven tag roster tag that isn't set
+const fn default_tag_value(name: &str) -> &'static str {
+    match name.as_bytes() {
+        b"Date" => "????.??.??",
+        _ => "?",
+    }
+}
+
+/// Write the movetext tokens of a line played from a position, restoring the position afterwards
+fn write_line(
+    tokens: &mut Vec<String>,
+    position: &mut LegalPosition,
+    mut fullmove: u16,
+    line: &PgnLine,
+) {
+    let mut played = Vec::with_capacity(line.moves.len());
+    write_comments(tokens, &line.comments);
+    // Black's moves only need a number at the start of a line, or after being interrupted by a comment or variation
+    let mut needs_number = true;
+
+    for pgn_move in &line.moves {
+        match position.player_to_move() {
+            PlayerColor::White => tokens.push(format!("{fullmove}.")),
+            PlayerColor::Black if needs_number => tokens.push(format!("{fullmove}...")),
+            PlayerColor::Black => {}
+        }
+        tokens.push(position.to_san(pgn_move.chess_move));
+        tokens.extend(pgn_move.nags.iter().map(|nag| format!("${nag}")));
+        write_comments(tokens, &pgn_move.comments);
+        for variation in &pgn_move.variations {
+            let start = tokens.len();
+            write_line(tokens, position, fullmove, variation);
+            enclose(tokens, start, '(', ')');
+        }
+        needs_number = !pgn_move.comments.is_empty() || !pgn_move.variations.is_empty();
+
+        if position.player_to_move() == PlayerColor::Black {
+            fullmove += 1;
+        }
+        played.push((pgn_move.chess_move, position.make_move(pgn_move.chess_move)));
+    }
+
+    for (chess_move, state) in played.into_iter().rev() {
+        position.unmake_move(chess_move, state);
+    }
+}
+
+/// Write brace comments, a word per token so they can be wrapped
+fn write_comments(tokens: &mut Vec<String>, comments: &[String]) {
+    for comment in comments {
+        let start = tokens.len();
+        tokens.extend(comment.split_whitespace().map(ToString::to_string));
+        enclose(tokens, start, '{', '}');
+    }
+}
+
+/// Enclose the tokens from an index on in a pair of delimiters, attached to the first and last of them
+fn enclose(tokens: &mut Vec<String>, start: usize, open: char, close: char) {
+    if tokens.len() == start {
+        tokens.push(format!("{open}{close}"));
+        return;
+    }
+
+    tokens[start].insert(0, open);
+    tokens
+        .last_mut()
+        .expect("there is a last token")
+        .push(close);
+}
+
+impl Display for PgnGame {
+    /// Write the game in the PGN export format
+    ///
+    /// The seven tag roster comes first (with placeholder values for any that aren't set) followed by every other
+    /// tag, then the movetext is wrapped to lines of at most 79 chars (where possible) and ends with the result.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for name in SEVEN_TAG_ROSTER {
+            let value = if name == "Result" {
+                self.result.to_string()
+            } else {
+                self.tag(name)
+                    .unwrap_or_else(|| default_tag_value(name))
+                    .to_string()
+            };
+            writeln!(f, "[{name} \"{}\"]", escape(&value))?;
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                writeln!(f, "[{name} \"{}\"]", escape(value))?;
+            }
+        }
+        writeln!(f)?;
+
+        let mut tokens = Vec::new();
+        let mut position = self.starting_position.clone();
+        write_line(
+            &mut tokens,
+            &mut position,
+            self.starting_fullmove,
+            &self.mainline,
+        );
+        tokens.push(self.result.to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 {
+                if line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                    writeln!(f)?;
+                    line_length = 0;
+                } else {
+                    write!(f, " ")?;
+                    line_length += 1;
+                }
+            }
+            write!(f, "{token}")?;
+            line_length += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pgn::{GameResult, PgnGame, PgnReader};
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn writes_export_format() {
+        let mut game = PgnGame::default();
+        game.set_tag("White", "Player \"One\"");
+        game.set_tag("ECO", "C20");
+        game.set_result(GameResult::WhiteWins);
+        for san in ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7"] {
+            let chess_move = game.final_position().parse_san(san).unwrap();
+            game.push_move(chess_move).unwrap();
+        }
+
+        assert_eq!(
+            game.to_string(),
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"Player \\\"One\\\"\"]\n\
+             [Black \"?\"]\n[Result \"1-0\"]\n[ECO \"C20\"]\n\n1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n"
+        );
+    }
+
+    #[test]
+    fn writes_annotations_and_variations() {
+        let pgn = "{Start} 1. e4 $1 e5 $5 {Open} (1... c5 {Sicilian} 2. Nf3 (2. c3) d6) 2. Nf3 *";
+        let game: PgnGame = pgn.parse().unwrap();
+        let written = game.to_string();
+
+        assert!(written.ends_with(
+            "\n\n{Start} 1. e4 $1 e5 $5 {Open} (1... c5 {Sicilian} 2. Nf3 (2. c3) 2... d6) 2.\nNf3 *\n"
+        ));
+    }
+
+    #[test]
+    fn writes_black_to_move_numbers() {
+        let mut game = PgnGame::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 12").unwrap();
+        for san in ["Kd7", "e4", "Ke6"] {
+            let chess_move = game.final_position().parse_san(san).unwrap();
+            game.push_move(chess_move).unwrap();
+        }
+        let written = game.to_string();
+
+        assert!(written.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n"));
+        assert!(written.ends_with("\n\n12... Kd7 13. e4 Ke6 *\n"));
+    }
+
+    #[test]
+    fn wraps_long_lines() {
+        let pgn = "1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 Ng8 5. Nf3 Nf6 6. Ng1 Ng8 7. Nf3 Nf6 8. Ng1 Ng8 9. Nf3 Nf6 \
+                   10. Ng1 {a very long comment that goes on and on and on and on and on and on} Ng8 1/2-1/2";
+        let written = pgn.parse::<PgnGame>().unwrap().to_string();
+        let movetext: Vec<&str> = written.lines().skip(8).collect();
+
+        assert!(movetext.len() > 1);
+        assert!(movetext.iter().all(|line| line.len() <= 79));
+        assert!(movetext
+            .iter()
+            .all(|line| !line.starts_with(' ') && !line.ends_with(' ')));
+    }
+
+    #[test]
+    fn round_trips() {
+        let pgn = "[Event \"A\"]\n\n1. e4 e5 (1... c5 2. Nf3) 2. Nf3 {Comment} 2... Nc6 3. Bb5 a6 $6 1/2-1/2\n\n\
+                   [Event \"B\"]\n[SetUp \"1\"]\n[FEN \"r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1\"]\n\n1. O-O O-O-O *\n";
+        let games = PgnReader::new(pgn).collect::<Result<Vec<_>, _>>().unwrap();
+        let written = games
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let reread = PgnReader::new(&written)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(reread.len(), games.len());
+        for (reread, game) in reread.iter().zip(&games) {
+            assert_eq!(reread.mainline(), game.mainline());
+            assert_eq!(reread.starting_position(), game.starting_position());
+            assert_eq!(reread.result(), game.result());
+            assert_eq!(reread.tag("Event"), game.tag("Event"));
+        }
+        assert_eq!(
+            reread
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            written
+        );
+    }
+}
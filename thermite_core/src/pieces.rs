@@ -171,7 +171,6 @@ impl Piece for PieceType {
     /// assert_eq!(PieceType::Queen.owned_by(PlayerColor::White), OwnedPiece { piece: PieceType::Queen, player: PlayerColor::White });
     /// assert_eq!(PieceType::Queen.owned_by(PlayerColor::Black), OwnedPiece { piece: PieceType::Queen, player: PlayerColor::Black });
     /// ```
-    #[must_use]
     fn owned_by(self, player: PlayerColor) -> OwnedPiece {
         OwnedPiece {
             piece: self,
@@ -190,7 +189,6 @@ impl Piece for PieceType {
     /// assert_eq!(PieceType::Queen.get_lower_char(), 'q');
     /// assert_eq!(PieceType::Rook.get_lower_char(), 'r');
     /// ```
-    #[must_use]
     fn get_lower_char(self) -> char {
         match self {
             Self::Pawn => 'p',
@@ -213,7 +211,6 @@ impl Piece for PieceType {
     /// assert_eq!(PieceType::Knight.get_upper_char(), 'N');
     /// assert_eq!(PieceType::Bishop.get_upper_char(), 'B');
     /// ```
-    #[must_use]
     fn get_upper_char(self) -> char {
         match self {
             Self::Pawn => 'P',
@@ -263,6 +260,52 @@ impl<P: Piece> OwnedPiece<P> {
             square,
         }
     }
+
+    /// Get the FEN char of the piece, upper-case for [white](PlayerColor::White) and lower-case for
+    /// [black](PlayerColor::Black)
+    ///
+    /// ```
+    /// use thermite_core::pieces::{Piece, PieceType};
+    /// use thermite_core::player_color::PlayerColor;
+    ///
+    /// assert_eq!(PieceType::Knight.owned_by(PlayerColor::White).fen_char(), 'N');
+    /// assert_eq!(PieceType::Knight.owned_by(PlayerColor::Black).fen_char(), 'n');
+    /// ```
+    #[must_use]
+    pub fn fen_char(self) -> char {
+        match self.player {
+            PlayerColor::White => self.piece.get_upper_char(),
+            PlayerColor::Black => self.piece.get_lower_char(),
+        }
+    }
+
+    /// Get the Unicode chess symbol of the piece, outlined for [white](PlayerColor::White) and filled for
+    /// [black](PlayerColor::Black)
+    ///
+    /// ```
+    /// use thermite_core::pieces::{Piece, PieceType};
+    /// use thermite_core::player_color::PlayerColor;
+    ///
+    /// assert_eq!(PieceType::Queen.owned_by(PlayerColor::White).unicode_char(), '♕');
+    /// assert_eq!(PieceType::Queen.owned_by(PlayerColor::Black).unicode_char(), '♛');
+    /// ```
+    #[must_use]
+    pub fn unicode_char(self) -> char {
+        match self.fen_char() {
+            'K' => '♔',
+            'Q' => '♕',
+            'R' => '♖',
+            'B' => '♗',
+            'N' => '♘',
+            'P' => '♙',
+            'k' => '♚',
+            'q' => '♛',
+            'r' => '♜',
+            'b' => '♝',
+            'n' => '♞',
+            _ => '♟',
+        }
+    }
 }
 
 #[cfg(test)]
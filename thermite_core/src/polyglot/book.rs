@@ -0,0 +1,243 @@
+use crate::chess_move::ChessMove;
+use crate::pieces::PromotablePieceType;
+use crate::polyglot::polyglot_key;
+use crate::position::LegalPosition;
+use crate::square::Square;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// The size in bytes of a single book entry
+const ENTRY_SIZE: usize = 16;
+
+/// Errors that can occur while loading a [`PolyglotBook`] from bytes
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PolyglotBookError {
+    /// The data isn't made up of whole 16 byte entries
+    UnexpectedLength(usize),
+}
+
+/// A single raw book entry, the learn field is ignored
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct BookEntry {
+    key: u64,
+    raw_move: u16,
+    weight: u16,
+}
+
+/// A legal move suggested by a [`PolyglotBook`] along with its relative weight
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BookMove {
+    /// The suggested move
+    pub chess_move: ChessMove,
+    /// How often the move should be played relative to the other moves for the same position
+    pub weight: u16,
+}
+
+/// An opening book in the Polyglot format
+///
+/// Each entry (all big-endian) is a `u64` [key](polyglot_key), a `u16` move, a `u16` weight and a `u32` learn value.
+/// Moves are packed as the to-file, to-rank, from-file, from-rank and promotion piece (3 bits each, lowest first),
+/// with castles written as the king capturing its own rook.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct PolyglotBook {
+    entries: Vec<BookEntry>,
+}
+
+impl PolyglotBook {
+    /// Load a book from the raw bytes of a `.bin` file, sorting the entries if needed
+    ///
+    /// # Errors
+    /// If the data isn't a whole number of entries
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PolyglotBookError> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(PolyglotBookError::UnexpectedLength(bytes.len()));
+        }
+
+        let mut entries: Vec<BookEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| BookEntry {
+                key: read_big_endian(&entry[0..8]),
+                raw_move: read_big_endian(&entry[8..10]) as u16,
+                weight: read_big_endian(&entry[10..12]) as u16,
+            })
+            .collect();
+
+        if !entries.is_sorted_by_key(|entry| entry.key) {
+            entries.sort_by_key(|entry| entry.key);
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// The number of entries in the book
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether or not the book has no entries
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the legal book moves for a position, highest weight first
+    ///
+    /// Entries whose move isn't legal in the position (typically a key collision) are skipped.
+    #[must_use]
+    pub fn candidate_moves(&self, position: &LegalPosition) -> Vec<BookMove> {
+        let key = polyglot_key(position);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let legal_moves = position.generate_legal_moves();
+
+        let mut book_moves: Vec<BookMove> = self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| {
+                decode_move(entry.raw_move, &legal_moves).map(|chess_move| BookMove {
+                    chess_move,
+                    weight: entry.weight,
+                })
+            })
+            .collect();
+        book_moves.sort_by_key(|book_move| Reverse(book_move.weight));
+        book_moves
+    }
+
+    /// Get the highest weighted legal book move for a position, if any
+    #[must_use]
+    pub fn best_move(&self, position: &LegalPosition) -> Option<ChessMove> {
+        self.candidate_moves(position)
+            .first()
+            .map(|book_move| book_move.chess_move)
+    }
+}
+
+/// Read an unsigned big-endian integer of up to 8 bytes
+fn read_big_endian(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0, |value, &byte| (value << 8) | u64::from(byte))
+}
+
+/// Find the legal move matching a raw Polyglot move, mapping king-takes-rook onto the [castle](ChessMove::Castle)
+fn decode_move(raw_move: u16, legal_moves: &[ChessMove]) -> Option<ChessMove> {
+    let to = Square::try_from((raw_move & 0x3f) as u8).ok()?;
+    let from = Square::try_from(((raw_move >> 6) & 0x3f) as u8).ok()?;
+    let promotion = match (raw_move >> 12) & 0x7 {
+        0 => None,
+        1 => Some(PromotablePieceType::Knight),
+        2 => Some(PromotablePieceType::Bishop),
+        3 => Some(PromotablePieceType::Rook),
+        4 => Some(PromotablePieceType::Queen),
+        _ => return None,
+    };
+
+    legal_moves
+        .iter()
+        .copied()
+        .find(|&chess_move| match chess_move {
+            ChessMove::Castle(castle) => castle.king_from() == from && castle.rook_from() == to,
+            _ => {
+                chess_move.from_square() == from
+                    && chess_move.to_square() == to
+                    && chess_move.promotion_piece() == promotion
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fen;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec;
+    use test_case::test_case;
+
+    const STARTPOS_KEY: u64 = 0x463b_9618_1691_fc9c;
+
+    fn entry(key: u64, from: Square, to: Square, promotion: u16, weight: u16) -> [u8; 16] {
+        let raw_move = (promotion << 12) | ((from as u16) << 6) | to as u16;
+        let mut bytes = [0; 16];
+        bytes[0..8].copy_from_slice(&key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&raw_move.to_be_bytes());
+        bytes[10..12].copy_from_slice(&weight.to_be_bytes());
+        bytes
+    }
+
+    fn book(entries: &[[u8; 16]]) -> PolyglotBook {
+        PolyglotBook::from_bytes(&entries.concat()).unwrap()
+    }
+
+    fn candidate_strings(book: &PolyglotBook, position: &LegalPosition) -> Vec<(String, u16)> {
+        book.candidate_moves(position)
+            .into_iter()
+            .map(|book_move| (format!("{}", book_move.chess_move), book_move.weight))
+            .collect()
+    }
+
+    #[test]
+    fn candidate_moves_sorted_by_weight() {
+        let book = book(&[
+            entry(STARTPOS_KEY + 1, Square::A2, Square::A3, 0, 50),
+            entry(STARTPOS_KEY, Square::E2, Square::E4, 0, 10),
+            entry(STARTPOS_KEY, Square::D2, Square::D4, 0, 20),
+            entry(STARTPOS_KEY - 1, Square::H2, Square::H3, 0, 50),
+        ]);
+        let position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+        assert_eq!(book.len(), 4);
+        assert_eq!(
+            candidate_strings(&book, &position),
+            vec![(String::from("d2d4"), 20), (String::from("e2e4"), 10)]
+        );
+        assert_eq!(format!("{}", book.best_move(&position).unwrap()), "d2d4");
+    }
+
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", Square::E1, Square::H1, "e1g1")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", Square::E1, Square::A1, "e1c1")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", Square::E8, Square::H8, "e8g8")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", Square::E8, Square::A8, "e8c8")]
+    fn king_takes_rook_is_castle(fen: &str, from: Square, to: Square, expected: &str) {
+        let position = fen!(fen);
+        let book = book(&[entry(polyglot_key(&position), from, to, 0, 1)]);
+
+        let best_move = book.best_move(&position).unwrap();
+        assert!(matches!(best_move, ChessMove::Castle(_)));
+        assert_eq!(format!("{best_move}"), expected);
+    }
+
+    #[test_case(4, "b7b8q")]
+    #[test_case(1, "b7b8n")]
+    #[test_case(3, "b7a8r")]
+    fn promotion_decoding_works(promotion: u16, expected: &str) {
+        let position = fen!("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        let to = if expected.starts_with("b7a8") {
+            Square::A8
+        } else {
+            Square::B8
+        };
+        let book = book(&[entry(polyglot_key(&position), Square::B7, to, promotion, 1)]);
+
+        assert_eq!(format!("{}", book.best_move(&position).unwrap()), expected);
+    }
+
+    #[test]
+    fn illegal_entries_are_skipped() {
+        let position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let book = book(&[entry(STARTPOS_KEY, Square::E2, Square::E5, 0, 1)]);
+
+        assert!(book.candidate_moves(&position).is_empty());
+        assert_eq!(book.best_move(&position), None);
+    }
+
+    #[test]
+    fn from_bytes_rejects_partial_entries() {
+        assert_eq!(
+            PolyglotBook::from_bytes(&[0; 17]),
+            Err(PolyglotBookError::UnexpectedLength(17))
+        );
+        assert!(PolyglotBook::from_bytes(&[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+//! Support for the Polyglot opening book format.
+//!
+//! Polyglot books are keyed by their own [hash](polyglot_key) of a position, independent of the engine's
+//! [`ZobristHash`](crate::zobrist::ZobristHash), so that books can be shared between engines.
+
+mod book;
+mod randoms;
+
+pub use book::{BookMove, PolyglotBook, PolyglotBookError};
+
+use crate::pieces::NonKingPieceType;
+use crate::player_color::PlayerColor;
+use crate::position::LegalPosition;
+use crate::square::Square;
+use enum_iterator::all;
+use enum_map::Enum;
+use randoms::RANDOMS;
+
+/// Offset of the castle rights keys, one for each [`CastleRights`](crate::castles::CastleRights) bit
+const CASTLE_OFFSET: usize = 768;
+/// Offset of the en-passant file keys
+const EN_PASSANT_OFFSET: usize = 772;
+/// Offset of the key for white to move
+const TURN_OFFSET: usize = 780;
+
+/// Compute the Polyglot key of a position
+///
+/// The en-passant file only contributes to the key when a pawn of the player to move could capture en-passant.
+///
+/// ```
+/// use thermite_core::fen;
+/// use thermite_core::polyglot::polyglot_key;
+///
+/// let position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+/// assert_eq!(polyglot_key(&position), 0x463b_9618_1691_fc9c);
+/// ```
+#[must_use]
+pub fn polyglot_key(position: &LegalPosition) -> u64 {
+    let pieces = all::<Square>()
+        .filter_map(|square| position.owned_piece_on(square).map(|piece| (square, piece)))
+        .fold(0, |key, (square, owned_piece)| {
+            let kind = 2 * owned_piece.piece.into_usize()
+                + usize::from(owned_piece.player == PlayerColor::White);
+            key ^ RANDOMS[64 * kind + square as usize]
+        });
+
+    let castles = (0..4)
+        .filter(|bit| position.castle_rights().bits() & (1 << bit) != 0)
+        .fold(0, |key, bit| key ^ RANDOMS[CASTLE_OFFSET + bit]);
+
+    let player = position.player_to_move();
+    let en_passant = position
+        .en_passant_square()
+        .filter(|&en_passant_square| {
+            let capturers = Square::from(en_passant_square)
+                .to_mask()
+                .pawn_attacks(player.switch())
+                & position.piece_mask(NonKingPieceType::Pawn)
+                & position.player_to_move_mask();
+            !capturers.is_empty()
+        })
+        .map_or(0, |en_passant_square| {
+            RANDOMS[EN_PASSANT_OFFSET + Square::from(en_passant_square).file() as usize]
+        });
+
+    let turn = if player == PlayerColor::White {
+        RANDOMS[TURN_OFFSET]
+    } else {
+        0
+    };
+
+    pieces ^ castles ^ en_passant ^ turn
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fen;
+    use test_case::test_case;
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b_9618_1691_fc9c; "start position")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c_9b50_fd11_4196; "e2e4")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756_b944_61c5_0fb0; "e2e4 d7d5")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662f_afb9_65db_29d4; "e2e4 d7d5 e4e5")]
+    #[test_case("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a4_8b5a_8e47_ff78; "e2e4 d7d5 e4e5 f7f5")]
+    #[test_case("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a_607c_a3f2_42c1; "e2e4 d7d5 e4e5 f7f5 e1e2")]
+    #[test_case("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fd_d303_c946_bdd9; "e2e4 d7d5 e4e5 f7f5 e1e2 e8f7")]
+    #[test_case("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c81_23ea_7b06_7637; "a2a4 b7b5 h2h4 b5b4 c2c4")]
+    #[test_case("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f_9b82_9b27_9560; "a2a4 b7b5 h2h4 b5b4 c2c4 b4c3 a1a3")]
+    fn polyglot_key_works(fen: &str, expected: u64) {
+        assert_eq!(polyglot_key(&fen!(fen)), expected);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::player_color::PlayerColor;
+use crate::position::{LegalPosition, PositionBuilder};
+use crate::square::Square;
+use core::fmt::{Display, Formatter};
+
+/// Draws the board of a [`LegalPosition`] along with its state, created by [`LegalPosition::board`]
+///
+/// The board is drawn from white's side with rank and file labels, using FEN letters for the pieces unless
+/// [Unicode symbols](Self::unicode) are chosen. Below it are the player to move, the castle rights, the en-passant
+/// square, the Zobrist key and the squares of any pieces giving check.
+#[derive(Copy, Clone, Debug)]
+#[must_use]
+pub struct Board<'a> {
+    position: &'a LegalPosition,
+    unicode: bool,
+}
+
+impl Board<'_> {
+    /// Draw the pieces as Unicode chess symbols rather than FEN letters
+    pub const fn unicode(mut self) -> Self {
+        self.unicode = true;
+
+        self
+    }
+}
+
+impl LegalPosition {
+    /// Get a [`Display`] adapter drawing the board and state of the position, also used by its own `Display`
+    ///
+    /// ```
+    /// use thermite_core::fen;
+    ///
+    /// let position = fen!("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    /// let board = position.board().unicode().to_string();
+    /// assert!(board.contains("1 | · · · · ♔ · · ♖ |"));
+    /// assert!(board.contains("Castle rights: K"));
+    /// ```
+    pub const fn board(&self) -> Board<'_> {
+        Board {
+            position: self,
+            unicode: false,
+        }
+    }
+}
+
+impl Display for Board<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let position = self.position;
+        let empty = if self.unicode { '·' } else { '.' };
+
+        writeln!(f, "  +-----------------+")?;
+        for rank in (0..8u8).rev() {
+            write!(f, "{} |", rank + 1)?;
+            for file in 0..8 {
+                let piece = Square::try_from(rank * 8 + file)
+                    .ok()
+                    .and_then(|square| position.owned_piece_on(square));
+                let symbol = piece.map_or(empty, |piece| {
+                    if self.unicode {
+                        piece.unicode_char()
+                    } else {
+                        piece.fen_char()
+                    }
+                });
+                write!(f, " {symbol}")?;
+            }
+            writeln!(f, " |")?;
+        }
+        writeln!(f, "  +-----------------+")?;
+        writeln!(f, "    a b c d e f g h")?;
+        writeln!(f)?;
+
+        let player = match position.player_to_move() {
+            PlayerColor::White => "white",
+            PlayerColor::Black => "black",
+        };
+        writeln!(f, "Side to move: {player}")?;
+        write!(f, "Castle rights: ")?;
+        PositionBuilder::from(position).write_castle_rights(f)?;
+        writeln!(f)?;
+        match position.en_passant_square() {
+            Some(square) => writeln!(f, "En passant: {}", Square::from(square))?,
+            None => writeln!(f, "En passant: -")?,
+        }
+        writeln!(f, "Key: {:016X}", position.hash().as_ref())?;
+        write!(f, "Checkers:")?;
+        if position.checkers().is_empty() {
+            write!(f, " -")?;
+        }
+        for square in position.checkers() {
+            write!(f, " {square}")?;
+        }
+
+        writeln!(f)
+    }
+}
+
+impl Display for LegalPosition {
+    /// Draw the board and state of the position with FEN letters, see [`Board`]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.board().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fen;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    #[test]
+    fn display_draws_the_board_and_state() {
+        let position = fen!("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3");
+        let expected = "  +-----------------+\n\
+                        8 | r n b q k b n r |\n\
+                        7 | p p p . p p p p |\n\
+                        6 | . . . . . . . . |\n\
+                        5 | . . . p P . . . |\n\
+                        4 | . . . . . . . . |\n\
+                        3 | . . . . . . . . |\n\
+                        2 | P P P P . P P P |\n\
+                        1 | R N B Q K B N R |\n\
+                        \x20 +-----------------+\n\
+                        \x20   a b c d e f g h\n\
+                        \n\
+                        Side to move: white\n\
+                        Castle rights: Kq\n\
+                        En passant: d6\n";
+
+        let written = position.to_string();
+        assert!(written.starts_with(expected), "{written}");
+        assert!(written.ends_with("Checkers: -\n"));
+    }
+
+    #[test_case("4k3/8/8/8/8/8/8/4K3 w - - 0 1", "Castle rights: -"; "no rights")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R b KQk - 0 1", "Castle rights: KQk"; "standard rights")]
+    #[test_case("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBg - 0 1", "Castle rights: GBg"; "chess960 rights")]
+    fn display_writes_castle_rights(fen: &str, expected: &str) {
+        let position = fen!(fen);
+
+        assert!(position.to_string().contains(expected), "{position}");
+    }
+
+    #[test_case("4k3/8/8/8/8/8/8/4K3 w - - 0 1", "Checkers: -"; "no checkers")]
+    #[test_case("4k3/8/8/8/8/5n2/8/R3K3 w - - 0 1", "Checkers: f3"; "single checker")]
+    #[test_case("4k3/8/8/8/8/5n2/8/4K2r w - - 0 1", "Checkers: h1 f3"; "double check")]
+    fn display_lists_checkers(fen: &str, expected: &str) {
+        let position = fen!(fen);
+
+        assert!(position.to_string().contains(expected), "{position}");
+    }
+
+    #[test]
+    fn unicode_draws_symbols() {
+        let position = fen!("4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        let written = position.board().unicode().to_string();
+
+        assert!(written.contains("8 | · · · · ♚ · · · |"));
+        assert!(written.contains("Castle rights: -"));
+        assert!(written.contains("Side to move: black"));
+    }
+}
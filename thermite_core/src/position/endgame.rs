@@ -0,0 +1,369 @@
+use crate::bitboard::BoardMask;
+use crate::evaluation::{Endgame, PawnEvaluation};
+use crate::pieces::NonKingPieceType;
+use crate::player_color::PlayerColor;
+use crate::position::kpk::KPK;
+use crate::position::material_evaluation::PIECE_VALUES;
+use crate::position::LegalPosition;
+use crate::square::{File, Rank, Square};
+
+/// The evaluation (in pawns) of an ending known to be won, on top of its material and the progress made
+const KNOWN_WIN: f32 = 50.0;
+/// Bonus (in pawns) for each step the losing king is from the centre of the board, squared per file and rank
+const PUSH_TO_EDGE: f32 = 0.1;
+/// Bonus (in pawns) for each step closer the kings are to each other
+const PUSH_CLOSE: f32 = 0.2;
+/// Bonus (in pawns) for each step closer the losing king is to a corner the bishop can cover
+const PUSH_TO_CORNER: f32 = 0.4;
+/// Bonus (in pawns) for each rank a winning king and pawn against king pawn has advanced
+const PAWN_ADVANCE: f32 = 0.1;
+/// Penalty (in pawns) for each step the rook's king is from the pawn in a won rook against pawn ending
+const KRKP_ROOK_CHASE: f32 = 0.05;
+/// Penalty (in pawns) for each step of progress towards the pawn in a contested rook against pawn ending
+const KRKP_PAWN_CHASE: f32 = 0.04;
+/// Scale factor of opposite colored bishops with a pawn advantage of at most one
+const OPPOSITE_BISHOPS_CLOSE: f32 = 0.25;
+/// Scale factor of opposite colored bishops with a larger pawn advantage
+const OPPOSITE_BISHOPS: f32 = 0.5;
+
+/// Flip a [`Square`] vertically for black, so it's as seen from a [player](PlayerColor)'s side of the board
+fn relative_square(player: PlayerColor, square: Square) -> Square {
+    match player {
+        PlayerColor::White => square,
+        PlayerColor::Black => Square::try_from(square as u8 ^ Square::A8 as u8)
+            .expect("vertically flipped square is on the board"),
+    }
+}
+
+/// Mirror a [`Square`] horizontally (A file to H file)
+fn mirrored_square(square: Square) -> Square {
+    Square::try_from(square as u8 ^ Square::H1 as u8).expect("mirrored square is on the board")
+}
+
+/// How many steps (0-3) a [`File`] or [`Rank`] index is from the centre of the board
+const fn centre_distance(index: u8) -> u8 {
+    if index < 4 {
+        3 - index
+    } else {
+        index - 4
+    }
+}
+
+/// Bonus for a losing king being near an edge (most in the corners)
+fn push_to_edge(square: Square) -> f32 {
+    let file_distance = centre_distance(square.file() as u8);
+    let rank_distance = centre_distance(square.rank() as u8);
+
+    PUSH_TO_EDGE * f32::from(file_distance * file_distance + rank_distance * rank_distance)
+}
+
+/// Bonus for the kings being near each other
+fn push_close(a: Square, b: Square) -> f32 {
+    PUSH_CLOSE * f32::from(7 - a.distance(b))
+}
+
+impl LegalPosition {
+    /// Get the [`Square`] of a [player](PlayerColor)'s only piece of a given type
+    fn only_piece_square(&self, player: PlayerColor, piece: NonKingPieceType) -> Option<Square> {
+        (self.pieces_masks[piece] & self.side_masks[player]).pop_square()
+    }
+
+    /// Evaluate a known [ending](Endgame) that replaces the general evaluation, from the [player to move](Self::player_to_move)'s perspective
+    pub(super) fn evaluate_endgame(&self) -> Option<PawnEvaluation> {
+        let (endgame, strong_player) = Endgame::lookup(self.material_hash())?;
+        let evaluation = match endgame {
+            Endgame::Kqk => Some(self.evaluate_mating(strong_player, NonKingPieceType::Queen)),
+            Endgame::Krk => Some(self.evaluate_mating(strong_player, NonKingPieceType::Rook)),
+            Endgame::Kbnk => self.evaluate_kbnk(strong_player),
+            Endgame::Kpk => self.evaluate_kpk(strong_player),
+            Endgame::Krkp => self.evaluate_krkp(strong_player),
+            Endgame::Kbpk | Endgame::OppositeBishops => return None,
+        }?;
+
+        Some(if self.player_to_move == strong_player {
+            evaluation
+        } else {
+            -evaluation
+        })
+    }
+
+    /// Drive the losing king to an edge and bring the winning king closer, for a queen or rook against a lone king
+    fn evaluate_mating(
+        &self,
+        strong_player: PlayerColor,
+        piece: NonKingPieceType,
+    ) -> PawnEvaluation {
+        let weak_player = strong_player.switch();
+        if self.player_to_move == weak_player
+            && !self.in_check()
+            && self.generate_legal_moves().is_empty()
+        {
+            return PawnEvaluation::default();
+        }
+
+        let strong_king = self.king_squares[strong_player];
+        let weak_king = self.king_squares[weak_player];
+
+        PawnEvaluation::new(
+            KNOWN_WIN
+                + *PIECE_VALUES[piece]
+                + push_to_edge(weak_king)
+                + push_close(strong_king, weak_king),
+        )
+    }
+
+    /// Drive the losing king to a corner the bishop can cover, for a bishop and knight against a lone king
+    fn evaluate_kbnk(&self, strong_player: PlayerColor) -> Option<PawnEvaluation> {
+        let bishop = self.only_piece_square(strong_player, NonKingPieceType::Bishop)?;
+        let strong_king = self.king_squares[strong_player];
+        let weak_king = self.king_squares[strong_player.switch()];
+        let corners = if bishop.is_light() {
+            [Square::A8, Square::H1]
+        } else {
+            [Square::A1, Square::H8]
+        };
+        let corner_distance = corners
+            .iter()
+            .map(|&corner| weak_king.distance(corner))
+            .min()
+            .unwrap_or_default();
+        let push_to_corner = PUSH_TO_CORNER * f32::from(7 - corner_distance);
+
+        Some(PawnEvaluation::new(
+            KNOWN_WIN
+                + *PIECE_VALUES[NonKingPieceType::Bishop]
+                + *PIECE_VALUES[NonKingPieceType::Knight]
+                + push_to_corner
+                + push_close(strong_king, weak_king),
+        ))
+    }
+
+    /// Look a king and pawn against king position up in the [bitbase](KPK), a win or a dead draw
+    fn evaluate_kpk(&self, strong_player: PlayerColor) -> Option<PawnEvaluation> {
+        let pawn = self.only_piece_square(strong_player, NonKingPieceType::Pawn)?;
+
+        // The bitbase has white as the strong side, with the pawn on the queen side
+        let mirror = pawn.file() > File::D;
+        let normalize = |square: Square| {
+            let square = relative_square(strong_player, square);
+            if mirror {
+                mirrored_square(square)
+            } else {
+                square
+            }
+        };
+        let pawn = normalize(pawn);
+        let player_to_move = if self.player_to_move == strong_player {
+            PlayerColor::White
+        } else {
+            PlayerColor::Black
+        };
+        let is_win = KPK.is_win(
+            player_to_move,
+            pawn,
+            normalize(self.king_squares[strong_player]),
+            normalize(self.king_squares[strong_player.switch()]),
+        );
+
+        let advance = PAWN_ADVANCE * f32::from(pawn.rank() as u8);
+
+        Some(if is_win {
+            PawnEvaluation::new(KNOWN_WIN + *PIECE_VALUES[NonKingPieceType::Pawn] + advance)
+        } else {
+            PawnEvaluation::default()
+        })
+    }
+
+    /// Rook against pawn, won when the rook's king is in front of the pawn or the pawn's king is too far away
+    ///
+    /// Otherwise it depends on how advanced the pawn is and how close each king is to it.
+    fn evaluate_krkp(&self, strong_player: PlayerColor) -> Option<PawnEvaluation> {
+        let weak_player = strong_player.switch();
+        // Squares as seen by the strong side, the pawn moving down the board
+        let strong_king = relative_square(strong_player, self.king_squares[strong_player]);
+        let weak_king = relative_square(strong_player, self.king_squares[weak_player]);
+        let rook = relative_square(
+            strong_player,
+            self.only_piece_square(strong_player, NonKingPieceType::Rook)?,
+        );
+        let pawn = relative_square(
+            strong_player,
+            self.only_piece_square(weak_player, NonKingPieceType::Pawn)?,
+        );
+        let queening_square = Square::new(pawn.file(), Rank::First);
+        let in_front_of_pawn = pawn.checked_sub(8)?;
+        let rook_value = *PIECE_VALUES[NonKingPieceType::Rook];
+        let strong_to_move = self.player_to_move == strong_player;
+
+        // Each step the rook's king needs to reach the pawn makes the win (or the draw) slower
+        let rook_chase = KRKP_ROOK_CHASE * f32::from(strong_king.distance(pawn));
+        let pawn_chase = KRKP_PAWN_CHASE * f32::from(strong_king.distance(pawn));
+
+        let evaluation = if (strong_king.file() == pawn.file() && strong_king.rank() < pawn.rank())
+            || (weak_king.distance(pawn) >= 3 + u8::from(!strong_to_move)
+                && weak_king.distance(rook) >= 3)
+        {
+            rook_value - rook_chase
+        } else if weak_king.rank() <= Rank::Third
+            && weak_king.distance(pawn) == 1
+            && strong_king.rank() >= Rank::Fourth
+            && strong_king.distance(pawn) > 2 + u8::from(strong_to_move)
+        {
+            0.4 - pawn_chase
+        } else {
+            let progress = i16::from(strong_king.distance(in_front_of_pawn))
+                - i16::from(weak_king.distance(in_front_of_pawn))
+                - i16::from(pawn.distance(queening_square));
+            let progress = KRKP_PAWN_CHASE * f32::from(progress);
+            1.0 - progress
+        };
+
+        Some(PawnEvaluation::new(evaluation))
+    }
+
+    /// The fraction of the evaluation to keep in a known [ending](Endgame) that is harder to win than its material suggests
+    pub(super) fn endgame_scale_factor(&self) -> f32 {
+        match Endgame::lookup(self.material_hash()) {
+            Some((Endgame::Kbpk, strong_player)) => self.kbpk_scale_factor(strong_player),
+            Some((Endgame::OppositeBishops, _)) => self.opposite_bishops_scale_factor(),
+            _ => 1.0,
+        }
+    }
+
+    /// Rook pawns with a bishop that can't cover the promotion square are a draw once the king reaches the corner
+    fn kbpk_scale_factor(&self, strong_player: PlayerColor) -> f32 {
+        let pawns = self.pieces_masks[NonKingPieceType::Pawn];
+        let pawn_file = [File::A, File::H]
+            .into_iter()
+            .find(|&file| (pawns & !BoardMask::FILES[file]).is_empty());
+        let (Some(file), Some(bishop)) = (
+            pawn_file,
+            self.only_piece_square(strong_player, NonKingPieceType::Bishop),
+        ) else {
+            return 1.0;
+        };
+
+        let queening_square = relative_square(strong_player, Square::new(file, Rank::Eighth));
+        let weak_king = self.king_squares[strong_player.switch()];
+        if bishop.is_light() != queening_square.is_light()
+            && weak_king.distance(queening_square) <= 1
+        {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Bishops of opposite colors are drawish, more so the closer the pawn counts are
+    fn opposite_bishops_scale_factor(&self) -> f32 {
+        let bishops = [PlayerColor::White, PlayerColor::Black]
+            .map(|player| self.only_piece_square(player, NonKingPieceType::Bishop));
+        let [Some(white_bishop), Some(black_bishop)] = bishops else {
+            return 1.0;
+        };
+        if white_bishop.is_light() == black_bishop.is_light() {
+            return 1.0;
+        }
+
+        let pawns = self.pieces_masks[NonKingPieceType::Pawn];
+        let white_pawns = (pawns & self.side_masks[PlayerColor::White]).num_squares();
+        let black_pawns = (pawns & self.side_masks[PlayerColor::Black]).num_squares();
+        if white_pawns.abs_diff(black_pawns) <= 1 {
+            OPPOSITE_BISHOPS_CLOSE
+        } else {
+            OPPOSITE_BISHOPS
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluation::PawnEvaluation;
+    use crate::fen;
+    use test_case::test_case;
+
+    #[test_case("7k/8/8/8/8/8/8/K2Q4 w - - 0 1", "8/8/8/4k3/8/8/8/K2Q4 w - - 0 1"; "queen pushes to edge")]
+    #[test_case("8/8/8/3k4/8/2K5/8/7R w - - 0 1", "8/8/8/3k4/8/8/8/K6R w - - 0 1"; "rook brings king closer")]
+    #[test_case("k7/8/1K6/8/8/8/8/1B1N4 w - - 0 1", "7k/8/6K1/8/8/8/8/1B1N4 w - - 0 1"; "bishop and knight right corner")]
+    fn winning_ending_prefers_progress(better: &str, worse: &str) {
+        let better = fen!(better).evaluate();
+        let worse = fen!(worse).evaluate();
+        assert!(better > worse, "{better:?} <= {worse:?}");
+        assert!(worse > PawnEvaluation::new(super::KNOWN_WIN));
+    }
+
+    #[test_case("8/8/8/3k4/8/8/8/K5Q1 w - - 0 1"; "queen")]
+    #[test_case("8/8/8/3k4/8/8/8/K6R w - - 0 1"; "rook")]
+    #[test_case("8/8/8/3k4/8/8/8/K1BN4 w - - 0 1"; "bishop and knight")]
+    #[test_case("3k4/8/3K4/3P4/8/8/8/8 w - - 0 1"; "king and pawn")]
+    fn known_wins_are_color_symmetrical(fen: &str) {
+        let position = fen!(fen);
+        let flipped = fen!(&flip_fen(fen));
+        assert!(position.evaluate() > PawnEvaluation::new(super::KNOWN_WIN));
+        assert_eq!(position.evaluate(), flipped.evaluate());
+    }
+
+    /// Flip a FEN's board vertically and swap the colors (and the player to move)
+    fn flip_fen(fen: &str) -> alloc::string::String {
+        let (board, rest) = fen.split_once(' ').unwrap();
+        let board = board
+            .split('/')
+            .rev()
+            .map(|rank| {
+                rank.chars()
+                    .map(|c| {
+                        if c.is_ascii_uppercase() {
+                            c.to_ascii_lowercase()
+                        } else {
+                            c.to_ascii_uppercase()
+                        }
+                    })
+                    .collect::<alloc::string::String>()
+            })
+            .collect::<alloc::vec::Vec<_>>()
+            .join("/");
+        let rest = rest
+            .replacen('w', "x", 1)
+            .replacen('b', "w", 1)
+            .replacen('x', "b", 1);
+
+        board + " " + &rest
+    }
+
+    #[test_case("8/8/8/8/8/4k3/4P3/4K3 w - - 0 1"; "king in front of pawn")]
+    #[test_case("k7/8/8/8/8/8/P7/K7 w - - 0 1"; "rook pawn")]
+    #[test_case("8/8/8/8/8/8/4p3/3K2k1 w - - 0 1"; "black pawn in front of white king")]
+    #[test_case("7k/8/8/8/8/8/7P/4KB2 w - - 0 1"; "wrong colored bishop")]
+    fn drawn_endings_are_equal(fen: &str) {
+        assert_eq!(fen!(fen).evaluate(), PawnEvaluation::default());
+    }
+
+    #[test_case("5k2/8/8/8/8/8/7P/4KB2 w - - 0 1", "7k/8/8/8/8/8/7P/4KB2 w - - 0 1"; "wrong bishop king off the corner")]
+    #[test_case("7k/8/8/8/8/8/7P/4K1B1 w - - 0 1", "7k/8/8/8/8/8/7P/4KB2 w - - 0 1"; "right colored bishop")]
+    #[test_case("4k3/2b5/p7/8/8/PPB5/8/4K3 w - - 0 1", "4k3/1b6/p7/8/8/PPB5/8/4K3 w - - 0 1"; "same colored bishops")]
+    #[test_case("4k3/1b6/8/8/8/PPB5/8/4K3 w - - 0 1", "4k3/1b6/p7/8/8/PPB5/8/4K3 w - - 0 1"; "more pawns up")]
+    fn scaled_endings_are_harder_to_win(easier: &str, harder: &str) {
+        assert!(fen!(easier).evaluate() > fen!(harder).evaluate());
+    }
+
+    #[test_case("R7/7K/8/8/8/3k4/3p4/8 w - - 0 1", true; "pawn supported and advanced")]
+    #[test_case("6k1/8/8/8/3p4/8/3K4/R7 w - - 0 1", false; "king in front of pawn")]
+    #[test_case("8/8/8/8/3p4/8/8/R1K1k3 w - - 0 1", false; "defending king too far")]
+    fn krkp_works(fen: &str, drawish: bool) {
+        let evaluation = fen!(fen).evaluate();
+        assert_eq!(
+            evaluation < PawnEvaluation::new(2.0),
+            drawish,
+            "{evaluation:?}"
+        );
+        assert!(evaluation > PawnEvaluation::new(0.0));
+    }
+
+    #[test]
+    fn stalemate_is_drawn() {
+        assert_eq!(
+            fen!("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1").evaluate(),
+            PawnEvaluation::default()
+        );
+    }
+}
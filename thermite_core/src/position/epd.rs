@@ -0,0 +1,359 @@
+use crate::node_count::NodeCount;
+use crate::ply_count::PlyCount;
+use crate::position::{FenParseError, PositionBuilder};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+/// A single operation (an opcode and its operands) of an [`EpdRecord`]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum EpdOperation {
+    /// `bm`, the moves (in SAN) considered best
+    BestMoves(Vec<String>),
+    /// `am`, the moves (in SAN) that should be avoided
+    AvoidMoves(Vec<String>),
+    /// `id`, the name of the position within its suite
+    Id(String),
+    /// `c0` to `c9`, a numbered comment
+    Comment(u8, String),
+    /// `dm`, the player to move mates in this many full moves
+    DirectMate(u8),
+    /// `acd`, the depth the position was analysed to
+    AnalysisDepth(PlyCount),
+    /// `acn`, the number of nodes the position was analysed for
+    AnalysisNodes(NodeCount),
+    /// `ce`, the evaluation (in centi-pawns) of the position for the player to move
+    CentipawnEvaluation(i32),
+    /// `D1` to `D6` (or deeper), the number of leaf nodes of a perft to a depth
+    PerftNodes(PlyCount, NodeCount),
+    /// Any other opcode, with its operands as written
+    Other(String, Vec<String>),
+}
+
+/// An error encountered parsing an [`EpdRecord`]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum EpdParseError {
+    /// The position fields couldn't be parsed
+    InvalidFen(FenParseError),
+    /// A quoted operand is missing its closing quote
+    UnterminatedString,
+    /// An opcode is missing an operand it requires
+    MissingOperand(String),
+    /// An operand isn't valid for its opcode
+    InvalidOperand(String),
+}
+
+/// A position in Extended Position Description, a FEN with its clocks replaced (or followed) by opcodes
+///
+/// ```
+/// use thermite_core::position::{EpdOperation, EpdRecord};
+///
+/// let record: EpdRecord = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(record.id(), Some("WAC.001"));
+/// assert_eq!(record.operations()[0], EpdOperation::BestMoves(vec![String::from("Qg6")]));
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct EpdRecord {
+    position: PositionBuilder,
+    operations: Vec<EpdOperation>,
+}
+
+impl EpdRecord {
+    /// The position described by the record
+    pub const fn position(&self) -> PositionBuilder {
+        self.position
+    }
+
+    /// Every operation of the record, in the order they were written
+    #[must_use]
+    pub fn operations(&self) -> &[EpdOperation] {
+        &self.operations
+    }
+
+    /// The `id` of the record, if it has one
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        self.operations
+            .iter()
+            .find_map(|operation| match operation {
+                EpdOperation::Id(id) => Some(id.as_str()),
+                _ => None,
+            })
+    }
+
+    /// The moves (in SAN) of the `bm` operation, or an empty slice without one
+    #[must_use]
+    pub fn best_moves(&self) -> &[String] {
+        self.operations
+            .iter()
+            .find_map(|operation| match operation {
+                EpdOperation::BestMoves(moves) => Some(moves.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The moves (in SAN) of the `am` operation, or an empty slice without one
+    #[must_use]
+    pub fn avoid_moves(&self) -> &[String] {
+        self.operations
+            .iter()
+            .find_map(|operation| match operation {
+                EpdOperation::AvoidMoves(moves) => Some(moves.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The full moves to mate of the `dm` operation, if it has one
+    #[must_use]
+    pub fn direct_mate(&self) -> Option<u8> {
+        self.operations
+            .iter()
+            .find_map(|operation| match operation {
+                EpdOperation::DirectMate(moves) => Some(*moves),
+                _ => None,
+            })
+    }
+
+    /// The expected perft node counts of the record by depth, in the order they were written
+    pub fn perft_nodes(&self) -> impl Iterator<Item = (PlyCount, NodeCount)> + '_ {
+        self.operations
+            .iter()
+            .filter_map(|operation| match operation {
+                EpdOperation::PerftNodes(depth, nodes) => Some((*depth, *nodes)),
+                _ => None,
+            })
+    }
+}
+
+/// Split the first whitespace separated field from the rest of a string
+fn split_field(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    s.split_once(char::is_whitespace).unwrap_or((s, ""))
+}
+
+/// Split the operations of an EPD record into their opcodes and operands
+fn split_operations(mut s: &str) -> Result<Vec<(&str, Vec<&str>)>, EpdParseError> {
+    let mut operations = Vec::new();
+    loop {
+        s = s.trim_start_matches(|c: char| c.is_whitespace() || c == ';');
+        if s.is_empty() {
+            return Ok(operations);
+        }
+
+        let opcode_end = s
+            .find(|c: char| c.is_whitespace() || c == ';')
+            .unwrap_or(s.len());
+        let (opcode, mut rest) = s.split_at(opcode_end);
+        let mut operands = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() || rest.starts_with(';') {
+                break;
+            }
+            if let Some(quoted) = rest.strip_prefix('"') {
+                let (operand, after) = quoted
+                    .split_once('"')
+                    .ok_or(EpdParseError::UnterminatedString)?;
+                operands.push(operand);
+                rest = after;
+            } else {
+                let operand_end = rest
+                    .find(|c: char| c.is_whitespace() || c == ';')
+                    .unwrap_or(rest.len());
+                let (operand, after) = rest.split_at(operand_end);
+                operands.push(operand);
+                rest = after;
+            }
+        }
+        operations.push((opcode, operands));
+        s = rest;
+    }
+}
+
+/// Parse the single operand of an opcode
+fn parse_operand<T: FromStr>(opcode: &str, operands: &[&str]) -> Result<T, EpdParseError> {
+    operands
+        .first()
+        .ok_or_else(|| EpdParseError::MissingOperand(opcode.to_string()))?
+        .parse()
+        .map_err(|_| EpdParseError::InvalidOperand(opcode.to_string()))
+}
+
+impl EpdOperation {
+    /// Interpret the operands of an opcode
+    fn new(opcode: &str, operands: &[&str]) -> Result<Self, EpdParseError> {
+        let strings = || operands.iter().map(ToString::to_string).collect();
+        let joined = || operands.join(" ");
+        let operation = match opcode {
+            "bm" => Self::BestMoves(strings()),
+            "am" => Self::AvoidMoves(strings()),
+            "id" => Self::Id(joined()),
+            "dm" => Self::DirectMate(parse_operand(opcode, operands)?),
+            "acd" => Self::AnalysisDepth(PlyCount::new(parse_operand(opcode, operands)?)),
+            "acn" => Self::AnalysisNodes(NodeCount::new(parse_operand(opcode, operands)?)),
+            "ce" => Self::CentipawnEvaluation(parse_operand(opcode, operands)?),
+            _ => {
+                if let Some(index) = opcode
+                    .strip_prefix('c')
+                    .filter(|index| index.len() == 1)
+                    .and_then(|index| index.parse().ok())
+                {
+                    Self::Comment(index, joined())
+                } else if let Some(depth) = opcode
+                    .strip_prefix('D')
+                    .and_then(|depth| depth.parse::<u8>().ok())
+                    .filter(|&depth| depth > 0)
+                {
+                    Self::PerftNodes(
+                        PlyCount::new(depth),
+                        NodeCount::new(parse_operand(opcode, operands)?),
+                    )
+                } else {
+                    Self::Other(opcode.to_string(), strings())
+                }
+            }
+        };
+
+        Ok(operation)
+    }
+}
+
+impl FromStr for EpdRecord {
+    type Err = EpdParseError;
+
+    /// Parse an EPD record, the four position fields of a FEN (optionally followed by its clocks) then its operations
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rest = s;
+        let mut fields = Vec::with_capacity(6);
+        for _ in 0..4 {
+            let (field, after) = split_field(rest);
+            fields.push(field);
+            rest = after;
+        }
+        // Some suites keep the halfmove clock and fullmove number of the FEN
+        for _ in 0..2 {
+            let (field, after) = split_field(rest);
+            if field.is_empty() || !field.bytes().all(|byte| byte.is_ascii_digit()) {
+                break;
+            }
+            fields.push(field);
+            rest = after;
+        }
+        let position = fields
+            .join(" ")
+            .parse::<PositionBuilder>()
+            .map_err(EpdParseError::InvalidFen)?;
+        let operations = split_operations(rest)?
+            .into_iter()
+            .map(|(opcode, operands)| EpdOperation::new(opcode, &operands))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            position,
+            operations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::node_count::NodeCount;
+    use crate::ply_count::PlyCount;
+    use crate::position::{EpdOperation, EpdParseError, EpdRecord, FenParseError, PositionBuilder};
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use test_case::test_case;
+
+    #[test]
+    fn parses_test_suite_records() {
+        let record: EpdRecord = "r1b1k2r/1p1pppb1/p5pp/3P4/q2p1B2/3P1Q2/PPP2PPP/R3R1K1 w kq - \
+                                 bm Bxh6 Qf3xf7+; am Rxe7; id \"STS(v1.0) Undermine.001\"; \
+                                 c0 \"Bxh6=10, Qxf7+=3\"; dm 4; acd 12; acn 123456; ce -35;"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            record.position(),
+            "r1b1k2r/1p1pppb1/p5pp/3P4/q2p1B2/3P1Q2/PPP2PPP/R3R1K1 w kq -"
+                .parse::<PositionBuilder>()
+                .unwrap()
+        );
+        assert_eq!(record.best_moves(), ["Bxh6", "Qf3xf7+"]);
+        assert_eq!(record.avoid_moves(), ["Rxe7"]);
+        assert_eq!(record.id(), Some("STS(v1.0) Undermine.001"));
+        assert_eq!(record.direct_mate(), Some(4));
+        assert_eq!(
+            record.operations()[3..],
+            [
+                EpdOperation::Comment(0, String::from("Bxh6=10, Qxf7+=3")),
+                EpdOperation::DirectMate(4),
+                EpdOperation::AnalysisDepth(PlyCount::new(12)),
+                EpdOperation::AnalysisNodes(NodeCount::new(123_456)),
+                EpdOperation::CentipawnEvaluation(-35),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_perft_records() {
+        let record: EpdRecord =
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ;D1 20 ;D2 400 ;D3 8902"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            record.perft_nodes().collect::<Vec<_>>(),
+            [
+                (PlyCount::new(1), NodeCount::new(20)),
+                (PlyCount::new(2), NodeCount::new(400)),
+                (PlyCount::new(3), NodeCount::new(8902)),
+            ]
+        );
+        assert!(record.best_moves().is_empty());
+        assert_eq!(record.id(), None);
+    }
+
+    #[test]
+    fn keeps_unknown_opcodes() {
+        let record: EpdRecord = "4k3/8/8/8/8/8/8/4K3 b - - hmvc 12; pv Kd7 Kd2;"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            record.operations(),
+            [
+                EpdOperation::Other(String::from("hmvc"), vec![String::from("12")]),
+                EpdOperation::Other(
+                    String::from("pv"),
+                    vec![String::from("Kd7"), String::from("Kd2")]
+                ),
+            ]
+        );
+    }
+
+    #[test_case("", EpdParseError::InvalidFen(FenParseError::MissingPosition))]
+    #[test_case(
+        "4k3/8/8/8/8/8/8/4K3 w - - id \"open;",
+        EpdParseError::UnterminatedString
+    )]
+    #[test_case(
+        "4k3/8/8/8/8/8/8/4K3 w - - dm;",
+        EpdParseError::MissingOperand(String::from("dm"))
+    )]
+    #[test_case(
+        "4k3/8/8/8/8/8/8/4K3 w - - acn many;",
+        EpdParseError::InvalidOperand(String::from("acn"))
+    )]
+    #[test_case(
+        "4k3/8/8/8/8/8/8/4K3 w - - ;D1 -20",
+        EpdParseError::InvalidOperand(String::from("D1"))
+    )]
+    fn parse_errors(epd: &str, expected: EpdParseError) {
+        assert_eq!(epd.parse::<EpdRecord>(), Err(expected));
+    }
+}
@@ -0,0 +1,439 @@
+use crate::bitboard::BoardMask;
+use crate::direction::Direction;
+use crate::evaluation::{
+    EvaluationFeatures, EvaluationParameter, EvaluationTerm, EvaluationTrace, GamePhase,
+    PawnEvaluation, TaperedEvaluation,
+};
+use crate::pieces::{NonKingPieceType, Piece, PieceType, PromotablePieceType};
+use crate::player_color::PlayerColor;
+use crate::position::material_evaluation::PIECE_VALUES;
+use crate::position::piece_square_tables::piece_square_evaluation;
+use crate::position::LegalPosition;
+use crate::square::{File, Rank, Square};
+use enum_iterator::all;
+use enum_map::{Enum, EnumMap};
+
+/// Penalty (mid-game, end-game centipawns) for each extra pawn stacked on a file
+const DOUBLED_PAWN: (i16, i16) = (-10, -20);
+/// Penalty (mid-game, end-game centipawns) for each pawn without any friendly pawns on the neighbouring files
+const ISOLATED_PAWN: (i16, i16) = (-10, -15);
+/// Bonus (mid-game, end-game centipawns) for a pawn with no opposing pawns in front of it, or on the neighbouring files, by relative rank
+#[rustfmt::skip]
+const PASSED_PAWN: [(i16, i16); Rank::LENGTH] = [
+    (0, 0), (5, 10), (10, 20), (15, 35), (25, 60), (45, 100), (70, 150), (0, 0),
+];
+/// Bonus (mid-game, end-game centipawns) for each square a piece can safely move to
+const MOBILITY: EnumMap<PromotablePieceType, (i16, i16)> =
+    EnumMap::from_array([(4, 4), (5, 5), (2, 4), (1, 2)]);
+/// The number of safe squares a piece is expected to have, anything less is penalized and anything more is rewarded
+const MOBILITY_BASELINE: EnumMap<PromotablePieceType, u8> = EnumMap::from_array([4, 7, 7, 14]);
+/// Bonus (mid-game, end-game centipawns) for each friendly pawn sheltering the king
+const PAWN_SHIELD: (i16, i16) = (12, 0);
+/// Penalty (mid-game, end-game centipawns) for each square around the king attacked by an opposing piece
+const KING_ZONE_ATTACK: EnumMap<PromotablePieceType, (i16, i16)> =
+    EnumMap::from_array([(-8, 0), (-8, 0), (-12, 0), (-20, 0)]);
+
+/// Convert a (mid-game, end-game) pair of centipawns to a [`TaperedEvaluation`]
+fn centipawns((mid_game, end_game): (i16, i16)) -> TaperedEvaluation {
+    TaperedEvaluation::new(
+        PawnEvaluation::from_centipawns(mid_game),
+        PawnEvaluation::from_centipawns(end_game),
+    )
+}
+
+/// Get the [mask](BoardMask) of the files on either side of a [`File`]
+fn adjacent_files_mask(file: File) -> BoardMask {
+    let file_mask = BoardMask::FILES[file];
+
+    file_mask.shift(Direction::East) | file_mask.shift(Direction::West)
+}
+
+/// Get the [mask](BoardMask) of every rank in front of a [`Rank`] from a [player](PlayerColor)'s perspective
+fn forward_ranks_mask(player: PlayerColor, rank: Rank) -> BoardMask {
+    (0..Rank::LENGTH)
+        .map(Rank::from_usize)
+        .filter(|&other| match player {
+            PlayerColor::White => other > rank,
+            PlayerColor::Black => other < rank,
+        })
+        .fold(BoardMask::EMPTY, |mask, other| {
+            mask | BoardMask::RANKS[other]
+        })
+}
+
+/// Get the index of a [`Rank`] from a [player](PlayerColor)'s perspective (their back rank being 0)
+const fn relative_rank_index(player: PlayerColor, rank: Rank) -> usize {
+    match player {
+        PlayerColor::White => rank as usize,
+        PlayerColor::Black => Rank::Eighth as usize - rank as usize,
+    }
+}
+
+impl LegalPosition {
+    /// Get the [mask](BoardMask) of a [piece](NonKingPieceType) for a single [player](PlayerColor)
+    fn player_piece_mask(&self, player: PlayerColor, piece: NonKingPieceType) -> BoardMask {
+        self.pieces_masks[piece] & self.side_masks[player]
+    }
+
+    /// Get the number of each [piece](NonKingPieceType) on the board for both [players](PlayerColor)
+    fn piece_counts(&self) -> EnumMap<NonKingPieceType, u8> {
+        EnumMap::from_fn(|piece| self.pieces_masks[piece].num_squares())
+    }
+
+    /// Get the [`GamePhase`] based on the non-pawn material left on the board
+    ///
+    /// ```
+    /// use thermite_core::evaluation::GamePhase;
+    /// use thermite_core::fen;
+    ///
+    /// assert_eq!(fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").game_phase(), GamePhase::MAX);
+    /// assert_eq!(fen!("8/5pk1/8/8/8/8/2PK4/8 w - - 0 1").game_phase(), GamePhase::MIN);
+    /// ```
+    #[must_use]
+    pub fn game_phase(&self) -> GamePhase {
+        GamePhase::from_piece_counts(self.piece_counts())
+    }
+
+    /// Visit the number of every non-king piece a [player](PlayerColor) has
+    fn visit_material(
+        &self,
+        player: PlayerColor,
+        visit: &mut impl FnMut(EvaluationParameter, i16),
+    ) {
+        for piece in all::<NonKingPieceType>() {
+            let count = self.player_piece_mask(player, piece).num_squares();
+            visit(EvaluationParameter::PieceValue(piece), i16::from(count));
+        }
+    }
+
+    /// Visit the square of every piece a [player](PlayerColor) has, as seen from their side of the board
+    fn visit_piece_squares(
+        &self,
+        player: PlayerColor,
+        visit: &mut impl FnMut(EvaluationParameter, i16),
+    ) {
+        let relative_square = |square: Square| match player {
+            PlayerColor::White => square,
+            PlayerColor::Black => Square::try_from(square as u8 ^ Square::A8 as u8)
+                .expect("vertically flipped square is on the board"),
+        };
+
+        let king_square = relative_square(self.king_squares[player]);
+        visit(
+            EvaluationParameter::PieceSquare(PieceType::King, king_square),
+            1,
+        );
+        for piece in all::<NonKingPieceType>() {
+            for square in self.player_piece_mask(player, piece) {
+                let parameter = EvaluationParameter::PieceSquare(
+                    PieceType::from(piece),
+                    relative_square(square),
+                );
+                visit(parameter, 1);
+            }
+        }
+    }
+
+    /// Visit the structure of a [player](PlayerColor)'s pawns, counting doubled, isolated and passed pawns
+    fn visit_pawns(&self, player: PlayerColor, visit: &mut impl FnMut(EvaluationParameter, i16)) {
+        let pawns = self.player_piece_mask(player, NonKingPieceType::Pawn);
+        let opposing_pawns = self.player_piece_mask(player.switch(), NonKingPieceType::Pawn);
+
+        for file in (0..File::LENGTH).map(File::from_usize) {
+            let file_pawns = (pawns & BoardMask::FILES[file]).num_squares();
+            let doubled_pawns = file_pawns.saturating_sub(1);
+            let isolated_pawns = if (pawns & adjacent_files_mask(file)).is_empty() {
+                file_pawns
+            } else {
+                0
+            };
+
+            visit(EvaluationParameter::DoubledPawn, i16::from(doubled_pawns));
+            visit(EvaluationParameter::IsolatedPawn, i16::from(isolated_pawns));
+        }
+
+        for square in pawns {
+            let file = square.file();
+            let front_span = (BoardMask::FILES[file] | adjacent_files_mask(file))
+                & forward_ranks_mask(player, square.rank());
+            if (opposing_pawns & front_span).is_empty() {
+                let rank = Rank::from_usize(relative_rank_index(player, square.rank()));
+                visit(EvaluationParameter::PassedPawn(rank), 1);
+            }
+        }
+    }
+
+    /// Visit the number of squares a [player](PlayerColor)'s pieces can move to without being attacked by an opposing pawn
+    fn visit_mobility(
+        &self,
+        player: PlayerColor,
+        visit: &mut impl FnMut(EvaluationParameter, i16),
+    ) {
+        let opponent = self.player_piece_mask(player.switch(), NonKingPieceType::Pawn);
+        let safe_squares = !self.side_masks[player] & !opponent.pawn_attacks(player.switch());
+
+        for piece in all::<PromotablePieceType>() {
+            let piece_mask = self.player_piece_mask(
+                player,
+                NonKingPieceType::try_from(PieceType::from(piece))
+                    .expect("all promotable pieces are non-king"),
+            );
+            for square in piece_mask {
+                let moves =
+                    self.get_attacks_for_promotable_piece_type(square, piece) & safe_squares;
+                let excess_moves =
+                    i16::from(moves.num_squares()) - i16::from(MOBILITY_BASELINE[piece]);
+
+                visit(EvaluationParameter::Mobility(piece), excess_moves);
+            }
+        }
+    }
+
+    /// Visit the safety of a [player](PlayerColor)'s king, counting sheltering pawns and opposing pieces attacking nearby squares
+    fn visit_king_safety(
+        &self,
+        player: PlayerColor,
+        visit: &mut impl FnMut(EvaluationParameter, i16),
+    ) {
+        let opponent = player.switch();
+        let king_square = self.king_squares[player];
+        let king_mask = king_square.to_mask();
+        let king_zone = king_mask.king_attacks() | king_mask;
+
+        let shield_rows =
+            king_mask.pawn_push(player) | king_mask.pawn_push(player).pawn_push(player);
+        let shield_mask =
+            shield_rows | shield_rows.shift(Direction::East) | shield_rows.shift(Direction::West);
+        let shield_pawns =
+            (self.player_piece_mask(player, NonKingPieceType::Pawn) & shield_mask).num_squares();
+        visit(EvaluationParameter::PawnShield, i16::from(shield_pawns));
+
+        for piece in all::<PromotablePieceType>() {
+            let piece_mask = self.player_piece_mask(
+                opponent,
+                NonKingPieceType::try_from(PieceType::from(piece))
+                    .expect("all promotable pieces are non-king"),
+            );
+            for square in piece_mask {
+                let attacked_squares = (self.get_attacks_for_promotable_piece_type(square, piece)
+                    & king_zone)
+                    .num_squares();
+
+                visit(
+                    EvaluationParameter::KingZoneAttack(piece),
+                    i16::from(attacked_squares),
+                );
+            }
+        }
+    }
+
+    /// Visit every [parameter](EvaluationParameter) that applies to a [player](PlayerColor), along with how many times it applies
+    fn visit_parameters(
+        &self,
+        player: PlayerColor,
+        visit: &mut impl FnMut(EvaluationParameter, i16),
+    ) {
+        self.visit_material(player, visit);
+        self.visit_piece_squares(player, visit);
+        self.visit_pawns(player, visit);
+        self.visit_mobility(player, visit);
+        self.visit_king_safety(player, visit);
+    }
+
+    /// Statically evaluate the position from the perspective of the [player to move](Self::player_to_move),
+    /// using the neural network if one has been set (with the `nnue` feature) and the handcrafted evaluation otherwise
+    ///
+    /// Known [endings](crate::evaluation::Endgame) are evaluated by their dedicated knowledge instead.
+    #[must_use]
+    pub fn evaluate(&self) -> PawnEvaluation {
+        if let Some(evaluation) = self.evaluate_endgame() {
+            return evaluation;
+        }
+
+        #[cfg(feature = "nnue")]
+        if let Some(accumulator) = &self.accumulator {
+            return accumulator.evaluate(self.player_to_move);
+        }
+
+        self.evaluate_with_trace()
+            .evaluation_for(self.player_to_move)
+    }
+
+    /// Statically evaluate the position, keeping the breakdown of each [term](EvaluationTerm) for both [players](PlayerColor)
+    ///
+    /// ```
+    /// use thermite_core::evaluation::{EvaluationTerm, GamePhase, PawnEvaluation};
+    /// use thermite_core::fen;
+    /// use thermite_core::player_color::PlayerColor;
+    ///
+    /// let trace = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").evaluate_with_trace();
+    /// assert_eq!(trace.phase(), GamePhase::MAX);
+    /// assert_eq!(trace.term(EvaluationTerm::Material, PlayerColor::White), trace.term(EvaluationTerm::Material, PlayerColor::Black));
+    /// assert_eq!(trace.evaluation_for(PlayerColor::White), PawnEvaluation::new(0.0));
+    /// ```
+    #[must_use]
+    pub fn evaluate_with_trace(&self) -> EvaluationTrace {
+        let mut terms =
+            EnumMap::<EvaluationTerm, EnumMap<PlayerColor, TaperedEvaluation>>::default();
+        for player in all::<PlayerColor>() {
+            self.visit_parameters(player, &mut |parameter, count| {
+                terms[parameter.term()][player] += parameter.value() * f32::from(count);
+            });
+        }
+
+        EvaluationTrace {
+            terms,
+            phase: self.game_phase(),
+            scale_factor: self.endgame_scale_factor(),
+        }
+    }
+
+    /// Extract the [features](EvaluationFeatures) of the static evaluation, for tuning the [parameters](EvaluationParameter)
+    ///
+    /// ```
+    /// use thermite_core::evaluation::EvaluationParameter;
+    /// use thermite_core::fen;
+    /// use thermite_core::pieces::NonKingPieceType;
+    ///
+    /// let features = fen!("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1").evaluation_features();
+    /// assert!(features.coefficients().contains(&(EvaluationParameter::PieceValue(NonKingPieceType::Pawn), 2)));
+    /// ```
+    #[must_use]
+    pub fn evaluation_features(&self) -> EvaluationFeatures {
+        let mut coefficients = [0i16; EvaluationParameter::COUNT];
+        for player in all::<PlayerColor>() {
+            let sign = match player {
+                PlayerColor::White => 1,
+                PlayerColor::Black => -1,
+            };
+            self.visit_parameters(player, &mut |parameter, count| {
+                coefficients[parameter.index()] += sign * count;
+            });
+        }
+
+        EvaluationFeatures {
+            coefficients: EvaluationParameter::all()
+                .filter(|parameter| coefficients[parameter.index()] != 0)
+                .map(|parameter| (parameter, coefficients[parameter.index()]))
+                .collect(),
+            phase: self.game_phase(),
+            scale_factor: self.endgame_scale_factor(),
+        }
+    }
+}
+
+impl EvaluationParameter {
+    /// Get the weight currently used by the engine's evaluation for the parameter
+    ///
+    /// ```
+    /// use thermite_core::evaluation::{EvaluationParameter, PawnEvaluation, TaperedEvaluation};
+    ///
+    /// assert_eq!(
+    ///     EvaluationParameter::DoubledPawn.value(),
+    ///     TaperedEvaluation::new(PawnEvaluation::new(-0.1), PawnEvaluation::new(-0.2))
+    /// );
+    /// ```
+    #[must_use]
+    pub fn value(self) -> TaperedEvaluation {
+        match self {
+            Self::PieceValue(piece) => TaperedEvaluation::flat(PIECE_VALUES[piece]),
+            Self::PieceSquare(piece, square) => {
+                piece_square_evaluation(piece.owned_by(PlayerColor::White), square)
+            }
+            Self::DoubledPawn => centipawns(DOUBLED_PAWN),
+            Self::IsolatedPawn => centipawns(ISOLATED_PAWN),
+            Self::PassedPawn(rank) => centipawns(PASSED_PAWN[rank as usize]),
+            Self::Mobility(piece) => centipawns(MOBILITY[piece]),
+            Self::PawnShield => centipawns(PAWN_SHIELD),
+            Self::KingZoneAttack(piece) => centipawns(KING_ZONE_ATTACK[piece]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluation::{
+        EvaluationParameters, EvaluationTerm, PawnEvaluation, TaperedEvaluation,
+    };
+    use crate::fen;
+    use crate::player_color::PlayerColor;
+    use enum_iterator::all;
+    use test_case::test_case;
+
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"
+    )]
+    #[test_case(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1"
+    )]
+    #[test_case(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "8/4p1p1/8/1r3P1K/kp5R/3P4/2P5/8 b - - 0 1"
+    )]
+    #[test_case(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1"
+    )]
+    fn evaluate_is_color_symmetrical(fen: &str, mirrored_fen: &str) {
+        let trace = fen!(fen).evaluate_with_trace();
+        let mirrored_trace = fen!(mirrored_fen).evaluate_with_trace();
+        for term in all::<EvaluationTerm>() {
+            assert_eq!(
+                trace.term(term, PlayerColor::White),
+                mirrored_trace.term(term, PlayerColor::Black),
+                "{term} differs for white"
+            );
+            assert_eq!(
+                trace.term(term, PlayerColor::Black),
+                mirrored_trace.term(term, PlayerColor::White),
+                "{term} differs for black"
+            );
+        }
+        assert_eq!(fen!(fen).evaluate(), fen!(mirrored_fen).evaluate());
+    }
+
+    #[test_case("1r4k1/p4pbp/6p1/8/8/5QPb/PPP2P1P/R1BNrBK1 b - - 2 4")]
+    #[test_case("8/2q3kp/6p1/3Bp3/5n2/Q3BPK1/1r5P/8 b - - 0 1")]
+    #[test_case("4r3/1pP2kp1/3R4/1p3pP1/1r6/1P6/1K2p3/4R3 w - - 0 1")]
+    fn material_term_matches_material_evaluation(fen: &str) {
+        let position = fen!(fen);
+        let material = position
+            .evaluate_with_trace()
+            .term_total(EvaluationTerm::Material);
+        let expected = position.material_eval.for_player(PlayerColor::White);
+        assert_eq!(material.mid_game, expected);
+        assert_eq!(material.end_game, expected);
+    }
+
+    #[test_case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", false; "unscaled")]
+    #[test_case("4k3/1b6/p7/8/8/PPB5/8/4K3 w - - 0 1", true; "opposite colored bishops")]
+    #[test_case("6k1/8/8/8/8/8/7P/4KB2 b - - 0 1", true; "wrong colored bishop")]
+    fn features_match_the_evaluation(fen: &str, scaled: bool) {
+        let position = fen!(fen);
+        let features = position.evaluation_features();
+        let trace = position.evaluate_with_trace();
+
+        assert_eq!(features.scale_factor() < 1.0, scaled);
+        let evaluation = features.evaluate(&EvaluationParameters::default());
+        let expected = trace.evaluation_for(PlayerColor::White);
+        assert!(
+            (*evaluation - *expected).abs() < 0.001,
+            "{evaluation:?} {expected:?}"
+        );
+    }
+
+    #[test_case("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1", -0.10, -0.15)]
+    #[test_case("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1", 0.10, 0.20)]
+    #[test_case("4k3/8/8/8/4P3/8/4P3/4K3 w - - 0 1", -0.10, -0.05)]
+    fn pawns_term_works(fen: &str, mid_game: f32, end_game: f32) {
+        let pawns = fen!(fen)
+            .evaluate_with_trace()
+            .term(EvaluationTerm::Pawns, PlayerColor::White);
+        assert_eq!(
+            pawns,
+            TaperedEvaluation::new(PawnEvaluation::new(mid_game), PawnEvaluation::new(end_game))
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use crate::pieces::{NonKingPieceType, NonPawnPieceType, Piece, PieceType};
+use crate::player_color::PlayerColor;
+use crate::position::LegalPosition;
+use crate::zobrist::{MaterialHash, NonPawnHash, PawnHash, ZobristHash};
+use enum_iterator::all;
+
+impl LegalPosition {
+    /// Get the [`ZobristHash`] of the entire position
+    #[must_use]
+    pub const fn hash(&self) -> ZobristHash {
+        self.state.hash
+    }
+
+    /// Get the [`PawnHash`] of only the pawns on the board
+    ///
+    /// ```
+    /// use thermite_core::fen;
+    ///
+    /// let position = fen!("4k3/pp6/8/8/8/8/3P4/R3K3 w - - 0 1");
+    /// let moved_pieces = fen!("3qk3/pp6/8/8/8/8/3P4/4K3 b - - 0 1");
+    /// assert_eq!(position.pawn_hash(), moved_pieces.pawn_hash());
+    /// ```
+    #[must_use]
+    pub const fn pawn_hash(&self) -> PawnHash {
+        self.state.pawn_hash
+    }
+
+    /// Get the [`MaterialHash`] of the number of each piece both sides have
+    ///
+    /// ```
+    /// use thermite_core::fen;
+    ///
+    /// let position = fen!("4k3/pp6/8/8/8/8/3P4/R3K3 w - - 0 1");
+    /// let moved_pieces = fen!("4k3/8/p7/2p5/8/P7/8/K6R b - - 0 1");
+    /// assert_eq!(position.material_hash(), moved_pieces.material_hash());
+    /// ```
+    #[must_use]
+    pub const fn material_hash(&self) -> MaterialHash {
+        self.state.material_hash
+    }
+
+    /// Get the [`NonPawnHash`] of a [player](PlayerColor)'s non-pawn pieces
+    #[must_use]
+    pub fn non_pawn_hash(&self, player: PlayerColor) -> NonPawnHash {
+        self.state.non_pawn_hashes[player]
+    }
+
+    /// Compute the [`PawnHash`] from scratch
+    fn compute_pawn_hash(&self) -> PawnHash {
+        let mut pawn_hash = PawnHash::default();
+        for player in all::<PlayerColor>() {
+            let pawns = self.pieces_masks[NonKingPieceType::Pawn] & self.side_masks[player];
+            for square in pawns {
+                pawn_hash.toggle_pawn_square(player, square);
+            }
+        }
+
+        pawn_hash
+    }
+
+    /// Compute the [`MaterialHash`] from scratch
+    fn compute_material_hash(&self) -> MaterialHash {
+        let mut material_hash = MaterialHash::default();
+        for player in all::<PlayerColor>() {
+            for piece in all::<NonKingPieceType>() {
+                let count = (self.pieces_masks[piece] & self.side_masks[player]).num_squares();
+                for index in 0..count {
+                    material_hash.toggle_piece_count(piece.owned_by(player), index);
+                }
+            }
+        }
+
+        material_hash
+    }
+
+    /// Compute a [player](PlayerColor)'s [`NonPawnHash`] from scratch
+    fn compute_non_pawn_hash(&self, player: PlayerColor) -> NonPawnHash {
+        let mut non_pawn_hash = NonPawnHash::default();
+        non_pawn_hash.toggle_piece_square(
+            PieceType::King
+                .owned_by(player)
+                .placed_on(self.king_squares[player]),
+        );
+        for piece in all::<NonPawnPieceType>() {
+            let Ok(non_king_piece) = NonKingPieceType::try_from(PieceType::from(piece)) else {
+                continue;
+            };
+            for square in self.pieces_masks[non_king_piece] & self.side_masks[player] {
+                non_pawn_hash
+                    .toggle_piece_square(PieceType::from(piece).owned_by(player).placed_on(square));
+            }
+        }
+
+        non_pawn_hash
+    }
+
+    /// Check that every incrementally updated hash matches a from-scratch computation (only in debug builds)
+    ///
+    /// # Panics
+    /// Will panic in debug mode if any incremental hash has diverged from the board
+    pub(super) fn debug_verify_hashes(&self) {
+        debug_assert_eq!(
+            self.state.pawn_hash,
+            self.compute_pawn_hash(),
+            "incremental `PawnHash` diverged"
+        );
+        debug_assert_eq!(
+            self.state.material_hash,
+            self.compute_material_hash(),
+            "incremental `MaterialHash` diverged"
+        );
+        for player in all::<PlayerColor>() {
+            debug_assert_eq!(
+                self.state.non_pawn_hashes[player],
+                self.compute_non_pawn_hash(player),
+                "incremental `NonPawnHash` diverged"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fen;
+    use crate::player_color::PlayerColor;
+    use crate::position::LegalPosition;
+    use crate::zobrist::{MaterialHash, NonPawnHash, PawnHash};
+    use test_case::test_case;
+
+    /// The incrementally updated pawn, material and non-pawn hashes of a position
+    fn incremental_hashes(position: &LegalPosition) -> (PawnHash, MaterialHash, [NonPawnHash; 2]) {
+        (
+            position.pawn_hash(),
+            position.material_hash(),
+            [PlayerColor::White, PlayerColor::Black].map(|player| position.non_pawn_hash(player)),
+        )
+    }
+
+    /// The pawn, material and non-pawn hashes of a position computed from scratch
+    fn computed_hashes(position: &LegalPosition) -> (PawnHash, MaterialHash, [NonPawnHash; 2]) {
+        (
+            position.compute_pawn_hash(),
+            position.compute_material_hash(),
+            [PlayerColor::White, PlayerColor::Black]
+                .map(|player| position.compute_non_pawn_hash(player)),
+        )
+    }
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+    #[test_case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")]
+    #[test_case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+    fn incremental_hashes_match_recomputation(fen: &str) {
+        let mut position = fen!(fen);
+        position.debug_verify_hashes();
+        assert_eq!(incremental_hashes(&position), computed_hashes(&position));
+
+        let mut line = Vec::new();
+        for _ in 0..16 {
+            let moves = position.generate_legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            // Every move must restore the hashes when unmade, not just the one played
+            let before = incremental_hashes(&position);
+            for &chess_move in &moves {
+                let state = position.make_move(chess_move);
+                assert_eq!(incremental_hashes(&position), computed_hashes(&position));
+                position.unmake_move(chess_move, state);
+                assert_eq!(incremental_hashes(&position), before, "{chess_move}");
+            }
+
+            let chess_move = moves[fastrand::usize(..moves.len())];
+            let state = position.make_move(chess_move);
+            line.push((chess_move, state, before));
+        }
+
+        while let Some((chess_move, state, before)) = line.pop() {
+            position.unmake_move(chess_move, state);
+            assert_eq!(incremental_hashes(&position), before, "{chess_move}");
+            assert_eq!(incremental_hashes(&position), computed_hashes(&position));
+        }
+    }
+}
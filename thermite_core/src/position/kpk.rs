@@ -0,0 +1,399 @@
+use crate::bitboard::BoardMask;
+use crate::pieces::SlidingPieceType;
+use crate::player_color::PlayerColor;
+use crate::square::{File, Rank, Square};
+use alloc::vec;
+use alloc::vec::Vec;
+use enum_map::Enum;
+use once_cell::sync::Lazy;
+
+/// The number of squares a pawn can stand on, with its file mirrored into files A to D
+const PAWN_SQUARES: usize = 24;
+/// The number of positions in the bitbase, by side to move, pawn, white king and black king
+const POSITIONS: usize = 2 * PAWN_SQUARES * 64 * 64;
+
+/// The result of a single position while the bitbase is being generated
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Classification {
+    /// Can't arise in a game (overlapping pieces, touching kings or the side not to move in check)
+    Invalid,
+    /// Not yet known to be won, which is a draw once nothing more can be resolved
+    Unknown,
+    /// Drawn for white, whatever white plays
+    Draw,
+    /// Won for white, whatever black plays
+    Win,
+}
+
+/// Where a legal move from a bitbase position leads
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Successor {
+    /// To a position with a known result, after the pawn is captured or promoted (or there are no moves)
+    Known(Classification),
+    /// To another position in the bitbase
+    Position(usize),
+}
+
+/// Whether each king and pawn against king position (with white having the pawn) is won for white
+///
+/// Generated by retrograde analysis: positions where the pawn safely promotes are wins, and a win is then propagated
+/// backwards to every position where white has a move into one, or where every move black has leads into one, until
+/// no more positions are resolved. Everything else is drawn.
+pub(super) struct KpkBitbase {
+    wins: Vec<u64>,
+}
+
+/// The shared [`KpkBitbase`], generated on first use
+pub(super) static KPK: Lazy<KpkBitbase> = Lazy::new(KpkBitbase::generate);
+
+/// Get the bitbase index of a position, with the pawn on files A to D
+const fn index(
+    player_to_move: PlayerColor,
+    pawn: Square,
+    white_king: Square,
+    black_king: Square,
+) -> usize {
+    let pawn_index = (pawn.rank() as usize - Rank::Second as usize) * 4 + pawn.file() as usize;
+
+    ((player_to_move as usize * PAWN_SQUARES + pawn_index) * 64 + white_king as usize) * 64
+        + black_king as usize
+}
+
+/// Get the (player to move, pawn, white king, black king) of a bitbase index
+fn position_of(index: usize) -> (PlayerColor, Square, Square, Square) {
+    let square = |offset: usize| Square::try_from(offset as u8).expect("index is within the board");
+    let black_king = square(index % 64);
+    let white_king = square(index / 64 % 64);
+    let pawn_index = index / (64 * 64) % PAWN_SQUARES;
+    let pawn = Square::new(
+        File::from_usize(pawn_index % 4),
+        Rank::from_usize(pawn_index / 4 + Rank::Second as usize),
+    );
+    let player_to_move = if index / (64 * 64 * PAWN_SQUARES) == 0 {
+        PlayerColor::White
+    } else {
+        PlayerColor::Black
+    };
+
+    (player_to_move, pawn, white_king, black_king)
+}
+
+/// Whether a position could arise in a game
+fn is_valid(
+    player_to_move: PlayerColor,
+    pawn: Square,
+    white_king: Square,
+    black_king: Square,
+) -> bool {
+    // The player not to move can't be in check
+    let pawn_gives_check =
+        !(pawn.to_mask().pawn_attacks(PlayerColor::White) & black_king.to_mask()).is_empty();
+
+    white_king != black_king
+        && pawn != white_king
+        && pawn != black_king
+        && white_king.distance(black_king) > 1
+        && !(player_to_move == PlayerColor::White && pawn_gives_check)
+}
+
+/// The result of promoting to a queen or rook, a win unless the new piece is lost straight away or it's stalemate
+fn promotion_result(
+    piece: SlidingPieceType,
+    promotion: Square,
+    white_king: Square,
+    black_king: Square,
+) -> Classification {
+    let white_king_attacks = white_king.to_mask().king_attacks();
+    let black_king_attacks = black_king.to_mask().king_attacks();
+    // The black king doesn't block the attacks on the squares behind it
+    let piece_attacks = BoardMask::sliding_attacks_for(
+        piece,
+        promotion,
+        white_king.to_mask() | promotion.to_mask(),
+    );
+    let can_capture = !(black_king_attacks & promotion.to_mask()).is_empty()
+        && (white_king_attacks & promotion.to_mask()).is_empty();
+    let escapes = black_king_attacks & !white_king_attacks & !piece_attacks & !promotion.to_mask();
+    let in_check = !(piece_attacks & black_king.to_mask()).is_empty();
+
+    if can_capture || (escapes.is_empty() && !in_check) {
+        Classification::Draw
+    } else {
+        Classification::Win
+    }
+}
+
+/// Find where every legal move of a valid position leads
+///
+/// The moves are found from the piece geometry directly, rather than setting up a full position for each of the
+/// bitbase's positions. Underpromotions are left out since they never win more than a queen or rook does.
+fn successors(
+    player_to_move: PlayerColor,
+    pawn: Square,
+    white_king: Square,
+    black_king: Square,
+) -> Vec<Successor> {
+    let white_king_attacks = white_king.to_mask().king_attacks();
+    let black_king_attacks = black_king.to_mask().king_attacks();
+    let mut successors = Vec::new();
+
+    match player_to_move {
+        PlayerColor::White => {
+            for to in white_king_attacks & !black_king_attacks & !pawn.to_mask() {
+                successors.push(Successor::Position(index(
+                    PlayerColor::Black,
+                    pawn,
+                    to,
+                    black_king,
+                )));
+            }
+
+            let kings = white_king.to_mask() | black_king.to_mask();
+            let single_push = pawn.to_mask().pawn_push(PlayerColor::White) & !kings;
+            let double_push = (single_push & BoardMask::RANKS[Rank::Third])
+                .pawn_push(PlayerColor::White)
+                & !kings;
+            for to in single_push | double_push {
+                if to.rank() == Rank::Eighth {
+                    for piece in [SlidingPieceType::Queen, SlidingPieceType::Rook] {
+                        let result = promotion_result(piece, to, white_king, black_king);
+                        successors.push(Successor::Known(result));
+                    }
+                } else {
+                    successors.push(Successor::Position(index(
+                        PlayerColor::Black,
+                        to,
+                        white_king,
+                        black_king,
+                    )));
+                }
+            }
+        }
+        PlayerColor::Black => {
+            let pawn_attacks = pawn.to_mask().pawn_attacks(PlayerColor::White);
+            for to in black_king_attacks & !white_king_attacks & !pawn_attacks {
+                if to == pawn {
+                    // Capturing the pawn leaves bare kings
+                    successors.push(Successor::Known(Classification::Draw));
+                } else {
+                    successors.push(Successor::Position(index(
+                        PlayerColor::White,
+                        pawn,
+                        white_king,
+                        to,
+                    )));
+                }
+            }
+
+            // A lone king can't be checkmated by a king and pawn, but this doesn't rely on it
+            if successors.is_empty() && !(pawn_attacks & black_king.to_mask()).is_empty() {
+                return vec![Successor::Known(Classification::Win)];
+            }
+        }
+    }
+
+    if successors.is_empty() {
+        // Stalemate
+        successors.push(Successor::Known(Classification::Draw));
+    }
+
+    successors
+}
+
+impl KpkBitbase {
+    fn generate() -> Self {
+        // Every position's successors are kept in one list, with the range of each position's successors within it
+        let mut classifications = vec![Classification::Invalid; POSITIONS];
+        let mut successor_ranges = vec![0..0; POSITIONS];
+        let mut all_successors = Vec::new();
+        for (index, (classification, range)) in classifications
+            .iter_mut()
+            .zip(&mut successor_ranges)
+            .enumerate()
+        {
+            let (player_to_move, pawn, white_king, black_king) = position_of(index);
+            if is_valid(player_to_move, pawn, white_king, black_king) {
+                let start = all_successors.len();
+                all_successors.extend(successors(player_to_move, pawn, white_king, black_king));
+                *range = start..all_successors.len();
+                *classification = Classification::Unknown;
+            }
+        }
+
+        // Keep propagating the known results until nothing changes
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..POSITIONS {
+                if classifications[index] != Classification::Unknown {
+                    continue;
+                }
+                let mut results =
+                    all_successors[successor_ranges[index].clone()]
+                        .iter()
+                        .map(|&successor| match successor {
+                            Successor::Known(classification) => classification,
+                            Successor::Position(index) => classifications[index],
+                        });
+                // The player to move picks their best move, a single win for white (or draw for black) is enough
+                let (best, worst) = match position_of(index).0 {
+                    PlayerColor::White => (Classification::Win, Classification::Draw),
+                    PlayerColor::Black => (Classification::Draw, Classification::Win),
+                };
+                let classification = if results.clone().any(|result| result == best) {
+                    best
+                } else if results.all(|result| result == worst) {
+                    worst
+                } else {
+                    Classification::Unknown
+                };
+                if classification != Classification::Unknown {
+                    classifications[index] = classification;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut wins = vec![0; POSITIONS / 64];
+        for (index, &classification) in classifications.iter().enumerate() {
+            if classification == Classification::Win {
+                wins[index / 64] |= 1 << (index % 64);
+            }
+        }
+
+        Self { wins }
+    }
+
+    /// Whether white wins with the pawn on files A to D (on ranks 2 to 7), whatever the player to move
+    pub(super) fn is_win(
+        &self,
+        player_to_move: PlayerColor,
+        pawn: Square,
+        white_king: Square,
+        black_king: Square,
+    ) -> bool {
+        let index = index(player_to_move, pawn, white_king, black_king);
+
+        self.wins[index / 64] & (1 << (index % 64)) != 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pieces::{NonKingPieceType, Piece, PieceType};
+    use crate::position::{LegalPosition, PositionBuilder};
+    use test_case::test_case;
+
+    #[test_case(PlayerColor::White, Square::A2, Square::A1, Square::A8)]
+    #[test_case(PlayerColor::Black, Square::D7, Square::H8, Square::A1)]
+    #[test_case(PlayerColor::White, Square::C4, Square::E6, Square::B3)]
+    fn index_round_trips(
+        player_to_move: PlayerColor,
+        pawn: Square,
+        white_king: Square,
+        black_king: Square,
+    ) {
+        let index = index(player_to_move, pawn, white_king, black_king);
+        assert!(index < POSITIONS);
+        assert_eq!(
+            position_of(index),
+            (player_to_move, pawn, white_king, black_king)
+        );
+    }
+
+    #[test]
+    fn successors_match_legal_moves() {
+        for sample in (0..POSITIONS).step_by(37) {
+            let (player_to_move, pawn, white_king, black_king) = position_of(sample);
+            if !is_valid(player_to_move, pawn, white_king, black_king) {
+                continue;
+            }
+            let mut position = LegalPosition::try_from(
+                PositionBuilder::default()
+                    .with_piece(
+                        PieceType::King
+                            .owned_by(PlayerColor::White)
+                            .placed_on(white_king),
+                    )
+                    .with_piece(
+                        PieceType::King
+                            .owned_by(PlayerColor::Black)
+                            .placed_on(black_king),
+                    )
+                    .with_piece(PieceType::Pawn.owned_by(PlayerColor::White).placed_on(pawn))
+                    .with_starting_player(player_to_move),
+            )
+            .unwrap();
+
+            let legal_moves = position.generate_legal_moves();
+            let mut expected = Vec::new();
+            for &chess_move in &legal_moves {
+                if chess_move.is_capture() || chess_move.promotion_piece().is_some() {
+                    continue;
+                }
+                let state = position.make_move(chess_move);
+                let pawn = position
+                    .piece_mask(NonKingPieceType::Pawn)
+                    .pop_square()
+                    .unwrap();
+                expected.push(index(
+                    position.player_to_move,
+                    pawn,
+                    position.king_squares[PlayerColor::White],
+                    position.king_squares[PlayerColor::Black],
+                ));
+                position.unmake_move(chess_move, state);
+            }
+            let has_known = legal_moves.is_empty()
+                || legal_moves.iter().any(|chess_move| {
+                    chess_move.is_capture() || chess_move.promotion_piece().is_some()
+                });
+
+            let successors = successors(player_to_move, pawn, white_king, black_king);
+            let mut actual = successors
+                .iter()
+                .filter_map(|&successor| match successor {
+                    Successor::Position(index) => Some(index),
+                    Successor::Known(_) => None,
+                })
+                .collect::<Vec<_>>();
+            expected.sort_unstable();
+            actual.sort_unstable();
+            assert_eq!(actual, expected, "{position:?}");
+            assert_eq!(
+                successors
+                    .iter()
+                    .any(|successor| matches!(successor, Successor::Known(_))),
+                has_known,
+                "{position:?}"
+            );
+        }
+    }
+
+    // King on the sixth in front of its pawn, whoever is to move
+    #[test_case(PlayerColor::White, Square::D5, Square::D6, Square::D8, true)]
+    #[test_case(PlayerColor::Black, Square::D5, Square::D6, Square::D8, true)]
+    // Defending king in front of the pawn
+    #[test_case(PlayerColor::White, Square::D2, Square::D1, Square::D3, false)]
+    // Defending king in the corner in front of a rook pawn
+    #[test_case(PlayerColor::White, Square::A2, Square::A1, Square::A8, false)]
+    #[test_case(PlayerColor::White, Square::A5, Square::B5, Square::A8, false)]
+    // Defending king outside the square of the pawn
+    #[test_case(PlayerColor::White, Square::A2, Square::A1, Square::H8, true)]
+    #[test_case(PlayerColor::Black, Square::A4, Square::A1, Square::G7, true)]
+    // Defending king inside the square of the pawn
+    #[test_case(PlayerColor::Black, Square::A4, Square::H1, Square::F7, false)]
+    fn is_win_works(
+        player_to_move: PlayerColor,
+        pawn: Square,
+        white_king: Square,
+        black_king: Square,
+        expected: bool,
+    ) {
+        assert_eq!(
+            KPK.is_win(player_to_move, pawn, white_king, black_king),
+            expected
+        );
+    }
+}
@@ -1,12 +1,14 @@
 use crate::bitboard::BoardMask;
-use crate::castles::CastleRights;
+use crate::castles::{CastleRights, CastleSquares};
 use crate::half_move_clock::HalfMoveClock;
+#[cfg(feature = "nnue")]
+use crate::nnue::Accumulator;
 use crate::pieces::{NonKingPieceType, OwnedPiece, Piece, PieceType};
 use crate::player_color::PlayerColor;
 use crate::position::hash_history::HashHistory;
 use crate::position::material_evaluation::MaterialEvaluation;
 use crate::square::{EnPassantSquare, Square};
-use crate::zobrist::ZobristHash;
+use crate::zobrist::{MaterialHash, NonPawnHash, PawnHash, ZobristHash};
 use derive_more::{AsMut, AsRef};
 use enum_iterator::all;
 use enum_map::EnumMap;
@@ -16,6 +18,8 @@ use enum_map::EnumMap;
 pub enum IllegalPosition {
     /// Missing a king on the board for a given [player](PlayerColor)
     MissingKing(PlayerColor),
+    /// The king of the player who isn't to move is in check, so it could be captured
+    OpponentInCheck,
 }
 
 /// The hard to compute or irrecoverable/irreversible state
@@ -23,6 +27,9 @@ pub enum IllegalPosition {
 pub struct State {
     // Irrecoverable state
     pub(super) hash: ZobristHash,
+    pub(super) pawn_hash: PawnHash,
+    pub(super) material_hash: MaterialHash,
+    pub(super) non_pawn_hashes: EnumMap<PlayerColor, NonPawnHash>,
     pub(super) halfmove_clock: HalfMoveClock,
     pub(super) en_passant_square: Option<EnPassantSquare>,
     pub(super) castles: CastleRights,
@@ -42,8 +49,11 @@ pub struct LegalPosition {
     pub(super) pieces_masks: EnumMap<NonKingPieceType, BoardMask>,
     pub(super) side_masks: EnumMap<PlayerColor, BoardMask>,
     pub(super) king_squares: EnumMap<PlayerColor, Square>,
+    pub(super) castle_squares: CastleSquares,
     pub(super) state: State,
     pub(super) hash_history: HashHistory,
+    #[cfg(feature = "nnue")]
+    pub(super) accumulator: Option<Accumulator>,
 }
 
 impl LegalPosition {
@@ -85,6 +95,27 @@ impl LegalPosition {
         !(self.state.checkers).is_empty()
     }
 
+    /// Get a [mask](BoardMask) of the pieces giving check to the current player
+    pub const fn checkers(&self) -> BoardMask {
+        self.state.checkers
+    }
+
+    /// Get a [mask](BoardMask) of the sliding pieces pinning a [player](PlayerColor)'s pieces to their king
+    pub fn pinners_for(&self, player: PlayerColor) -> BoardMask {
+        self.state.pinners_for[player]
+    }
+
+    /// Get a [mask](BoardMask) of the pieces (of either player) that alone block a slider from a
+    /// [player](PlayerColor)'s king
+    pub fn blockers_for(&self, player: PlayerColor) -> BoardMask {
+        self.state.blockers_for[player]
+    }
+
+    /// Get a [mask](BoardMask) of the squares a piece of the current player could give check from
+    pub fn check_squares(&self, piece: NonKingPieceType) -> BoardMask {
+        self.state.check_squares[piece]
+    }
+
     /// Get a mask for given piece (both players).
     pub fn piece_mask(&self, piece: NonKingPieceType) -> BoardMask {
         self.pieces_masks[piece]
@@ -96,6 +127,30 @@ impl LegalPosition {
         self.player_to_move
     }
 
+    /// Get the [`CastleSquares`] the kings and rooks castle from, which differ from standard chess in Chess960
+    #[must_use]
+    pub const fn castle_squares(&self) -> CastleSquares {
+        self.castle_squares
+    }
+
+    /// Get the [`CastleRights`] still available to both players
+    #[must_use]
+    pub const fn castle_rights(&self) -> CastleRights {
+        self.state.castles
+    }
+
+    /// Get the [`EnPassantSquare`] behind a pawn that just double pushed, if any
+    #[must_use]
+    pub const fn en_passant_square(&self) -> Option<EnPassantSquare> {
+        self.state.en_passant_square
+    }
+
+    /// Get the [`HalfMoveClock`] counting plies since the last capture or pawn move
+    #[must_use]
+    pub const fn halfmove_clock(&self) -> HalfMoveClock {
+        self.state.halfmove_clock
+    }
+
     /// Get a [`BoardMask`] of the pieces for the [`PlayerColor`] moving
     pub fn player_to_move_mask(&self) -> BoardMask {
         self.side_masks[self.player_to_move]
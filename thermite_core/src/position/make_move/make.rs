@@ -1,8 +1,7 @@
 use crate::bitboard::BoardMask;
-use crate::castles::{CastleRights, KING_FROM_SQUARES};
+use crate::castles::CastleRights;
 use crate::chess_move::capture::Capture;
 use crate::chess_move::castle::Castle;
-use crate::chess_move::castle::CastleQuietMoves;
 use crate::chess_move::double_pawn_push::DoublePawnPush;
 use crate::chess_move::en_passant_capture::EnPassantCapture;
 use crate::chess_move::promoting_capture::PromotingCapture;
@@ -152,7 +151,7 @@ impl LegalPosition {
         if self.state.castles.has_rights(rights) {
             self.state.castles.remove_rights(rights);
             // Remove any rights from the hash
-            Castle::all()
+            Castle::all(self.castle_squares)
                 .filter(|castle| rights.has_rights(castle.required_rights()))
                 .for_each(|castle| self.state.hash.toggle_castle_ability(castle));
         }
@@ -165,8 +164,9 @@ impl LegalPosition {
 
         // Check if piece that was captured was a castle rook to invalidate rights
         if captured_piece.owned_piece.piece == NonKingPieceType::Rook {
-            let invalidated_castle = Castle::all_for_player(captured_piece.owned_piece.player)
-                .find(|castle| captured_piece.square == castle.rook_from());
+            let invalidated_castle =
+                Castle::all_for_player(captured_piece.owned_piece.player, self.castle_squares)
+                    .find(|castle| captured_piece.square == castle.rook_from());
             if let Some(invalidated_castle) = invalidated_castle {
                 let invalidated_rights = invalidated_castle.required_rights();
                 self.try_remove_castle_rights(invalidated_rights);
@@ -183,14 +183,12 @@ impl LegalPosition {
         let owned_piece = quiet.piece();
         let player = owned_piece.player;
         match owned_piece.piece {
-            PieceType::King => {
-                if quiet.from() == KING_FROM_SQUARES[player] {
-                    let invalidated_rights = CastleRights::for_player(player);
-                    self.try_remove_castle_rights(invalidated_rights);
-                }
+            PieceType::King if quiet.from() == self.castle_squares.king_from(player) => {
+                let invalidated_rights = CastleRights::for_player(player);
+                self.try_remove_castle_rights(invalidated_rights);
             }
             PieceType::Rook => {
-                let invalidated_castle = Castle::all_for_player(player)
+                let invalidated_castle = Castle::all_for_player(player, self.castle_squares)
                     .find(|castle| quiet.from() == castle.rook_from());
                 if let Some(invalidated_castle) = invalidated_castle {
                     let invalidated_rights = invalidated_castle.required_rights();
@@ -278,12 +276,7 @@ impl LegalPosition {
         );
 
         // Move the pieces
-        let CastleQuietMoves {
-            rook_quiet,
-            king_quiet,
-        } = CastleQuietMoves::new(castle);
-        self.move_piece(king_quiet);
-        self.move_piece(rook_quiet);
+        self.move_castling_pieces(castle, false);
 
         // Remove both queen/king castle rights once a player has castled
         let both_castle_rights = CastleRights::for_player(castle.player());
@@ -343,6 +336,27 @@ impl LegalPosition {
         }
 
         self.switch_perspectives();
+        self.debug_verify_hashes();
+
+        previous_state
+    }
+
+    /// Pass the turn to the other player without moving (a null move), returning the [`LegalPositionState`] from
+    /// before in order to [undo it](LegalPosition::unmake_null_move).
+    ///
+    /// Passing is illegal in chess, but the search uses it to prove a position is so good that even giving the
+    /// opponent two moves in a row can't save them.
+    ///
+    /// # Panics
+    /// Panics in debug mode when the player to move is in check, as passing would leave their king capturable
+    pub fn make_null_move(&mut self) -> LegalPositionState {
+        debug_assert!(!self.in_check(), "attempting to pass while in check");
+        let previous_state = self.state;
+
+        self.try_clear_en_passant();
+        self.increment_halfmove_clock();
+        self.switch_perspectives();
+        self.debug_verify_hashes();
 
         previous_state
     }
@@ -374,6 +388,20 @@ mod test {
 
     const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
+    #[test]
+    fn make_null_move_only_switches_sides() {
+        let mut pos = fen!("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3");
+        let original = pos.clone();
+        let state = pos.make_null_move();
+        assert_eq!(pos.player_to_move(), White);
+        assert_eq!(pos.en_passant_square(), None);
+        assert_ne!(pos.hash(), original.hash());
+        assert_eq!(pos.occupied_mask(), original.occupied_mask());
+
+        pos.unmake_null_move(state);
+        assert_eq!(pos, original);
+    }
+
     #[test]
     fn make_move_switches_sides() {
         let mut pos = fen!(STARTPOS);
@@ -11,7 +11,7 @@ use crate::position::legal_position::State;
 use crate::position::material_evaluation::MaterialEvaluation;
 use crate::position::{IllegalPosition, LegalPosition, PositionBuilder};
 use crate::square::Square;
-use crate::zobrist::ZobristHash;
+use crate::zobrist::{MaterialHash, NonPawnHash, PawnHash, ZobristHash};
 
 mod make;
 mod unmake;
@@ -24,6 +24,19 @@ impl LegalPosition {
         self.state.hash.switch_sides();
     }
 
+    /// Toggle a [piece](PlacedPiece) in the position's hash, and in either the pawn hash or its side's non-pawn hash
+    fn toggle_piece_hashes(&mut self, placed_piece: PlacedPiece) {
+        let OwnedPiece { player, piece } = placed_piece.owned_piece;
+        self.state.hash.toggle_piece_square(placed_piece);
+        if piece == PieceType::Pawn {
+            self.state
+                .pawn_hash
+                .toggle_pawn_square(player, placed_piece.square);
+        } else {
+            self.state.non_pawn_hashes[player].toggle_piece_square(placed_piece);
+        }
+    }
+
     /// Place [a piece](PlacedPiece) on the board
     ///
     /// # Panics
@@ -50,19 +63,26 @@ impl LegalPosition {
             "attempting to `add_piece` to a non-empty square"
         );
 
-        // Update the hash
-        self.state.hash.toggle_piece_square(placed_piece);
+        // Update the hashes
+        self.toggle_piece_hashes(placed_piece);
         // Update the side mask
         let to_mask = to.to_mask();
         self.side_masks[player] |= to_mask;
         // Update the piece masks or king square
         if let Ok(piece_type) = NonKingPieceType::try_from(piece_type) {
+            let count = (self.pieces_masks[piece_type] & self.side_masks[player]).num_squares();
+            self.state
+                .material_hash
+                .toggle_piece_count(piece_type.owned_by(player), count);
             self.pieces_masks[piece_type] |= to_mask;
             // Update the material evaluation
             self.material_eval.add_piece(piece_type.owned_by(player));
         } else {
             self.king_squares[player] = to;
         }
+        // Update the neural network accumulator
+        #[cfg(feature = "nnue")]
+        self.accumulator_add_piece(placed_piece);
 
         // TODO: Update piece square evaluation
     }
@@ -93,8 +113,8 @@ impl LegalPosition {
             "attempting to `remove_piece` from a `Square` not occupied by the specified `PlayerColor`"
         );
 
-        // Update the hash
-        self.state.hash.toggle_piece_square(
+        // Update the hashes
+        self.toggle_piece_hashes(
             PieceType::from(piece_type)
                 .owned_by(player)
                 .placed_on(square),
@@ -104,9 +124,20 @@ impl LegalPosition {
         self.side_masks[player] ^= square_mask;
         // Update the piece mask
         self.pieces_masks[piece_type] ^= square_mask;
+        let count = (self.pieces_masks[piece_type] & self.side_masks[player]).num_squares();
+        self.state
+            .material_hash
+            .toggle_piece_count(placed_piece.owned_piece, count);
 
         // Update the material evaluation
         self.material_eval.remove_piece(placed_piece.owned_piece);
+        // Update the neural network accumulator
+        #[cfg(feature = "nnue")]
+        self.accumulator_remove_piece(
+            PieceType::from(piece_type)
+                .owned_by(player)
+                .placed_on(square),
+        );
 
         // TODO: Update piece square evaluation
     }
@@ -142,13 +173,9 @@ impl LegalPosition {
             "attempting to `move_piece` to a non-empty square"
         );
 
-        // Update the hash
-        self.state
-            .hash
-            .toggle_piece_square(owned_piece.placed_on(from));
-        self.state
-            .hash
-            .toggle_piece_square(owned_piece.placed_on(to));
+        // Update the hashes
+        self.toggle_piece_hashes(owned_piece.placed_on(from));
+        self.toggle_piece_hashes(owned_piece.placed_on(to));
         // Update side mask
         let to_mask = to.to_mask();
         let from_mask = from.to_mask();
@@ -160,11 +187,49 @@ impl LegalPosition {
         } else {
             self.king_squares[player] = to;
         }
+        // Update the neural network accumulator
+        #[cfg(feature = "nnue")]
+        self.accumulator_move_piece(owned_piece.placed_on(from), to);
 
         // TODO: Update piece square evaluation
     }
 }
 
+impl LegalPosition {
+    /// Move the king and rook of a [`Castle`] to their destinations, or back to their origins when undoing it
+    ///
+    /// In Chess960 either piece may already be on its destination, or land on the other's origin, so the rook is
+    /// lifted off the board while the king moves.
+    fn move_castling_pieces(&mut self, castle: Castle, undo: bool) {
+        let (king_from, king_to, rook_from, rook_to) = if undo {
+            (
+                castle.king_to(),
+                castle.king_from(),
+                castle.rook_to(),
+                castle.rook_from(),
+            )
+        } else {
+            (
+                castle.king_from(),
+                castle.king_to(),
+                castle.rook_from(),
+                castle.rook_to(),
+            )
+        };
+        let rook = NonKingPieceType::Rook.owned_by(castle.player());
+
+        self.remove_piece(rook.placed_on(rook_from));
+        if let Some(king_quiet) = Quiet::new(
+            king_from,
+            king_to,
+            PieceType::King.owned_by(castle.player()),
+        ) {
+            self.move_piece(king_quiet);
+        }
+        self.add_piece(PieceType::Rook.owned_by(castle.player()).placed_on(rook_to));
+    }
+}
+
 impl TryFrom<PositionBuilder> for LegalPosition {
     type Error = IllegalPosition;
 
@@ -175,6 +240,7 @@ impl TryFrom<PositionBuilder> for LegalPosition {
             squares,
             starting_player: player_to_move,
             castle_rights: castles,
+            castle_squares,
             en_passant_square,
         } = position;
         let (king_squares, side_masks, mut hash) = all::<PlayerColor>().try_fold(
@@ -203,9 +269,18 @@ impl TryFrom<PositionBuilder> for LegalPosition {
         if let Some(en_passant_square) = en_passant_square {
             hash.toggle_en_passant_square(en_passant_square);
         }
-        Castle::all()
+        Castle::all(castle_squares)
             .filter(|castle| castles.has_rights(castle.required_rights()))
             .for_each(|castle| hash.toggle_castle_ability(castle));
+        let non_pawn_hashes = EnumMap::from_fn(|player| {
+            let mut non_pawn_hash = NonPawnHash::default();
+            non_pawn_hash.toggle_piece_square(
+                PieceType::King
+                    .owned_by(player)
+                    .placed_on(king_squares[player]),
+            );
+            non_pawn_hash
+        });
 
         let mut pseudo_legal_position = Self {
             material_eval: MaterialEvaluation::default(),
@@ -213,8 +288,12 @@ impl TryFrom<PositionBuilder> for LegalPosition {
             pieces_masks: EnumMap::default(),
             side_masks,
             king_squares,
+            castle_squares,
             state: State {
                 hash,
+                pawn_hash: PawnHash::default(),
+                material_hash: MaterialHash::default(),
+                non_pawn_hashes,
                 halfmove_clock,
                 en_passant_square,
                 castles,
@@ -224,6 +303,8 @@ impl TryFrom<PositionBuilder> for LegalPosition {
                 check_squares: EnumMap::default(),
             },
             hash_history: HashHistory::default(),
+            #[cfg(feature = "nnue")]
+            accumulator: None,
         };
 
         // Add each piece
@@ -236,6 +317,13 @@ impl TryFrom<PositionBuilder> for LegalPosition {
             .for_each(|p| pseudo_legal_position.add_piece(p));
 
         pseudo_legal_position.update_masks();
+        let opponent_king = pseudo_legal_position.king_squares[player_to_move.switch()];
+        let attackers = pseudo_legal_position
+            .attackers_to(opponent_king, pseudo_legal_position.occupied_mask())
+            & pseudo_legal_position.player_to_move_mask();
+        if !attackers.is_empty() {
+            return Err(IllegalPosition::OpponentInCheck);
+        }
         // TODO: Check legality (ie. back rank pawns)
 
         Ok(pseudo_legal_position)
@@ -252,8 +340,18 @@ mod test {
     use crate::pieces::PlacedPiece;
     use crate::pieces::{NonKingPieceType, Piece, PieceType::*};
     use crate::player_color::PlayerColor::*;
+    use crate::position::{IllegalPosition, LegalPosition, PositionBuilder};
     use crate::square::{File, Square::*};
 
+    #[test_case("7k/8/8/8/8/8/8/K6R w - - 0 1", Err(IllegalPosition::OpponentInCheck); "opponent in check")]
+    #[test_case("7k/8/8/8/8/8/8/K6R b - - 0 1", Ok(()); "player to move in check")]
+    #[test_case("k7/8/8/8/8/8/8/8 w - - 0 1", Err(IllegalPosition::MissingKing(White)); "missing king")]
+    fn rejects_illegal_positions(fen: &str, expected: Result<(), IllegalPosition>) {
+        let builder = fen.parse::<PositionBuilder>().unwrap();
+
+        assert_eq!(LegalPosition::try_from(builder).map(|_| ()), expected);
+    }
+
     #[test_case("1r4k1/p4pbp/6p1/8/8/5QPb/PPP2P1P/R1BNrBK1 b - - 2 4")]
     fn switch_sides_is_symmetrical(fen: &str) {
         let original_position = fen!(fen);
@@ -4,7 +4,6 @@
 
 use crate::chess_move::capture::Capture;
 use crate::chess_move::castle::Castle;
-use crate::chess_move::castle::CastleQuietMoves;
 use crate::chess_move::double_pawn_push::DoublePawnPush;
 use crate::chess_move::en_passant_capture::EnPassantCapture;
 use crate::chess_move::promoting_capture::PromotingCapture;
@@ -59,12 +58,7 @@ impl LegalPosition {
     /// - Move the [King](PieceType::King) back to its original [`Square`](square::Square)
     /// - Move the [Rook](PieceType::Rook) back to its original [`Square`](square::Square)
     fn unmake_castle(&mut self, castle: Castle) {
-        let CastleQuietMoves {
-            rook_quiet,
-            king_quiet,
-        } = CastleQuietMoves::new(castle);
-        self.unmake_quiet(king_quiet);
-        self.unmake_quiet(rook_quiet);
+        self.move_castling_pieces(castle, true);
     }
 
     /// Undo a [`Promotion`]
@@ -97,7 +91,7 @@ impl LegalPosition {
     }
 
     /// Reset state from a previous move
-    fn restore_state(&mut self, previous_state: State) {
+    const fn restore_state(&mut self, previous_state: State) {
         self.state = previous_state;
     }
 
@@ -120,6 +114,14 @@ impl LegalPosition {
         }
 
         self.restore_state(previous_state);
+        self.debug_verify_hashes();
+    }
+
+    /// Undo a [null move](LegalPosition::make_null_move) given the previous [`LegalPositionState`]
+    pub fn unmake_null_move(&mut self, previous_state: LegalPositionState) {
+        self.switch_player_to_move();
+        self.restore_state(previous_state);
+        self.debug_verify_hashes();
     }
 }
 
@@ -9,7 +9,7 @@ use once_cell::sync::Lazy;
 pub struct MaterialEvaluation(PawnEvaluation);
 
 /// Piece approximate valuations
-static PIECE_VALUES: Lazy<EnumMap<NonKingPieceType, PawnEvaluation>> = Lazy::new(|| {
+pub(super) static PIECE_VALUES: Lazy<EnumMap<NonKingPieceType, PawnEvaluation>> = Lazy::new(|| {
     EnumMap::from_array([
         PawnEvaluation(1.26),  // Pawn
         PawnEvaluation(7.81),  // Knight
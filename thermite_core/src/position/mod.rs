@@ -1,9 +1,26 @@
+mod display;
+mod endgame;
+mod epd;
+mod evaluation;
 mod hash_history;
+mod hashes;
+mod kpk;
 mod legal_position;
 mod make_move;
 mod material_evaluation;
 mod move_gen;
+#[cfg(feature = "nnue")]
+mod nnue;
+mod packed;
+mod piece_square_tables;
 mod position_builder;
+mod san;
 
+pub use display::Board;
+pub use epd::{EpdOperation, EpdParseError, EpdRecord};
 pub use legal_position::{IllegalPosition, LegalPosition, State as LegalPositionState};
-pub use position_builder::PositionBuilder;
+pub use packed::{PackedBoard, PackedBoardError, PackedEntry};
+#[cfg(feature = "std")]
+pub use packed::{PackedReader, PackedStreamError, PackedWriter};
+pub use position_builder::{FenParseError, PositionBuilder};
+pub use san::SanParseError;
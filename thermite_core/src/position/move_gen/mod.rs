@@ -2,7 +2,9 @@ use crate::bitboard::BoardMask;
 use crate::chess_move::capture::Capture;
 use crate::chess_move::quiet::Quiet;
 use crate::chess_move::ChessMove;
+use crate::node_count::NodeCount;
 use crate::pieces::{NonKingPieceType, Piece, PieceType};
+use crate::ply_count::PlyCount;
 use crate::position::LegalPosition;
 use crate::square::Square;
 use alloc::vec::Vec;
@@ -22,6 +24,51 @@ impl LegalPosition {
         }
     }
 
+    /// Find the legal move written in UCI's long algebraic notation, see [`ChessMove::to_uci`]
+    ///
+    /// ```
+    /// use thermite_core::fen;
+    ///
+    /// let position = fen!("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+    /// assert_eq!(position.parse_uci("e1g1", false).unwrap().to_uci(true), "e1h1");
+    /// assert_eq!(position.parse_uci("e1a1", true).unwrap().to_uci(false), "e1c1");
+    /// assert_eq!(position.parse_uci("e1h1", false), None);
+    /// ```
+    #[must_use]
+    pub fn parse_uci(&self, uci: &str, chess960: bool) -> Option<ChessMove> {
+        self.generate_legal_moves()
+            .into_iter()
+            .find(|chess_move| chess_move.to_uci(chess960) == uci)
+    }
+
+    /// Count the leaf nodes of every legal line of play to a depth
+    ///
+    /// ```
+    /// use thermite_core::fen;
+    /// use thermite_core::node_count::NodeCount;
+    /// use thermite_core::ply_count::PlyCount;
+    ///
+    /// let mut position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// assert_eq!(position.perft(PlyCount::new(3)), NodeCount::new(8902));
+    /// ```
+    pub fn perft(&mut self, depth: PlyCount) -> NodeCount {
+        let moves = self.generate_legal_moves();
+        match *depth.as_ref() {
+            0 => NodeCount::new(1),
+            1 => NodeCount::new(moves.len() as u64),
+            _ => moves
+                .into_iter()
+                .map(|chess_move| {
+                    let state = self.make_move(chess_move);
+                    let nodes = self.perft(depth - PlyCount::new(1));
+                    self.unmake_move(chess_move, state);
+
+                    nodes
+                })
+                .sum(),
+        }
+    }
+
     /// Create a quiet move for the current player
     /// # Panics
     /// - If from and to are the same square
@@ -64,29 +111,12 @@ impl LegalPosition {
 mod test {
     use crate::node_count::NodeCount;
     use crate::ply_count::PlyCount;
-    use crate::position::LegalPosition;
+    use crate::position::{LegalPosition, PositionBuilder};
+    use std::collections::HashSet;
 
     use crate::fen;
     use test_case::test_case;
 
-    pub fn perft(position: &mut LegalPosition, depth: PlyCount) -> NodeCount {
-        if depth == PlyCount::new(0) {
-            NodeCount::new(1)
-        } else {
-            position
-                .generate_legal_moves()
-                .into_iter()
-                .map(|m| {
-                    let state = position.make_move(m);
-                    let nodes = perft(position, depth - PlyCount::new(1));
-                    position.unmake_move(m, state);
-
-                    nodes
-                })
-                .sum()
-        }
-    }
-
     const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
     const STARTPOS_C2C3: &str = "rnbqkbnr/pppppppp/8/8/8/2P5/PP1PPPPP/RNBQKBNR b KQkq - 0 1";
     const STARTPOS_C2C3_D7D5: &str = "rnbqkbnr/ppp1pppp/8/3p4/8/2P5/PP1PPPPP/RNBQKBNR w KQkq - 0 2";
@@ -102,6 +132,10 @@ mod test {
     const POSITION_3_B4B1: &str = "8/2p5/3p4/KP5r/5p1k/8/4P1P1/1R6 b - - 1 1";
     const POSITION_3_B4B1_H4G3: &str = "8/2p5/3p4/KP5r/5p2/6k1/4P1P1/1R6 w - - 2 2";
     const POSITION_3_B4B1_H4G3_B1G1: &str = "8/2p5/3p4/KP5r/5p2/6k1/4P1P1/6R1 b - - 3 2";
+    const CHESS960_1: &str = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+    const CHESS960_2: &str = "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9";
+    const CHESS960_3: &str = "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9";
+    const CHESS960_4: &str = "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9";
     const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
     const POSITION_4_MIRRORED: &str =
         "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1 ";
@@ -140,6 +174,57 @@ mod test {
     // #[test_case(POSITION_4, PlyCount::new(4), NodeCount::new(422_333))]
     // #[test_case(POSITION_4_MIRRORED, PlyCount::new(4), NodeCount::new(422_333))]
     fn perft_works(fen: &str, depth: PlyCount, expected_nodes: NodeCount) {
-        assert_eq!(perft(&mut fen!(fen), depth), expected_nodes);
+        assert_eq!(fen!(fen).perft(depth), expected_nodes);
+    }
+
+    #[test_case(CHESS960_1, PlyCount::new(1), NodeCount::new(21))]
+    #[test_case(CHESS960_1, PlyCount::new(2), NodeCount::new(528))]
+    #[test_case(CHESS960_1, PlyCount::new(3), NodeCount::new(12_189))]
+    #[test_case(CHESS960_1, PlyCount::new(4), NodeCount::new(326_672))]
+    #[test_case(CHESS960_2, PlyCount::new(1), NodeCount::new(20))]
+    #[test_case(CHESS960_2, PlyCount::new(2), NodeCount::new(479))]
+    #[test_case(CHESS960_2, PlyCount::new(3), NodeCount::new(10_471))]
+    #[test_case(CHESS960_2, PlyCount::new(4), NodeCount::new(273_318))]
+    #[test_case(CHESS960_3, PlyCount::new(1), NodeCount::new(22))]
+    #[test_case(CHESS960_3, PlyCount::new(2), NodeCount::new(593))]
+    #[test_case(CHESS960_3, PlyCount::new(3), NodeCount::new(13_440))]
+    #[test_case(CHESS960_3, PlyCount::new(4), NodeCount::new(382_958))]
+    #[test_case(CHESS960_4, PlyCount::new(1), NodeCount::new(28))]
+    #[test_case(CHESS960_4, PlyCount::new(2), NodeCount::new(1_120))]
+    #[test_case(CHESS960_4, PlyCount::new(3), NodeCount::new(31_058))]
+    #[test_case(CHESS960_4, PlyCount::new(4), NodeCount::new(1_171_749))]
+    fn chess960_perft_works(fen: &str, depth: PlyCount, expected_nodes: NodeCount) {
+        assert_eq!(fen!(fen).perft(depth), expected_nodes);
+    }
+
+    #[test]
+    fn chess960_start_positions_work() {
+        let mut seen = HashSet::new();
+        let expected = include_str!("chess960_perft.txt")
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|count| count.parse::<u64>().unwrap())
+                    .collect::<Vec<_>>()
+            });
+        for (number, expected) in (0..960).zip(expected) {
+            let builder = PositionBuilder::chess960(number).unwrap();
+            let mut position = LegalPosition::try_from(builder).unwrap();
+            assert!(
+                seen.insert(*position.hash().as_ref()),
+                "{number} is a duplicate"
+            );
+
+            assert_eq!(expected[0], u64::from(number), "{number}");
+            for (depth, &nodes) in (1..).zip(&expected[1..]) {
+                assert_eq!(
+                    *position.perft(PlyCount::new(depth)).as_ref(),
+                    nodes,
+                    "{number} at depth {depth}"
+                );
+            }
+        }
+        assert_eq!(seen.len(), 960);
     }
 }
@@ -34,12 +34,15 @@ impl LegalPosition {
     /// Generate legal king/rook castle moves
     fn generate_castle_moves(&self) -> impl Iterator<Item = ChessMove> + '_ {
         let occupied_mask = self.occupied_mask();
-        Castle::all_for_player(self.player_to_move)
+        Castle::all_for_player(self.player_to_move, self.castle_squares)
             .filter(|castle| self.state.castles.has_rights(castle.required_rights()))
             .filter(move |castle| (occupied_mask & castle.unoccupied_mask()).is_empty())
             .filter(move |castle| {
+                // Without the castling pieces, in case (in Chess960) the rook was shielding the king's path
+                let occupied_mask =
+                    occupied_mask ^ castle.king_from().to_mask() ^ castle.rook_from().to_mask();
                 castle.unattacked_mask().into_iter().all(|pass_through_sq| {
-                    (self.attackers_to(pass_through_sq, self.occupied_mask())
+                    (self.attackers_to(pass_through_sq, occupied_mask)
                         & self.opposite_player_mask())
                     .is_empty()
                 })
@@ -108,7 +111,8 @@ impl LegalPosition {
         })
     }
 
-    fn get_attacks_for_promotable_piece_type(
+    /// Get the attacks for a [promotable piece](PromotablePieceType) on a [`Square`] given the current occupancy
+    pub(in crate::position) fn get_attacks_for_promotable_piece_type(
         &self,
         from: Square,
         piece: PromotablePieceType,
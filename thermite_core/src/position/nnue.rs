@@ -0,0 +1,142 @@
+use crate::nnue::{Accumulator, Network};
+use crate::pieces::{NonKingPieceType, Piece, PieceType, PlacedPiece};
+use crate::player_color::PlayerColor;
+use crate::position::LegalPosition;
+use crate::square::Square;
+use alloc::sync::Arc;
+use enum_iterator::all;
+
+impl LegalPosition {
+    /// Evaluate the position with a [`Network`] from now on, instead of the handcrafted evaluation
+    ///
+    /// The network's [accumulator](Accumulator) is built from the current board and updated incrementally as moves are made and unmade.
+    pub fn set_network(&mut self, network: Arc<Network>) {
+        self.accumulator = Some(Accumulator::new(network));
+        for perspective in all::<PlayerColor>() {
+            self.refresh_accumulator(perspective);
+        }
+    }
+
+    /// Go back to the handcrafted evaluation, dropping the [`Network`] if any
+    pub fn clear_network(&mut self) {
+        self.accumulator = None;
+    }
+
+    /// Get the [accumulator](Accumulator) if the position is being evaluated by a [`Network`]
+    #[must_use]
+    pub const fn accumulator(&self) -> Option<&Accumulator> {
+        self.accumulator.as_ref()
+    }
+
+    /// Get every [piece](PlacedPiece) on the board, including the kings
+    fn placed_pieces(&self) -> impl Iterator<Item = PlacedPiece> + '_ {
+        let kings = all::<PlayerColor>().map(|player| {
+            PieceType::King
+                .owned_by(player)
+                .placed_on(self.king_squares[player])
+        });
+        let pieces = all::<PlayerColor>().flat_map(move |player| {
+            all::<NonKingPieceType>().flat_map(move |piece| {
+                (self.pieces_masks[piece] & self.side_masks[player])
+                    .into_iter()
+                    .map(move |square| PieceType::from(piece).owned_by(player).placed_on(square))
+            })
+        });
+
+        kings.chain(pieces)
+    }
+
+    /// Recompute a [perspective](PlayerColor) of the [accumulator](Accumulator) from scratch
+    fn refresh_accumulator(&mut self, perspective: PlayerColor) {
+        if let Some(mut accumulator) = self.accumulator.take() {
+            accumulator.refresh(
+                perspective,
+                self.king_squares[perspective],
+                self.placed_pieces(),
+            );
+            self.accumulator = Some(accumulator);
+        }
+    }
+
+    /// Update each perspective of the [accumulator](Accumulator) after a [piece](PlacedPiece) changed on the board.
+    /// If the piece is a king its own perspective is refreshed, as the king square is part of every one of its features.
+    fn update_accumulator(
+        &mut self,
+        changed_piece: PlacedPiece,
+        update: impl Fn(&mut Accumulator, PlayerColor, Square),
+    ) {
+        let Some(mut accumulator) = self.accumulator.take() else {
+            return;
+        };
+        for perspective in all::<PlayerColor>() {
+            let king_square = self.king_squares[perspective];
+            if changed_piece.owned_piece == PieceType::King.owned_by(perspective) {
+                accumulator.refresh(perspective, king_square, self.placed_pieces());
+            } else {
+                update(&mut accumulator, perspective, king_square);
+            }
+        }
+        self.accumulator = Some(accumulator);
+    }
+
+    /// Update the [accumulator](Accumulator) after a [piece](PlacedPiece) was added to the board
+    pub(super) fn accumulator_add_piece(&mut self, placed_piece: PlacedPiece) {
+        self.update_accumulator(placed_piece, |accumulator, perspective, king_square| {
+            accumulator.add_piece(perspective, king_square, placed_piece);
+        });
+    }
+
+    /// Update the [accumulator](Accumulator) after a [piece](PlacedPiece) was removed from the board
+    pub(super) fn accumulator_remove_piece(&mut self, placed_piece: PlacedPiece) {
+        self.update_accumulator(placed_piece, |accumulator, perspective, king_square| {
+            accumulator.remove_piece(perspective, king_square, placed_piece);
+        });
+    }
+
+    /// Update the [accumulator](Accumulator) after a [piece](PlacedPiece) was moved to another [square](Square)
+    pub(super) fn accumulator_move_piece(&mut self, from: PlacedPiece, to: Square) {
+        self.update_accumulator(from, |accumulator, perspective, king_square| {
+            accumulator.move_piece(perspective, king_square, from, to);
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fen;
+    use crate::nnue::random_network_bytes;
+    use crate::nnue::Network;
+    use alloc::sync::Arc;
+    use test_case::test_case;
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+    #[test_case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")]
+    #[test_case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+    fn incremental_updates_match_refresh(fen: &str) {
+        let network = Arc::new(Network::from_bytes(&random_network_bytes(16)).unwrap());
+        let mut position = fen!(fen);
+        position.set_network(Arc::clone(&network));
+        let original_position = position.clone();
+
+        let mut history = alloc::vec::Vec::new();
+        for _ in 0..8 {
+            let moves = position.generate_legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            let chess_move = moves[fastrand::usize(..moves.len())];
+            history.push((chess_move, position.make_move(chess_move)));
+
+            let mut refreshed_position = position.clone();
+            refreshed_position.set_network(Arc::clone(&network));
+            assert_eq!(position.accumulator(), refreshed_position.accumulator());
+            assert_eq!(position.evaluate(), refreshed_position.evaluate());
+        }
+
+        while let Some((chess_move, state)) = history.pop() {
+            position.unmake_move(chess_move, state);
+        }
+        assert_eq!(position, original_position);
+    }
+}
@@ -0,0 +1,446 @@
+#[cfg(feature = "std")]
+mod stream;
+
+use crate::bitboard::BoardMask;
+use crate::castles::{CastleDirection, CastleRights};
+use crate::chess_move::castle::Castle;
+use crate::half_move_clock::HalfMoveClock;
+use crate::pgn::GameResult;
+use crate::pieces::{OwnedPiece, Piece, PieceType};
+use crate::player_color::PlayerColor;
+use crate::ply_count::PlyCount;
+use crate::position::{IllegalPosition, LegalPosition, PositionBuilder};
+use crate::square::{EnPassantSquare, File, Rank, Square};
+use enum_map::Enum;
+#[cfg(feature = "std")]
+pub use stream::{PackedReader, PackedStreamError, PackedWriter};
+
+/// The most pieces a packed board has room for
+const MAX_PIECES: usize = 32;
+/// The nibble of a rook its player can still castle with, instead of the nibble of its piece type
+const CASTLING_ROOK: u8 = 6;
+/// The bit of a piece's nibble set for black's pieces
+const BLACK_PIECE: u8 = 8;
+/// The bit of the side to move and en-passant byte set when black is to move
+const BLACK_TO_MOVE: u8 = 0x80;
+/// The en-passant square written when there isn't one
+const NO_EN_PASSANT: u8 = 64;
+
+/// Errors that can occur while packing a position, or unpacking a [`PackedBoard`] or [`PackedEntry`]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PackedBoardError {
+    /// The position has more than the 32 pieces a packed board has room for
+    TooManyPieces,
+    /// A piece's nibble isn't one of the pieces (or castling rooks) of either player
+    InvalidPiece,
+    /// The en-passant square isn't on the third or sixth rank
+    InvalidEnPassant,
+    /// The castle rights have bits set beyond the four castles
+    InvalidCastleRights,
+    /// The halfmove clock is beyond the fifty-move rule
+    InvalidHalfmoveClock,
+    /// The result of an entry isn't one of the four game results
+    InvalidResult,
+    /// The board unpacked but doesn't describe a legal position
+    IllegalPosition(IllegalPosition),
+}
+
+/// A position packed into 29 bytes, for storing large numbers of them (such as training data)
+///
+/// Like the marlinformat layout, the occupied squares are a [`BoardMask`] followed by a nibble for each of up to 32
+/// pieces, in square order. Rooks their player can still castle with have their own nibble, which keeps the squares
+/// castled from in Chess960. The rest of the board is the player to move with the en-passant square, the castle
+/// rights, the halfmove clock and the fullmove number.
+///
+/// ```
+/// use thermite_core::fen;
+/// use thermite_core::position::{LegalPosition, PackedBoard};
+///
+/// let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+/// let packed = PackedBoard::try_from(&position).unwrap();
+/// assert_eq!(LegalPosition::try_from(packed), Ok(position));
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct PackedBoard {
+    occupancy: BoardMask,
+    pieces: [u8; MAX_PIECES / 2],
+    side_and_en_passant: u8,
+    castle_rights: u8,
+    halfmove_clock: u8,
+    fullmove: u16,
+}
+
+/// The nibble of a piece, its type (from `0` for a pawn to `5` for a king) with the black bit for black's pieces
+const fn piece_nibble(piece: OwnedPiece, castling: bool) -> u8 {
+    let kind = if castling {
+        CASTLING_ROOK
+    } else {
+        match piece.piece {
+            PieceType::Pawn => 0,
+            PieceType::Knight => 1,
+            PieceType::Bishop => 2,
+            PieceType::Rook => 3,
+            PieceType::Queen => 4,
+            PieceType::King => 5,
+        }
+    };
+
+    match piece.player {
+        PlayerColor::White => kind,
+        PlayerColor::Black => kind | BLACK_PIECE,
+    }
+}
+
+/// The piece of a nibble, and whether it's a rook that can castle
+fn nibble_piece(nibble: u8) -> Result<(OwnedPiece, bool), PackedBoardError> {
+    let player = if nibble & BLACK_PIECE == 0 {
+        PlayerColor::White
+    } else {
+        PlayerColor::Black
+    };
+    let piece = match nibble & !BLACK_PIECE {
+        0 => PieceType::Pawn,
+        1 => PieceType::Knight,
+        2 => PieceType::Bishop,
+        3 | CASTLING_ROOK => PieceType::Rook,
+        4 => PieceType::Queen,
+        5 => PieceType::King,
+        _ => return Err(PackedBoardError::InvalidPiece),
+    };
+
+    Ok((
+        piece.owned_by(player),
+        nibble & !BLACK_PIECE == CASTLING_ROOK,
+    ))
+}
+
+/// The rank a player's king and rooks castle on
+const fn back_rank(player: PlayerColor) -> Rank {
+    match player {
+        PlayerColor::White => Rank::First,
+        PlayerColor::Black => Rank::Eighth,
+    }
+}
+
+impl PackedBoard {
+    /// The size of a packed board in bytes
+    pub const SIZE: usize = 29;
+
+    /// The squares with a piece on them
+    pub const fn occupancy(&self) -> BoardMask {
+        self.occupancy
+    }
+
+    /// Write the board as bytes, multi-byte fields little-endian
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..8].copy_from_slice(&self.occupancy.0.to_le_bytes());
+        bytes[8..24].copy_from_slice(&self.pieces);
+        bytes[24] = self.side_and_en_passant;
+        bytes[25] = self.castle_rights;
+        bytes[26] = self.halfmove_clock;
+        bytes[27..].copy_from_slice(&self.fullmove.to_le_bytes());
+
+        bytes
+    }
+
+    /// Read a board written by [`to_bytes`](Self::to_bytes), which is only checked once it's unpacked
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let mut occupancy = [0; 8];
+        occupancy.copy_from_slice(&bytes[..8]);
+        let mut pieces = [0; MAX_PIECES / 2];
+        pieces.copy_from_slice(&bytes[8..24]);
+
+        Self {
+            occupancy: BoardMask(u64::from_le_bytes(occupancy)),
+            pieces,
+            side_and_en_passant: bytes[24],
+            castle_rights: bytes[25],
+            halfmove_clock: bytes[26],
+            fullmove: u16::from_le_bytes([bytes[27], bytes[28]]),
+        }
+    }
+}
+
+impl TryFrom<&PositionBuilder> for PackedBoard {
+    type Error = PackedBoardError;
+
+    /// Pack a position, failing if it has more than 32 pieces
+    fn try_from(builder: &PositionBuilder) -> Result<Self, Self::Error> {
+        let castling = |piece: OwnedPiece, square: Square| {
+            piece.piece == PieceType::Rook
+                && [CastleDirection::KingSide, CastleDirection::QueenSide]
+                    .into_iter()
+                    .any(|direction| {
+                        let castle =
+                            Castle::with_squares(piece.player, direction, builder.castle_squares);
+                        builder.castle_rights.has_rights(castle.required_rights())
+                            && builder.castle_squares.rook_from(direction, piece.player) == square
+                    })
+        };
+
+        let mut occupancy = BoardMask::EMPTY;
+        let mut pieces = [0; MAX_PIECES / 2];
+        let mut count = 0;
+        for (square, piece) in builder.squares {
+            let Some(piece) = piece else {
+                continue;
+            };
+            if count == MAX_PIECES {
+                return Err(PackedBoardError::TooManyPieces);
+            }
+            pieces[count / 2] |= piece_nibble(piece, castling(piece, square)) << (4 * (count % 2));
+            occupancy |= square.to_mask();
+            count += 1;
+        }
+
+        let side = match builder.starting_player {
+            PlayerColor::White => 0,
+            PlayerColor::Black => BLACK_TO_MOVE,
+        };
+        let en_passant = builder
+            .en_passant_square
+            .map_or(NO_EN_PASSANT, |square| Square::from(square) as u8);
+
+        Ok(Self {
+            occupancy,
+            pieces,
+            side_and_en_passant: side | en_passant,
+            castle_rights: builder.castle_rights.bits(),
+            halfmove_clock: u8::from(PlyCount::from(builder.halfmove_clock)),
+            fullmove: u16::from(u8::from(builder.halfmove_count) / 2).max(1),
+        })
+    }
+}
+
+impl TryFrom<&LegalPosition> for PackedBoard {
+    type Error = PackedBoardError;
+
+    /// Pack a legal position, as the first move of a game (positions don't keep the fullmove count)
+    fn try_from(position: &LegalPosition) -> Result<Self, Self::Error> {
+        Self::try_from(&PositionBuilder::from(position))
+    }
+}
+
+impl TryFrom<PackedBoard> for PositionBuilder {
+    type Error = PackedBoardError;
+
+    /// Unpack a board, with fullmove numbers past what a builder can hold clamped to its largest
+    fn try_from(packed: PackedBoard) -> Result<Self, Self::Error> {
+        let mut builder = Self::default();
+        let mut castling_rooks = alloc::vec::Vec::new();
+        for (index, square) in packed.occupancy.into_iter().enumerate() {
+            let (piece, castling) =
+                nibble_piece((packed.pieces[index / 2] >> (4 * (index % 2))) & 0xF)?;
+            builder = builder.with_piece(piece.placed_on(square));
+            if castling {
+                castling_rooks.push((piece.player, square));
+            }
+        }
+
+        // Castle from the same squares a FEN naming the files of the castling rooks would
+        for (player, rook_square) in castling_rooks {
+            let king = PieceType::King.owned_by(player);
+            let king_file = (0..File::LENGTH)
+                .map(File::from_usize)
+                .find(|&file| builder.squares[Square::new(file, back_rank(player))] == Some(king))
+                .unwrap_or(File::E);
+            let direction = if rook_square.file() > king_file {
+                CastleDirection::KingSide
+            } else {
+                CastleDirection::QueenSide
+            };
+            builder.castle_squares = builder
+                .castle_squares
+                .with_king_from(player, Square::new(king_file, back_rank(player)))
+                .with_rook_from(direction, player, rook_square);
+        }
+
+        if packed.castle_rights > CastleRights::All.bits() {
+            return Err(PackedBoardError::InvalidCastleRights);
+        }
+        builder.castle_rights = CastleRights::from(packed.castle_rights);
+        builder.starting_player = if packed.side_and_en_passant & BLACK_TO_MOVE == 0 {
+            PlayerColor::White
+        } else {
+            PlayerColor::Black
+        };
+        builder.en_passant_square = match packed.side_and_en_passant & !BLACK_TO_MOVE {
+            NO_EN_PASSANT => None,
+            index => Some(
+                Square::try_from(index)
+                    .ok()
+                    .and_then(|square| EnPassantSquare::try_from(square).ok())
+                    .ok_or(PackedBoardError::InvalidEnPassant)?,
+            ),
+        };
+        builder.halfmove_clock = HalfMoveClock::new(PlyCount::new(packed.halfmove_clock))
+            .map_err(|_| PackedBoardError::InvalidHalfmoveClock)?;
+        let fullmove = u8::try_from(packed.fullmove)
+            .unwrap_or(u8::MAX)
+            .min(u8::MAX / 2);
+
+        Ok(builder.with_fullmove_count(PlyCount::new(fullmove)))
+    }
+}
+
+impl TryFrom<PackedBoard> for LegalPosition {
+    type Error = PackedBoardError;
+
+    fn try_from(packed: PackedBoard) -> Result<Self, Self::Error> {
+        let builder = PositionBuilder::try_from(packed)?;
+
+        Self::try_from(builder).map_err(PackedBoardError::IllegalPosition)
+    }
+}
+
+/// A packed position with its search score and the result of the game it was taken from, 32 bytes in all
+///
+/// The score is in centipawns and the result is from white's point of view, as is usual for training data.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PackedEntry {
+    /// The position
+    pub board: PackedBoard,
+    /// The search score of the position, in centipawns from white's point of view
+    pub score: i16,
+    /// The result of the game the position was taken from
+    pub result: GameResult,
+}
+
+impl PackedEntry {
+    /// The size of a packed entry in bytes
+    pub const SIZE: usize = 32;
+
+    /// Write the entry as bytes, the board followed by the little-endian score and the result (from `0` for a black
+    /// win to `2` for a white win, or `3` when unknown)
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..PackedBoard::SIZE].copy_from_slice(&self.board.to_bytes());
+        bytes[PackedBoard::SIZE..PackedBoard::SIZE + 2].copy_from_slice(&self.score.to_le_bytes());
+        bytes[Self::SIZE - 1] = match self.result {
+            GameResult::BlackWins => 0,
+            GameResult::Draw => 1,
+            GameResult::WhiteWins => 2,
+            GameResult::Unknown => 3,
+        };
+
+        bytes
+    }
+
+    /// Read an entry written by [`to_bytes`](Self::to_bytes)
+    ///
+    /// # Errors
+    /// If the result isn't one of the four game results, the board is only checked once it's unpacked
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Result<Self, PackedBoardError> {
+        let mut board = [0; PackedBoard::SIZE];
+        board.copy_from_slice(&bytes[..PackedBoard::SIZE]);
+        let result = match bytes[Self::SIZE - 1] {
+            0 => GameResult::BlackWins,
+            1 => GameResult::Draw,
+            2 => GameResult::WhiteWins,
+            3 => GameResult::Unknown,
+            _ => return Err(PackedBoardError::InvalidResult),
+        };
+
+        Ok(Self {
+            board: PackedBoard::from_bytes(&board),
+            score: i16::from_le_bytes([bytes[PackedBoard::SIZE], bytes[PackedBoard::SIZE + 1]]),
+            result,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fen;
+    use crate::pgn::GameResult;
+    use crate::position::{
+        LegalPosition, PackedBoard, PackedBoardError, PackedEntry, PositionBuilder,
+    };
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"; "startpos")]
+    #[test_case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 3 10"; "partial castle rights")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3"; "white en passant")]
+    #[test_case("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 3"; "black en passant")]
+    #[test_case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 99 120"; "endgame")]
+    #[test_case("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"; "chess960")]
+    #[test_case("1r2k1r1/8/8/8/8/8/8/1R2K1R1 b Gb - 12 40"; "chess960 partial rights")]
+    fn builders_round_trip(fen: &str) {
+        let builder = fen.parse::<PositionBuilder>().unwrap();
+        let packed = PackedBoard::try_from(&builder).unwrap();
+        let unpacked =
+            PositionBuilder::try_from(PackedBoard::from_bytes(&packed.to_bytes())).unwrap();
+
+        assert_eq!(unpacked, builder);
+        assert_eq!(unpacked.to_string(), fen);
+    }
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"; "startpos")]
+    #[test_case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"; "kiwipete")]
+    #[test_case("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"; "chess960")]
+    fn legal_positions_round_trip(fen: &str) {
+        let position = fen!(fen);
+        let packed = PackedBoard::try_from(&position).unwrap();
+
+        assert_eq!(packed.occupancy(), position.occupied_mask());
+        assert_eq!(LegalPosition::try_from(packed), Ok(position));
+    }
+
+    #[test]
+    fn too_many_pieces_fail_to_pack() {
+        let builder = "qqqqkqqq/qqqqqqqq/8/8/8/Q7/QQQQQQQQ/QQQQKQQQ w - - 0 1"
+            .parse::<PositionBuilder>()
+            .unwrap();
+
+        assert_eq!(
+            PackedBoard::try_from(&builder),
+            Err(PackedBoardError::TooManyPieces)
+        );
+    }
+
+    #[test_case(24, 0x20, PackedBoardError::InvalidEnPassant; "en passant off the third or sixth rank")]
+    #[test_case(25, 0x10, PackedBoardError::InvalidCastleRights; "unknown castle rights")]
+    #[test_case(26, 101, PackedBoardError::InvalidHalfmoveClock; "halfmove clock beyond fifty moves")]
+    #[test_case(8, 0x07, PackedBoardError::InvalidPiece; "unknown piece")]
+    fn corrupt_boards_fail_to_unpack(index: usize, value: u8, expected: PackedBoardError) {
+        let position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let mut bytes = PackedBoard::try_from(&position).unwrap().to_bytes();
+        bytes[index] = value;
+
+        assert_eq!(
+            PositionBuilder::try_from(PackedBoard::from_bytes(&bytes)),
+            Err(expected)
+        );
+    }
+
+    #[test_case(GameResult::WhiteWins, -35; "white wins")]
+    #[test_case(GameResult::Draw, 0; "draw")]
+    #[test_case(GameResult::BlackWins, i16::MIN; "black wins")]
+    #[test_case(GameResult::Unknown, i16::MAX; "unknown")]
+    fn entries_round_trip(result: GameResult, score: i16) {
+        let position = fen!("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1");
+        let entry = PackedEntry {
+            board: PackedBoard::try_from(&position).unwrap(),
+            score,
+            result,
+        };
+
+        assert_eq!(PackedEntry::from_bytes(&entry.to_bytes()), Ok(entry));
+    }
+
+    #[test]
+    fn entries_with_unknown_results_fail_to_read() {
+        let mut bytes = [0; PackedEntry::SIZE];
+        bytes[PackedEntry::SIZE - 1] = 4;
+
+        assert_eq!(
+            PackedEntry::from_bytes(&bytes),
+            Err(PackedBoardError::InvalidResult)
+        );
+    }
+}
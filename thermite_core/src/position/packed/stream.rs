@@ -0,0 +1,349 @@
+use crate::position::{PackedBoardError, PackedEntry};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use std::io::{self, ErrorKind, Read, Write};
+
+/// The bytes every packed stream starts with, its magic number and format version
+const HEADER: [u8; 8] = *b"THRMPK\x00\x01";
+/// The most entries in a block, each block followed by the checksum of its entries
+const BLOCK_ENTRIES: usize = 4096;
+
+/// The CRC-32 (IEEE) lookup table, for each value of a byte
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+
+    table
+};
+
+/// The CRC-32 checksum of some bytes
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Errors that can occur while reading a stream of [`PackedEntry`]s
+#[derive(Debug)]
+pub enum PackedStreamError {
+    /// The underlying reader failed, or the stream ended part way through a block
+    Io(io::Error),
+    /// The stream doesn't start with the packed header, or is of another version
+    InvalidHeader,
+    /// A block claims more entries than a block can hold, or none at all
+    InvalidBlock,
+    /// The entries of a block don't match its checksum
+    ChecksumMismatch,
+    /// An entry of a block couldn't be read
+    InvalidEntry(PackedBoardError),
+}
+
+impl Display for PackedStreamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::InvalidHeader => write!(f, "not a packed position stream"),
+            Self::InvalidBlock => write!(f, "invalid block size"),
+            Self::ChecksumMismatch => write!(f, "block checksum mismatch"),
+            Self::InvalidEntry(error) => write!(f, "invalid entry: {error:?}"),
+        }
+    }
+}
+
+impl From<io::Error> for PackedStreamError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Writes [`PackedEntry`]s to a stream in checksummed blocks
+///
+/// The stream starts with a header, then each block is its number of entries (a little-endian `u32`), the entries
+/// themselves and the CRC-32 of their bytes. Entries are buffered until a block is full, so [`finish`](Self::finish)
+/// must be called to write the last block and see any error (dropping the writer writes it but ignores errors).
+///
+/// ```
+/// use thermite_core::fen;
+/// use thermite_core::pgn::GameResult;
+/// use thermite_core::position::{PackedBoard, PackedEntry, PackedReader, PackedWriter};
+///
+/// let board = PackedBoard::try_from(&fen!("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1")).unwrap();
+/// let entry = PackedEntry { board, score: 150, result: GameResult::WhiteWins };
+///
+/// let mut writer = PackedWriter::new(Vec::new()).unwrap();
+/// writer.write(&entry).unwrap();
+/// let bytes = writer.finish().unwrap();
+///
+/// let entries = PackedReader::new(bytes.as_slice()).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(entries, vec![entry]);
+/// ```
+pub struct PackedWriter<W: Write> {
+    inner: Option<W>,
+    block: Vec<u8>,
+}
+
+impl<W: Write> PackedWriter<W> {
+    /// Start a stream, writing its header
+    ///
+    /// # Errors
+    /// If the header can't be written
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(&HEADER)?;
+
+        Ok(Self {
+            inner: Some(inner),
+            block: Vec::with_capacity(BLOCK_ENTRIES * PackedEntry::SIZE),
+        })
+    }
+
+    /// Add an entry to the stream, writing the current block once it's full
+    ///
+    /// # Errors
+    /// If a full block can't be written
+    pub fn write(&mut self, entry: &PackedEntry) -> io::Result<()> {
+        self.block.extend_from_slice(&entry.to_bytes());
+        if self.block.len() == BLOCK_ENTRIES * PackedEntry::SIZE {
+            self.write_block()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the buffered entries as a block
+    fn write_block(&mut self) -> io::Result<()> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Ok(());
+        };
+        if self.block.is_empty() {
+            return Ok(());
+        }
+        let entries = (self.block.len() / PackedEntry::SIZE) as u32;
+        inner.write_all(&entries.to_le_bytes())?;
+        inner.write_all(&self.block)?;
+        inner.write_all(&crc32(&self.block).to_le_bytes())?;
+        self.block.clear();
+
+        Ok(())
+    }
+
+    /// Write the last block and flush the stream, getting back the underlying writer
+    ///
+    /// # Errors
+    /// If the last block can't be written or the stream can't be flushed
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_block()?;
+        let mut inner = self
+            .inner
+            .take()
+            .ok_or_else(|| io::Error::other("packed writer already finished"))?;
+        inner.flush()?;
+
+        Ok(inner)
+    }
+}
+
+impl<W: Write> Drop for PackedWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.write_block();
+    }
+}
+
+/// Reads the [`PackedEntry`]s of a stream written by a [`PackedWriter`], checking the checksum of each block
+///
+/// Iterating stops after the first error, as the rest of the stream can't be trusted.
+pub struct PackedReader<R: Read> {
+    inner: R,
+    block: Vec<u8>,
+    next_entry: usize,
+    finished: bool,
+}
+
+impl<R: Read> PackedReader<R> {
+    /// Start reading a stream, checking its header
+    ///
+    /// # Errors
+    /// If the header can't be read or isn't the packed header
+    pub fn new(mut inner: R) -> Result<Self, PackedStreamError> {
+        let mut header = [0; HEADER.len()];
+        inner
+            .read_exact(&mut header)
+            .map_err(|error| match error.kind() {
+                ErrorKind::UnexpectedEof => PackedStreamError::InvalidHeader,
+                _ => PackedStreamError::Io(error),
+            })?;
+        if header != HEADER {
+            return Err(PackedStreamError::InvalidHeader);
+        }
+
+        Ok(Self {
+            inner,
+            block: Vec::new(),
+            next_entry: 0,
+            finished: false,
+        })
+    }
+
+    /// Read the next block, getting whether there was one (rather than the end of the stream)
+    fn read_block(&mut self) -> Result<bool, PackedStreamError> {
+        let mut entries = [0; 4];
+        // The stream can only end between blocks
+        match self.inner.read(&mut entries[..1])? {
+            0 => return Ok(false),
+            _ => self.inner.read_exact(&mut entries[1..])?,
+        }
+        let entries = u32::from_le_bytes(entries) as usize;
+        if entries == 0 || entries > BLOCK_ENTRIES {
+            return Err(PackedStreamError::InvalidBlock);
+        }
+
+        self.block.resize(entries * PackedEntry::SIZE, 0);
+        self.inner.read_exact(&mut self.block)?;
+        let mut checksum = [0; 4];
+        self.inner.read_exact(&mut checksum)?;
+        if u32::from_le_bytes(checksum) != crc32(&self.block) {
+            return Err(PackedStreamError::ChecksumMismatch);
+        }
+        self.next_entry = 0;
+
+        Ok(true)
+    }
+
+    /// Read the next entry, `None` at the end of the stream
+    fn read_entry(&mut self) -> Result<Option<PackedEntry>, PackedStreamError> {
+        if self.next_entry * PackedEntry::SIZE == self.block.len() && !self.read_block()? {
+            return Ok(None);
+        }
+        let start = self.next_entry * PackedEntry::SIZE;
+        let mut bytes = [0; PackedEntry::SIZE];
+        bytes.copy_from_slice(&self.block[start..start + PackedEntry::SIZE]);
+        self.next_entry += 1;
+
+        PackedEntry::from_bytes(&bytes)
+            .map(Some)
+            .map_err(PackedStreamError::InvalidEntry)
+    }
+}
+
+impl<R: Read> Iterator for PackedReader<R> {
+    type Item = Result<PackedEntry, PackedStreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let entry = self.read_entry().transpose();
+        self.finished = !matches!(entry, Some(Ok(_)));
+
+        entry
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fen;
+    use crate::pgn::GameResult;
+    use crate::position::packed::stream::{crc32, BLOCK_ENTRIES, HEADER};
+    use crate::position::{
+        PackedBoard, PackedEntry, PackedReader, PackedStreamError, PackedWriter,
+    };
+    use test_case::test_case;
+
+    /// Write entries with increasing scores to a stream
+    fn write_entries(count: usize) -> (Vec<PackedEntry>, Vec<u8>) {
+        let board = PackedBoard::try_from(&fen!("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1")).unwrap();
+        let entries = (0..count)
+            .map(|score| PackedEntry {
+                board,
+                score: score as i16,
+                result: GameResult::Draw,
+            })
+            .collect::<Vec<_>>();
+        let mut writer = PackedWriter::new(Vec::new()).unwrap();
+        for entry in &entries {
+            writer.write(entry).unwrap();
+        }
+
+        (entries, writer.finish().unwrap())
+    }
+
+    #[test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test_case(0; "empty")]
+    #[test_case(3; "single block")]
+    #[test_case(BLOCK_ENTRIES; "full block")]
+    #[test_case(BLOCK_ENTRIES * 2 + 5; "several blocks")]
+    fn entries_round_trip(count: usize) {
+        let (entries, bytes) = write_entries(count);
+        let read = PackedReader::new(bytes.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(read, entries);
+    }
+
+    #[test]
+    fn dropping_the_writer_writes_the_last_block() {
+        let (entries, _) = write_entries(2);
+        let mut bytes = Vec::new();
+        {
+            let mut writer = PackedWriter::new(&mut bytes).unwrap();
+            for entry in &entries {
+                writer.write(entry).unwrap();
+            }
+        }
+
+        assert_eq!(PackedReader::new(bytes.as_slice()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn corrupt_blocks_fail_their_checksum() {
+        let (_, mut bytes) = write_entries(3);
+        bytes[HEADER.len() + 4 + PackedEntry::SIZE + 1] ^= 1;
+        let mut reader = PackedReader::new(bytes.as_slice()).unwrap();
+
+        assert!(matches!(
+            reader.next(),
+            Some(Err(PackedStreamError::ChecksumMismatch))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn truncated_streams_fail() {
+        let (_, bytes) = write_entries(3);
+        let mut reader = PackedReader::new(&bytes[..bytes.len() - 2]).unwrap();
+
+        assert!(matches!(reader.next(), Some(Err(PackedStreamError::Io(_)))));
+    }
+
+    #[test]
+    fn other_streams_are_rejected() {
+        assert!(matches!(
+            PackedReader::new(b"rnbqkbnr/pppppppp".as_slice()),
+            Err(PackedStreamError::InvalidHeader)
+        ));
+        assert!(matches!(
+            PackedReader::new(b"THR".as_slice()),
+            Err(PackedStreamError::InvalidHeader)
+        ));
+    }
+}
@@ -1,9 +1,12 @@
-use crate::castles::CastleRights;
+use crate::castles::{CastleDirection, CastleRights, CastleSquares};
+use crate::chess_move::castle::Castle;
 use crate::half_move_clock::HalfMoveClock;
 use crate::pieces::{OwnedPiece, Piece, PieceType, PlacedPiece};
 use crate::player_color::PlayerColor;
 use crate::ply_count::PlyCount;
-use crate::square::{EnPassantSquare, File, Square};
+use crate::position::LegalPosition;
+use crate::square::{EnPassantSquare, File, Rank, Square};
+use core::fmt::{Display, Formatter};
 use core::str::FromStr;
 use enum_map::{Enum, EnumMap};
 
@@ -17,6 +20,7 @@ pub struct PositionBuilder {
     pub(super) squares: EnumMap<Square, Option<OwnedPiece>>,
     pub(super) starting_player: PlayerColor,
     pub(super) castle_rights: CastleRights,
+    pub(super) castle_squares: CastleSquares,
     pub(super) en_passant_square: Option<EnPassantSquare>,
 }
 
@@ -43,6 +47,12 @@ impl PositionBuilder {
         self
     }
 
+    /// Set the squares the kings and rooks [`castle`](CastleSquares) from, for Chess960
+    pub const fn with_castle_squares(mut self, castle_squares: CastleSquares) -> Self {
+        self.castle_squares = castle_squares;
+        self
+    }
+
     /// Set the halfmove clock
     pub const fn with_halfmove_clock(mut self, halfmove_clock: HalfMoveClock) -> Self {
         self.halfmove_clock = halfmove_clock;
@@ -62,6 +72,176 @@ impl PositionBuilder {
     }
 }
 
+impl PositionBuilder {
+    /// Set up one of the 960 starting positions of Chess960 by its Scharnagl number, with every castle right
+    ///
+    /// Number 518 is the starting position of standard chess.
+    ///
+    /// ```
+    /// use thermite_core::position::PositionBuilder;
+    ///
+    /// let standard = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse().unwrap();
+    /// assert_eq!(PositionBuilder::chess960(518), Some(standard));
+    /// assert_eq!(PositionBuilder::chess960(960), None);
+    /// ```
+    #[must_use]
+    pub fn chess960(number: u16) -> Option<Self> {
+        /// The pairs of empty squares (of the five left after the bishops and queen) the knights are placed on
+        const KNIGHT_SQUARES: [(usize, usize); 10] = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ];
+        if number >= 960 {
+            return None;
+        }
+
+        let number = usize::from(number);
+        let mut pieces = [None; File::LENGTH];
+        pieces[2 * (number % 4) + 1] = Some(PieceType::Bishop);
+        pieces[2 * (number / 4 % 4)] = Some(PieceType::Bishop);
+        let empty_files = |pieces: &[Option<PieceType>; File::LENGTH]| {
+            (0..File::LENGTH)
+                .filter(|&file| pieces[file].is_none())
+                .collect::<alloc::vec::Vec<_>>()
+        };
+        let queen_file = empty_files(&pieces)[number / 16 % 6];
+        pieces[queen_file] = Some(PieceType::Queen);
+        let (first_knight, second_knight) = KNIGHT_SQUARES[number / 96];
+        let remaining = empty_files(&pieces);
+        pieces[remaining[first_knight]] = Some(PieceType::Knight);
+        pieces[remaining[second_knight]] = Some(PieceType::Knight);
+        let remaining = empty_files(&pieces);
+        for (file, piece) in
+            remaining
+                .into_iter()
+                .zip([PieceType::Rook, PieceType::King, PieceType::Rook])
+        {
+            pieces[file] = Some(piece);
+        }
+
+        let mut builder = Self::default()
+            .with_castle_rights(CastleRights::All)
+            .with_fullmove_count(PlyCount::new(1));
+        let mut castle_squares = CastleSquares::default();
+        let mut rooks = 0;
+        for (file, piece) in pieces
+            .into_iter()
+            .enumerate()
+            .filter_map(|(file, piece)| piece.map(|piece| (file, piece)))
+        {
+            let file = File::from_usize(file);
+            for (player, back_rank, pawn_rank) in [
+                (PlayerColor::White, Rank::First, Rank::Second),
+                (PlayerColor::Black, Rank::Eighth, Rank::Seventh),
+            ] {
+                let square = Square::new(file, back_rank);
+                builder = builder
+                    .with_piece(piece.owned_by(player).placed_on(square))
+                    .with_piece(
+                        PieceType::Pawn
+                            .owned_by(player)
+                            .placed_on(Square::new(file, pawn_rank)),
+                    );
+                castle_squares = match piece {
+                    PieceType::King => castle_squares.with_king_from(player, square),
+                    PieceType::Rook if rooks == 0 => {
+                        castle_squares.with_rook_from(CastleDirection::QueenSide, player, square)
+                    }
+                    PieceType::Rook => {
+                        castle_squares.with_rook_from(CastleDirection::KingSide, player, square)
+                    }
+                    _ => castle_squares,
+                };
+            }
+            if piece == PieceType::Rook {
+                rooks += 1;
+            }
+        }
+
+        Some(builder.with_castle_squares(castle_squares))
+    }
+
+    /// Parse the castle rights of a FEN for the pieces placed so far
+    ///
+    /// Supports the standard `KQkq` letters, X-FEN (where `KQkq` refer to the outermost rooks and a file letter to an
+    /// inner rook) and Shredder-FEN (`HAha`, a file letter for every rook).
+    fn parse_castle_rights(
+        &self,
+        field: &str,
+    ) -> Result<(CastleRights, CastleSquares), FenParseError> {
+        let mut castle_rights = CastleRights::None;
+        let mut castle_squares = CastleSquares::default();
+        if field == "-" {
+            return Ok((castle_rights, castle_squares));
+        }
+
+        for castle_char in field.chars() {
+            let player = if castle_char.is_ascii_uppercase() {
+                PlayerColor::White
+            } else {
+                PlayerColor::Black
+            };
+            let back_rank = match player {
+                PlayerColor::White => Rank::First,
+                PlayerColor::Black => Rank::Eighth,
+            };
+            let back_rank_square = |file: usize| Square::new(File::from_usize(file), back_rank);
+            let has_piece = |file: usize, piece: PieceType| {
+                self.squares[back_rank_square(file)] == Some(piece.owned_by(player))
+            };
+            // A king off its back rank can't castle, but keep the standard squares to accept sloppy FENs
+            let king_file = (0..File::LENGTH)
+                .find(|&file| has_piece(file, PieceType::King))
+                .unwrap_or(File::E as usize);
+
+            let (direction, rook_file) = match castle_char.to_ascii_lowercase() {
+                'k' => (
+                    CastleDirection::KingSide,
+                    (king_file + 1..File::LENGTH)
+                        .rev()
+                        .find(|&file| has_piece(file, PieceType::Rook))
+                        .unwrap_or(File::H as usize),
+                ),
+                'q' => (
+                    CastleDirection::QueenSide,
+                    (0..king_file)
+                        .find(|&file| has_piece(file, PieceType::Rook))
+                        .unwrap_or(File::A as usize),
+                ),
+                file_char @ 'a'..='h' => {
+                    let rook_file = usize::from(file_char as u8 - b'a');
+                    if !has_piece(rook_file, PieceType::Rook) || rook_file == king_file {
+                        return Err(FenParseError::IllegalCastleRights);
+                    }
+                    let direction = if rook_file > king_file {
+                        CastleDirection::KingSide
+                    } else {
+                        CastleDirection::QueenSide
+                    };
+                    (direction, rook_file)
+                }
+                _ => return Err(FenParseError::IllegalCastleRights),
+            };
+
+            castle_squares = castle_squares
+                .with_king_from(player, back_rank_square(king_file))
+                .with_rook_from(direction, player, back_rank_square(rook_file));
+            castle_rights |=
+                Castle::with_squares(player, direction, castle_squares).required_rights();
+        }
+
+        Ok((castle_rights, castle_squares))
+    }
+}
+
 impl Default for PositionBuilder {
     fn default() -> Self {
         Self {
@@ -70,6 +250,7 @@ impl Default for PositionBuilder {
             squares: EnumMap::default(),
             starting_player: PlayerColor::White,
             castle_rights: CastleRights::None,
+            castle_squares: CastleSquares::default(),
             en_passant_square: None,
         }
     }
@@ -84,7 +265,7 @@ pub enum FenParseError {
     MissingPosition,
     /// Missing the side to move, 'w' or 'b' after the position.
     MissingSide,
-    /// Missing castle rights, 'KQkq', 'Kq', etc, '-' after side to move.
+    /// Missing castle rights, `KQkq`, `Kq`, etc, `-` after side to move.
     MissingCastleRights,
     /// Missing the en-passant square after the castle rights
     MissingEnPassant,
@@ -197,11 +378,13 @@ impl FromStr for PositionBuilder {
         let mut builder = builder.with_starting_player(starting_player);
 
         // Read castle rights
-        if let Some(castle_rights) = fen_chunks
+        if let Some((castle_rights, castle_squares)) = fen_chunks
             .next()
-            .and_then(|s| s.parse::<CastleRights>().ok())
+            .and_then(|s| builder.parse_castle_rights(s).ok())
         {
-            builder = builder.with_castle_rights(castle_rights);
+            builder = builder
+                .with_castle_rights(castle_rights)
+                .with_castle_squares(castle_squares);
         }
 
         // Read en passant square
@@ -236,10 +419,112 @@ impl FromStr for PositionBuilder {
     }
 }
 
+impl From<&LegalPosition> for PositionBuilder {
+    /// Get the pieces and state of a position, as the first move of a game (positions don't keep the fullmove count)
+    fn from(position: &LegalPosition) -> Self {
+        Self {
+            halfmove_clock: position.halfmove_clock(),
+            halfmove_count: PlyCount::new(2),
+            squares: EnumMap::from_fn(|square| position.owned_piece_on(square)),
+            starting_player: position.player_to_move(),
+            castle_rights: position.castle_rights(),
+            castle_squares: position.castle_squares(),
+            en_passant_square: position.en_passant_square(),
+        }
+    }
+}
+
+impl PositionBuilder {
+    /// Write the castle rights field of a FEN, using the files of the rooks (Shredder-FEN) for Chess960 squares
+    pub(super) fn write_castle_rights(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut any = false;
+        for player in [PlayerColor::White, PlayerColor::Black] {
+            for direction in [CastleDirection::KingSide, CastleDirection::QueenSide] {
+                let castle = Castle::with_squares(player, direction, self.castle_squares);
+                if !self.castle_rights.has_rights(castle.required_rights()) {
+                    continue;
+                }
+                let letter = if self.castle_squares.is_standard() {
+                    match direction {
+                        CastleDirection::KingSide => 'k',
+                        CastleDirection::QueenSide => 'q',
+                    }
+                } else {
+                    char::from(b'a' + self.castle_squares.rook_from(direction, player).file() as u8)
+                };
+                let letter = match player {
+                    PlayerColor::White => letter.to_ascii_uppercase(),
+                    PlayerColor::Black => letter,
+                };
+                write!(f, "{letter}")?;
+                any = true;
+            }
+        }
+
+        if any {
+            Ok(())
+        } else {
+            write!(f, "-")
+        }
+    }
+}
+
+impl Display for PositionBuilder {
+    /// Write the position as a FEN
+    ///
+    /// ```
+    /// use thermite_core::position::PositionBuilder;
+    ///
+    /// let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2";
+    /// assert_eq!(fen.parse::<PositionBuilder>().unwrap().to_string(), fen);
+    /// ```
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for rank in (0..Rank::LENGTH).rev() {
+            let mut empty = 0;
+            for file in 0..File::LENGTH {
+                let square = Square::new(File::from_usize(file), Rank::from_usize(rank));
+                if let Some(piece) = self.squares[square] {
+                    if empty > 0 {
+                        write!(f, "{empty}")?;
+                        empty = 0;
+                    }
+                    write!(f, "{}", piece.fen_char())?;
+                } else {
+                    empty += 1;
+                }
+            }
+            if empty > 0 {
+                write!(f, "{empty}")?;
+            }
+            if rank > 0 {
+                write!(f, "/")?;
+            }
+        }
+
+        let player = match self.starting_player {
+            PlayerColor::White => 'w',
+            PlayerColor::Black => 'b',
+        };
+        write!(f, " {player} ")?;
+        self.write_castle_rights(f)?;
+        match self.en_passant_square {
+            Some(square) => write!(f, " {}", Square::from(square))?,
+            None => write!(f, " -")?,
+        }
+
+        write!(
+            f,
+            " {} {}",
+            PlyCount::from(self.halfmove_clock),
+            (u8::from(self.halfmove_count) / 2).max(1)
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::bitboard::BoardMask;
-    use crate::castles::CastleRights;
+    use crate::castles::{CastleDirection, CastleRights};
     use crate::half_move_clock::HalfMoveClock;
     use crate::pieces::{NonKingPieceType, Piece, PieceType, PlacedPiece};
     use crate::player_color::PlayerColor;
@@ -389,4 +674,70 @@ mod test {
         let position = fen!(fen);
         assert_eq!(position.pieces_masks, masks);
     }
+
+    #[test_case(
+        "bqnb1rkr/8/8/8/8/8/8/BQNBNRKR w HFhf - 0 1",
+        "bqnb1rkr/8/8/8/8/8/8/BQNBNRKR w KQkq - 0 1"
+    )]
+    #[test_case(
+        "b1q1rrkb/8/8/8/8/8/8/BQNNRKRB w GE - 0 1",
+        "b1q1rrkb/8/8/8/8/8/8/BQNNRKRB w KQ - 0 1"
+    )]
+    #[test_case(
+        "rnbqkbnr/8/8/8/8/8/8/RNBQKBNR w HAha - 0 1",
+        "rnbqkbnr/8/8/8/8/8/8/RNBQKBNR w KQkq - 0 1"
+    )]
+    fn shredder_and_x_fen_castle_rights_match(shredder_fen: &str, x_fen: &str) {
+        let shredder = shredder_fen.parse::<PositionBuilder>().unwrap();
+        let x_fen = x_fen.parse::<PositionBuilder>().unwrap();
+        assert_eq!(shredder.castle_rights, x_fen.castle_rights);
+        assert_eq!(shredder.castle_squares, x_fen.castle_squares);
+    }
+
+    #[test_case("rnbqkbnr/8/8/8/8/8/8/RNBQKBNR w KQkq - 0 1", E1, A1, H1)]
+    #[test_case("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1", E1, B1, G1)]
+    #[test_case("rk4r1/8/8/8/8/8/8/RK4R1 w Qq - 0 1", B1, A1, H1)]
+    #[test_case("rkr5/8/8/8/8/8/8/RKR5 w KQ - 0 1", B1, A1, C1)]
+    fn castle_squares_are_parsed(fen: &str, king: Square, queen_rook: Square, king_rook: Square) {
+        let castle_squares = fen.parse::<PositionBuilder>().unwrap().castle_squares;
+        assert_eq!(castle_squares.king_from(PlayerColor::White), king);
+        assert_eq!(
+            castle_squares.rook_from(CastleDirection::QueenSide, PlayerColor::White),
+            queen_rook
+        );
+        assert_eq!(
+            castle_squares.rook_from(CastleDirection::KingSide, PlayerColor::White),
+            king_rook
+        );
+    }
+
+    #[test]
+    fn chess960_start_positions_are_numbered() {
+        let builder = PositionBuilder::chess960(0).unwrap();
+        assert_eq!(
+            builder,
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+                .parse::<PositionBuilder>()
+                .unwrap()
+        );
+    }
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"; "startpos")]
+    #[test_case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10"; "kiwipete")]
+    #[test_case("4rrk1/pp1n3p/3q2pQ/2p1pb2/2PP4/2P3N1/P2B2PP/4RRK1 b - - 7 19"; "no castle rights")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3"; "en passant")]
+    #[test_case("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"; "chess960")]
+    #[test_case("1r2k1r1/8/8/8/8/8/8/1R2K1R1 b Gb - 12 40"; "chess960 partial rights")]
+    fn display_writes_the_fen(fen: &str) {
+        assert_eq!(fen.parse::<PositionBuilder>().unwrap().to_string(), fen);
+    }
+
+    #[test_case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"; "kiwipete")]
+    #[test_case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 5 1"; "halfmove clock")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 1"; "en passant")]
+    fn legal_positions_convert_to_builders(fen: &str) {
+        let position = fen!(fen);
+
+        assert_eq!(PositionBuilder::from(&position).to_string(), fen);
+    }
 }
@@ -0,0 +1,359 @@
+use crate::castles::CastleDirection;
+use crate::chess_move::ChessMove;
+use crate::pieces::{Piece, PieceType, PromotablePieceType};
+use crate::position::LegalPosition;
+use crate::square::{File, Rank, Square};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Errors that can occur while parsing a move in standard algebraic notation
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SanParseError {
+    /// Isn't a move in algebraic notation (`e4`, `Nbd7`, `exd8=Q+`, `O-O`, etc)
+    InvalidSyntax,
+    /// Doesn't match any legal move in the position
+    IllegalMove,
+    /// Matches more than one legal move in the position, missing the disambiguating file or rank
+    AmbiguousMove,
+}
+
+/// Get the (lower-case) char of a [`File`]
+fn file_char(file: File) -> char {
+    char::from(b'a' + file as u8)
+}
+
+/// Get the char of a [`Rank`]
+fn rank_char(rank: Rank) -> char {
+    char::from(b'1' + rank as u8)
+}
+
+/// Parse the (upper-case) piece letter of a non-pawn piece
+const fn parse_piece(letter: u8) -> Option<PieceType> {
+    match letter {
+        b'N' => Some(PieceType::Knight),
+        b'B' => Some(PieceType::Bishop),
+        b'R' => Some(PieceType::Rook),
+        b'Q' => Some(PieceType::Queen),
+        b'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
+
+/// Parse the letter of a piece a pawn can promote to, in either case
+const fn parse_promotion(letter: u8) -> Option<PromotablePieceType> {
+    match letter.to_ascii_uppercase() {
+        b'N' => Some(PromotablePieceType::Knight),
+        b'B' => Some(PromotablePieceType::Bishop),
+        b'R' => Some(PromotablePieceType::Rook),
+        b'Q' => Some(PromotablePieceType::Queen),
+        _ => None,
+    }
+}
+
+impl LegalPosition {
+    /// Find the legal move for a move in standard algebraic notation
+    ///
+    /// Check, mate and annotation suffixes (`+`, `#`, `!`, `?`) are ignored, along with a missing `x` for captures
+    /// and a missing `=` for promotions.
+    ///
+    /// ```
+    /// use thermite_core::fen;
+    /// use thermite_core::position::SanParseError;
+    ///
+    /// let position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// assert_eq!(position.parse_san("Nf3").unwrap().to_string(), "g1f3");
+    /// assert_eq!(position.parse_san("e4").unwrap().to_string(), "e2e4");
+    /// assert_eq!(position.parse_san("e5"), Err(SanParseError::IllegalMove));
+    /// ```
+    ///
+    /// # Errors
+    /// If the move can't be parsed, or doesn't match exactly one legal move
+    pub fn parse_san(&self, san: &str) -> Result<ChessMove, SanParseError> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        let legal_moves = self.generate_legal_moves();
+
+        let castle_direction = match san {
+            "O-O" | "0-0" => Some(CastleDirection::KingSide),
+            "O-O-O" | "0-0-0" => Some(CastleDirection::QueenSide),
+            _ => None,
+        };
+        if let Some(direction) = castle_direction {
+            return legal_moves
+                .into_iter()
+                .find(|chess_move| {
+                    matches!(chess_move, ChessMove::Castle(castle) if castle.direction() == direction)
+                })
+                .ok_or(SanParseError::IllegalMove);
+        }
+
+        let san = san.as_bytes();
+        let (piece, san) = match san.split_first() {
+            Some((&letter, rest)) => {
+                parse_piece(letter).map_or((PieceType::Pawn, san), |piece| (piece, rest))
+            }
+            None => return Err(SanParseError::InvalidSyntax),
+        };
+        let (san, promotion) = match san {
+            [rest @ .., b'=', letter] if piece == PieceType::Pawn => (
+                rest,
+                Some(parse_promotion(*letter).ok_or(SanParseError::InvalidSyntax)?),
+            ),
+            [rest @ .., b'1'..=b'8', letter] if piece == PieceType::Pawn => {
+                parse_promotion(*letter).map_or((san, None), |promotion| {
+                    (&san[..=rest.len()], Some(promotion))
+                })
+            }
+            _ => (san, None),
+        };
+        let Some((target, qualifiers)) = san
+            .len()
+            .checked_sub(2)
+            .map(|split| (&san[split..], &san[..split]))
+        else {
+            return Err(SanParseError::InvalidSyntax);
+        };
+        let to = core::str::from_utf8(target)
+            .ok()
+            .and_then(|target| target.parse::<Square>().ok())
+            .ok_or(SanParseError::InvalidSyntax)?;
+        let qualifiers = qualifiers
+            .strip_suffix(b"x")
+            .or_else(|| qualifiers.strip_suffix(b":"))
+            .unwrap_or(qualifiers);
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &qualifier in qualifiers {
+            match qualifier {
+                b'a'..=b'h' if from_file.is_none() && from_rank.is_none() => {
+                    from_file = Some(qualifier - b'a');
+                }
+                b'1'..=b'8' if from_rank.is_none() => from_rank = Some(qualifier - b'1'),
+                _ => return Err(SanParseError::InvalidSyntax),
+            }
+        }
+
+        let mut candidates = legal_moves.into_iter().filter(|chess_move| {
+            let from = chess_move.from_square();
+            !matches!(chess_move, ChessMove::Castle(_))
+                && chess_move.moved_piece() == piece
+                && chess_move.to_square() == to
+                && chess_move.promotion_piece() == promotion
+                && from_file.is_none_or(|file| from.file() as u8 == file)
+                && from_rank.is_none_or(|rank| from.rank() as u8 == rank)
+        });
+
+        match (candidates.next(), candidates.next()) {
+            (Some(chess_move), None) => Ok(chess_move),
+            (Some(_), Some(_)) => Err(SanParseError::AmbiguousMove),
+            (None, _) => Err(SanParseError::IllegalMove),
+        }
+    }
+
+    /// Write a legal move in standard algebraic notation, with the check (`+`) or mate (`#`) suffix
+    ///
+    /// The position is left unchanged once the move has been checked for mate.
+    ///
+    /// ```
+    /// use thermite_core::fen;
+    ///
+    /// let mut position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// let knight_move = position.parse_san("Nf3").unwrap();
+    /// assert_eq!(position.to_san(knight_move), "Nf3");
+    /// ```
+    pub fn to_san(&mut self, chess_move: ChessMove) -> String {
+        let mut san = if let ChessMove::Castle(castle) = chess_move {
+            String::from(match castle.direction() {
+                CastleDirection::KingSide => "O-O",
+                CastleDirection::QueenSide => "O-O-O",
+            })
+        } else {
+            self.piece_move_san(chess_move)
+        };
+
+        let state = self.make_move(chess_move);
+        if self.in_check() {
+            san.push(if self.generate_legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+        self.unmake_move(chess_move, state);
+
+        san
+    }
+
+    /// Write a legal move other than a castle in standard algebraic notation, without its check suffix
+    fn piece_move_san(&self, chess_move: ChessMove) -> String {
+        let mut san = String::new();
+        let piece = chess_move.moved_piece();
+        let from = chess_move.from_square();
+        let to = chess_move.to_square();
+        if piece == PieceType::Pawn {
+            if chess_move.is_capture() {
+                san.push(file_char(from.file()));
+            }
+        } else {
+            san.push(piece.get_upper_char());
+            let others: Vec<Square> = self
+                .generate_legal_moves()
+                .into_iter()
+                .filter(|&other| {
+                    other != chess_move
+                        && !matches!(other, ChessMove::Castle(_))
+                        && other.moved_piece() == piece
+                        && other.to_square() == to
+                })
+                .map(|other| other.from_square())
+                .collect();
+            if !others.is_empty() {
+                if others.iter().all(|other| other.file() != from.file()) {
+                    san.push(file_char(from.file()));
+                } else if others.iter().all(|other| other.rank() != from.rank()) {
+                    san.push(rank_char(from.rank()));
+                } else {
+                    san.push(file_char(from.file()));
+                    san.push(rank_char(from.rank()));
+                }
+            }
+        }
+        if chess_move.is_capture() {
+            san.push('x');
+        }
+        san.push(file_char(to.file()));
+        san.push(rank_char(to.rank()));
+        if let Some(promotion) = chess_move.promotion_piece() {
+            san.push('=');
+            san.push(promotion.get_upper_char());
+        }
+
+        san
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fen;
+    use test_case::test_case;
+
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "e4",
+        "e2e4"
+    )]
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "Nc3",
+        "b1c3"
+    )]
+    #[test_case(
+        "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        "Nxe5",
+        "f3e5"
+    )]
+    #[test_case("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3", "Ne5", "f3e5"; "missing capture")]
+    #[test_case(
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+        "exd5",
+        "e4d5"
+    )]
+    #[test_case("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "exf6", "e5f6"; "en passant")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "O-O", "e1g1")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "O-O-O", "e8c8")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "0-0-0", "e1c1"; "zero castle")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "Rab1", "a1b1")]
+    #[test_case("R6R/8/8/8/8/8/1k6/4K3 w - - 0 1", "Rhd8", "h8d8")]
+    #[test_case("1k6/8/8/8/Q7/8/8/Q2Q1K2 w - - 0 1", "Qa1d4", "a1d4")]
+    #[test_case("1k6/8/8/8/Q7/8/8/Q2Q1K2 w - - 0 1", "Q4d4", "a4d4")]
+    #[test_case("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "axb8=N", "a7b8n")]
+    #[test_case("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8Q+", "a7a8q"; "missing equals")]
+    #[test_case("6k1/5ppp/8/8/8/8/8/R3K3 w Q - 0 1", "Ra8#!?", "a1a8"; "with suffixes")]
+    fn parse_san_works(fen: &str, san: &str, expected: &str) {
+        let position = fen!(fen);
+        assert_eq!(position.parse_san(san).unwrap().to_string(), expected);
+    }
+
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "e5",
+        SanParseError::IllegalMove
+    )]
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "O-O",
+        SanParseError::IllegalMove
+    )]
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "Zf3",
+        SanParseError::InvalidSyntax
+    )]
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "N",
+        SanParseError::InvalidSyntax
+    )]
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "",
+        SanParseError::InvalidSyntax
+    )]
+    #[test_case("R6R/8/8/8/8/8/1k6/4K3 w - - 0 1", "Rd8", SanParseError::AmbiguousMove)]
+    #[test_case(
+        "1k6/8/8/8/Q7/8/8/Q2Q1K2 w - - 0 1",
+        "Qad4",
+        SanParseError::AmbiguousMove
+    )]
+    #[test_case(
+        "1k6/8/8/8/Q7/8/8/Q2Q1K2 w - - 0 1",
+        "Q1d4",
+        SanParseError::AmbiguousMove
+    )]
+    #[test_case(
+        "1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+        "a8=K",
+        SanParseError::InvalidSyntax
+    )]
+    fn parse_san_errors(fen: &str, san: &str, expected: SanParseError) {
+        assert_eq!(fen!(fen).parse_san(san), Err(expected));
+    }
+
+    #[test_case(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "e2e4",
+        "e4"
+    )]
+    #[test_case(
+        "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        "f3e5",
+        "Nxe5"
+    )]
+    #[test_case(
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "e5f6",
+        "exf6"
+    )]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1", "O-O")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8", "O-O-O")]
+    #[test_case("R6R/8/8/8/8/8/1k6/4K3 w - - 0 1", "a8d8", "Rad8")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "a1d1", "Rd1")]
+    #[test_case("1k6/8/8/8/Q7/8/8/Q2Q1K2 w - - 0 1", "a1d4", "Qa1d4")]
+    #[test_case("1k6/8/8/8/Q7/8/8/Q2Q1K2 w - - 0 1", "a4d4", "Q4d4")]
+    #[test_case("1k6/8/8/8/Q7/8/8/Q2Q1K2 w - - 0 1", "d1d4", "Qdd4")]
+    #[test_case("1k6/8/8/8/Q7/8/8/Q3K2Q w - - 0 1", "a1d4", "Q1d4")]
+    #[test_case("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q", "axb8=Q+")]
+    #[test_case("6k1/5ppp/8/8/8/8/8/R3K3 w Q - 0 1", "a1a8", "Ra8#")]
+    fn to_san_works(fen: &str, uci: &str, expected: &str) {
+        let mut position = fen!(fen);
+        let original = position.clone();
+        let chess_move = position
+            .generate_legal_moves()
+            .into_iter()
+            .find(|chess_move| chess_move.to_string() == uci)
+            .unwrap();
+
+        assert_eq!(position.to_san(chess_move), expected);
+        assert_eq!(position, original);
+    }
+}
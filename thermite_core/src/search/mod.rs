@@ -0,0 +1,801 @@
+//! Finding the best move of a position.
+//!
+//! An iterative deepening, fail-soft alpha-beta search that remembers the positions it has searched in a
+//! [`TranspositionTable`], and ends every line with a quiescence search of captures and promotions so only quiet
+//! positions are evaluated. Scores are relative to the player to move, with mates counted in plies from the position
+//! being searched (so they can be stored and reused regardless of how the position was reached).
+//!
+//! Searches can use multiple threads (Lazy SMP), each searching its own copy of the position while sharing the
+//! transposition table, so the helper threads speed up the main thread by filling the table with results it reuses.
+
+mod move_picker;
+mod pruning;
+mod search_stack;
+mod time_manager;
+mod transposition_table;
+mod worker;
+
+pub use move_picker::MovePicker;
+pub use pruning::{late_move_reduction, Pruning};
+pub use search_stack::{history_bonus, Frame, PlayedMove, SearchStack, MAX_HISTORY};
+pub use time_manager::{Clock, ManualClock, Stability, SystemClock, TimeManager};
+pub use transposition_table::{Bound, TranspositionEntry, TranspositionTable};
+
+use crate::chess_move::ChessMove;
+use crate::evaluation::Score;
+#[cfg(feature = "nnue")]
+use crate::nnue::Network;
+use crate::node_count::NodeCount;
+use crate::player_color::PlayerColor;
+use crate::ply_count::PlyCount;
+use crate::position::LegalPosition;
+use crate::tablebase::SyzygyTablebase;
+use crate::zobrist::ZobristHash;
+use alloc::vec::Vec;
+use enum_map::EnumMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use worker::{SharedState, Worker};
+
+/// The deepest the search iterates without a depth limit
+pub const MAX_DEPTH: PlyCount = PlyCount(64);
+/// The size (in megabytes) of the transposition table of a [default](Search::default) search
+pub const DEFAULT_HASH_MEGABYTES: usize = 16;
+/// The time (in milliseconds) kept in reserve for communicating each move by a [default](Search::default) search
+pub const DEFAULT_MOVE_OVERHEAD_MILLISECONDS: u64 = 10;
+/// The most threads a search can use
+pub const MAX_THREADS: usize = 1024;
+/// The most lines a search can find at once
+pub const MAX_MULTIPV: usize = 256;
+
+/// When to stop searching (and what to search), the search stops at whichever limit is reached first
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct SearchLimits {
+    /// The deepest iteration to search
+    pub depth: Option<PlyCount>,
+    /// How long to search for
+    pub movetime: Option<Duration>,
+    /// The time left on each player's clock, the search manages its time when the player to move has one
+    pub time_left: EnumMap<PlayerColor, Option<Duration>>,
+    /// The time added to each player's clock after every move
+    pub increment: EnumMap<PlayerColor, Duration>,
+    /// How many moves are left until the next time control, sudden death if `None`
+    pub moves_to_go: Option<u32>,
+    /// The most nodes to search, exactly reached by a single threaded search so its results are reproducible
+    pub nodes: Option<NodeCount>,
+    /// Search for a mate in at most this many moves, without pruning so no shorter mate is missed, stopping once one
+    /// is found
+    pub mate: Option<u8>,
+    /// The only moves to search from the position, every legal move if empty (illegal moves are ignored)
+    pub search_moves: Vec<ChessMove>,
+}
+
+/// The outcome of a line of a completed iteration of a search
+#[derive(Clone, PartialEq, Debug)]
+pub struct SearchResult {
+    /// The best move found, `None` if the position has no legal moves
+    pub best_move: Option<ChessMove>,
+    /// The score of the best move for the player to move
+    pub score: Score,
+    /// The depth of the iteration
+    pub depth: PlyCount,
+    /// The positions visited by the search so far
+    pub nodes: NodeCount,
+    /// How long the search has taken so far
+    pub elapsed: Duration,
+    /// The line of best play expected from the position, starting with the best move
+    pub principal_variation: Vec<ChessMove>,
+    /// Which of the best lines this is, starting at `1` for the best (other lines are only found with
+    /// [`Search::set_multipv`])
+    pub multipv: usize,
+}
+
+/// A flag to stop a search from another thread
+///
+/// Once raised, the flag stays raised (stopping any later search straight after its first iteration) until it's
+/// [reset](Self::reset).
+#[derive(Clone, Debug, Default)]
+pub struct StopSignal(Arc<AtomicBool>);
+
+impl StopSignal {
+    /// Stop the search as soon as possible, it will still return the result of its deepest completed iteration
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Lower the flag so another search can run
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether or not the flag is raised
+    #[must_use]
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A flag for searching while it's the opponent's turn (pondering) from another thread
+///
+/// While raised, the search ignores its time limits, thinking about the move it expects the opponent to play until
+/// it's either [hit](Self::hit) (the opponent played it, so the time limits apply from then on) or stopped.
+#[derive(Clone, Debug, Default)]
+pub struct PonderSignal(Arc<AtomicBool>);
+
+impl PonderSignal {
+    /// Start pondering, set before the search starts
+    pub fn start(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// The opponent played the expected move, so manage the time as usual (including the time spent pondering)
+    pub fn hit(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether or not the search is pondering
+    #[must_use]
+    pub fn is_pondering(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A reusable search, keeping its transposition table between searches
+#[derive(Debug)]
+pub struct Search {
+    table: TranspositionTable,
+    tablebase: Option<Arc<SyzygyTablebase>>,
+    #[cfg(feature = "nnue")]
+    network: Option<Arc<Network>>,
+    threads: usize,
+    multipv: usize,
+    stop_signal: StopSignal,
+    ponder_signal: PonderSignal,
+    move_overhead: Duration,
+    clock: Arc<dyn Clock>,
+    pruning: Pruning,
+    /// The killer moves and histories of each thread
+    stacks: Vec<SearchStack>,
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MEGABYTES)
+    }
+}
+
+impl Search {
+    /// Create a single threaded search with a transposition table of (at most) a number of megabytes
+    #[must_use]
+    pub fn new(hash_megabytes: usize) -> Self {
+        Self {
+            table: TranspositionTable::new(hash_megabytes),
+            tablebase: None,
+            #[cfg(feature = "nnue")]
+            network: None,
+            threads: 1,
+            multipv: 1,
+            stop_signal: StopSignal::default(),
+            ponder_signal: PonderSignal::default(),
+            move_overhead: Duration::from_millis(DEFAULT_MOVE_OVERHEAD_MILLISECONDS),
+            clock: Arc::new(SystemClock::default()),
+            pruning: Pruning::default(),
+            stacks: Vec::new(),
+        }
+    }
+
+    /// Probe a Syzygy tablebase for the result of positions reached by a capture or pawn move
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<SyzygyTablebase>>) {
+        self.tablebase = tablebase;
+    }
+
+    /// Evaluate searched positions with a [`Network`], or with the handcrafted evaluation (unless the position
+    /// searched already has one) without
+    #[cfg(feature = "nnue")]
+    pub fn set_network(&mut self, network: Option<Arc<Network>>) {
+        self.network = network;
+    }
+
+    /// Replace the transposition table with an empty one of (at most) a number of megabytes
+    pub fn set_hash(&mut self, hash_megabytes: usize) {
+        self.table = TranspositionTable::new(hash_megabytes);
+    }
+
+    /// Search with a number of threads, clamped between one and [`MAX_THREADS`]
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.clamp(1, MAX_THREADS);
+    }
+
+    /// Get the number of threads searches use
+    #[must_use]
+    pub const fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Find a number of the best lines (clamped between one and [`MAX_MULTIPV`]), each starting with a different move
+    pub fn set_multipv(&mut self, multipv: usize) {
+        self.multipv = multipv.clamp(1, MAX_MULTIPV);
+    }
+
+    /// Get the number of the best lines searches find
+    #[must_use]
+    pub const fn multipv(&self) -> usize {
+        self.multipv
+    }
+
+    /// Keep some time in reserve for every move searched on a clock, for the time lost communicating it
+    pub const fn set_move_overhead(&mut self, move_overhead: Duration) {
+        self.move_overhead = move_overhead;
+    }
+
+    /// Time searches with another [`Clock`]
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Choose which selectivity techniques searches use
+    pub const fn set_pruning(&mut self, pruning: Pruning) {
+        self.pruning = pruning;
+    }
+
+    /// Get the selectivity techniques searches use
+    #[must_use]
+    pub const fn pruning(&self) -> Pruning {
+        self.pruning
+    }
+
+    /// Get a flag for stopping searches from another thread
+    #[must_use]
+    pub fn stop_signal(&self) -> StopSignal {
+        self.stop_signal.clone()
+    }
+
+    /// Get a flag for pondering (ignoring the time limits until it's hit) from another thread
+    #[must_use]
+    pub fn ponder_signal(&self) -> PonderSignal {
+        self.ponder_signal.clone()
+    }
+
+    /// Forget every searched position and the killer moves and histories learned, as for a new game
+    pub fn clear(&mut self) {
+        self.table.clear();
+        self.stacks.iter_mut().for_each(SearchStack::clear);
+    }
+
+    /// Search a position until a limit is reached, getting the result of the deepest completed iteration
+    ///
+    /// `history` holds the hashes of the positions played before it (oldest first, as from [`Game::history`](crate::game::Game::history)), so
+    /// lines repeating them are scored as draws.
+    pub fn search(
+        &mut self,
+        position: &LegalPosition,
+        history: &[ZobristHash],
+        limits: SearchLimits,
+    ) -> SearchResult {
+        self.search_with_progress(position, history, limits, |_| {})
+    }
+
+    /// Search a position (played after the positions of `history`, as for [`search`](Self::search)) until a limit is
+    /// reached, reporting the result of each line of each iteration of the main thread as it completes
+    ///
+    /// With more than one thread, the result is from whichever thread completed the deepest iteration, preferring
+    /// the better score between threads reaching the same depth. When the position is in the tablebase, only the root
+    /// moves its DTZ tables rank best are searched.
+    pub fn search_with_progress(
+        &mut self,
+        position: &LegalPosition,
+        history: &[ZobristHash],
+        limits: SearchLimits,
+        report: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        #[cfg(feature = "nnue")]
+        let position = &{
+            let mut position = position.clone();
+            if let Some(network) = &self.network {
+                position.set_network(Arc::clone(network));
+            }
+            position
+        };
+        self.table.new_search();
+        let legal_moves = position.generate_legal_moves();
+        let shared = SharedState {
+            table: &self.table,
+            tablebase: self.tablebase.as_deref(),
+            stop_signal: &self.stop_signal,
+            ponder_signal: &self.ponder_signal,
+            finished: AtomicBool::new(false),
+            nodes: AtomicU64::new(0),
+            time: TimeManager::new(
+                &limits,
+                position.player_to_move(),
+                self.move_overhead,
+                self.clock.clone(),
+            ),
+            pruning: if limits.mate.is_some() {
+                Pruning::NONE
+            } else {
+                self.pruning
+            },
+            multipv: self.multipv,
+            search_moves: tablebase_root_moves(
+                self.tablebase.as_deref(),
+                position,
+                limits
+                    .search_moves
+                    .into_iter()
+                    .filter(|chess_move| legal_moves.contains(chess_move))
+                    .collect(),
+            ),
+            node_limit: limits.nodes,
+            history,
+        };
+        // Every mate in some number of moves is found by searching (without pruning) to as many of the mating
+        // player's moves and the replies between them
+        let mate_depth = limits
+            .mate
+            .map(|moves| PlyCount(moves.saturating_mul(2).saturating_sub(1)));
+        let max_depth = limits
+            .depth
+            .into_iter()
+            .chain(mate_depth)
+            .min()
+            .unwrap_or(MAX_DEPTH)
+            .clamp(PlyCount(1), MAX_DEPTH);
+
+        self.stacks.resize_with(self.threads, SearchStack::default);
+        let (main_stack, helper_stacks) = self.stacks.split_at_mut(1);
+
+        let mut result = thread::scope(|scope| {
+            let helpers: Vec<_> = helper_stacks
+                .iter_mut()
+                .enumerate()
+                .map(|(index, stack)| {
+                    let (shared, position) = (&shared, position.clone());
+                    scope.spawn(move || {
+                        Worker::new(index + 1, shared, stack).iterate(position, max_depth, |_| {})
+                    })
+                })
+                .collect();
+
+            let main = Worker::new(0, &shared, &mut main_stack[0]).iterate(
+                position.clone(),
+                max_depth,
+                report,
+            );
+            shared.finished.store(true, Ordering::Relaxed);
+
+            helpers
+                .into_iter()
+                .filter_map(|helper| helper.join().ok())
+                .fold(main, |best, result| {
+                    let is_better = result.best_move.is_some()
+                        && (result.depth > best.depth
+                            || (result.depth == best.depth && result.score > best.score));
+                    if is_better {
+                        result
+                    } else {
+                        best
+                    }
+                })
+        });
+
+        result.nodes = NodeCount::new(shared.nodes.load(Ordering::Relaxed));
+        result.elapsed = shared.time.elapsed();
+        result
+    }
+}
+
+/// Narrow the moves searched at the root (all of them when there are no search moves) to those the tablebase ranks
+/// best, so the search can't throw away a tablebase result or let the fifty-move rule draw a win
+fn tablebase_root_moves(
+    tablebase: Option<&SyzygyTablebase>,
+    position: &LegalPosition,
+    search_moves: Vec<ChessMove>,
+) -> Vec<ChessMove> {
+    let Some(root_moves) =
+        tablebase.and_then(|tablebase| tablebase.rank_root_moves(&mut position.clone()))
+    else {
+        return search_moves;
+    };
+    let allowed: Vec<_> = root_moves
+        .into_iter()
+        .filter(|root_move| search_moves.is_empty() || search_moves.contains(&root_move.chess_move))
+        .collect();
+    let Some(best_rank) = allowed.iter().map(|root_move| root_move.rank).max() else {
+        return search_moves;
+    };
+
+    allowed
+        .into_iter()
+        .filter(|root_move| root_move.rank == best_rank)
+        .map(|root_move| root_move.chess_move)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluation::{PawnEvaluation, Score};
+    use crate::fen;
+    use crate::game::Game;
+    use crate::node_count::NodeCount;
+    use crate::ply_count::PlyCount;
+    use crate::search::{Pruning, Search, SearchLimits};
+    use crate::tablebase::fixtures::FIXTURES;
+    use crate::tablebase::SyzygyTablebase;
+    use std::sync::Arc;
+    use test_case::test_case;
+
+    /// Search a position to a fixed depth
+    fn search(fen: &str, depth: u8) -> (String, Score) {
+        search_with_threads(fen, depth, 1)
+    }
+
+    /// Search a position to a fixed depth with a number of threads
+    fn search_with_threads(fen: &str, depth: u8, threads: usize) -> (String, Score) {
+        let position = fen!(fen);
+        let mut search = Search::new(1);
+        search.set_threads(threads);
+        let result = search.search(
+            &position,
+            &[],
+            SearchLimits {
+                depth: Some(PlyCount::new(depth)),
+                ..SearchLimits::default()
+            },
+        );
+
+        (result.best_move.unwrap().to_string(), result.score)
+    }
+
+    /// A mate in two with two solutions, Kb6 (then Rh8) and Kc7 (then Ra1)
+    const MATE_IN_TWO: &str = "k7/8/2K5/8/8/8/8/7R w - - 0 1";
+    /// The first moves of both solutions to [`MATE_IN_TWO`]
+    const MATE_IN_TWO_MOVES: &[&str] = &["c6b6", "c6c7"];
+
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 2, &["a1a8"], 1; "back rank")]
+    #[test_case("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4", 2, &["h5f7"], 1; "scholars mate")]
+    #[test_case(MATE_IN_TWO, 4, MATE_IN_TWO_MOVES, 3; "mate in two")]
+    #[test_case("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1", 3, &["g3g6"], 3; "queen sacrifice")]
+    fn finds_mates(fen: &str, depth: u8, expected_moves: &[&str], expected_plies: u8) {
+        let (best_move, score) = search(fen, depth);
+
+        assert!(expected_moves.contains(&best_move.as_str()));
+        assert_eq!(score, Score::Mating(PlyCount::new(expected_plies)));
+    }
+
+    #[test_case(2)]
+    #[test_case(4)]
+    fn finds_mates_with_threads(threads: usize) {
+        let (best_move, score) = search_with_threads(MATE_IN_TWO, 4, threads);
+
+        assert!(MATE_IN_TWO_MOVES.contains(&best_move.as_str()));
+        assert_eq!(score, Score::Mating(PlyCount::new(3)));
+    }
+
+    #[test_case(Pruning::NONE; "none")]
+    #[test_case(Pruning { null_move: true, ..Pruning::NONE }; "null move")]
+    #[test_case(Pruning { late_move_reductions: true, ..Pruning::NONE }; "late move reductions")]
+    #[test_case(Pruning { reverse_futility: true, ..Pruning::NONE }; "reverse futility")]
+    #[test_case(Pruning { futility: true, ..Pruning::NONE }; "futility")]
+    #[test_case(Pruning { razoring: true, ..Pruning::NONE }; "razoring")]
+    #[test_case(Pruning { late_move_pruning: true, ..Pruning::NONE }; "late move pruning")]
+    fn finds_mates_with_each_pruning_technique(pruning: Pruning) {
+        let position = fen!(MATE_IN_TWO);
+        let mut search = Search::new(1);
+        search.set_pruning(pruning);
+        let result = search.search(
+            &position,
+            &[],
+            SearchLimits {
+                depth: Some(PlyCount::new(5)),
+                ..SearchLimits::default()
+            },
+        );
+
+        assert!(MATE_IN_TWO_MOVES.contains(&result.best_move.unwrap().to_string().as_str()));
+        assert_eq!(result.score, Score::Mating(PlyCount::new(3)));
+    }
+
+    #[test]
+    fn pruning_searches_fewer_nodes() {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let nodes = |pruning| {
+            let mut search = Search::new(1);
+            search.set_pruning(pruning);
+            let limits = SearchLimits {
+                depth: Some(PlyCount::new(4)),
+                ..SearchLimits::default()
+            };
+
+            *search.search(&position, &[], limits).nodes.as_ref()
+        };
+
+        assert!(nodes(Pruning::ALL) < nodes(Pruning::NONE));
+    }
+
+    #[test_case(3, 3; "three lines")]
+    #[test_case(20, 10; "more lines than moves")]
+    fn finds_multiple_lines(multipv: usize, expected_lines: usize) {
+        let position = fen!("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+        let mut search = Search::new(1);
+        search.set_multipv(multipv);
+        let limits = SearchLimits {
+            depth: Some(PlyCount::new(5)),
+            ..SearchLimits::default()
+        };
+        let mut lines = Vec::new();
+        let result = search.search_with_progress(&position, &[], limits, |line| {
+            if line.depth == PlyCount::new(5) {
+                lines.push(line.clone());
+            }
+        });
+
+        assert_eq!(lines.len(), expected_lines);
+        assert_eq!(result.principal_variation, lines[0].principal_variation);
+        assert_eq!(result.best_move.unwrap().to_string(), "d1d5");
+        for (index, line) in lines.iter().enumerate() {
+            assert_eq!(line.multipv, index + 1);
+            assert!(lines[..index]
+                .iter()
+                .all(|better| better.best_move != line.best_move && better.score >= line.score));
+        }
+    }
+
+    #[test]
+    fn searches_only_the_search_moves() {
+        let position = fen!("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+        let search_moves = ["e1e2", "e1f1"]
+            .map(|written| position.parse_uci(written, false).unwrap())
+            .to_vec();
+        let result = Search::new(1).search(
+            &position,
+            &[],
+            SearchLimits {
+                depth: Some(PlyCount::new(3)),
+                search_moves: search_moves.clone(),
+                ..SearchLimits::default()
+            },
+        );
+
+        assert!(search_moves.contains(&result.best_move.unwrap()));
+        assert!(result.score < Score::Approximate(PawnEvaluation::new(0.0)));
+    }
+
+    #[test]
+    fn searches_only_the_best_tablebase_moves() {
+        // Only the mate in two (Rd1 then Rd8) wins before the fifty-move rule draws the game
+        let position = fen!("2k5/8/1K6/8/8/8/8/4R3 w - - 96 80");
+        let mut search = Search::new(1);
+        search.set_tablebase(Some(Arc::new(SyzygyTablebase::open(FIXTURES).unwrap())));
+        let result = search.search(
+            &position,
+            &[],
+            SearchLimits {
+                depth: Some(PlyCount::new(1)),
+                ..SearchLimits::default()
+            },
+        );
+
+        assert_eq!(result.best_move.unwrap().to_string(), "e1d1");
+    }
+
+    #[test_case("k7/8/2K5/8/8/8/8/7R w - - 0 1", 2, 3; "mate in two")]
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3, 1; "shorter mate")]
+    fn finds_mates_in_mate_mode(fen: &str, moves: u8, expected_plies: u8) {
+        let result = Search::new(1).search(
+            &fen!(fen),
+            &[],
+            SearchLimits {
+                mate: Some(moves),
+                ..SearchLimits::default()
+            },
+        );
+
+        assert_eq!(result.score, Score::Mating(PlyCount::new(expected_plies)));
+        assert!(result.depth <= PlyCount::new(2 * moves - 1));
+    }
+
+    #[test]
+    fn avoids_repeating_positions_played_before_the_root() {
+        // Kg6 is the only mate in two, but the position after it was already played before the root
+        let mut game = Game::from_fen("7k/R7/5K2/8/8/8/8/8 w - - 0 1").unwrap();
+        for written in ["f6g6", "h8g8", "g6f6", "g8h8"] {
+            let chess_move = game.position().parse_uci(written, false).unwrap();
+            game.push(chess_move).unwrap();
+        }
+        let limits = SearchLimits {
+            depth: Some(PlyCount::new(5)),
+            ..SearchLimits::default()
+        };
+
+        let without_history = Search::new(1).search(game.position(), &[], limits.clone());
+        assert_eq!(without_history.best_move.unwrap().to_string(), "f6g6");
+        assert_eq!(without_history.score, Score::Mating(PlyCount::new(3)));
+
+        let with_history = Search::new(1).search(game.position(), game.history(), limits);
+        assert_ne!(with_history.best_move.unwrap().to_string(), "f6g6");
+        assert!(with_history.score > Score::Approximate(PawnEvaluation::new(0.0)));
+    }
+
+    #[test]
+    fn mate_mode_stops_without_a_mate() {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let result = Search::new(1).search(
+            &position,
+            &[],
+            SearchLimits {
+                mate: Some(2),
+                ..SearchLimits::default()
+            },
+        );
+
+        assert_eq!(result.depth, PlyCount::new(3));
+        assert!(!matches!(result.score, Score::Mating(_)));
+    }
+
+    #[test_case(1)]
+    #[test_case(1000)]
+    #[test_case(12345)]
+    fn searches_exactly_the_node_limit(nodes: u64) {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let search = || {
+            Search::new(1).search(
+                &position,
+                &[],
+                SearchLimits {
+                    nodes: Some(NodeCount::new(nodes)),
+                    ..SearchLimits::default()
+                },
+            )
+        };
+        let first = search();
+        let second = search();
+
+        assert_eq!(first.nodes, NodeCount::new(nodes));
+        assert_eq!(first.best_move, second.best_move);
+        assert_eq!(first.principal_variation, second.principal_variation);
+        assert_eq!(first.nodes, second.nodes);
+    }
+
+    #[test]
+    fn fixed_depth_searches_are_reproducible() {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let limits = SearchLimits {
+            depth: Some(PlyCount::new(5)),
+            ..SearchLimits::default()
+        };
+        let mut search = Search::new(1);
+        let first = search.search(&position, &[], limits.clone());
+        search.clear();
+        let second = search.search(&position, &[], limits);
+
+        assert_eq!(first.nodes, second.nodes);
+        assert_eq!(first.principal_variation, second.principal_variation);
+    }
+
+    #[test_case("rnb1kbnr/pppp1ppp/8/4p1q1/3P4/2N5/PPP1PPPP/R1BQKBNR w KQkq - 0 3", "c1g5"; "free queen")]
+    #[test_case("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", "d1d5"; "undefended queen")]
+    fn wins_material(fen: &str, expected_move: &str) {
+        assert_eq!(search(fen, 3).0, expected_move);
+    }
+
+    #[test]
+    fn scores_stalemate() {
+        let position = fen!("7k/8/6QK/8/8/8/8/8 b - - 0 1");
+        let result = Search::default().search(&position, &[], SearchLimits::default());
+
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, Score::Stalemate);
+    }
+
+    #[test]
+    fn stops_at_the_movetime() {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let result = Search::default().search(
+            &position,
+            &[],
+            SearchLimits {
+                movetime: Some(std::time::Duration::from_millis(50)),
+                ..SearchLimits::default()
+            },
+        );
+
+        assert!(result.best_move.is_some());
+        assert!(result.elapsed < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn stops_on_the_stop_signal() {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let mut search = Search::default();
+        search.set_threads(2);
+        let stop_signal = search.stop_signal();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            stop_signal.stop();
+        });
+        let result = search.search(&position, &[], SearchLimits::default());
+        stopper.join().unwrap();
+
+        assert!(result.best_move.is_some());
+        assert!(result.depth < crate::search::MAX_DEPTH);
+        assert!(*result.nodes.as_ref() > 0);
+    }
+
+    #[test]
+    fn pondering_ignores_the_time_until_hit() {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let clock = std::sync::Arc::new(crate::search::ManualClock::default());
+        let mut search = Search::default();
+        search.set_clock(clock.clone());
+        let ponder_signal = search.ponder_signal();
+        ponder_signal.start();
+        // Already out of time, so only pondering keeps the search going
+        clock.advance(std::time::Duration::from_secs(1));
+        let limits = SearchLimits {
+            movetime: Some(std::time::Duration::from_millis(100)),
+            ..SearchLimits::default()
+        };
+
+        let pondering = std::thread::spawn(move || search.search(&position, &[], limits));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(!pondering.is_finished());
+
+        ponder_signal.hit();
+        clock.advance(std::time::Duration::from_secs(1));
+        let result = pondering.join().unwrap();
+        assert!(result.best_move.is_some());
+        assert!(result.depth < crate::search::MAX_DEPTH);
+    }
+
+    #[test]
+    fn plays_forced_moves_immediately() {
+        let position = fen!("k7/8/8/8/8/8/8/KR6 b - - 0 1");
+        let mut search = Search::default();
+        search.set_clock(std::sync::Arc::new(crate::search::ManualClock::default()));
+        let mut limits = SearchLimits::default();
+        limits.time_left[crate::player_color::PlayerColor::Black] =
+            Some(std::time::Duration::from_mins(1));
+        let result = search.search(&position, &[], limits);
+
+        assert_eq!(result.best_move.unwrap().to_string(), "a8a7");
+        assert_eq!(result.depth, PlyCount::new(1));
+    }
+
+    #[cfg(feature = "nnue")]
+    #[test]
+    fn searches_with_the_network() {
+        use crate::nnue::{random_network_bytes, Network};
+        use alloc::sync::Arc;
+
+        let network = Arc::new(Network::from_bytes(&random_network_bytes(8)).unwrap());
+        let position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let mut search = Search::new(1);
+        search.set_network(Some(Arc::clone(&network)));
+        let result = search.search(
+            &position,
+            &[],
+            SearchLimits {
+                depth: Some(PlyCount::new(1)),
+                ..SearchLimits::default()
+            },
+        );
+
+        // Without captures the replies are only statically evaluated, by the network
+        let mut networked = position.clone();
+        networked.set_network(network);
+        let expected = networked
+            .generate_legal_moves()
+            .into_iter()
+            .map(|chess_move| {
+                let mut child = networked.clone();
+                child.make_move(chess_move);
+                -child.evaluate()
+            })
+            .max_by(|a, b| a.partial_cmp(b).unwrap())
+            .unwrap();
+        assert_eq!(result.score, Score::Approximate(expected));
+    }
+}
@@ -0,0 +1,257 @@
+use crate::chess_move::ChessMove;
+use crate::pieces::PieceType;
+use crate::player_color::PlayerColor;
+use crate::ply_count::PlyCount;
+use crate::search::search_stack::SearchStack;
+use alloc::vec::Vec;
+
+/// The rough value (in pawns) of a piece for ordering captures
+const fn piece_value(piece: PieceType) -> i32 {
+    match piece {
+        PieceType::Pawn => 1,
+        PieceType::Knight | PieceType::Bishop => 3,
+        PieceType::Rook => 5,
+        PieceType::Queen => 9,
+        PieceType::King => 0,
+    }
+}
+
+/// Whether or not a move is a capture or a promotion, rather than a quiet move
+const fn is_tactical(chess_move: ChessMove) -> bool {
+    chess_move.is_capture() || chess_move.promotion_piece().is_some()
+}
+
+/// Score a capture or promotion, the most valuable victim captured by the least valuable attacker first, then the
+/// most valuable promotion
+fn tactical_score(chess_move: ChessMove) -> i32 {
+    let victim = match chess_move {
+        ChessMove::Capture(capture) => Some(PieceType::from(capture.captured_piece())),
+        ChessMove::PromotingCapture(capture) => Some(PieceType::from(capture.captured_piece())),
+        ChessMove::EnPassantCapture(_) => Some(PieceType::Pawn),
+        _ => None,
+    };
+    let capture = victim.map_or(0, |victim| {
+        100 + 10 * piece_value(victim) - piece_value(chess_move.moved_piece())
+    });
+    let promotion = chess_move
+        .promotion_piece()
+        .map_or(0, |piece| 10 * piece_value(PieceType::from(piece)));
+
+    capture + promotion
+}
+
+/// Take the highest scored move out of a list
+fn take_best(scored: &mut Vec<(ChessMove, i32)>) -> Option<ChessMove> {
+    let best = scored
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, (_, score))| *score)
+        .map(|(index, _)| index)?;
+
+    Some(scored.swap_remove(best).0)
+}
+
+/// The stages a [`MovePicker`] goes through, in order
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Stage {
+    TableMove,
+    ScoreTactical,
+    Tactical,
+    Killer(usize),
+    Countermove,
+    ScoreQuiets,
+    Quiets,
+}
+
+/// Picks the legal moves of a position one at a time, in the order they should be searched
+///
+/// Each group of moves is only scored once the earlier ones have all been searched, so a cutoff saves the work of
+/// ordering the rest.
+///
+/// The stages are:
+/// 1. The move from the transposition table
+/// 2. Captures and promotions, the most valuable victim captured by the least valuable attacker first
+/// 3. The two killer moves of the ply
+/// 4. The countermove to the previous move
+/// 5. The remaining quiet moves, by their history scores
+#[derive(Debug)]
+pub struct MovePicker {
+    /// The moves not yet picked or scored
+    moves: Vec<ChessMove>,
+    /// The moves of the current stage not yet picked, with their scores
+    scored: Vec<(ChessMove, i32)>,
+    stage: Stage,
+    table_move: Option<ChessMove>,
+    ply: PlyCount,
+    player: PlayerColor,
+}
+
+impl MovePicker {
+    /// Pick from the legal moves of a position at a ply, with the player to move and the move from the
+    /// transposition table
+    #[must_use]
+    pub const fn new(
+        moves: Vec<ChessMove>,
+        table_move: Option<ChessMove>,
+        ply: PlyCount,
+        player: PlayerColor,
+    ) -> Self {
+        Self {
+            moves,
+            scored: Vec::new(),
+            stage: Stage::TableMove,
+            table_move,
+            ply,
+            player,
+        }
+    }
+
+    /// Take a move out of those not yet picked, if it's there
+    fn take(&mut self, chess_move: Option<ChessMove>) -> Option<ChessMove> {
+        let index = self
+            .moves
+            .iter()
+            .position(|&candidate| Some(candidate) == chess_move)?;
+
+        Some(self.moves.swap_remove(index))
+    }
+
+    /// Pick the next move to search, using the killers, countermoves and history learned so far by the search
+    pub fn next(&mut self, stack: &SearchStack) -> Option<ChessMove> {
+        loop {
+            let picked = match self.stage {
+                Stage::TableMove => {
+                    self.stage = Stage::ScoreTactical;
+                    self.take(self.table_move)
+                }
+                Stage::ScoreTactical => {
+                    self.stage = Stage::Tactical;
+                    let (tactical, quiet) = self
+                        .moves
+                        .iter()
+                        .partition::<Vec<_>, _>(|&&chess_move| is_tactical(chess_move));
+                    self.scored = tactical
+                        .into_iter()
+                        .map(|chess_move| (chess_move, tactical_score(chess_move)))
+                        .collect();
+                    self.moves = quiet;
+                    None
+                }
+                Stage::Tactical => {
+                    let picked = take_best(&mut self.scored);
+                    if picked.is_none() {
+                        self.stage = Stage::Killer(0);
+                    }
+                    picked
+                }
+                Stage::Killer(index) => {
+                    let killers = stack[self.ply].killers;
+                    self.stage = if index + 1 < killers.len() {
+                        Stage::Killer(index + 1)
+                    } else {
+                        Stage::Countermove
+                    };
+                    self.take(killers[index])
+                }
+                Stage::Countermove => {
+                    self.stage = Stage::ScoreQuiets;
+                    self.take(stack.countermove(self.ply))
+                }
+                Stage::ScoreQuiets => {
+                    self.stage = Stage::Quiets;
+                    let (ply, player) = (self.ply, self.player);
+                    self.scored = self
+                        .moves
+                        .drain(..)
+                        .map(|chess_move| (chess_move, stack.quiet_score(ply, player, chess_move)))
+                        .collect();
+                    None
+                }
+                Stage::Quiets => return take_best(&mut self.scored),
+            };
+
+            if picked.is_some() {
+                return picked;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chess_move::ChessMove;
+    use crate::fen;
+    use crate::pieces::PieceType;
+    use crate::ply_count::PlyCount;
+    use crate::search::move_picker::{is_tactical, MovePicker};
+    use crate::search::search_stack::{PlayedMove, SearchStack};
+    use crate::square::Square;
+    use alloc::vec::Vec;
+
+    /// Pick every move of a position
+    fn pick_all(picker: &mut MovePicker, stack: &SearchStack) -> Vec<ChessMove> {
+        core::iter::from_fn(|| picker.next(stack)).collect()
+    }
+
+    #[test]
+    fn picks_every_move_once() {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let moves = position.generate_legal_moves();
+        let stack = SearchStack::default();
+        let mut picker = MovePicker::new(
+            moves.clone(),
+            moves.last().copied(),
+            PlyCount::new(0),
+            position.player_to_move(),
+        );
+
+        let mut order = pick_all(&mut picker, &stack);
+        assert_eq!(order.first(), moves.last());
+        let mut expected = moves;
+        order.sort_by_key(ToString::to_string);
+        expected.sort_by_key(ToString::to_string);
+        assert_eq!(order, expected);
+    }
+
+    #[test]
+    fn picks_in_stages() {
+        let position = fen!("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let player = position.player_to_move();
+        let ply = PlyCount::new(1);
+        let moves = position.generate_legal_moves();
+        let mut quiets = moves
+            .iter()
+            .copied()
+            .filter(|&chess_move| !is_tactical(chess_move));
+        let (killer, countermove, history, failed) = (
+            quiets.next().unwrap(),
+            quiets.next().unwrap(),
+            quiets.next().unwrap(),
+            quiets.next().unwrap(),
+        );
+        let mut stack = SearchStack::default();
+        stack[PlyCount::new(0)].played = Some(PlayedMove {
+            player: player.switch(),
+            piece: PieceType::Pawn,
+            to: Square::H3,
+        });
+        stack.update_quiet_cutoff(ply, PlyCount::new(2), player, history, &[failed]);
+        stack.update_quiet_cutoff(ply, PlyCount::new(2), player, countermove, &[]);
+        stack[ply].killers = [Some(killer), None];
+
+        let mut picker = MovePicker::new(moves, None, ply, player);
+        let order = pick_all(&mut picker, &stack);
+        let captures = order
+            .iter()
+            .take_while(|&&chess_move| is_tactical(chess_move))
+            .count();
+        assert!(order[captures..]
+            .iter()
+            .all(|&chess_move| !is_tactical(chess_move)));
+        assert_eq!(
+            order[captures..captures + 3],
+            [killer, countermove, history]
+        );
+        assert_eq!(order.last(), Some(&failed));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::evaluation::PawnEvaluation;
+use crate::ply_count::PlyCount;
+use once_cell::sync::Lazy;
+
+/// The shallowest depth to try a null move at
+pub const NULL_MOVE_MIN_DEPTH: u8 = 3;
+/// How many plies shallower than a normal move a null move is always searched
+pub const NULL_MOVE_REDUCTION: u8 = 3;
+/// The deepest depth reverse futility pruning is applied at
+pub const REVERSE_FUTILITY_MAX_DEPTH: u8 = 6;
+/// How far (per ply of depth) the static evaluation has to be above beta for reverse futility pruning
+pub const REVERSE_FUTILITY_MARGIN: PawnEvaluation = PawnEvaluation(0.75);
+/// The deepest depth futility pruning is applied at
+pub const FUTILITY_MAX_DEPTH: u8 = 3;
+/// How far (per ply of depth) the static evaluation has to be below alpha for futility pruning
+pub const FUTILITY_MARGIN: PawnEvaluation = PawnEvaluation(1.0);
+/// The deepest depth razoring is applied at, any deeper and the captures alone miss too many quiet attacks
+pub const RAZORING_MAX_DEPTH: u8 = 1;
+/// How far (per ply of depth) the static evaluation has to be below alpha to razor
+pub const RAZORING_MARGIN: PawnEvaluation = PawnEvaluation(2.5);
+/// The deepest depth late move pruning is applied at
+pub const LATE_MOVE_PRUNING_MAX_DEPTH: u8 = 4;
+/// The shallowest depth late moves are reduced at
+pub const LATE_MOVE_REDUCTION_MIN_DEPTH: u8 = 3;
+/// How many moves are searched at full depth before later moves are reduced
+pub const LATE_MOVE_REDUCTION_MIN_MOVES: usize = 3;
+
+/// The depths and move counts covered by the [late move reduction](late_move_reduction) table
+const REDUCTION_TABLE_SIZE: usize = 64;
+
+/// The late move reduction table, indexed by depth and the number of moves already searched
+static REDUCTIONS: Lazy<[[u8; REDUCTION_TABLE_SIZE]; REDUCTION_TABLE_SIZE]> = Lazy::new(|| {
+    let mut reductions = [[0; REDUCTION_TABLE_SIZE]; REDUCTION_TABLE_SIZE];
+    for (depth, row) in reductions.iter_mut().enumerate().skip(1) {
+        for (moves, reduction) in row.iter_mut().enumerate().skip(1) {
+            let depth = f64::from(u8::try_from(depth).unwrap_or(u8::MAX));
+            let moves = f64::from(u8::try_from(moves).unwrap_or(u8::MAX));
+            // Truncating to whole plies, the table only covers small positive values
+            *reduction = depth.ln().mul_add(moves.ln() / 2.25, 0.75) as u8;
+        }
+    }
+
+    reductions
+});
+
+/// Which selectivity techniques a search uses, each can be switched off to measure (with an SPRT) what it gains
+///
+/// All are enabled by default.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Pruning {
+    /// Skip positions where passing the turn still fails high, unless the player to move has only pawns (where
+    /// zugzwang makes passing better than any move)
+    pub null_move: bool,
+    /// Search moves late in the ordering to a shallower depth, by a [log based amount](late_move_reduction)
+    pub late_move_reductions: bool,
+    /// Skip shallow positions whose static evaluation is far enough above beta
+    pub reverse_futility: bool,
+    /// Skip quiet moves in shallow positions whose static evaluation is far enough below alpha
+    pub futility: bool,
+    /// Drop straight into the quiescence search in shallow positions whose static evaluation is far below alpha,
+    /// unless a quiet move gives check
+    pub razoring: bool,
+    /// Skip quiet moves after a depth dependent number have been searched in shallow positions
+    pub late_move_pruning: bool,
+}
+
+impl Pruning {
+    /// Every technique enabled
+    pub const ALL: Self = Self {
+        null_move: true,
+        late_move_reductions: true,
+        reverse_futility: true,
+        futility: true,
+        razoring: true,
+        late_move_pruning: true,
+    };
+    /// Every technique disabled, searching every move to its full depth
+    pub const NONE: Self = Self {
+        null_move: false,
+        late_move_reductions: false,
+        reverse_futility: false,
+        futility: false,
+        razoring: false,
+        late_move_pruning: false,
+    };
+}
+
+impl Default for Pruning {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// How many plies to reduce a late move by, growing with the logarithm of both the depth and the number of moves
+/// already searched
+///
+/// ```
+/// use thermite_core::ply_count::PlyCount;
+/// use thermite_core::search::late_move_reduction;
+///
+/// assert_eq!(late_move_reduction(PlyCount::new(3), 3), 1);
+/// assert_eq!(late_move_reduction(PlyCount::new(12), 30), 4);
+/// ```
+#[must_use]
+pub fn late_move_reduction(depth: PlyCount, moves_searched: usize) -> u8 {
+    let depth = usize::from(depth.0).min(REDUCTION_TABLE_SIZE - 1);
+    let moves_searched = moves_searched.min(REDUCTION_TABLE_SIZE - 1);
+
+    REDUCTIONS[depth][moves_searched]
+}
+
+/// How many quiet moves are searched in a shallow position before the rest are skipped by late move pruning
+#[must_use]
+pub const fn late_move_pruning_count(depth: PlyCount) -> usize {
+    let depth = depth.0 as usize;
+
+    3 + depth * depth
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ply_count::PlyCount;
+    use crate::search::pruning::{late_move_pruning_count, late_move_reduction};
+    use test_case::test_case;
+
+    #[test_case(1, 1, 0)]
+    #[test_case(3, 1, 0)]
+    #[test_case(3, 3, 1)]
+    #[test_case(6, 10, 2)]
+    #[test_case(20, 40, 5)]
+    #[test_case(255, 255, 8)]
+    fn late_move_reduction_works(depth: u8, moves_searched: usize, expected: u8) {
+        assert_eq!(
+            late_move_reduction(PlyCount::new(depth), moves_searched),
+            expected
+        );
+    }
+
+    #[test]
+    fn late_move_reduction_grows_with_depth_and_moves() {
+        for depth in 1..63 {
+            for moves_searched in 1..63 {
+                let reduction = late_move_reduction(PlyCount::new(depth), moves_searched);
+                assert!(late_move_reduction(PlyCount::new(depth + 1), moves_searched) >= reduction);
+                assert!(late_move_reduction(PlyCount::new(depth), moves_searched + 1) >= reduction);
+            }
+        }
+    }
+
+    #[test_case(1, 4)]
+    #[test_case(2, 7)]
+    #[test_case(4, 19)]
+    fn late_move_pruning_count_works(depth: u8, expected: usize) {
+        assert_eq!(late_move_pruning_count(PlyCount::new(depth)), expected);
+    }
+}
@@ -0,0 +1,281 @@
+use crate::chess_move::ChessMove;
+use crate::pieces::PieceType;
+use crate::player_color::PlayerColor;
+use crate::ply_count::PlyCount;
+use crate::square::Square;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ops::{Index, IndexMut};
+use enum_map::EnumMap;
+
+/// The bound every history score is kept within, gravity pulls scores back as they approach it
+pub const MAX_HISTORY: i32 = 16384;
+/// The largest bonus (or malus) a single cutoff applies to a history score
+const MAX_HISTORY_BONUS: i32 = 1536;
+
+/// A history score of a quiet move by the squares it moves between, for each player
+type ButterflyHistory = EnumMap<PlayerColor, EnumMap<Square, EnumMap<Square, i16>>>;
+/// A history score of a quiet move by the piece moved and the square it moves to
+type PieceToHistory = EnumMap<PieceType, EnumMap<Square, i16>>;
+/// A table of [piece to](PieceToHistory) scores following each previous move, for each player
+type ContinuationHistory =
+    EnumMap<PlayerColor, EnumMap<PieceType, EnumMap<Square, Box<PieceToHistory>>>>;
+/// The move that refuted each previous move, for each player
+type Countermoves = EnumMap<PlayerColor, EnumMap<PieceType, EnumMap<Square, Option<ChessMove>>>>;
+
+/// How much a quiet move's history changes when it causes (or fails to cause) a cutoff at a depth
+///
+/// ```
+/// use thermite_core::ply_count::PlyCount;
+/// use thermite_core::search::history_bonus;
+///
+/// assert_eq!(history_bonus(PlyCount::new(2)), 128);
+/// assert_eq!(history_bonus(PlyCount::new(30)), 1536);
+/// ```
+#[must_use]
+pub fn history_bonus(depth: PlyCount) -> i32 {
+    let depth = i32::from(depth.0);
+
+    (16 * depth * depth + 32 * depth).min(MAX_HISTORY_BONUS)
+}
+
+/// Apply a bonus (or a negative malus) to a history score with gravity, so the closer the score already is to
+/// [`MAX_HISTORY`] the less it moves towards it, keeping it within the bound
+fn apply_gravity(score: &mut i16, bonus: i32) {
+    let bonus = bonus.clamp(-MAX_HISTORY, MAX_HISTORY);
+    let current = i32::from(*score);
+    let updated = current + bonus - current * bonus.abs() / MAX_HISTORY;
+    *score = i16::try_from(updated.clamp(-MAX_HISTORY, MAX_HISTORY)).unwrap_or_default();
+}
+
+/// A move made during the search, as remembered by the heuristics that follow up on it
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PlayedMove {
+    /// The player who made the move
+    pub player: PlayerColor,
+    /// The piece that moved
+    pub piece: PieceType,
+    /// The square the piece moved to
+    pub to: Square,
+}
+
+impl PlayedMove {
+    /// Remember a move made by a player
+    #[must_use]
+    pub fn new(player: PlayerColor, chess_move: ChessMove) -> Self {
+        Self {
+            player,
+            piece: chess_move.moved_piece(),
+            to: chess_move.to_square(),
+        }
+    }
+}
+
+/// What the search remembers about a single ply of the line it's searching
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Frame {
+    /// The last two quiet moves to cause a cutoff at this ply, the most recent first
+    pub killers: [Option<ChessMove>; 2],
+    /// The move made from the position at this ply, if it wasn't a null move
+    pub played: Option<PlayedMove>,
+}
+
+/// The per thread state of a search, remembering which quiet moves caused cutoffs to search them earlier next time
+///
+/// Holds a [`Frame`] for each ply of the line being searched (indexed by [`PlyCount`]) with its killer moves, along
+/// with history tables scoring quiet moves by how often they've caused cutoffs. Every history score is updated with
+/// gravity, so it stays within [`MAX_HISTORY`] and recent results outweigh old ones.
+#[derive(Debug)]
+pub struct SearchStack {
+    frames: Vec<Frame>,
+    butterfly: Box<ButterflyHistory>,
+    continuation: Box<ContinuationHistory>,
+    countermoves: Box<Countermoves>,
+}
+
+impl Default for SearchStack {
+    fn default() -> Self {
+        Self {
+            frames: alloc::vec![Frame::default(); usize::from(u8::MAX) + 1],
+            butterfly: Box::default(),
+            continuation: Box::default(),
+            countermoves: Box::default(),
+        }
+    }
+}
+
+impl Index<PlyCount> for SearchStack {
+    type Output = Frame;
+
+    fn index(&self, ply: PlyCount) -> &Self::Output {
+        &self.frames[usize::from(ply.0)]
+    }
+}
+
+impl IndexMut<PlyCount> for SearchStack {
+    fn index_mut(&mut self, ply: PlyCount) -> &mut Self::Output {
+        &mut self.frames[usize::from(ply.0)]
+    }
+}
+
+impl SearchStack {
+    /// Prepare for searching a new position, forgetting the killer moves which only apply to the last one
+    pub fn new_search(&mut self) {
+        self.frames.fill(Frame::default());
+    }
+
+    /// Forget everything learned, as for a new game
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The move made before the position at a ply, if any
+    #[must_use]
+    pub fn previous_move(&self, ply: PlyCount) -> Option<PlayedMove> {
+        let previous = ply.0.checked_sub(1)?;
+
+        self[PlyCount(previous)].played
+    }
+
+    /// The move that last refuted the move made before the position at a ply
+    #[must_use]
+    pub fn countermove(&self, ply: PlyCount) -> Option<ChessMove> {
+        self.previous_move(ply)
+            .and_then(|previous| self.countermoves[previous.player][previous.piece][previous.to])
+    }
+
+    /// Score a quiet move by a player at a ply by its butterfly history and its continuation history following the
+    /// previous move
+    #[must_use]
+    pub fn quiet_score(&self, ply: PlyCount, player: PlayerColor, chess_move: ChessMove) -> i32 {
+        let to = chess_move.to_square();
+        let butterfly = self.butterfly[player][chess_move.from_square()][to];
+        let continuation = self.previous_move(ply).map_or(0, |previous| {
+            self.continuation[previous.player][previous.piece][previous.to]
+                [chess_move.moved_piece()][to]
+        });
+
+        i32::from(butterfly) + i32::from(continuation)
+    }
+
+    /// Learn from a quiet move by a player causing a cutoff at a ply, after the other quiet moves searched before it
+    /// failed to
+    ///
+    /// The move becomes the first killer and the countermove to the previous move, and its history scores get a
+    /// bonus while the moves that failed get a malus.
+    pub fn update_quiet_cutoff(
+        &mut self,
+        ply: PlyCount,
+        depth: PlyCount,
+        player: PlayerColor,
+        cutoff: ChessMove,
+        failed: &[ChessMove],
+    ) {
+        let killers = &mut self[ply].killers;
+        if killers[0] != Some(cutoff) {
+            killers[1] = killers[0];
+            killers[0] = Some(cutoff);
+        }
+
+        let previous = self.previous_move(ply);
+        if let Some(previous) = previous {
+            self.countermoves[previous.player][previous.piece][previous.to] = Some(cutoff);
+        }
+
+        let bonus = history_bonus(depth);
+        let updates = core::iter::once((cutoff, bonus))
+            .chain(failed.iter().map(|&chess_move| (chess_move, -bonus)));
+        for (chess_move, bonus) in updates {
+            let to = chess_move.to_square();
+            apply_gravity(
+                &mut self.butterfly[player][chess_move.from_square()][to],
+                bonus,
+            );
+            if let Some(previous) = previous {
+                apply_gravity(
+                    &mut self.continuation[previous.player][previous.piece][previous.to]
+                        [chess_move.moved_piece()][to],
+                    bonus,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chess_move::quiet::Quiet;
+    use crate::chess_move::ChessMove;
+    use crate::pieces::{Piece, PieceType};
+    use crate::player_color::PlayerColor;
+    use crate::ply_count::PlyCount;
+    use crate::search::search_stack::{apply_gravity, PlayedMove, SearchStack, MAX_HISTORY};
+    use crate::square::Square;
+    use test_case::test_case;
+
+    fn quiet(piece: PieceType, from: Square, to: Square) -> ChessMove {
+        ChessMove::Quiet(Quiet::new(from, to, piece.owned_by(PlayerColor::White)).unwrap())
+    }
+
+    #[test_case(0, 1000, 1000; "bonus from zero")]
+    #[test_case(8192, 1000, 8692; "bonus")]
+    #[test_case(8192, -1000, 6692; "malus")]
+    #[test_case(16384, 1000, 16384; "bonus at the bound")]
+    #[test_case(-16384, -1000, -16384; "malus at the bound")]
+    fn apply_gravity_works(score: i16, bonus: i32, expected: i16) {
+        let mut score = score;
+        apply_gravity(&mut score, bonus);
+        assert_eq!(score, expected);
+    }
+
+    #[test]
+    fn repeated_bonuses_stay_within_bounds() {
+        let mut score = 0;
+        for _ in 0..1000 {
+            apply_gravity(&mut score, 1536);
+            assert!(i32::from(score) <= MAX_HISTORY);
+        }
+        assert!(i32::from(score) > MAX_HISTORY * 9 / 10);
+    }
+
+    #[test]
+    fn cutoffs_update_killers_countermoves_and_history() {
+        let mut stack = SearchStack::default();
+        let ply = PlyCount::new(3);
+        let depth = PlyCount::new(4);
+        let previous = PlayedMove {
+            player: PlayerColor::Black,
+            piece: PieceType::Knight,
+            to: Square::F6,
+        };
+        stack[PlyCount::new(2)].played = Some(previous);
+
+        let first = quiet(PieceType::Knight, Square::G1, Square::F3);
+        let second = quiet(PieceType::Bishop, Square::F1, Square::C4);
+        let failed = quiet(PieceType::Pawn, Square::A2, Square::A3);
+        stack.update_quiet_cutoff(ply, depth, PlayerColor::White, first, &[failed]);
+        stack.update_quiet_cutoff(ply, depth, PlayerColor::White, second, &[]);
+        stack.update_quiet_cutoff(ply, depth, PlayerColor::White, second, &[]);
+
+        assert_eq!(stack[ply].killers, [Some(second), Some(first)]);
+        assert_eq!(stack.countermove(ply), Some(second));
+        assert!(stack.quiet_score(ply, PlayerColor::White, second) > 0);
+        assert!(stack.quiet_score(ply, PlayerColor::White, first) > 0);
+        assert!(stack.quiet_score(ply, PlayerColor::White, failed) < 0);
+        // Only the butterfly history applies without the previous move
+        assert!(
+            stack.quiet_score(ply, PlayerColor::White, first)
+                > stack.quiet_score(PlyCount::new(0), PlayerColor::White, first)
+        );
+
+        stack.new_search();
+        assert_eq!(stack[ply].killers, [None, None]);
+        assert!(stack.quiet_score(PlyCount::new(0), PlayerColor::White, second) > 0);
+
+        stack.clear();
+        assert_eq!(
+            stack.quiet_score(PlyCount::new(0), PlayerColor::White, second),
+            0
+        );
+    }
+}
@@ -0,0 +1,372 @@
+use crate::chess_move::ChessMove;
+use crate::evaluation::Score;
+use crate::player_color::PlayerColor;
+use crate::search::{SearchLimits, SearchResult};
+use core::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How many moves are assumed to be left in the game when the time control doesn't say
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// How many times longer than the soft limit the search may take to finish an iteration
+const HARD_LIMIT_SCALE: u32 = 3;
+/// How much (in centipawns) the score has to drop between iterations to spend more time on the move
+const SCORE_DROP_CENTIPAWNS: i32 = 25;
+
+/// A source of time for the [`TimeManager`], so it can be replaced with a [`ManualClock`] to test deterministically
+pub trait Clock: Debug + Send + Sync {
+    /// The time since a fixed point, only the differences between readings are meaningful
+    fn now(&self) -> Duration;
+}
+
+/// A [`Clock`] reading the system's monotonic time
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct SystemClock {
+    epoch: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            epoch: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.epoch.elapsed()
+    }
+}
+
+/// A [`Clock`] that only moves when it's [advanced](Self::advance)
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    nanoseconds: AtomicU64,
+}
+
+impl ManualClock {
+    /// Move the clock forwards
+    pub fn advance(&self, duration: Duration) {
+        let nanoseconds = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.nanoseconds.fetch_add(nanoseconds, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanoseconds.load(Ordering::Relaxed))
+    }
+}
+
+/// When a search should stop, turning the [limits](SearchLimits) of a time control into deadlines
+///
+/// The soft limit is checked between iterations, where starting another is unlikely to finish in time, and is
+/// stretched or shrunk by the [`Stability`] of the search. The hard limit stops the search mid-iteration.
+#[derive(Clone, Debug)]
+pub struct TimeManager {
+    clock: Arc<dyn Clock>,
+    start: Duration,
+    soft_limit: Option<Duration>,
+    hard_limit: Option<Duration>,
+}
+
+impl TimeManager {
+    /// Start timing a search for a player, keeping a move overhead in reserve for the time lost communicating moves
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use thermite_core::player_color::PlayerColor;
+    /// use thermite_core::search::{ManualClock, SearchLimits, TimeManager};
+    ///
+    /// let mut limits = SearchLimits::default();
+    /// limits.time_left[PlayerColor::White] = Some(Duration::from_secs(60));
+    /// let time = TimeManager::new(&limits, PlayerColor::White, Duration::ZERO, Arc::new(ManualClock::default()));
+    ///
+    /// assert_eq!(time.soft_limit(), Some(Duration::from_secs(2)));
+    /// assert_eq!(time.hard_limit(), Some(Duration::from_secs(6)));
+    /// ```
+    #[must_use]
+    pub fn new(
+        limits: &SearchLimits,
+        player: PlayerColor,
+        move_overhead: Duration,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let (soft_limit, clock_limit) =
+            limits.time_left[player].map_or((None, None), |time_left| {
+                let available = time_left.saturating_sub(move_overhead);
+                let moves_to_go = limits.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+                let soft_limit = (available / moves_to_go + limits.increment[player] * 3 / 4)
+                    .min(available * 3 / 4);
+                let hard_limit = (soft_limit * HARD_LIMIT_SCALE).min(available * 9 / 10);
+
+                (Some(soft_limit), Some(hard_limit))
+            });
+        let movetime_limit = limits
+            .movetime
+            .map(|movetime| movetime.saturating_sub(move_overhead));
+        let hard_limit = match (clock_limit, movetime_limit) {
+            (Some(clock_limit), Some(movetime_limit)) => Some(clock_limit.min(movetime_limit)),
+            (limit, None) | (None, limit) => limit,
+        };
+
+        Self {
+            start: clock.now(),
+            clock,
+            soft_limit,
+            hard_limit,
+        }
+    }
+
+    /// The time the search has taken so far
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_sub(self.start)
+    }
+
+    /// The time after which another iteration shouldn't be started, before any scaling for the stability of the search
+    #[must_use]
+    pub const fn soft_limit(&self) -> Option<Duration> {
+        self.soft_limit
+    }
+
+    /// The time by which the search must stop
+    #[must_use]
+    pub const fn hard_limit(&self) -> Option<Duration> {
+        self.hard_limit
+    }
+
+    /// Whether or not the search must stop immediately
+    #[must_use]
+    pub fn is_out_of_time(&self) -> bool {
+        self.hard_limit
+            .is_some_and(|hard_limit| self.elapsed() >= hard_limit)
+    }
+
+    /// Whether or not the search should stop between iterations rather than start another
+    #[must_use]
+    pub fn should_stop_iterating(&self, stability: &Stability) -> bool {
+        let Some(soft_limit) = self.soft_limit else {
+            return self.is_out_of_time();
+        };
+
+        stability.is_forced() || self.elapsed() >= soft_limit.mul_f64(stability.scale())
+    }
+}
+
+/// How settled the result of a search is between iterations, used to scale its [soft limit](TimeManager::soft_limit)
+///
+/// Time is saved when the best move stays the same for several iterations, and extended while the best move keeps
+/// changing or the score drops, as a deeper search is more likely to change the move played.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Stability {
+    legal_moves: usize,
+    best_move: Option<ChessMove>,
+    score: Option<Score>,
+    unchanged_iterations: u8,
+    score_dropped: bool,
+}
+
+impl Stability {
+    /// Start tracking the search of a position with a number of legal moves
+    #[must_use]
+    pub const fn new(legal_moves: usize) -> Self {
+        Self {
+            legal_moves,
+            best_move: None,
+            score: None,
+            unchanged_iterations: 0,
+            score_dropped: false,
+        }
+    }
+
+    /// Record the result of a completed iteration
+    pub fn update(&mut self, result: &SearchResult) {
+        if self.best_move.is_some() && self.best_move == result.best_move {
+            self.unchanged_iterations = self.unchanged_iterations.saturating_add(1);
+        } else {
+            self.unchanged_iterations = 0;
+        }
+        self.score_dropped = self
+            .score
+            .is_some_and(|previous| match (previous, result.score) {
+                (Score::Approximate(previous), Score::Approximate(current)) => {
+                    previous.centipawns() - current.centipawns() >= SCORE_DROP_CENTIPAWNS
+                }
+                (previous, current) => current < previous,
+            });
+        self.best_move = result.best_move;
+        self.score = Some(result.score);
+    }
+
+    /// Whether or not there's only one move to play, so searching it any deeper is pointless
+    #[must_use]
+    pub const fn is_forced(&self) -> bool {
+        self.legal_moves <= 1 && self.score.is_some()
+    }
+
+    /// How much to stretch the soft limit by
+    #[must_use]
+    pub fn scale(&self) -> f64 {
+        let stability = match self.unchanged_iterations {
+            0 => 1.6,
+            1 => 1.2,
+            2 | 3 => 1.0,
+            _ => 0.8,
+        };
+        let score = if self.score_dropped { 1.3 } else { 1.0 };
+
+        stability * score
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluation::{PawnEvaluation, Score};
+    use crate::fen;
+    use crate::player_color::PlayerColor;
+    use crate::ply_count::PlyCount;
+    use crate::search::{ManualClock, SearchLimits, SearchResult, Stability, TimeManager};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use test_case::test_case;
+
+    /// Time a search for white with a clock and no overhead
+    fn time_manager(limits: &SearchLimits, clock: Arc<ManualClock>) -> TimeManager {
+        TimeManager::new(limits, PlayerColor::White, Duration::ZERO, clock)
+    }
+
+    /// Limits for white having some milliseconds left, with an increment and moves to go
+    fn limits(time_left: u64, increment: u64, moves_to_go: Option<u32>) -> SearchLimits {
+        let mut limits = SearchLimits {
+            moves_to_go,
+            ..SearchLimits::default()
+        };
+        limits.time_left[PlayerColor::White] = Some(Duration::from_millis(time_left));
+        limits.increment[PlayerColor::White] = Duration::from_millis(increment);
+        limits.time_left[PlayerColor::Black] = Some(Duration::from_millis(1));
+
+        limits
+    }
+
+    /// The result of an iteration playing a move, by its index in the legal moves of the start position
+    fn result(move_index: usize, centipawns: i16) -> SearchResult {
+        let position = fen!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+        SearchResult {
+            best_move: position.generate_legal_moves().get(move_index).copied(),
+            score: Score::Approximate(PawnEvaluation::from_centipawns(centipawns)),
+            depth: PlyCount::new(1),
+            nodes: crate::node_count::NodeCount::default(),
+            elapsed: Duration::ZERO,
+            principal_variation: Vec::new(),
+            multipv: 1,
+        }
+    }
+
+    #[test_case(&limits(60_000, 0, None), 2_000, 6_000; "sudden death")]
+    #[test_case(&limits(60_000, 1_000, None), 2_750, 8_250; "increment")]
+    #[test_case(&limits(10_000, 0, Some(1)), 7_500, 9_000; "last move before the time control")]
+    #[test_case(&limits(40_000, 0, Some(20)), 2_000, 6_000; "moves to go")]
+    #[test_case(&limits(100, 5_000, None), 75, 90; "increment larger than the time left")]
+    fn allocates_time(limits: &SearchLimits, soft_limit: u64, hard_limit: u64) {
+        let time = time_manager(limits, Arc::default());
+
+        assert_eq!(time.soft_limit(), Some(Duration::from_millis(soft_limit)));
+        assert_eq!(time.hard_limit(), Some(Duration::from_millis(hard_limit)));
+    }
+
+    #[test]
+    fn keeps_the_move_overhead_in_reserve() {
+        let limits = SearchLimits {
+            movetime: Some(Duration::from_secs(1)),
+            ..limits(30_100, 0, Some(1))
+        };
+        let time = TimeManager::new(
+            &limits,
+            PlayerColor::White,
+            Duration::from_millis(100),
+            Arc::new(ManualClock::default()),
+        );
+
+        assert_eq!(time.soft_limit(), Some(Duration::from_millis(22_500)));
+        assert_eq!(time.hard_limit(), Some(Duration::from_millis(900)));
+    }
+
+    #[test]
+    fn stops_at_the_hard_limit() {
+        let clock = Arc::new(ManualClock::default());
+        clock.advance(Duration::from_secs(5));
+        let limits = SearchLimits {
+            movetime: Some(Duration::from_millis(100)),
+            ..SearchLimits::default()
+        };
+        let time = time_manager(&limits, clock.clone());
+
+        assert_eq!(time.soft_limit(), None);
+        clock.advance(Duration::from_millis(99));
+        assert!(!time.is_out_of_time());
+        assert!(!time.should_stop_iterating(&Stability::new(20)));
+        clock.advance(Duration::from_millis(1));
+        assert!(time.is_out_of_time());
+        assert!(time.should_stop_iterating(&Stability::new(20)));
+    }
+
+    #[test]
+    fn extends_while_the_best_move_is_unstable() {
+        let clock = Arc::new(ManualClock::default());
+        let time = time_manager(&limits(60_000, 0, None), clock.clone());
+        let mut stability = Stability::new(20);
+        stability.update(&result(0, 10));
+        stability.update(&result(1, 10));
+
+        clock.advance(Duration::from_millis(2_500));
+        assert!(!time.should_stop_iterating(&stability));
+        clock.advance(Duration::from_millis(700));
+        assert!(time.should_stop_iterating(&stability));
+    }
+
+    #[test]
+    fn extends_when_the_score_drops() {
+        let clock = Arc::new(ManualClock::default());
+        let time = time_manager(&limits(60_000, 0, None), clock.clone());
+        let mut stable = Stability::new(20);
+        let mut dropping = Stability::new(20);
+        for _ in 0..3 {
+            stable.update(&result(0, 50));
+            dropping.update(&result(0, 50));
+        }
+        dropping.update(&result(0, 0));
+        stable.update(&result(0, 45));
+
+        clock.advance(Duration::from_millis(2_100));
+        assert!(time.should_stop_iterating(&stable));
+        assert!(!time.should_stop_iterating(&dropping));
+    }
+
+    #[test]
+    fn saves_time_once_the_best_move_is_stable() {
+        let clock = Arc::new(ManualClock::default());
+        let time = time_manager(&limits(60_000, 0, None), clock.clone());
+        let mut stability = Stability::new(20);
+        for _ in 0..6 {
+            stability.update(&result(0, 10));
+        }
+
+        clock.advance(Duration::from_millis(1_700));
+        assert!(time.should_stop_iterating(&stability));
+    }
+
+    #[test]
+    fn stops_early_on_forced_moves() {
+        let time = time_manager(&limits(60_000, 0, None), Arc::default());
+        let mut stability = Stability::new(1);
+
+        assert!(!time.should_stop_iterating(&stability));
+        stability.update(&result(0, 10));
+        assert!(time.should_stop_iterating(&stability));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::chess_move::ChessMove;
+use crate::evaluation::Score;
+use crate::ply_count::PlyCount;
+use crate::zobrist::ZobristHash;
+use alloc::vec::Vec;
+use core::mem::size_of;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// How a stored score relates to the true score of its position
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Bound {
+    /// The score is exact, every move was searched inside the window
+    Exact,
+    /// The score is at most this, no move raised alpha
+    Upper,
+    /// The score is at least this, a move failed high
+    Lower,
+}
+
+/// The result of searching a position, stored to be reused when it's reached again
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TranspositionEntry {
+    hash: ZobristHash,
+    /// The [generation](TranspositionTable::new_search) of the search that stored it
+    generation: u8,
+    /// The depth the position was searched to
+    pub depth: PlyCount,
+    /// The score of the position, with mates counted from the position itself
+    pub score: Score,
+    /// How the score bounds the true score
+    pub bound: Bound,
+    /// The best (or refuting) move found, if any
+    pub best_move: Option<ChessMove>,
+}
+
+/// A fixed size hash table of [searched positions](TranspositionEntry), indexed by their [`ZobristHash`]
+///
+/// Every slot is locked separately so the table can be shared by the threads of a search.
+#[derive(Debug)]
+pub struct TranspositionTable {
+    entries: Vec<Mutex<Option<TranspositionEntry>>>,
+    /// Counts the searches using the table (wrapping), so entries left by earlier searches can be replaced first
+    generation: u8,
+}
+
+impl TranspositionTable {
+    /// Create a table using at most a number of megabytes, rounded down to a power of two number of entries
+    #[must_use]
+    pub fn new(megabytes: usize) -> Self {
+        let capacity =
+            (megabytes * 1024 * 1024 / size_of::<Mutex<Option<TranspositionEntry>>>()).max(1);
+        let capacity = 1 << capacity.ilog2();
+
+        Self {
+            entries: (0..capacity).map(|_| Mutex::new(None)).collect(),
+            generation: 0,
+        }
+    }
+
+    /// Start a new search, making every entry stored so far replaceable by its results
+    pub const fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Lock the slot for a hash
+    fn slot(&self, hash: ZobristHash) -> MutexGuard<'_, Option<TranspositionEntry>> {
+        let index = (*hash.as_ref() as usize) & (self.entries.len() - 1);
+
+        // Entries are written whole, so one from a panicked thread is still valid
+        self.entries[index]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the entry stored for a position, if it hasn't been replaced
+    #[must_use]
+    pub fn probe(&self, hash: ZobristHash) -> Option<TranspositionEntry> {
+        self.slot(hash).filter(|entry| entry.hash == hash)
+    }
+
+    /// Store the result of searching a position
+    ///
+    /// Results for the same position are always replaced, as are those left by an earlier search, while another
+    /// position's from the current search are only replaced by a search at least as deep.
+    pub fn store(
+        &self,
+        hash: ZobristHash,
+        depth: PlyCount,
+        score: Score,
+        bound: Bound,
+        best_move: Option<ChessMove>,
+    ) {
+        let mut slot = self.slot(hash);
+        if slot.is_some_and(|existing| {
+            existing.hash != hash
+                && existing.generation == self.generation
+                && existing.depth > depth
+        }) {
+            return;
+        }
+
+        // Keep the position's previous best move when failing low, which doesn't find one
+        let best_move = best_move.or_else(|| {
+            slot.filter(|existing| existing.hash == hash)
+                .and_then(|existing| existing.best_move)
+        });
+        *slot = Some(TranspositionEntry {
+            hash,
+            generation: self.generation,
+            depth,
+            score,
+            bound,
+            best_move,
+        });
+    }
+
+    /// Remove every entry, as for a new game
+    pub fn clear(&mut self) {
+        for entry in &mut self.entries {
+            *entry.get_mut().unwrap_or_else(PoisonError::into_inner) = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluation::{PawnEvaluation, Score};
+    use crate::fen;
+    use crate::ply_count::PlyCount;
+    use crate::search::{Bound, TranspositionTable};
+    use core::hash::Hasher;
+
+    #[test]
+    fn stores_and_probes() {
+        let mut table = TranspositionTable::new(1);
+        let position = fen!("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        let best_move = position.generate_legal_moves()[0];
+        let score = Score::Approximate(PawnEvaluation::new(1.0));
+
+        assert_eq!(table.probe(position.hash()), None);
+        table.store(
+            position.hash(),
+            PlyCount::new(3),
+            score,
+            Bound::Exact,
+            Some(best_move),
+        );
+        let entry = table.probe(position.hash()).unwrap();
+        assert_eq!(entry.depth, PlyCount::new(3));
+        assert_eq!(entry.score, score);
+        assert_eq!(entry.best_move, Some(best_move));
+
+        table.store(
+            position.hash(),
+            PlyCount::new(1),
+            Score::Stalemate,
+            Bound::Upper,
+            None,
+        );
+        let entry = table.probe(position.hash()).unwrap();
+        assert_eq!(entry.bound, Bound::Upper);
+        assert_eq!(entry.best_move, Some(best_move));
+
+        table.clear();
+        assert_eq!(table.probe(position.hash()), None);
+    }
+
+    #[test]
+    fn replaces_other_positions() {
+        let mut table = TranspositionTable::new(1);
+        let position = fen!("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        let best_move = position.generate_legal_moves()[0];
+        // A different position sharing the slot
+        let mut other_hash = position.hash();
+        other_hash.write_u64(1 << 40);
+        let score = Score::Approximate(PawnEvaluation::new(1.0));
+        table.store(
+            position.hash(),
+            PlyCount::new(5),
+            score,
+            Bound::Exact,
+            Some(best_move),
+        );
+
+        table.store(other_hash, PlyCount::new(2), score, Bound::Upper, None);
+        assert!(table.probe(other_hash).is_none());
+        assert!(table.probe(position.hash()).is_some());
+
+        table.new_search();
+        table.store(other_hash, PlyCount::new(2), score, Bound::Upper, None);
+        let entry = table.probe(other_hash).unwrap();
+        assert!(table.probe(position.hash()).is_none());
+        assert_eq!(entry.best_move, None);
+    }
+}
@@ -0,0 +1,921 @@
+use crate::chess_move::ChessMove;
+use crate::evaluation::{PawnEvaluation, Score};
+use crate::half_move_clock::HALF_MOVE_LIMIT;
+use crate::node_count::NodeCount;
+use crate::pieces::NonKingPieceType;
+use crate::ply_count::PlyCount;
+use crate::position::LegalPosition;
+use crate::search::move_picker::MovePicker;
+use crate::search::pruning::{
+    late_move_pruning_count, late_move_reduction, FUTILITY_MARGIN, FUTILITY_MAX_DEPTH,
+    LATE_MOVE_PRUNING_MAX_DEPTH, LATE_MOVE_REDUCTION_MIN_DEPTH, LATE_MOVE_REDUCTION_MIN_MOVES,
+    NULL_MOVE_MIN_DEPTH, NULL_MOVE_REDUCTION, RAZORING_MARGIN, RAZORING_MAX_DEPTH,
+    REVERSE_FUTILITY_MARGIN, REVERSE_FUTILITY_MAX_DEPTH,
+};
+use crate::search::search_stack::{PlayedMove, SearchStack};
+use crate::search::{
+    Bound, PonderSignal, Pruning, SearchResult, Stability, StopSignal, TimeManager,
+    TranspositionEntry, TranspositionTable,
+};
+use crate::tablebase::{SyzygyTablebase, Wdl};
+use crate::zobrist::ZobristHash;
+use alloc::vec::Vec;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The width of a null window, the smallest difference in evaluation the search distinguishes
+const NULL_WINDOW: PawnEvaluation = PawnEvaluation(0.01);
+/// How many nodes are searched between checks of the clock and the stop flags
+const NODES_PER_TIME_CHECK: u64 = 2048;
+/// The shallowest iteration searched with an aspiration window around the score from the previous iteration
+const ASPIRATION_MIN_DEPTH: u8 = 4;
+/// How far either side of the previous score an aspiration window starts, doubling whenever the score falls outside
+const ASPIRATION_WINDOW: PawnEvaluation = PawnEvaluation(0.5);
+/// The widest an aspiration window grows before falling back to the full window
+const ASPIRATION_MAX_WINDOW: PawnEvaluation = PawnEvaluation(4.0);
+
+/// How many depths in a row a helper thread searches before skipping as many, by helper
+///
+/// Helpers are staggered so they don't all search the same iteration, spreading their results through the shared
+/// table for the main thread to reuse.
+const SKIP_SIZES: [u8; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+/// How far into its pattern of searched and skipped depths a helper thread starts, by helper
+const SKIP_PHASES: [u8; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+/// The state shared by every thread of a single search
+#[derive(Debug)]
+pub struct SharedState<'a> {
+    /// The table of searched positions
+    pub table: &'a TranspositionTable,
+    /// The tablebase to probe, if any
+    pub tablebase: Option<&'a SyzygyTablebase>,
+    /// Stops the search when raised by the caller
+    pub stop_signal: &'a StopSignal,
+    /// Suspends the time limits while raised by the caller
+    pub ponder_signal: &'a PonderSignal,
+    /// Stops the helper threads once the main thread has finished
+    pub finished: AtomicBool,
+    /// The nodes searched by every thread
+    pub nodes: AtomicU64,
+    /// When the search should stop
+    pub time: TimeManager,
+    /// The selectivity techniques to use
+    pub pruning: Pruning,
+    /// How many of the best lines to find
+    pub multipv: usize,
+    /// The only root moves to search, every legal move if empty
+    pub search_moves: Vec<ChessMove>,
+    /// The most nodes to search (across every thread)
+    pub node_limit: Option<NodeCount>,
+    /// The hashes of the positions played before the root, oldest first
+    pub history: &'a [ZobristHash],
+}
+
+impl SharedState<'_> {
+    /// Whether or not every thread should stop searching
+    fn should_stop(&self) -> bool {
+        self.stop_signal.is_stopped()
+            || self.finished.load(Ordering::Relaxed)
+            || (!self.ponder_signal.is_pondering() && self.time.is_out_of_time())
+    }
+}
+
+/// Convert the score of a child position to the score of its parent, a ply further from any mate
+fn from_child(score: Score) -> Score {
+    match -score {
+        Score::Mating(mut plies) => {
+            plies.increment();
+            Score::Mating(plies)
+        }
+        Score::Mated(mut plies) => {
+            plies.increment();
+            Score::Mated(plies)
+        }
+        score => score,
+    }
+}
+
+/// Convert a bound of a parent position to the equivalent bound of its child, the inverse of [`from_child`]
+fn to_child(bound: Score) -> Score {
+    match -bound {
+        Score::Mating(mut plies) => {
+            plies.decrement();
+            Score::Mating(plies)
+        }
+        Score::Mated(mut plies) => {
+            plies.decrement();
+            Score::Mated(plies)
+        }
+        bound => bound,
+    }
+}
+
+/// The score just above another, the upper bound of a null window searched to prove whether a score is above it
+fn just_above(score: Score) -> Score {
+    match score {
+        Score::Stalemate => Score::Approximate(NULL_WINDOW),
+        Score::Approximate(evaluation) => Score::Approximate(evaluation + NULL_WINDOW),
+        Score::Mating(mut plies) => {
+            plies.decrement();
+            Score::Mating(plies)
+        }
+        Score::Mated(mut plies) => {
+            plies.increment();
+            Score::Mated(plies)
+        }
+    }
+}
+
+/// The score just below another, the inverse of [`just_above`]
+fn just_below(score: Score) -> Score {
+    -just_above(-score)
+}
+
+/// The score of a position without any legal moves, checkmate when in check and stalemate otherwise
+const fn no_moves_score(in_check: bool) -> Score {
+    if in_check {
+        Score::MIN
+    } else {
+        Score::Stalemate
+    }
+}
+
+/// Get the score stored for a position if it's usable within the window between alpha and beta
+fn table_cutoff(entry: TranspositionEntry, alpha: Score, beta: Score) -> Option<Score> {
+    match entry.bound {
+        Bound::Exact => Some(entry.score),
+        Bound::Lower if entry.score >= beta => Some(entry.score),
+        Bound::Upper if entry.score <= alpha => Some(entry.score),
+        _ => None,
+    }
+}
+
+/// Whether or not the player to move has any pieces besides pawns, without which they're likely to be in zugzwang
+fn has_non_pawn_material(position: &LegalPosition) -> bool {
+    [
+        NonKingPieceType::Knight,
+        NonKingPieceType::Bishop,
+        NonKingPieceType::Rook,
+        NonKingPieceType::Queen,
+    ]
+    .into_iter()
+    .any(|piece| !(position.piece_mask(piece) & position.player_to_move_mask()).is_empty())
+}
+
+/// Whether or not the player to move has a quiet move giving (direct) check, which the quiescence search won't try
+fn has_quiet_check(position: &LegalPosition) -> bool {
+    position
+        .generate_legal_moves()
+        .into_iter()
+        .any(|chess_move| {
+            !chess_move.is_capture()
+                && NonKingPieceType::try_from(chess_move.moved_piece()).is_ok_and(|piece| {
+                    !(position.check_squares(piece) & chess_move.to_square().to_mask()).is_empty()
+                })
+        })
+}
+
+/// The plies since the last capture or pawn move of a position
+fn halfmove_clock(position: &LegalPosition) -> usize {
+    usize::from(u8::from(PlyCount::from(position.halfmove_clock())))
+}
+
+/// One thread of a search, iteratively deepening its own copy of the position
+pub struct Worker<'a> {
+    /// Which thread this is, the main thread being `0`
+    id: usize,
+    shared: &'a SharedState<'a>,
+    /// Nodes searched since they were last added to the shared count
+    unreported_nodes: u64,
+    stopped: bool,
+    /// Whether the node being entered was reached by a null move, so another can't be tried straight after
+    after_null_move: bool,
+    /// The hashes of the positions played before the root and of the line to the current node, to detect repetitions
+    hashes: Vec<ZobristHash>,
+    /// The killer moves and histories this thread has learned, kept between searches
+    stack: &'a mut SearchStack,
+    /// The root moves of the lines already found in the current iteration, skipped when searching for the next best
+    excluded_root_moves: Vec<ChessMove>,
+}
+
+impl<'a> Worker<'a> {
+    /// Create a thread of a search
+    pub fn new(id: usize, shared: &'a SharedState<'a>, stack: &'a mut SearchStack) -> Self {
+        Self {
+            id,
+            shared,
+            unreported_nodes: 0,
+            stopped: false,
+            after_null_move: false,
+            hashes: shared.history.to_vec(),
+            stack,
+            excluded_root_moves: Vec::new(),
+        }
+    }
+
+    /// The nodes searched by every thread so far
+    fn total_nodes(&self) -> NodeCount {
+        NodeCount::new(self.shared.nodes.load(Ordering::Relaxed) + self.unreported_nodes)
+    }
+
+    /// Whether or not a helper thread skips an iteration, so the threads are spread across depths
+    const fn skips_depth(&self, depth: u8) -> bool {
+        if self.id == 0 {
+            return false;
+        }
+
+        let index = (self.id - 1) % SKIP_SIZES.len();
+        !((depth + SKIP_PHASES[index]) / SKIP_SIZES[index]).is_multiple_of(2)
+    }
+
+    /// Iteratively deepen a position until the search is stopped or the max depth is reached, reporting the result of
+    /// each line of each completed iteration and getting the best line of the deepest
+    ///
+    /// With more than one line (`MultiPV`), each iteration searches the root again for every line, skipping the root
+    /// moves of the lines already found.
+    pub fn iterate(
+        &mut self,
+        mut position: LegalPosition,
+        max_depth: PlyCount,
+        mut report: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.stack.new_search();
+        // Without any completed iteration, play any legal move
+        let mut legal_moves = position.generate_legal_moves();
+        legal_moves.retain(|&chess_move| self.is_root_move(chess_move));
+        let mut stability = Stability::new(legal_moves.len());
+        let mut result = SearchResult {
+            best_move: legal_moves.first().copied(),
+            score: Score::Stalemate,
+            depth: PlyCount::default(),
+            nodes: NodeCount::default(),
+            elapsed: Duration::ZERO,
+            principal_variation: Vec::new(),
+            multipv: 1,
+        };
+        let mut line_scores =
+            alloc::vec![None; self.shared.multipv.clamp(1, legal_moves.len().max(1))];
+
+        for depth in 1..=max_depth.0 {
+            if self.skips_depth(depth) {
+                continue;
+            }
+
+            self.excluded_root_moves.clear();
+            for (index, line_score) in line_scores.iter_mut().enumerate() {
+                let (score, principal_variation) =
+                    self.aspiration_search(&mut position, PlyCount(depth), *line_score);
+                if self.stopped {
+                    break;
+                }
+
+                *line_score = Some(score);
+                let line = SearchResult {
+                    best_move: principal_variation.first().copied(),
+                    score,
+                    depth: PlyCount(depth),
+                    nodes: self.total_nodes(),
+                    elapsed: self.shared.time.elapsed(),
+                    principal_variation,
+                    multipv: index + 1,
+                };
+                report(&line);
+                self.excluded_root_moves.extend(line.best_move);
+                if index == 0 {
+                    result = line;
+                }
+            }
+            if self.stopped {
+                break;
+            }
+            stability.update(&result);
+
+            // Only the main thread manages the time (once it's not pondering), helpers are stopped once it finishes
+            if self.id == 0
+                && !self.shared.ponder_signal.is_pondering()
+                && self.shared.time.should_stop_iterating(&stability)
+            {
+                break;
+            }
+            // Every mate within the depth has been found, searching deeper can't find a shorter one (though it can
+            // still improve the other lines)
+            if line_scores.len() == 1
+                && matches!(result.score, Score::Mating(plies) | Score::Mated(plies) if plies.0 <= depth)
+            {
+                break;
+            }
+        }
+
+        self.shared
+            .nodes
+            .fetch_add(self.unreported_nodes, Ordering::Relaxed);
+        self.unreported_nodes = 0;
+
+        result
+    }
+
+    /// Search the root to a depth, getting the score and principal variation of its best move (other than any
+    /// excluded)
+    ///
+    /// From [`ASPIRATION_MIN_DEPTH`], the root is first searched with a narrow (aspiration) window around the score of
+    /// the same line in the previous iteration, which is cheaper than the full window when the score barely changes.
+    /// The score is only exact within the window, so whenever it falls outside the window is doubled and the root
+    /// searched again, falling back to the full window once it grows too wide (or around mate scores).
+    fn aspiration_search(
+        &mut self,
+        position: &mut LegalPosition,
+        depth: PlyCount,
+        previous_score: Option<Score>,
+    ) -> (Score, Vec<ChessMove>) {
+        let center = match previous_score {
+            Some(Score::Approximate(evaluation)) if depth.0 >= ASPIRATION_MIN_DEPTH => {
+                Some(evaluation)
+            }
+            _ => None,
+        };
+        let mut window = ASPIRATION_WINDOW;
+        loop {
+            let bounds = center
+                .filter(|_| window.0 <= ASPIRATION_MAX_WINDOW.0)
+                .map(|center| {
+                    (
+                        Score::Approximate(center - window),
+                        Score::Approximate(center + window),
+                    )
+                });
+            let (alpha, beta) = bounds.unwrap_or((Score::MIN, Score::MAX));
+            let mut principal_variation = Vec::new();
+            let score = self.alpha_beta(
+                position,
+                depth,
+                PlyCount::default(),
+                alpha,
+                beta,
+                Some(&mut principal_variation),
+            );
+            if self.stopped || bounds.is_none() || (score > alpha && score < beta) {
+                return (score, principal_variation);
+            }
+
+            window *= 2.0;
+        }
+    }
+
+    /// Whether or not a root move is searched, being one of the moves the search is restricted to (if any) and not
+    /// the first move of a line already found in the current iteration
+    fn is_root_move(&self, chess_move: ChessMove) -> bool {
+        (self.shared.search_moves.is_empty() || self.shared.search_moves.contains(&chess_move))
+            && !self.excluded_root_moves.contains(&chess_move)
+    }
+
+    /// Count a visited node, periodically adding to the shared count and checking whether the search should stop
+    ///
+    /// With a node limit, the search stops before visiting a node past it, so a single threaded search visits exactly
+    /// as many nodes as the limit (unless it finishes first).
+    fn visit_node(&mut self) {
+        if self.stopped {
+            return;
+        }
+        if self
+            .shared
+            .node_limit
+            .is_some_and(|limit| self.total_nodes() >= limit)
+        {
+            self.stopped = true;
+            return;
+        }
+
+        self.unreported_nodes += 1;
+        if self.unreported_nodes >= NODES_PER_TIME_CHECK {
+            self.shared
+                .nodes
+                .fetch_add(self.unreported_nodes, Ordering::Relaxed);
+            self.unreported_nodes = 0;
+            self.stopped = self.shared.should_stop();
+        }
+    }
+
+    /// Whether or not a position is drawn by the fifty-move rule, or repeats a position since the last capture or
+    /// pawn move
+    fn is_draw(&self, position: &LegalPosition) -> bool {
+        let halfmove_clock = halfmove_clock(position);
+        let hash = position.hash();
+
+        halfmove_clock >= HALF_MOVE_LIMIT
+            || self
+                .hashes
+                .iter()
+                .rev()
+                .take(halfmove_clock)
+                .skip(1)
+                .step_by(2)
+                .any(|&previous| previous == hash)
+    }
+
+    /// Get the tablebase result of a position just reached by a capture or pawn move
+    fn probe_tablebase(&self, position: &mut LegalPosition) -> Option<Score> {
+        if halfmove_clock(position) != 0 {
+            return None;
+        }
+
+        self.shared
+            .tablebase?
+            .probe_wdl(position)
+            .map(Wdl::to_score)
+    }
+
+    /// Search a position to a depth, finding the best line of play within the window between alpha and beta
+    ///
+    /// Nodes given a principal variation to fill are on the principal variation (PV nodes), searched with a full
+    /// window and never pruned. Every other node is searched with a null window, only proving whether the score is
+    /// above or below it, so it can be pruned more aggressively.
+    fn alpha_beta(
+        &mut self,
+        position: &mut LegalPosition,
+        depth: PlyCount,
+        ply: PlyCount,
+        alpha: Score,
+        beta: Score,
+        principal_variation: Option<&mut Vec<ChessMove>>,
+    ) -> Score {
+        let after_null_move = core::mem::take(&mut self.after_null_move);
+        self.visit_node();
+        if self.stopped {
+            return Score::Stalemate;
+        }
+
+        let is_pv = principal_variation.is_some();
+        if ply != PlyCount::default() {
+            if let Some(score) = self.known_result(position) {
+                return score;
+            }
+            // Mate distance pruning, nothing here can beat mating on the next move
+            if alpha >= Score::Mating(PlyCount(1)) {
+                return alpha;
+            }
+        }
+        if depth == PlyCount::default() {
+            return self.quiescence(position, ply, alpha, beta);
+        }
+
+        let hash = position.hash();
+        let table_entry = self.shared.table.probe(hash);
+        if let Some(score) = table_entry
+            .filter(|entry| !is_pv && entry.depth >= depth)
+            .and_then(|entry| table_cutoff(entry, alpha, beta))
+        {
+            return score;
+        }
+
+        let in_check = position.in_check();
+        let mut moves = position.generate_legal_moves();
+        if moves.is_empty() {
+            return no_moves_score(in_check);
+        }
+        let is_root = ply == PlyCount::default();
+        if is_root {
+            moves.retain(|&chess_move| self.is_root_move(chess_move));
+        }
+
+        // Only non-PV nodes out of check are pruned, based on their static evaluation
+        let static_evaluation = (!in_check && !is_pv).then(|| position.evaluate());
+        if let Some(score) = static_evaluation.and_then(|evaluation| {
+            self.prune_node(
+                position,
+                evaluation,
+                (depth, ply),
+                (alpha, beta),
+                after_null_move,
+            )
+        }) {
+            return score;
+        }
+
+        let picker = MovePicker::new(
+            moves,
+            table_entry.and_then(|entry| entry.best_move),
+            ply,
+            position.player_to_move(),
+        );
+        self.hashes.push(hash);
+        let (best_score, best_move) = self.search_moves(
+            position,
+            picker,
+            (depth, ply),
+            (alpha, beta),
+            static_evaluation,
+            principal_variation,
+        );
+        self.hashes.pop();
+
+        // A root searched without some of its moves doesn't have a result worth keeping
+        let is_partial_root = is_root
+            && !(self.shared.search_moves.is_empty() && self.excluded_root_moves.is_empty());
+        if !self.stopped && !is_partial_root {
+            self.store(hash, depth, best_score, (alpha, beta), best_move);
+        }
+
+        best_score
+    }
+
+    /// Search the moves of a position in the order they're picked, getting the best score and the move reaching it
+    ///
+    /// Quiet moves are pruned by the [static evaluation](Self::quiet_move_pruning) of non-PV nodes, and a quiet move
+    /// causing a cutoff updates the killer moves and histories.
+    fn search_moves(
+        &mut self,
+        position: &mut LegalPosition,
+        mut picker: MovePicker,
+        (depth, ply): (PlyCount, PlyCount),
+        (mut alpha, beta): (Score, Score),
+        static_evaluation: Option<PawnEvaluation>,
+        mut principal_variation: Option<&mut Vec<ChessMove>>,
+    ) -> (Score, Option<ChessMove>) {
+        let is_pv = principal_variation.is_some();
+        let in_check = position.in_check();
+        let player = position.player_to_move();
+        let (is_futile, late_move_count) = self.quiet_move_pruning(static_evaluation, depth, alpha);
+
+        let mut best_score = Score::MIN;
+        let mut best_move = None;
+        let mut moves_searched = 0;
+        let mut quiets_searched = Vec::new();
+        let (mut child_depth, mut child_ply) = (depth, ply);
+        child_depth.decrement();
+        child_ply.increment();
+        while let Some(chess_move) = picker.next(self.stack) {
+            let is_quiet = !chess_move.is_capture() && chess_move.promotion_piece().is_none();
+            let state = position.make_move(chess_move);
+            let gives_check = position.in_check();
+
+            let is_late = late_move_count.is_some_and(|count| moves_searched >= count);
+            // Never prune until a move has been found that avoids being mated
+            if is_quiet
+                && !gives_check
+                && (is_late || is_futile)
+                && !matches!(best_score, Score::Mated(_))
+            {
+                position.unmake_move(chess_move, state);
+                continue;
+            }
+
+            let is_reducible = is_quiet && !in_check && !gives_check;
+            let reduction = self.reduction(depth, moves_searched, is_pv, is_reducible);
+            self.stack[ply].played = Some(PlayedMove::new(player, chess_move));
+            let mut child_variation = Vec::new();
+            let score = self.search_move(
+                position,
+                (child_depth, child_ply),
+                (alpha, beta),
+                (moves_searched == 0, reduction),
+                is_pv.then_some(&mut child_variation),
+            );
+            position.unmake_move(chess_move, state);
+            if self.stopped {
+                break;
+            }
+            moves_searched += 1;
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(chess_move);
+                if score > alpha {
+                    alpha = score;
+                    if let Some(principal_variation) = principal_variation.as_deref_mut() {
+                        *principal_variation = core::iter::once(chess_move)
+                            .chain(child_variation)
+                            .collect();
+                    }
+                    if score >= beta {
+                        if is_quiet {
+                            self.stack.update_quiet_cutoff(
+                                ply,
+                                depth,
+                                player,
+                                chess_move,
+                                &quiets_searched,
+                            );
+                        }
+                        break;
+                    }
+                }
+            }
+            if is_quiet {
+                quiets_searched.push(chess_move);
+            }
+        }
+
+        (best_score, best_move)
+    }
+
+    /// Get the result of a position that doesn't need searching, a draw by repetition or the fifty-move rule, or a
+    /// tablebase result
+    fn known_result(&self, position: &mut LegalPosition) -> Option<Score> {
+        if self.is_draw(position) {
+            return Some(Score::Stalemate);
+        }
+
+        self.probe_tablebase(position)
+    }
+
+    /// Decide how quiet moves of a node are pruned, getting whether they're futile (can't raise a position far
+    /// enough below alpha above it) and after how many moves the rest are skipped by late move pruning
+    fn quiet_move_pruning(
+        &self,
+        static_evaluation: Option<PawnEvaluation>,
+        depth: PlyCount,
+        alpha: Score,
+    ) -> (bool, Option<usize>) {
+        // Without a static evaluation the node is a PV node or in check, where every move needs searching
+        let Some(evaluation) = static_evaluation else {
+            return (false, None);
+        };
+        let pruning = self.shared.pruning;
+        let is_futile = pruning.futility
+            && depth.0 <= FUTILITY_MAX_DEPTH
+            && Score::Approximate(evaluation + FUTILITY_MARGIN * f32::from(depth.0)) <= alpha;
+        let late_move_count = (pruning.late_move_pruning && depth.0 <= LATE_MOVE_PRUNING_MAX_DEPTH)
+            .then(|| late_move_pruning_count(depth));
+
+        (is_futile, late_move_count)
+    }
+
+    /// How many plies to reduce a move by, given how many moves of the node have already been searched
+    ///
+    /// Only quiet moves that neither escape nor give check are reducible.
+    fn reduction(
+        &self,
+        depth: PlyCount,
+        moves_searched: usize,
+        is_pv: bool,
+        is_reducible: bool,
+    ) -> u8 {
+        if !is_reducible
+            || !self.shared.pruning.late_move_reductions
+            || depth.0 < LATE_MOVE_REDUCTION_MIN_DEPTH
+            || moves_searched < LATE_MOVE_REDUCTION_MIN_MOVES
+        {
+            return 0;
+        }
+
+        // Reduce PV nodes less, and always leave at least a ply to search
+        late_move_reduction(depth, moves_searched)
+            .saturating_sub(u8::from(is_pv))
+            .min(depth.0.saturating_sub(2))
+    }
+
+    /// Search a move just made with principal variation search, getting its score for the parent position
+    ///
+    /// The first move of a node is searched with the full window. Later moves are expected to be worse, so they're
+    /// searched with a null window (reduced by any late move reduction) to prove it, and only re-searched at full
+    /// depth, then with the full window on PV nodes, when they turn out better than alpha.
+    fn search_move(
+        &mut self,
+        position: &mut LegalPosition,
+        (child_depth, child_ply): (PlyCount, PlyCount),
+        (alpha, beta): (Score, Score),
+        (is_first, reduction): (bool, u8),
+        principal_variation: Option<&mut Vec<ChessMove>>,
+    ) -> Score {
+        if is_first {
+            return from_child(self.alpha_beta(
+                position,
+                child_depth,
+                child_ply,
+                to_child(beta),
+                to_child(alpha),
+                principal_variation,
+            ));
+        }
+
+        let (null_alpha, null_beta) = (to_child(just_above(alpha)), to_child(alpha));
+        let mut score = from_child(self.alpha_beta(
+            position,
+            PlyCount(child_depth.0 - reduction),
+            child_ply,
+            null_alpha,
+            null_beta,
+            None,
+        ));
+        if reduction > 0 && score > alpha && !self.stopped {
+            score = from_child(self.alpha_beta(
+                position,
+                child_depth,
+                child_ply,
+                null_alpha,
+                null_beta,
+                None,
+            ));
+        }
+        if principal_variation.is_some() && score > alpha && score < beta && !self.stopped {
+            score = from_child(self.alpha_beta(
+                position,
+                child_depth,
+                child_ply,
+                to_child(beta),
+                to_child(alpha),
+                principal_variation,
+            ));
+        }
+
+        score
+    }
+
+    /// Store the result of searching a node in the transposition table, bounded by the window it was searched with
+    fn store(
+        &self,
+        hash: ZobristHash,
+        depth: PlyCount,
+        best_score: Score,
+        (alpha, beta): (Score, Score),
+        best_move: Option<ChessMove>,
+    ) {
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        let best_move = best_move.filter(|_| bound != Bound::Upper);
+        self.shared
+            .table
+            .store(hash, depth, best_score, bound, best_move);
+    }
+
+    /// Try to prune a whole (non-PV, not in check) node using its static evaluation, before searching any moves
+    ///
+    /// Applies reverse futility pruning, razoring and null move pruning (as far as they're enabled), getting the
+    /// score to return for the node if any of them prune it.
+    fn prune_node(
+        &mut self,
+        position: &mut LegalPosition,
+        evaluation: PawnEvaluation,
+        (depth, ply): (PlyCount, PlyCount),
+        (alpha, beta): (Score, Score),
+        after_null_move: bool,
+    ) -> Option<Score> {
+        let pruning = self.shared.pruning;
+        let depth_scale = f32::from(depth.0);
+        let is_beta_mate = matches!(beta, Score::Mating(_) | Score::Mated(_));
+
+        // So far above beta that even a ply of depth won't drop it below
+        if pruning.reverse_futility
+            && depth.0 <= REVERSE_FUTILITY_MAX_DEPTH
+            && !is_beta_mate
+            && Score::Approximate(evaluation - REVERSE_FUTILITY_MARGIN * depth_scale) >= beta
+        {
+            return Some(Score::Approximate(evaluation));
+        }
+
+        // So far below alpha that only a capture could help, so check the captures alone (unless a quiet check could
+        // still be a mating attack)
+        if pruning.razoring
+            && depth.0 <= RAZORING_MAX_DEPTH
+            && !matches!(alpha, Score::Mating(_) | Score::Mated(_))
+            && Score::Approximate(evaluation + RAZORING_MARGIN * depth_scale) < alpha
+            && !has_quiet_check(position)
+        {
+            let score = self.quiescence(position, ply, alpha, beta);
+            if score <= alpha {
+                return Some(score);
+            }
+        }
+
+        // Even passing fails high, so a real move almost certainly would too
+        if pruning.null_move
+            && !after_null_move
+            && depth.0 >= NULL_MOVE_MIN_DEPTH
+            && !is_beta_mate
+            && Score::Approximate(evaluation) >= beta
+            && has_non_pawn_material(position)
+        {
+            let reduction = NULL_MOVE_REDUCTION + depth.0 / 4;
+            let null_depth = PlyCount(depth.0.saturating_sub(reduction + 1));
+            let hash = position.hash();
+            let state = position.make_null_move();
+            let mut child_ply = ply;
+            child_ply.increment();
+            self.hashes.push(hash);
+            self.stack[ply].played = None;
+            self.after_null_move = true;
+            let score = from_child(self.alpha_beta(
+                position,
+                null_depth,
+                child_ply,
+                to_child(beta),
+                to_child(just_below(beta)),
+                None,
+            ));
+            self.hashes.pop();
+            position.unmake_null_move(state);
+
+            // Don't trust a mate found by passing, it's only proven the position is at least as good as beta
+            if !self.stopped && score >= beta {
+                return Some(if matches!(score, Score::Mating(_)) {
+                    beta
+                } else {
+                    score
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Search only the captures and promotions of a position (or every evasion, in check) until it's quiet enough
+    /// for the static evaluation to be trusted
+    fn quiescence(
+        &mut self,
+        position: &mut LegalPosition,
+        ply: PlyCount,
+        mut alpha: Score,
+        beta: Score,
+    ) -> Score {
+        self.visit_node();
+        if self.stopped {
+            return Score::Stalemate;
+        }
+
+        let in_check = position.in_check();
+        let mut moves = position.generate_legal_moves();
+        if moves.is_empty() {
+            return no_moves_score(in_check);
+        }
+
+        // Without being in check, the player to move can choose not to capture anything (standing pat)
+        let mut best_score = if in_check {
+            Score::MIN
+        } else {
+            Score::Approximate(position.evaluate())
+        };
+        if best_score >= beta {
+            return best_score;
+        }
+        if best_score > alpha {
+            alpha = best_score;
+        }
+
+        if !in_check {
+            moves.retain(|chess_move| {
+                chess_move.is_capture() || chess_move.promotion_piece().is_some()
+            });
+        }
+        let player = position.player_to_move();
+        let mut picker = MovePicker::new(moves, None, ply, player);
+        let mut child_ply = ply;
+        child_ply.increment();
+        while let Some(chess_move) = picker.next(self.stack) {
+            self.stack[ply].played = Some(PlayedMove::new(player, chess_move));
+            let state = position.make_move(chess_move);
+            let score =
+                from_child(self.quiescence(position, child_ply, to_child(beta), to_child(alpha)));
+            position.unmake_move(chess_move, state);
+            if self.stopped {
+                break;
+            }
+
+            if score > best_score {
+                best_score = score;
+                if score > alpha {
+                    alpha = score;
+                    if score >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+
+        best_score
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::evaluation::{PawnEvaluation, Score};
+    use crate::fen;
+    use crate::ply_count::PlyCount;
+    use crate::search::worker::{has_non_pawn_material, just_above, just_below};
+    use core::cmp::Ordering;
+    use test_case::test_case;
+
+    #[test_case(Score::Stalemate)]
+    #[test_case(Score::Approximate(PawnEvaluation::new(-1.5)))]
+    #[test_case(Score::Mating(PlyCount::new(5)))]
+    #[test_case(Score::Mated(PlyCount::new(5)))]
+    fn null_windows_are_ordered(score: Score) {
+        assert!(just_above(score) > score);
+        assert!(just_below(score) < score);
+        assert_eq!(
+            just_below(just_above(score)).partial_cmp(&score),
+            Some(Ordering::Equal)
+        );
+    }
+
+    #[test_case("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", false; "pawns only")]
+    #[test_case("4k3/8/8/8/8/8/4P3/4KN2 w - - 0 1", true; "knight")]
+    #[test_case("4k3/8/8/8/8/8/4P3/4KN2 b - - 0 1", false; "opponent's knight")]
+    fn has_non_pawn_material_works(fen: &str, expected: bool) {
+        assert_eq!(has_non_pawn_material(&fen!(fen)), expected);
+    }
+}
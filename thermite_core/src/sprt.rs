@@ -0,0 +1,256 @@
+/// The number of standard deviations either side of an estimate covering 95% of a normal distribution
+const CONFIDENCE_95: f64 = 1.959_963_984_540_054;
+
+/// The expected score (from 0 to 1) of a player rated some Elo above their opponent
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// The Elo difference giving an expected score, infinite for a score of 0 or 1 (or beyond)
+fn elo_difference(score: f64) -> f64 {
+    if score <= 0.0 {
+        f64::NEG_INFINITY
+    } else if score >= 1.0 {
+        f64::INFINITY
+    } else {
+        400.0 * (score / (1.0 - score)).log10()
+    }
+}
+
+/// An Elo difference estimated from the results of a match, along with the margin of its 95% confidence interval
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EloEstimate {
+    /// The estimated Elo difference, infinite if every game was won (or lost)
+    pub difference: f64,
+    /// Half the width of the 95% confidence interval around the difference, infinite if it's unbounded
+    pub margin: f64,
+}
+
+/// The wins, draws and losses of one player against another
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct MatchScore {
+    /// The games won
+    pub wins: u32,
+    /// The games drawn
+    pub draws: u32,
+    /// The games lost
+    pub losses: u32,
+}
+
+impl MatchScore {
+    /// The number of games played
+    #[must_use]
+    pub const fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// The fraction of the available points scored, `None` before any games have been played
+    ///
+    /// ```
+    /// use thermite_core::sprt::MatchScore;
+    ///
+    /// let score = MatchScore { wins: 3, draws: 2, losses: 1 };
+    /// assert_eq!(score.score(), Some(4.0 / 6.0));
+    /// assert_eq!(MatchScore::default().score(), None);
+    /// ```
+    #[must_use]
+    pub fn score(&self) -> Option<f64> {
+        let games = self.games();
+
+        (games > 0).then(|| (f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(games))
+    }
+
+    /// The variance of the score of a single game, along with the mean it was taken around
+    fn variance(&self) -> Option<(f64, f64)> {
+        let score = self.score()?;
+        let games = f64::from(self.games());
+        let variance = [(self.wins, 1.0), (self.draws, 0.5), (self.losses, 0.0)]
+            .into_iter()
+            .map(|(count, result): (u32, f64)| f64::from(count) * (result - score).powi(2))
+            .sum::<f64>()
+            / games;
+
+        Some((score, variance))
+    }
+
+    /// Estimate the Elo difference between the players, `None` before any games have been played
+    #[must_use]
+    pub fn elo(&self) -> Option<EloEstimate> {
+        let (score, variance) = self.variance()?;
+        let deviation = (variance / f64::from(self.games())).sqrt() * CONFIDENCE_95;
+        let difference = elo_difference(score);
+        let margin = if difference.is_finite() {
+            (elo_difference(score + deviation) - elo_difference(score - deviation)) / 2.0
+        } else {
+            f64::INFINITY
+        };
+
+        Some(EloEstimate { difference, margin })
+    }
+
+    /// The log-likelihood ratio of the player being `elo1` rather than `elo0` Elo stronger than their opponent
+    ///
+    /// Uses the normal approximation of the generalized SPRT over game results, which is zero until there's been at
+    /// least one decisive game (or a mix of results).
+    #[must_use]
+    pub fn log_likelihood_ratio(&self, elo0: f64, elo1: f64) -> f64 {
+        let Some((score, variance)) = self.variance() else {
+            return 0.0;
+        };
+        if variance <= 0.0 {
+            return 0.0;
+        }
+        let score0 = expected_score(elo0);
+        let score1 = expected_score(elo1);
+
+        f64::from(self.games()) * (score1 - score0) * 2.0f64.mul_add(score, -score0 - score1)
+            / (2.0 * variance)
+    }
+}
+
+/// The hypothesis accepted by a finished [`Sprt`]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SprtDecision {
+    /// The player isn't stronger than `elo0`, the null hypothesis
+    AcceptH0,
+    /// The player is at least as strong as `elo1`, the alternative hypothesis
+    AcceptH1,
+}
+
+/// A sequential probability ratio test of whether a player is `elo1` rather than `elo0` Elo stronger than another
+///
+/// ```
+/// use thermite_core::sprt::{MatchScore, Sprt, SprtDecision};
+///
+/// let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+/// assert_eq!(sprt.decision(&MatchScore { wins: 10, draws: 10, losses: 10 }), None);
+/// assert_eq!(sprt.decision(&MatchScore { wins: 700, draws: 1000, losses: 500 }), Some(SprtDecision::AcceptH1));
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Sprt {
+    elo0: f64,
+    elo1: f64,
+    alpha: f64,
+    beta: f64,
+}
+
+impl Sprt {
+    /// Create a test between two Elo differences, with the chances of falsely accepting each hypothesis
+    ///
+    /// `alpha` is the chance of accepting `elo1` when `elo0` is true, and `beta` the chance of the reverse.
+    #[must_use]
+    pub const fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Self {
+        Self {
+            elo0,
+            elo1,
+            alpha,
+            beta,
+        }
+    }
+
+    /// The Elo differences of the null and alternative hypotheses
+    #[must_use]
+    pub const fn hypotheses(&self) -> (f64, f64) {
+        (self.elo0, self.elo1)
+    }
+
+    /// The log-likelihood ratios at or below which `elo0` is accepted, and at or above which `elo1` is
+    #[must_use]
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    /// The log-likelihood ratio of the test for the results so far
+    #[must_use]
+    pub fn log_likelihood_ratio(&self, score: &MatchScore) -> f64 {
+        score.log_likelihood_ratio(self.elo0, self.elo1)
+    }
+
+    /// Whether the results so far are enough to stop the test, and the hypothesis accepted if so
+    #[must_use]
+    pub fn decision(&self, score: &MatchScore) -> Option<SprtDecision> {
+        let ratio = self.log_likelihood_ratio(score);
+        let (lower, upper) = self.bounds();
+
+        if ratio <= lower {
+            Some(SprtDecision::AcceptH0)
+        } else if ratio >= upper {
+            Some(SprtDecision::AcceptH1)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sprt::{MatchScore, Sprt, SprtDecision};
+    use test_case::test_case;
+
+    const fn score(wins: u32, draws: u32, losses: u32) -> MatchScore {
+        MatchScore {
+            wins,
+            draws,
+            losses,
+        }
+    }
+
+    #[test_case(score(10, 10, 10), 0.0; "even")]
+    #[test_case(score(75, 0, 25), 190.848_501_887_864_98; "three quarters")]
+    #[test_case(score(25, 0, 75), -190.848_501_887_864_98; "one quarter")]
+    fn elo_difference_works(score: MatchScore, expected: f64) {
+        let difference = score.elo().unwrap().difference;
+
+        assert!((difference - expected).abs() < 1e-9, "{difference}");
+    }
+
+    #[test]
+    fn winning_every_game_is_unbounded() {
+        let estimate = score(5, 0, 0).elo().unwrap();
+
+        assert!(estimate.difference.is_infinite() && estimate.difference.is_sign_positive());
+        assert!(estimate.margin.is_infinite());
+    }
+
+    #[test]
+    fn elo_margin_shrinks_with_more_games() {
+        let few = score(6, 8, 6).elo().unwrap().margin;
+        let many = score(600, 800, 600).elo().unwrap().margin;
+
+        assert!(few.is_finite() && many.is_finite());
+        assert!(many < few / 5.0, "{many} {few}");
+    }
+
+    #[test]
+    fn no_games_has_no_estimate() {
+        assert_eq!(MatchScore::default().elo(), None);
+        assert!(MatchScore::default().log_likelihood_ratio(0.0, 5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn bounds_work() {
+        let (lower, upper) = Sprt::new(0.0, 5.0, 0.05, 0.05).bounds();
+
+        assert!((lower + 2.944_438_979_166_44).abs() < 1e-9, "{lower}");
+        assert!((upper - 2.944_438_979_166_44).abs() < 1e-9, "{upper}");
+    }
+
+    #[test_case(score(700, 1000, 500), Some(SprtDecision::AcceptH1); "clearly stronger")]
+    #[test_case(score(500, 1000, 700), Some(SprtDecision::AcceptH0); "clearly weaker")]
+    #[test_case(score(12, 20, 10), None; "too few games")]
+    fn decision_works(score: MatchScore, expected: Option<SprtDecision>) {
+        assert_eq!(Sprt::new(0.0, 10.0, 0.05, 0.05).decision(&score), expected);
+    }
+
+    #[test]
+    fn log_likelihood_ratio_favours_the_closer_hypothesis() {
+        // Scoring ~51.4% is about 10 Elo
+        let results = score(2140, 5000, 1860);
+
+        assert!(results.log_likelihood_ratio(0.0, 10.0) > 0.0);
+        assert!(results.log_likelihood_ratio(10.0, 20.0) < 0.0);
+    }
+}
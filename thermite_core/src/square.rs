@@ -149,6 +149,21 @@ impl Square {
         unsafe { core::mem::transmute::<u8, File>((self as u8) % File::LENGTH as u8) }
     }
 
+    /// Whether a [`Square`] is a light square
+    ///
+    /// ```
+    /// use thermite_core::square::Square;
+    ///
+    /// assert!(!Square::A1.is_light());
+    /// assert!(Square::B1.is_light());
+    /// assert!(Square::H1.is_light());
+    /// assert!(!Square::H8.is_light());
+    /// ```
+    #[must_use]
+    pub const fn is_light(self) -> bool {
+        (self.file() as u8 + self.rank() as u8) % 2 == 1
+    }
+
     /// Try to add an offset to a square
     ///
     /// ```
@@ -202,6 +217,27 @@ impl Square {
     pub fn checked_sub(self, rhs: u8) -> Option<Self> {
         Self::try_from((self as u8).wrapping_sub(rhs)).ok()
     }
+
+    /// Get the number of king moves between two squares (the larger of the file and rank distances)
+    ///
+    /// ```
+    /// use thermite_core::square::Square;
+    /// assert_eq!(Square::A1.distance(Square::A1), 0);
+    /// assert_eq!(Square::A1.distance(Square::B2), 1);
+    /// assert_eq!(Square::A1.distance(Square::H8), 7);
+    /// assert_eq!(Square::E4.distance(Square::C7), 3);
+    /// ```
+    #[must_use]
+    pub const fn distance(self, other: Self) -> u8 {
+        let file_distance = (self.file() as u8).abs_diff(other.file() as u8);
+        let rank_distance = (self.rank() as u8).abs_diff(other.rank() as u8);
+
+        if file_distance > rank_distance {
+            file_distance
+        } else {
+            rank_distance
+        }
+    }
 }
 
 impl FromStr for Square {
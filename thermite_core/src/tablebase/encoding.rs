@@ -0,0 +1,211 @@
+use alloc::vec::Vec;
+use once_cell::sync::Lazy;
+
+/// The most pieces (including kings) a single table can hold
+pub const MAX_PIECES: usize = 7;
+
+/// The number of ways to place a unique leading group of three pieces, with the first in the a1-d1-d4 triangle
+pub const UNIQUE_TRIPLET_PLACEMENTS: u64 = 31332;
+/// The number of legal placements of the two kings, with the first in the a1-d1-d4 triangle
+pub const KING_PAIR_PLACEMENTS: u64 = 462;
+
+/// Get the file (0-7) of a square offset
+pub const fn file_of(square: u8) -> u8 {
+    square & 7
+}
+
+/// Get the rank (0-7) of a square offset
+pub const fn rank_of(square: u8) -> u8 {
+    square >> 3
+}
+
+/// How far (and on which side) a square is from the a1-h8 diagonal, positive above and negative below
+pub const fn off_diagonal(square: u8) -> i8 {
+    rank_of(square) as i8 - file_of(square) as i8
+}
+
+/// Mirror a square across the a1-h8 diagonal
+pub const fn flip_diagonal(square: u8) -> u8 {
+    ((square >> 3) | (square << 3)) & 63
+}
+
+/// The lookup tables used to turn a placement of pieces into a table index
+pub struct Encoding {
+    /// Maps the squares below the a1-h8 diagonal to 0..28
+    pub map_b1h1h7: [u16; 64],
+    /// Maps the a1-d1-d4 triangle to 0..10, the diagonal squares last
+    pub map_a1d1d4: [u16; 64],
+    /// Maps the legal placements of two kings, the first in the a1-d1-d4 triangle, to 0..462
+    pub map_kk: [[u16; 64]; 10],
+    /// The binomial coefficients, the ways to choose `k` of `n` squares as `binomial[k][n]`
+    pub binomial: [[u64; 64]; MAX_PIECES + 1],
+    /// Maps the pawn squares to 0..48, the leading pawn of a table being the one with the highest value
+    pub map_pawns: [u16; 64],
+    /// The index of each leading pawn square for a given number of leading pawns
+    pub lead_pawn_index: [[u64; 64]; MAX_PIECES],
+    /// The number of leading pawn placements for each number of leading pawns and leading file (a-d)
+    pub lead_pawns_size: [[u64; 4]; MAX_PIECES],
+}
+
+/// The shared [`Encoding`] tables, built on first use
+pub static ENCODING: Lazy<Encoding> = Lazy::new(Encoding::new);
+
+impl Encoding {
+    fn new() -> Self {
+        let mut encoding = Self {
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; MAX_PIECES + 1],
+            map_pawns: [0; 64],
+            lead_pawn_index: [[0; 64]; MAX_PIECES],
+            lead_pawns_size: [[0; 4]; MAX_PIECES],
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                encoding.map_b1h1h7[square as usize] = code;
+                code += 1;
+            }
+        }
+
+        // The triangle below the diagonal first, then the diagonal itself
+        let mut code = 0;
+        let mut diagonal = [0; 4];
+        let mut diagonal_count = 0;
+        for square in 0..=27 {
+            if file_of(square) > 3 {
+                continue;
+            }
+            if off_diagonal(square) < 0 {
+                encoding.map_a1d1d4[square as usize] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 {
+                diagonal[diagonal_count] = square;
+                diagonal_count += 1;
+            }
+        }
+        for square in diagonal {
+            encoding.map_a1d1d4[square as usize] = code;
+            code += 1;
+        }
+
+        // Placements with both kings on the diagonal are encoded last
+        let mut code = 0;
+        let mut both_on_diagonal = Vec::new();
+        for index in 0..10 {
+            for first in 0..=27u8 {
+                // b1 is the only square mapped to 0, every other square outside the triangle is also 0
+                if encoding.map_a1d1d4[first as usize] != index || (index == 0 && first != 1) {
+                    continue;
+                }
+                for second in 0..64u8 {
+                    let file_distance = file_of(first).abs_diff(file_of(second));
+                    let rank_distance = rank_of(first).abs_diff(rank_of(second));
+                    if file_distance <= 1 && rank_distance <= 1 {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) > 0 {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((index, second));
+                    } else {
+                        encoding.map_kk[index as usize][second as usize] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (index, second) in both_on_diagonal {
+            encoding.map_kk[index as usize][second as usize] = code;
+            code += 1;
+        }
+
+        encoding.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..=MAX_PIECES.min(n) {
+                let with = if k > 0 {
+                    encoding.binomial[k - 1][n - 1]
+                } else {
+                    0
+                };
+                let without = if k < n {
+                    encoding.binomial[k][n - 1]
+                } else {
+                    0
+                };
+                encoding.binomial[k][n] = with + without;
+            }
+        }
+
+        // The pawns nearest the edge and lowest rank lead, mirrored files share a value range
+        let mut available_squares = 47;
+        for lead_pawns in 1..MAX_PIECES {
+            for file in 0..4u8 {
+                let mut index = 0;
+                for rank in 1..7u8 {
+                    let square = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        encoding.map_pawns[square as usize] = available_squares;
+                        encoding.map_pawns[(square ^ 7) as usize] = available_squares - 1;
+                        available_squares = available_squares.saturating_sub(2);
+                    }
+                    encoding.lead_pawn_index[lead_pawns][square as usize] = index;
+                    index += encoding.binomial[lead_pawns - 1]
+                        [encoding.map_pawns[square as usize] as usize];
+                }
+                encoding.lead_pawns_size[lead_pawns][file as usize] = index;
+            }
+        }
+
+        encoding
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn king_pair_placements_are_dense() {
+        let max = ENCODING.map_kk.iter().flatten().copied().max().unwrap();
+        assert_eq!(u64::from(max) + 1, KING_PAIR_PLACEMENTS);
+    }
+
+    #[test]
+    fn triangle_maps_work() {
+        // b1, c1, d1, c2, d2, d3 then the diagonal a1, b2, c3, d4
+        let triangle = [1, 2, 3, 10, 11, 19, 0, 9, 18, 27];
+        for (code, square) in triangle.into_iter().enumerate() {
+            assert_eq!(ENCODING.map_a1d1d4[square] as usize, code);
+        }
+        assert_eq!(ENCODING.map_b1h1h7[55], 27);
+    }
+
+    #[test_case(0, 0, 1)]
+    #[test_case(1, 63, 63)]
+    #[test_case(2, 62, 1891)]
+    #[test_case(3, 61, 35990)]
+    fn binomial_works(k: usize, n: usize, expected: u64) {
+        assert_eq!(ENCODING.binomial[k][n], expected);
+    }
+
+    #[test_case(8, 47)]
+    #[test_case(15, 46)]
+    #[test_case(16, 45)]
+    #[test_case(9, 35)]
+    #[test_case(51, 1)]
+    #[test_case(52, 0)]
+    fn map_pawns_works(square: usize, expected: u16) {
+        assert_eq!(ENCODING.map_pawns[square], expected);
+    }
+
+    #[test]
+    fn single_lead_pawn_covers_every_square() {
+        let total: u64 = ENCODING.lead_pawns_size[1].iter().sum();
+        assert_eq!(total, 24);
+    }
+}
@@ -0,0 +1,350 @@
+//! The Syzygy tables under `fixtures/syzygy`, and the retrograde analysis they're generated by.
+//!
+//! The tables cover a king and any other piece against a lone king. They're solved here from the engine's own
+//! move generation, independently of the probing code, then written in the Syzygy layout:
+//!
+//! ```text
+//! cargo test -p thermite_core --release -- --ignored generate_fixtures
+//! ```
+
+use crate::pieces::{Piece, PieceType};
+use crate::player_color::PlayerColor;
+use crate::position::{LegalPosition, PositionBuilder};
+use crate::square::Square;
+use crate::tablebase::material::{piece_code, Material};
+use crate::tablebase::table::test::{
+    dtz_plies_flags, index_table, write_table, SideValues, KPVK_PIECES, KQVK_PIECES,
+};
+use crate::tablebase::table::{placed_pieces, TableKind};
+use alloc::vec::Vec;
+use std::fs;
+use std::path::Path;
+
+/// The directory holding the fixture tables
+pub const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/syzygy");
+
+/// The number of positions of a solved table, by side to move, white king, white piece then black king
+const POSITIONS: usize = 2 * 64 * 64 * 64;
+
+/// Where a legal move from a solved position leads
+#[derive(Copy, Clone, Debug)]
+enum Successor {
+    /// To a position of the same table, and whether the move zeroes the fifty-move counter
+    Position(usize, bool),
+    /// To a position of another table (always by a zeroing move), with its WDL value
+    Known(i8),
+}
+
+/// Every position of a king and piece against king table, with its result for the player to move
+struct Solved {
+    /// The WDL value of each position (`-2`, `0` or `2`), `None` when it can't arise in a game
+    wdl: Vec<Option<i8>>,
+    /// The number of plies to the next zeroing move or mate, with the winner hurrying and the loser delaying
+    ///
+    /// Zero for draws and for checkmated positions.
+    dtz: Vec<u8>,
+}
+
+/// Get the solved index of a position with a single white piece
+fn index_of(position: &LegalPosition, piece: PieceType) -> usize {
+    let mut squares = [0; 3];
+    for (square, code) in placed_pieces(position) {
+        match code {
+            6 => squares[0] = square as usize,
+            14 => squares[2] = square as usize,
+            _ => {
+                assert_eq!(code, piece_code(PlayerColor::White, piece));
+                squares[1] = square as usize;
+            }
+        }
+    }
+    let black_to_move = usize::from(position.player_to_move() == PlayerColor::Black);
+
+    ((black_to_move * 64 + squares[0]) * 64 + squares[1]) * 64 + squares[2]
+}
+
+/// Set up the position of a solved index, `None` when it can't arise in a game
+fn position_of(index: usize, piece: PieceType) -> Option<LegalPosition> {
+    let square = |offset: usize| Square::try_from((offset % 64) as u8).unwrap();
+    let (white_king, white_piece, black_king) =
+        (square(index >> 12), square(index >> 6), square(index));
+    let player_to_move = if index >> 18 == 0 {
+        PlayerColor::White
+    } else {
+        PlayerColor::Black
+    };
+    let pawn_rank = white_piece as usize / 8;
+    if white_king == white_piece
+        || white_king == black_king
+        || white_piece == black_king
+        || (piece == PieceType::Pawn && (pawn_rank == 0 || pawn_rank == 7))
+    {
+        return None;
+    }
+
+    LegalPosition::try_from(
+        PositionBuilder::default()
+            .with_piece(
+                PieceType::King
+                    .owned_by(PlayerColor::White)
+                    .placed_on(white_king),
+            )
+            .with_piece(piece.owned_by(PlayerColor::White).placed_on(white_piece))
+            .with_piece(
+                PieceType::King
+                    .owned_by(PlayerColor::Black)
+                    .placed_on(black_king),
+            )
+            .with_starting_player(player_to_move),
+    )
+    .ok()
+}
+
+/// Find where every legal move of a position leads, looking promotions up in the queen and rook tables
+fn successors(
+    position: &mut LegalPosition,
+    piece: PieceType,
+    promotions: &[&Solved],
+) -> Vec<Successor> {
+    let mut successors = Vec::new();
+    for chess_move in position.generate_legal_moves() {
+        let state = position.make_move(chess_move);
+        let successor = if chess_move.is_capture() {
+            // Only the white piece can be captured, leaving bare kings
+            Successor::Known(0)
+        } else {
+            match chess_move.promotion_piece().map(PieceType::from) {
+                Some(PieceType::Queen) => Successor::Known(
+                    promotions[0].wdl[index_of(position, PieceType::Queen)].unwrap(),
+                ),
+                Some(PieceType::Rook) => Successor::Known(
+                    promotions[1].wdl[index_of(position, PieceType::Rook)].unwrap(),
+                ),
+                // A lone bishop or knight can't mate
+                Some(_) => Successor::Known(0),
+                None => Successor::Position(
+                    index_of(position, piece),
+                    chess_move.moved_piece() == PieceType::Pawn,
+                ),
+            }
+        };
+        position.unmake_move(chess_move, state);
+        successors.push(successor);
+    }
+
+    successors
+}
+
+impl Solved {
+    /// Solve every position of a king and `piece` against king, with `promotions` the solved queen and rook tables
+    fn solve(piece: PieceType, promotions: &[&Self]) -> Self {
+        let positions = (0..POSITIONS)
+            .map(|index| {
+                position_of(index, piece).map(|mut position| {
+                    (
+                        position.in_check(),
+                        successors(&mut position, piece, promotions),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Checkmates and stalemates are known straight away, then wins and losses are propagated backwards until
+        // nothing changes, with everything left a draw
+        let mut wdl = positions
+            .iter()
+            .map(|position| match position {
+                Some((true, moves)) if moves.is_empty() => Some(-2),
+                Some((false, moves)) if moves.is_empty() => Some(0),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        loop {
+            let mut changed = false;
+            for (index, position) in positions.iter().enumerate() {
+                let (Some((_, moves)), None) = (position, wdl[index]) else {
+                    continue;
+                };
+                let values = moves.iter().map(|&successor| match successor {
+                    Successor::Position(next, _) => wdl[next],
+                    Successor::Known(value) => Some(value),
+                });
+                if values.clone().any(|value| value == Some(-2)) {
+                    wdl[index] = Some(2);
+                    changed = true;
+                } else if values.clone().all(|value| value == Some(2)) {
+                    wdl[index] = Some(-2);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let wdl = positions
+            .iter()
+            .zip(wdl)
+            .map(|(position, value)| position.as_ref().map(|_| value.unwrap_or(0)))
+            .collect::<Vec<_>>();
+
+        let dtz = Self::distances(&wdl, &positions);
+        Self { wdl, dtz }
+    }
+
+    /// Count the plies to zeroing of every decided position, given their results
+    ///
+    /// Positions are resolved one distance at a time: a win is `n` plies from zeroing if it has a move into a loss
+    /// `n - 1` plies from it (or a zeroing move keeping the win), and a loss once every move it has leads to a decided
+    /// win.
+    fn distances(wdl: &[Option<i8>], positions: &[Option<(bool, Vec<Successor>)>]) -> Vec<u8> {
+        let mut dtz = positions
+            .iter()
+            .zip(wdl)
+            .map(|(position, value)| match (position, value) {
+                (Some((_, moves)), Some(-2)) if moves.is_empty() => Some(0),
+                (_, Some(-2 | 2)) => None,
+                _ => Some(0),
+            })
+            .collect::<Vec<_>>();
+        for plies in 1..=u8::MAX {
+            for decided in [2, -2] {
+                for (index, position) in positions.iter().enumerate() {
+                    let (Some((_, moves)), None) = (position, dtz[index]) else {
+                        continue;
+                    };
+                    if wdl[index] != Some(decided) {
+                        continue;
+                    }
+                    // The result after each move, and how many plies it takes to zero through it if it's decided
+                    let mut through = moves.iter().map(|&successor| match successor {
+                        Successor::Position(next, false) => {
+                            (wdl[next], dtz[next].map(|dtz| dtz + 1))
+                        }
+                        Successor::Position(next, true) => (wdl[next], Some(1)),
+                        Successor::Known(value) => (Some(value), Some(1)),
+                    });
+                    dtz[index] = if decided == 2 {
+                        through
+                            .any(|next| next == (Some(-2), Some(plies)))
+                            .then_some(plies)
+                    } else {
+                        through
+                            .map(|(_, plies)| plies)
+                            .collect::<Option<Vec<_>>>()
+                            .and_then(|plies| plies.into_iter().max())
+                    };
+                }
+            }
+            if dtz.iter().all(Option::is_some) {
+                break;
+            }
+        }
+
+        dtz.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Write the WDL and DTZ tables, storing DTZ for `dtz_side` to move only
+    fn write(
+        &self,
+        material: &str,
+        piece: PieceType,
+        pieces: &[u8],
+        dtz_side: usize,
+        directory: &Path,
+    ) {
+        let table = index_table(material, TableKind::Wdl, pieces);
+        let material: Material = material.parse().unwrap();
+        let files = if piece == PieceType::Pawn { 4 } else { 1 };
+        let mut wdl = (0..files)
+            .map(|file| {
+                (0..2)
+                    .map(|side| vec![None; table.size(side, file)])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut dtz = wdl.clone();
+        for index in 0..POSITIONS {
+            let Some(value) = self.wdl[index] else {
+                continue;
+            };
+            let (side, file, stored) = table.index_of(&position_of(index, piece).unwrap(), false);
+            // The stored value of a loss is one less than its plies, so mated positions probe as a loss in one
+            let plies = self.dtz[index].max(1) - 1;
+            for (values, value) in [(&mut wdl, (value + 2) as u8), (&mut dtz, plies)] {
+                let previous = values[file][side][stored].replace(value);
+                assert!(previous.is_none_or(|previous| previous == value));
+            }
+        }
+
+        // Indices no position maps to take the side's most common value, which compresses best
+        let side_values = |values: &[Option<u8>], flags: Option<u8>| {
+            let mut counts = [0usize; 256];
+            for value in values.iter().flatten() {
+                counts[*value as usize] += 1;
+            }
+            let common = (0..256).max_by_key(|&value| counts[value]).unwrap() as u8;
+            let values = values
+                .iter()
+                .map(|value| value.unwrap_or(common))
+                .collect::<Vec<_>>();
+            match flags {
+                None if values.iter().all(|&value| value == common) => SideValues::Single(common),
+                None => SideValues::Values(0, values),
+                Some(flags) => SideValues::Values(flags, values),
+            }
+        };
+        let wdl_files = wdl
+            .iter()
+            .map(|sides| {
+                sides
+                    .iter()
+                    .map(|values| side_values(values, None))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let dtz_files = dtz
+            .iter()
+            .map(|sides| {
+                vec![side_values(
+                    &sides[dtz_side],
+                    Some(dtz_plies_flags(dtz_side == 1)),
+                )]
+            })
+            .collect::<Vec<_>>();
+        for (kind, files, extension) in [
+            (TableKind::Wdl, wdl_files, "rtbw"),
+            (TableKind::Dtz, dtz_files, "rtbz"),
+        ] {
+            let files = files.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            let bytes = write_table(&material, kind, pieces, &files);
+            fs::write(directory.join(format!("{material}.{extension}")), bytes).unwrap();
+        }
+    }
+}
+
+#[test]
+#[ignore = "regenerates the committed fixtures"]
+fn generate_fixtures() {
+    let directory = Path::new(FIXTURES);
+    fs::create_dir_all(directory).unwrap();
+
+    let queen = Solved::solve(PieceType::Queen, &[]);
+    let rook = Solved::solve(PieceType::Rook, &[]);
+    let pawn = Solved::solve(PieceType::Pawn, &[&queen, &rook]);
+
+    // The longest wins are the published mates in 10 and 16 moves, both players moving in between
+    let white_to_move = POSITIONS / 2;
+    assert_eq!(queen.dtz[..white_to_move].iter().max(), Some(&19));
+    assert_eq!(rook.dtz[..white_to_move].iter().max(), Some(&31));
+
+    queen.write("KQvK", PieceType::Queen, &KQVK_PIECES, 0, directory);
+    rook.write("KRvK", PieceType::Rook, &[6, 4, 14], 0, directory);
+    pawn.write("KPvK", PieceType::Pawn, &KPVK_PIECES, 1, directory);
+    // Drawn throughout, but still needed for probing positions that can underpromote
+    for (material, piece, code) in [
+        ("KBvK", PieceType::Bishop, 3),
+        ("KNvK", PieceType::Knight, 2),
+    ] {
+        Solved::solve(piece, &[]).write(material, piece, &[6, code, 14], 0, directory);
+    }
+}
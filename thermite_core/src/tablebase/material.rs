@@ -0,0 +1,141 @@
+use crate::pieces::{Piece, PieceType};
+use crate::player_color::PlayerColor;
+use crate::position::LegalPosition;
+use core::fmt::{Display, Formatter, Write};
+use core::str::FromStr;
+use enum_iterator::{all, reverse_all};
+use enum_map::{Enum, EnumMap};
+
+/// The count of each piece for both players, which selects the table a position is stored in
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Material(EnumMap<PlayerColor, EnumMap<PieceType, u8>>);
+
+/// A table name that isn't a valid material signature like `KQvK`
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct InvalidMaterial;
+
+impl Material {
+    /// Count the pieces on the board for both players
+    pub fn from_position(position: &LegalPosition) -> Self {
+        let mut counts = EnumMap::<PlayerColor, EnumMap<PieceType, u8>>::default();
+        for square in position.occupied_mask() {
+            if let Some(owned_piece) = position.owned_piece_on(square) {
+                counts[owned_piece.player][owned_piece.piece] += 1;
+            }
+        }
+
+        Self(counts)
+    }
+
+    /// The number of a given piece owned by a player
+    pub fn count(&self, player: PlayerColor, piece: PieceType) -> u8 {
+        self.0[player][piece]
+    }
+
+    /// The total number of pieces on the board, including kings
+    pub fn piece_count(&self) -> usize {
+        self.0
+            .values()
+            .flat_map(EnumMap::values)
+            .map(|&count| count as usize)
+            .sum()
+    }
+
+    /// The same material with the players' pieces swapped
+    pub fn swapped(self) -> Self {
+        Self(EnumMap::from_array([
+            self.0[PlayerColor::Black],
+            self.0[PlayerColor::White],
+        ]))
+    }
+
+    /// Whether or not any player has exactly one of a non-king piece
+    pub fn has_unique_pieces(&self) -> bool {
+        all::<PlayerColor>().any(|player| {
+            all::<PieceType>()
+                .filter(|&piece| piece != PieceType::King)
+                .any(|piece| self.count(player, piece) == 1)
+        })
+    }
+}
+
+impl Display for Material {
+    /// The table name, white's pieces then black's, strongest first (`KRPvKR`)
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for player in all::<PlayerColor>() {
+            if player == PlayerColor::Black {
+                f.write_char('v')?;
+            }
+            for piece in reverse_all::<PieceType>() {
+                for _ in 0..self.count(player, piece) {
+                    f.write_char(piece.get_upper_char())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Material {
+    type Err = InvalidMaterial;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let (white, black) = name.split_once('v').ok_or(InvalidMaterial)?;
+        let mut counts = EnumMap::<PlayerColor, EnumMap<PieceType, u8>>::default();
+        for (player, pieces) in [(PlayerColor::White, white), (PlayerColor::Black, black)] {
+            for piece_char in pieces.chars() {
+                let piece = all::<PieceType>()
+                    .find(|piece| piece.get_upper_char() == piece_char)
+                    .ok_or(InvalidMaterial)?;
+                counts[player][piece] += 1;
+            }
+            if counts[player][PieceType::King] != 1 {
+                return Err(InvalidMaterial);
+            }
+        }
+
+        Ok(Self(counts))
+    }
+}
+
+/// The table code of a piece, `1..=6` for white's pawn to king and `9..=14` for black's
+pub fn piece_code(player: PlayerColor, piece: PieceType) -> u8 {
+    let color = if player == PlayerColor::Black { 8 } else { 0 };
+    (piece.into_usize() as u8 + 1) | color
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fen;
+    use alloc::string::ToString;
+    use test_case::test_case;
+
+    #[test_case("8/8/8/3k4/8/8/8/KQ6 w - - 0 1", "KQvK")]
+    #[test_case("kq6/8/8/3K4/8/8/8/8 b - - 0 1", "KvKQ")]
+    #[test_case("8/8/8/3k4/8/2r5/1P6/KR6 w - - 0 1", "KRPvKR")]
+    #[test_case("4k3/8/8/8/8/8/8/4K3 w - - 0 1", "KvK")]
+    fn name_works(fen: &str, expected: &str) {
+        let material = Material::from_position(&fen!(fen));
+        assert_eq!(material.to_string(), expected);
+        assert_eq!(expected.parse::<Material>(), Ok(material));
+    }
+
+    #[test_case("KQvK", "KvKQ", 3, true)]
+    #[test_case("KRRvK", "KvKRR", 4, false)]
+    #[test_case("KBNvK", "KvKBN", 4, true)]
+    fn swapped_works(name: &str, swapped: &str, piece_count: usize, has_unique_pieces: bool) {
+        let material = name.parse::<Material>().unwrap();
+        assert_eq!(material.swapped().to_string(), swapped);
+        assert_eq!(material.piece_count(), piece_count);
+        assert_eq!(material.has_unique_pieces(), has_unique_pieces);
+    }
+
+    #[test_case("KQK")]
+    #[test_case("QvK")]
+    #[test_case("KXvK")]
+    fn from_str_rejects_invalid_names(name: &str) {
+        assert_eq!(name.parse::<Material>(), Err(InvalidMaterial));
+    }
+}
@@ -0,0 +1,101 @@
+//! Probing of Syzygy endgame tablebases.
+//!
+//! WDL (`.rtbw`) tables give the result of a position under the fifty-move rule, and are cheap enough to probe during
+//! search. DTZ (`.rtbz`) tables give the distance to the next zeroing (capture or pawn) move, used at the root to
+//! keep only the moves that preserve the result while making progress.
+
+mod encoding;
+#[cfg(test)]
+pub(crate) mod fixtures;
+mod material;
+mod probe;
+mod table;
+
+pub use probe::{SyzygyTablebase, TablebaseRootMove};
+
+use crate::evaluation::{PawnEvaluation, Score};
+use core::ops::Neg;
+
+/// The evaluation (in pawns) of a tablebase win, above any material advantage but below a forced mate
+pub const TABLEBASE_WIN: f32 = 200.0;
+
+/// The result of a position with perfect play, accounting for the fifty-move rule
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Ord, PartialOrd, Hash)]
+pub enum Wdl {
+    /// The player to move loses
+    Loss,
+    /// The player to move loses, but can hold a draw by the fifty-move rule
+    BlessedLoss,
+    /// Neither player wins
+    Draw,
+    /// The player to move wins, but not before the fifty-move rule draws the game
+    CursedWin,
+    /// The player to move wins
+    Win,
+}
+
+impl Wdl {
+    /// Get the result from the value stored in a table (`-2` to `2`)
+    const fn from_value(value: i32) -> Option<Self> {
+        match value {
+            -2 => Some(Self::Loss),
+            -1 => Some(Self::BlessedLoss),
+            0 => Some(Self::Draw),
+            1 => Some(Self::CursedWin),
+            2 => Some(Self::Win),
+            _ => None,
+        }
+    }
+
+    /// The sign of the result, `1` for wins, `-1` for losses and `0` for draws
+    const fn signum(self) -> i32 {
+        match self {
+            Self::Loss | Self::BlessedLoss => -1,
+            Self::Draw => 0,
+            Self::CursedWin | Self::Win => 1,
+        }
+    }
+
+    /// The search score of the result, from the perspective of the player to move
+    ///
+    /// Cursed wins and blessed losses are drawn, but scored a centi-pawn away from zero to prefer the better side.
+    ///
+    /// ```
+    /// use thermite_core::evaluation::{PawnEvaluation, Score};
+    /// use thermite_core::tablebase::{Wdl, TABLEBASE_WIN};
+    ///
+    /// assert_eq!(Wdl::Win.to_score(), Score::Approximate(PawnEvaluation::new(TABLEBASE_WIN)));
+    /// assert_eq!(Wdl::Draw.to_score(), Score::Stalemate);
+    /// assert_eq!(Wdl::BlessedLoss.to_score(), Score::Approximate(PawnEvaluation::new(-0.01)));
+    /// ```
+    #[must_use]
+    pub fn to_score(self) -> Score {
+        match self {
+            Self::Loss => Score::Approximate(PawnEvaluation::new(-TABLEBASE_WIN)),
+            Self::BlessedLoss => Score::Approximate(PawnEvaluation::from_centipawns(-1)),
+            Self::Draw => Score::Stalemate,
+            Self::CursedWin => Score::Approximate(PawnEvaluation::from_centipawns(1)),
+            Self::Win => Score::Approximate(PawnEvaluation::new(TABLEBASE_WIN)),
+        }
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Self::Loss => Self::Win,
+            Self::BlessedLoss => Self::CursedWin,
+            Self::Draw => Self::Draw,
+            Self::CursedWin => Self::BlessedLoss,
+            Self::Win => Self::Loss,
+        }
+    }
+}
+
+impl From<Wdl> for Score {
+    fn from(wdl: Wdl) -> Self {
+        wdl.to_score()
+    }
+}
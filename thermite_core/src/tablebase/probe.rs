@@ -0,0 +1,482 @@
+use crate::castles::CastleRights;
+use crate::chess_move::ChessMove;
+use crate::evaluation::{PawnEvaluation, Score};
+use crate::pieces::PieceType;
+use crate::player_color::PlayerColor;
+use crate::ply_count::PlyCount;
+use crate::position::LegalPosition;
+use crate::tablebase::material::Material;
+use crate::tablebase::table::{placed_pieces, Table, TableKind, TableProbe};
+use crate::tablebase::{Wdl, TABLEBASE_WIN};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::{env, fs, io};
+
+/// The rank given to moves that win (or lose) regardless of the fifty-move counter
+const MAX_DTZ: i32 = 1 << 18;
+/// The halfmove clock at which the game is drawn by the fifty-move rule
+const FIFTY_MOVE_PLIES: i32 = 100;
+
+/// A table file found on disk, only read and parsed the first time it's probed
+#[derive(Debug)]
+struct TableFile {
+    path: PathBuf,
+    material: Material,
+    kind: TableKind,
+    table: OnceLock<Option<Table>>,
+}
+
+impl TableFile {
+    fn table(&self) -> Option<&Table> {
+        self.table
+            .get_or_init(|| {
+                fs::read(&self.path)
+                    .ok()
+                    .and_then(|bytes| Table::new(bytes, &self.material, self.kind).ok())
+            })
+            .as_ref()
+    }
+}
+
+/// A legal root move ranked by the tablebases
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TablebaseRootMove {
+    /// The move from the root position
+    pub chess_move: ChessMove,
+    /// Higher is better, moves with the same rank are equally good
+    pub rank: i32,
+    /// The score of the move from the perspective of the player to move at the root
+    pub score: Score,
+}
+
+/// A set of Syzygy tables found in one or more directories
+#[derive(Debug, Default)]
+pub struct SyzygyTablebase {
+    wdl: HashMap<Material, TableFile>,
+    dtz: HashMap<Material, TableFile>,
+    max_pieces: usize,
+}
+
+impl SyzygyTablebase {
+    /// Find the table files in one or more directories, separated like the `PATH` environment variable
+    ///
+    /// Tables are named by their material (`KRvK.rtbw`) and only loaded once they're first probed.
+    ///
+    /// # Errors
+    /// If any of the directories can't be read
+    pub fn open(directories: impl AsRef<OsStr>) -> io::Result<Self> {
+        let mut tablebase = Self::default();
+        for directory in env::split_paths(&directories) {
+            for entry in fs::read_dir(directory)? {
+                let path = entry?.path();
+                let kind = match path.extension().and_then(OsStr::to_str) {
+                    Some("rtbw") => TableKind::Wdl,
+                    Some("rtbz") => TableKind::Dtz,
+                    _ => continue,
+                };
+                let Some(material) = path
+                    .file_stem()
+                    .and_then(OsStr::to_str)
+                    .and_then(|name| name.parse::<Material>().ok())
+                else {
+                    continue;
+                };
+
+                let tables = match kind {
+                    TableKind::Wdl => {
+                        tablebase.max_pieces = tablebase.max_pieces.max(material.piece_count());
+                        &mut tablebase.wdl
+                    }
+                    TableKind::Dtz => &mut tablebase.dtz,
+                };
+                tables.entry(material).or_insert(TableFile {
+                    path,
+                    material,
+                    kind,
+                    table: OnceLock::new(),
+                });
+            }
+        }
+
+        Ok(tablebase)
+    }
+
+    /// The most pieces (including kings) of any WDL table found
+    #[must_use]
+    pub const fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Whether or not a position has few enough pieces, and no castle rights, to be in the tables
+    #[must_use]
+    pub fn can_probe(&self, position: &LegalPosition) -> bool {
+        position.castle_rights() == CastleRights::None
+            && position.occupied_mask().num_squares() as usize <= self.max_pieces
+    }
+
+    /// Get the result of a position, as if the halfmove clock was zero
+    ///
+    /// `None` if the position [can't be probed](Self::can_probe), or if a required table is missing or corrupt.
+    pub fn probe_wdl(&self, position: &mut LegalPosition) -> Option<Wdl> {
+        if !self.can_probe(position) {
+            return None;
+        }
+
+        self.search(position, false).map(|(wdl, _)| wdl)
+    }
+
+    /// Get the distance to zeroing (in plies) of a position, as if the halfmove clock was zero
+    ///
+    /// Positive when the player to move wins and negative when they lose, `±1` when the best move is itself zeroing,
+    /// and off by `±100` for cursed wins and blessed losses. Zero for draws.
+    pub fn probe_dtz(&self, position: &mut LegalPosition) -> Option<i32> {
+        if !self.can_probe(position) {
+            return None;
+        }
+
+        self.dtz(position)
+    }
+
+    /// Rank every legal move of a root position by its DTZ and the current halfmove clock, best first
+    ///
+    /// Wins that are sure to complete before the fifty-move rule are ranked equally, so the search can pick between
+    /// them, while moves giving up a result are ranked below; keeping only the moves sharing the best rank preserves
+    /// the tablebase result.
+    pub fn rank_root_moves(&self, position: &mut LegalPosition) -> Option<Vec<TablebaseRootMove>> {
+        if !self.can_probe(position) {
+            return None;
+        }
+
+        let root_clock = halfmove_clock(position);
+        let mut root_moves = Vec::new();
+        for chess_move in position.generate_legal_moves() {
+            let state = position.make_move(chess_move);
+            let clock = halfmove_clock(position);
+            let dtz = if clock == 0 {
+                // DTZ is one of -101, -1, 0, 1, 101 for a zeroing move
+                self.search(position, false)
+                    .map(|(wdl, _)| dtz_before_zeroing(-wdl))
+            } else if clock >= FIFTY_MOVE_PLIES {
+                Some(0)
+            } else {
+                self.dtz(position).map(|dtz| -dtz - dtz.signum())
+            };
+            let dtz = dtz.map(|dtz| {
+                if dtz == 2 && position.in_check() && position.generate_legal_moves().is_empty() {
+                    1
+                } else {
+                    dtz
+                }
+            });
+            position.unmake_move(chess_move, state);
+
+            let rank = root_rank(dtz?, root_clock);
+            root_moves.push(TablebaseRootMove {
+                chess_move,
+                rank,
+                score: root_score(rank),
+            });
+        }
+        root_moves.sort_by_key(|root_move| Reverse(root_move.rank));
+
+        Some(root_moves)
+    }
+
+    /// Look up the table holding a material signature, and whether the position's colors are swapped relative to it
+    fn table(&self, kind: TableKind, material: Material) -> Option<(&Table, bool)> {
+        let tables = match kind {
+            TableKind::Wdl => &self.wdl,
+            TableKind::Dtz => &self.dtz,
+        };
+        tables
+            .get(&material)
+            .map(|file| (file, false))
+            .or_else(|| tables.get(&material.swapped()).map(|file| (file, true)))
+            .and_then(|(file, black_stronger)| file.table().map(|table| (table, black_stronger)))
+    }
+
+    /// Probe a single table, without accounting for captures
+    fn probe_table(
+        &self,
+        position: &LegalPosition,
+        kind: TableKind,
+        wdl: Wdl,
+    ) -> Option<TableProbe> {
+        let material = Material::from_position(position);
+        if material.piece_count() == 2 {
+            return Some(TableProbe::Value(0));
+        }
+        let (table, black_stronger) = self.table(kind, material)?;
+        let black_to_move = position.player_to_move() == PlayerColor::Black;
+
+        table
+            .probe(&placed_pieces(position), black_to_move, black_stronger, wdl)
+            .ok()
+    }
+
+    /// Resolve the result of a position, along with whether its best move is zeroing
+    ///
+    /// Tables store "don't care" values for positions with a winning capture (and may store a loss for drawing
+    /// ones) to compress better, so the captures (and pawn moves when `check_zeroing`, as DTZ doesn't store them
+    /// either) need searching alongside the table value.
+    fn search(&self, position: &mut LegalPosition, check_zeroing: bool) -> Option<(Wdl, bool)> {
+        let legal_moves = position.generate_legal_moves();
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for &chess_move in &legal_moves {
+            if !chess_move.is_capture()
+                && (!check_zeroing || chess_move.moved_piece() != PieceType::Pawn)
+            {
+                continue;
+            }
+            searched += 1;
+            let state = position.make_move(chess_move);
+            let result = self.search(position, false);
+            position.unmake_move(chess_move, state);
+
+            let value = -result?.0;
+            if value > best {
+                best = value;
+                if value >= Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // With every move searched the table value can be skipped (it would be wrong with en-passant rights anyway)
+        let no_more_moves = searched > 0 && searched == legal_moves.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            match self.probe_table(position, TableKind::Wdl, Wdl::Draw)? {
+                TableProbe::Value(value) => Wdl::from_value(value)?,
+                TableProbe::ChangeSideToMove => return None,
+            }
+        };
+
+        if best >= value {
+            Some((best, best > Wdl::Draw || no_more_moves))
+        } else {
+            Some((value, false))
+        }
+    }
+
+    fn dtz(&self, position: &mut LegalPosition) -> Option<i32> {
+        let (wdl, zeroing) = self.search(position, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        // DTZ stores a "don't care" value when the best move zeroes
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+
+        match self.probe_table(position, TableKind::Dtz, wdl)? {
+            TableProbe::Value(dtz) => {
+                let cursed = if matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss) {
+                    FIFTY_MOVE_PLIES
+                } else {
+                    0
+                };
+                Some((dtz + cursed) * wdl.signum())
+            }
+            // The table only stores the other side, search a ply for the best DTZ preserving the result
+            TableProbe::ChangeSideToMove => {
+                let mut best = i32::MAX;
+                for chess_move in position.generate_legal_moves() {
+                    let zeroing =
+                        chess_move.is_capture() || chess_move.moved_piece() == PieceType::Pawn;
+                    let state = position.make_move(chess_move);
+                    let dtz = if zeroing {
+                        self.search(position, false)
+                            .map(|(wdl, _)| -dtz_before_zeroing(wdl))
+                    } else {
+                        self.dtz(position).map(|dtz| -dtz)
+                    };
+                    let mates = dtz == Some(1)
+                        && position.in_check()
+                        && position.generate_legal_moves().is_empty();
+                    position.unmake_move(chess_move, state);
+
+                    let mut dtz = dtz?;
+                    if mates {
+                        best = 1;
+                    }
+                    if !zeroing {
+                        dtz += dtz.signum();
+                    }
+                    if dtz < best && dtz.signum() == wdl.signum() {
+                        best = dtz;
+                    }
+                }
+
+                // No legal moves means the player to move is mated
+                Some(if best == i32::MAX { -1 } else { best })
+            }
+        }
+    }
+}
+
+/// The DTZ of a position whose best move is zeroing, with the given result
+const fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Loss => -1,
+        Wdl::BlessedLoss => -101,
+        Wdl::Draw => 0,
+        Wdl::CursedWin => 101,
+        Wdl::Win => 1,
+    }
+}
+
+/// The halfmove clock of a position as a plain number of plies
+fn halfmove_clock(position: &LegalPosition) -> i32 {
+    i32::from(u8::from(PlyCount::from(position.halfmove_clock())))
+}
+
+/// Rank a root move by its DTZ (from the root) given the root's halfmove clock
+const fn root_rank(dtz: i32, halfmove_clock: i32) -> i32 {
+    if dtz > 0 {
+        if dtz + halfmove_clock < FIFTY_MOVE_PLIES {
+            MAX_DTZ
+        } else {
+            MAX_DTZ - (dtz + halfmove_clock)
+        }
+    } else if dtz < 0 {
+        if -dtz * 2 + halfmove_clock < FIFTY_MOVE_PLIES {
+            -MAX_DTZ
+        } else {
+            -MAX_DTZ + (-dtz + halfmove_clock)
+        }
+    } else {
+        0
+    }
+}
+
+/// The score of a ranked root move, wins drawn by the fifty-move rule scoring between 1.5 and 50 centi-pawns
+fn root_score(rank: i32) -> Score {
+    let bound = MAX_DTZ - FIFTY_MOVE_PLIES;
+    match rank {
+        rank if rank >= bound => Score::Approximate(PawnEvaluation::new(TABLEBASE_WIN)),
+        rank if rank > 0 => Score::Approximate(PawnEvaluation::from_centipawns(
+            ((rank - (MAX_DTZ - 200)).max(3) / 2) as i16,
+        )),
+        0 => Score::Stalemate,
+        rank if rank > -bound => Score::Approximate(PawnEvaluation::from_centipawns(
+            ((rank + (MAX_DTZ - 200)).min(-3) / 2) as i16,
+        )),
+        _ => Score::Approximate(PawnEvaluation::new(-TABLEBASE_WIN)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fen;
+    use crate::tablebase::fixtures::FIXTURES;
+    use crate::tablebase::table::test::{kqvk, write_table, SideValues, KQVK_PIECES};
+    use test_case::test_case;
+
+    /// The position stalemating black, stored as a draw in the test tables
+    const STALEMATE: &str = "k7/2Q5/1K6/8/8/8/8/8 b - - 0 1";
+
+    /// Write `KQvK` tables into a fresh directory
+    ///
+    /// Every position is won for white, apart from the stalemate. The DTZ table only stores white to move, with every
+    /// position five moves from zeroing.
+    fn kqvk_tablebase(name: &str) -> SyzygyTablebase {
+        let directory =
+            env::temp_dir().join(format!("thermite-syzygy-{}-{name}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let single = write_table(
+            &kqvk(),
+            TableKind::Wdl,
+            &KQVK_PIECES,
+            &[&[SideValues::Single(4), SideValues::Single(0)]],
+        );
+        let table = Table::new(single, &kqvk(), TableKind::Wdl).unwrap();
+        let mut black_to_move = vec![0; table.size(1, 0)];
+        black_to_move[table.index_of(&fen!(STALEMATE), false).2] = 2;
+        let wdl = write_table(
+            &kqvk(),
+            TableKind::Wdl,
+            &KQVK_PIECES,
+            &[&[SideValues::Single(4), SideValues::Values(0, black_to_move)]],
+        );
+        let dtz = write_table(
+            &kqvk(),
+            TableKind::Dtz,
+            &KQVK_PIECES,
+            &[&[SideValues::Single(5)]],
+        );
+        fs::write(directory.join("KQvK.rtbw"), wdl).unwrap();
+        fs::write(directory.join("KQvK.rtbz"), dtz).unwrap();
+
+        SyzygyTablebase::open(&directory).unwrap()
+    }
+
+    #[test_case("8/8/8/3k4/8/8/8/KQ6 w - - 0 1", Some(Wdl::Win); "win")]
+    #[test_case("8/8/8/3k4/8/8/8/KQ6 b - - 0 1", Some(Wdl::Loss); "loss")]
+    #[test_case(STALEMATE, Some(Wdl::Draw); "stalemate")]
+    #[test_case("8/8/8/8/8/2k5/1Q6/7K b - - 0 1", Some(Wdl::Draw); "queen capture")]
+    #[test_case("kq6/8/8/3K4/8/8/8/8 b - - 0 1", Some(Wdl::Win); "swapped colors")]
+    #[test_case("8/8/8/3k4/8/8/8/K7 w - - 0 1", Some(Wdl::Draw); "bare kings")]
+    #[test_case("8/8/8/3k4/8/8/8/KR6 w - - 0 1", None; "missing table")]
+    #[test_case("r3k3/8/8/8/8/8/8/4K3 b q - 0 1", None; "castle rights")]
+    fn probe_wdl_works(fen: &str, expected: Option<Wdl>) {
+        let tablebase = kqvk_tablebase("wdl");
+        assert_eq!(tablebase.max_pieces(), 3);
+        assert_eq!(tablebase.probe_wdl(&mut fen!(fen)), expected);
+    }
+
+    #[test_case("8/8/8/3k4/8/8/8/KQ6 w - - 0 1", Some(11); "stored side")]
+    #[test_case("8/8/8/3k4/8/8/8/KQ6 b - - 0 1", Some(-12); "other side")]
+    #[test_case("8/8/8/8/8/2k5/1Q6/7K b - - 0 1", Some(0); "draw")]
+    fn probe_dtz_works(fen: &str, expected: Option<i32>) {
+        let tablebase = kqvk_tablebase("dtz");
+        assert_eq!(tablebase.probe_dtz(&mut fen!(fen)), expected);
+    }
+
+    #[test_case("k7/8/1K6/8/8/8/8/7R w - - 0 1", Wdl::Win, 1; "rook mate in one")]
+    #[test_case("R6k/8/6K1/8/8/8/8/8 b - - 0 1", Wdl::Loss, -1; "checkmated")]
+    #[test_case("k7/8/1K6/8/8/8/8/7R b - - 0 1", Wdl::Loss, -2; "mated next move")]
+    #[test_case("7k/8/8/8/8/8/8/KQ6 b - - 0 1", Wdl::Loss, -16; "queen in the corner")]
+    #[test_case("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1", Wdl::Draw, 0; "stalemate")]
+    #[test_case("8/8/8/8/8/8/8/kR1K4 b - - 0 1", Wdl::Draw, 0; "rook capture")]
+    #[test_case("k7/8/8/8/8/8/P7/7K w - - 0 1", Wdl::Draw, 0; "rook pawn")]
+    #[test_case("4k3/4P3/4K3/8/8/8/8/8 b - - 0 1", Wdl::Draw, 0; "pawn stalemate")]
+    #[test_case("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", Wdl::Win, 3; "key square")]
+    #[test_case("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", Wdl::Loss, -4; "key square defending")]
+    #[test_case("8/4P3/8/8/8/k7/8/K7 w - - 0 1", Wdl::Win, 1; "promotion")]
+    #[test_case("8/8/8/8/8/8/4p3/K1k5 w - - 0 1", Wdl::Loss, -2; "swapped colors")]
+    fn fixture_tables_work(fen: &str, wdl: Wdl, dtz: i32) {
+        let tablebase = SyzygyTablebase::open(FIXTURES).unwrap();
+        let mut position = fen!(fen);
+
+        assert_eq!(tablebase.max_pieces(), 3);
+        assert_eq!(tablebase.probe_wdl(&mut position), Some(wdl));
+        assert_eq!(tablebase.probe_dtz(&mut position), Some(dtz));
+    }
+
+    #[test_case(0, Score::Approximate(PawnEvaluation::new(TABLEBASE_WIN)); "fresh clock")]
+    #[test_case(95, Score::Approximate(PawnEvaluation::from_centipawns(46)); "fifty-move draw")]
+    fn rank_root_moves_works(halfmove_clock: u8, expected_score: Score) {
+        let tablebase = kqvk_tablebase("root");
+        let mut position = fen!(&format!("8/8/8/3k4/8/8/8/KQ6 w - - {halfmove_clock} 60"));
+        let root_moves = tablebase.rank_root_moves(&mut position).unwrap();
+
+        assert_eq!(root_moves.len(), position.generate_legal_moves().len());
+        assert_eq!(root_moves[0].score, expected_score);
+        let hanging_queen = root_moves
+            .iter()
+            .find(|root_move| root_move.chess_move.to_string() == "b1e4")
+            .unwrap();
+        assert_eq!(hanging_queen.rank, 0);
+        assert_eq!(hanging_queen.score, Score::Stalemate);
+        assert_eq!(root_moves.last().unwrap().rank, 0);
+    }
+}
@@ -0,0 +1,1310 @@
+use crate::pieces::PieceType;
+use crate::player_color::PlayerColor;
+use crate::position::LegalPosition;
+use crate::tablebase::encoding::{
+    file_of, flip_diagonal, off_diagonal, rank_of, ENCODING, KING_PAIR_PLACEMENTS, MAX_PIECES,
+    UNIQUE_TRIPLET_PLACEMENTS,
+};
+use crate::tablebase::material::{piece_code, Material};
+use crate::tablebase::Wdl;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The little-endian magic at the start of every WDL (`.rtbw`) file
+const WDL_MAGIC: u32 = 0x5d23_e871;
+/// The little-endian magic at the start of every DTZ (`.rtbz`) file
+const DTZ_MAGIC: u32 = 0xa50c_66d7;
+
+/// Header flag set when the table stores both sides to move separately
+const SPLIT: u8 = 1;
+/// Header flag set when the table has pawns, and so is split by the file of the leading pawn
+const HAS_PAWNS: u8 = 2;
+
+/// Pairs flag holding the side to move a DTZ table was stored for
+const STM: u8 = 1;
+/// Pairs flag set when DTZ values are remapped through a per-result lookup
+const MAPPED: u8 = 2;
+/// Pairs flag set when DTZ win values are stored in plies rather than moves
+const WIN_PLIES: u8 = 4;
+/// Pairs flag set when DTZ loss values are stored in plies rather than moves
+const LOSS_PLIES: u8 = 8;
+/// Pairs flag set when the DTZ lookup is made of `u16` rather than `u8`
+const WIDE: u8 = 16;
+/// Pairs flag set when every position in the table stores the same value
+const SINGLE_VALUE: u8 = 128;
+
+/// The kind of values a table file stores
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TableKind {
+    /// Win/draw/loss tables (`.rtbw`)
+    Wdl,
+    /// Distance to zeroing tables (`.rtbz`)
+    Dtz,
+}
+
+/// Errors that can occur while loading a table file
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TableError {
+    /// The file doesn't start with the magic for its kind
+    InvalidMagic,
+    /// The header doesn't match the material named by the file
+    MismatchedHeader,
+    /// The file ends before the header says it should
+    Truncated,
+}
+
+/// The result of looking a position up in a single table
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TableProbe {
+    /// The raw value stored for the position
+    Value(i32),
+    /// The (one sided) DTZ table only stores the other side to move
+    ChangeSideToMove,
+}
+
+/// The decompression data of one side to move (and leading pawn file) of a table
+#[derive(Clone, Debug, Default)]
+struct PairsData {
+    flags: u8,
+    pieces: [u8; MAX_PIECES],
+    group_len: [usize; MAX_PIECES + 1],
+    group_index: [u64; MAX_PIECES + 1],
+    block_size: usize,
+    span: u64,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    block_lengths: usize,
+    block_lengths_size: usize,
+    blocks: usize,
+    blocks_count: usize,
+    min_symbol_length: u8,
+    lowest_symbols: usize,
+    base: Vec<u64>,
+    symbol_lengths: Vec<u8>,
+    tree: usize,
+    map_index: [usize; 4],
+}
+
+/// A bounds checked little/big-endian reader over the raw bytes of a table
+#[derive(Copy, Clone)]
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn u8(self, offset: usize) -> Result<u8, TableError> {
+        self.0.get(offset).copied().ok_or(TableError::Truncated)
+    }
+
+    fn bytes<const N: usize>(self, offset: usize) -> Result<[u8; N], TableError> {
+        self.0
+            .get(offset..offset + N)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(TableError::Truncated)
+    }
+
+    fn u16_le(self, offset: usize) -> Result<u16, TableError> {
+        self.bytes(offset).map(u16::from_le_bytes)
+    }
+
+    fn u32_le(self, offset: usize) -> Result<u32, TableError> {
+        self.bytes(offset).map(u32::from_le_bytes)
+    }
+
+    fn u32_be(self, offset: usize) -> Result<u32, TableError> {
+        self.bytes(offset).map(u32::from_be_bytes)
+    }
+
+    fn u64_be(self, offset: usize) -> Result<u64, TableError> {
+        self.bytes(offset).map(u64::from_be_bytes)
+    }
+
+    /// The left child of a symbol in the pairing tree, or the value of a leaf
+    fn left(self, tree: usize, symbol: usize) -> Result<usize, TableError> {
+        let [low, mid, _] = self.bytes::<3>(tree + 3 * symbol)?;
+        Ok((usize::from(mid & 0xf) << 8) | usize::from(low))
+    }
+
+    /// The right child of a symbol in the pairing tree, `0xfff` for a leaf
+    fn right(self, tree: usize, symbol: usize) -> Result<usize, TableError> {
+        let [_, mid, high] = self.bytes::<3>(tree + 3 * symbol)?;
+        Ok((usize::from(high) << 4) | usize::from(mid >> 4))
+    }
+}
+
+/// A single loaded Syzygy table file
+///
+/// Positions are stored with the leading (stronger) side as white, split by side to move for WDL tables, and by the
+/// file of the leading pawn for tables with pawns.  Each position's pieces are encoded as an index, whose value is
+/// compressed using recursive pairing and canonical Huffman codes.
+#[derive(Clone, Debug)]
+pub struct Table {
+    bytes: Vec<u8>,
+    kind: TableKind,
+    symmetric: bool,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    pawn_counts: [usize; 2],
+    piece_count: usize,
+    pairs: Vec<Vec<PairsData>>,
+    map: usize,
+}
+
+impl Table {
+    /// Parse a table file for a given material signature (the file name, white being the left side)
+    pub fn new(bytes: Vec<u8>, material: &Material, kind: TableKind) -> Result<Self, TableError> {
+        let reader = Reader(&bytes);
+        let expected_magic = match kind {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        };
+        if reader.u32_le(0)? != expected_magic {
+            return Err(TableError::InvalidMagic);
+        }
+
+        let white_pawns = material.count(PlayerColor::White, PieceType::Pawn) as usize;
+        let black_pawns = material.count(PlayerColor::Black, PieceType::Pawn) as usize;
+        // The side with fewer (but some) pawns leads, for better compression
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        let pawn_counts = if white_leads {
+            [white_pawns, black_pawns]
+        } else {
+            [black_pawns, white_pawns]
+        };
+        let piece_count = material.piece_count();
+        if piece_count > MAX_PIECES {
+            return Err(TableError::MismatchedHeader);
+        }
+
+        let mut table = Self {
+            bytes: Vec::new(),
+            kind,
+            symmetric: *material == material.swapped(),
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces: material.has_unique_pieces(),
+            pawn_counts,
+            piece_count,
+            pairs: Vec::new(),
+            map: 0,
+        };
+
+        let flags = reader.u8(4)?;
+        if (flags & HAS_PAWNS != 0) != table.has_pawns || (flags & SPLIT != 0) == table.symmetric {
+            return Err(TableError::MismatchedHeader);
+        }
+        table.read_header(reader)?;
+        table.bytes = bytes;
+
+        Ok(table)
+    }
+
+    /// The number of sides to move stored
+    fn sides(&self) -> usize {
+        if self.kind == TableKind::Wdl && !self.symmetric {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// The number of leading pawn files stored
+    const fn files(&self) -> usize {
+        if self.has_pawns {
+            4
+        } else {
+            1
+        }
+    }
+
+    /// Whether or not both players have pawns, the remaining pawns are then their own group
+    const fn has_remaining_pawns(&self) -> bool {
+        self.has_pawns && self.pawn_counts[1] > 0
+    }
+
+    fn read_header(&mut self, reader: Reader) -> Result<(), TableError> {
+        let sides = self.sides();
+        let files = self.files();
+        let remaining_pawns = self.has_remaining_pawns();
+        self.pairs = vec![vec![PairsData::default(); files]; sides];
+
+        let mut offset = 5;
+        for file in 0..files {
+            let first = reader.u8(offset)?;
+            let second = if remaining_pawns {
+                reader.u8(offset + 1)?
+            } else {
+                0xff
+            };
+            let orders = [[first & 0xf, second & 0xf], [first >> 4, second >> 4]];
+            offset += 1 + usize::from(remaining_pawns);
+
+            for piece in 0..self.piece_count {
+                let codes = reader.u8(offset)?;
+                for side in 0..sides {
+                    self.pairs[side][file].pieces[piece] =
+                        if side == 0 { codes & 0xf } else { codes >> 4 };
+                }
+                offset += 1;
+            }
+            for (side, order) in orders.iter().enumerate().take(sides) {
+                self.set_groups(side, file, *order)?;
+            }
+        }
+        offset += offset & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                offset = self.set_sizes(reader, side, file, offset)?;
+            }
+        }
+        if self.kind == TableKind::Dtz {
+            offset = self.set_map(reader, offset)?;
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut self.pairs[side][file];
+                pairs.sparse_index = offset;
+                offset += pairs.sparse_index_size * 6;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut self.pairs[side][file];
+                pairs.block_lengths = offset;
+                offset += pairs.block_lengths_size * 2;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                offset = (offset + 0x3f) & !0x3f;
+                let pairs = &mut self.pairs[side][file];
+                pairs.blocks = offset;
+                offset += pairs.blocks_count * pairs.block_size;
+            }
+        }
+
+        if offset > reader.0.len() {
+            return Err(TableError::Truncated);
+        }
+
+        Ok(())
+    }
+
+    /// Split the pieces into the groups they are encoded by, and the multiplier of each group's index
+    ///
+    /// With groups `g1, g2, g3` placeable `N(g)` ways the index is `g1 * N(g2) * N(g3) + g2 * N(g3) + g3`, in the
+    /// per-table order given by `order` (the leading group, then the remaining pawns if any).
+    fn set_groups(&mut self, side: usize, file: usize, order: [u8; 2]) -> Result<(), TableError> {
+        let has_pawns = self.has_pawns;
+        let remaining_pawns = self.has_remaining_pawns();
+        let piece_count = self.piece_count;
+        let leading_group_size = if has_pawns {
+            0
+        } else if self.has_unique_pieces {
+            UNIQUE_TRIPLET_PLACEMENTS
+        } else {
+            KING_PAIR_PLACEMENTS
+        };
+        let pairs = &mut self.pairs[side][file];
+
+        let mut first_len: i32 = if has_pawns {
+            0
+        } else if self.has_unique_pieces {
+            3
+        } else {
+            2
+        };
+        let mut groups = 0;
+        pairs.group_len[0] = 1;
+        for piece in 1..piece_count {
+            first_len -= 1;
+            if first_len > 0 || pairs.pieces[piece] == pairs.pieces[piece - 1] {
+                pairs.group_len[groups] += 1;
+            } else {
+                groups += 1;
+                pairs.group_len[groups] = 1;
+            }
+        }
+        groups += 1;
+        pairs.group_len[groups] = 0;
+
+        let mut next = if remaining_pawns { 2 } else { 1 };
+        let mut free_squares = 64
+            - pairs.group_len[0]
+            - if remaining_pawns {
+                pairs.group_len[1]
+            } else {
+                0
+            };
+        let mut index = 1;
+        let mut group = 0;
+        while next < groups || group == order[0] || group == order[1] {
+            if group == order[0] {
+                pairs.group_index[0] = index;
+                index *= if has_pawns {
+                    ENCODING.lead_pawns_size[pairs.group_len[0]][file]
+                } else {
+                    leading_group_size
+                };
+            } else if group == order[1] {
+                pairs.group_index[1] = index;
+                index *= ENCODING.binomial[pairs.group_len[1]][48 - pairs.group_len[0]];
+            } else {
+                let len = pairs.group_len[next];
+                pairs.group_index[next] = index;
+                index *= ENCODING.binomial[len][free_squares];
+                free_squares = free_squares
+                    .checked_sub(len)
+                    .ok_or(TableError::MismatchedHeader)?;
+                next += 1;
+            }
+            group += 1;
+            if group > 0xf {
+                return Err(TableError::MismatchedHeader);
+            }
+        }
+        pairs.group_index[groups] = index;
+
+        Ok(())
+    }
+
+    /// Read the compression parameters of a single pairs data, returning the offset after them
+    fn set_sizes(
+        &mut self,
+        reader: Reader,
+        side: usize,
+        file: usize,
+        mut offset: usize,
+    ) -> Result<usize, TableError> {
+        let pairs = &mut self.pairs[side][file];
+        pairs.flags = reader.u8(offset)?;
+        offset += 1;
+        if pairs.flags & SINGLE_VALUE != 0 {
+            // The single value is kept as the minimum symbol length
+            pairs.min_symbol_length = reader.u8(offset)?;
+            return Ok(offset + 1);
+        }
+
+        let groups = pairs
+            .group_len
+            .iter()
+            .position(|&len| len == 0)
+            .unwrap_or(MAX_PIECES);
+        let table_size = pairs.group_index[groups];
+        pairs.block_size = 1 << reader.u8(offset)?;
+        pairs.span = 1 << reader.u8(offset + 1)?;
+        pairs.sparse_index_size = table_size.div_ceil(pairs.span) as usize;
+        let padding = reader.u8(offset + 2)? as usize;
+        pairs.blocks_count = reader.u32_le(offset + 3)? as usize;
+        pairs.block_lengths_size = pairs.blocks_count + padding;
+        let max_symbol_length = reader.u8(offset + 7)?;
+        pairs.min_symbol_length = reader.u8(offset + 8)?;
+        offset += 9;
+        pairs.lowest_symbols = offset;
+
+        // Canonical Huffman codes, longer codes have lower values. `base[i]` is the lowest code of length
+        // `min_symbol_length + i` left aligned to 64 bits.
+        let lengths = max_symbol_length
+            .checked_sub(pairs.min_symbol_length)
+            .ok_or(TableError::MismatchedHeader)? as usize
+            + 1;
+        pairs.base = vec![0; lengths];
+        for length in (0..lengths - 1).rev() {
+            let lowest = u64::from(reader.u16_le(pairs.lowest_symbols + 2 * length)?);
+            let next_lowest = u64::from(reader.u16_le(pairs.lowest_symbols + 2 * length + 2)?);
+            pairs.base[length] = (pairs.base[length + 1] + lowest).wrapping_sub(next_lowest) / 2;
+        }
+        for (length, base) in pairs.base.iter_mut().enumerate() {
+            let shift = 64 - length as u32 - u32::from(pairs.min_symbol_length);
+            *base = base.checked_shl(shift).unwrap_or(0);
+        }
+        offset += lengths * 2;
+
+        let symbols = reader.u16_le(offset)? as usize;
+        offset += 2;
+        pairs.tree = offset;
+        pairs.symbol_lengths = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            if !visited[symbol] {
+                let length = symbol_length(reader, pairs, symbol, &mut visited)?;
+                pairs.symbol_lengths[symbol] = length;
+            }
+        }
+
+        Ok(offset + symbols * 3 + (symbols & 1))
+    }
+
+    /// Read the DTZ value maps, one per result for each file that uses them
+    fn set_map(&mut self, reader: Reader, mut offset: usize) -> Result<usize, TableError> {
+        self.map = offset;
+        for file in 0..self.files() {
+            let flags = self.pairs[0][file].flags;
+            if flags & MAPPED == 0 {
+                continue;
+            }
+            for map_index in 0..4 {
+                if flags & WIDE != 0 {
+                    offset += offset & 1;
+                    self.pairs[0][file].map_index[map_index] = (offset - self.map) / 2 + 1;
+                    offset += 2 * reader.u16_le(offset)? as usize + 2;
+                } else {
+                    self.pairs[0][file].map_index[map_index] = offset - self.map + 1;
+                    offset += reader.u8(offset)? as usize + 1;
+                }
+            }
+        }
+
+        Ok(offset + (offset & 1))
+    }
+
+    /// Whether or not the DTZ table stores the given side to move
+    fn stores_side_to_move(&self, side: usize, file: usize) -> bool {
+        let flags = self.pairs[0][file].flags;
+        usize::from(flags & STM) == side || (self.symmetric && !self.has_pawns)
+    }
+
+    /// Look up the raw value stored for a position
+    ///
+    /// `pieces` holds every (square, [code](piece_code)) on the board, `black_stronger` whether the position has the
+    /// table's white material as black. `wdl` is the known result when probing a DTZ table.
+    pub fn probe(
+        &self,
+        pieces: &[(u8, u8)],
+        black_to_move: bool,
+        black_stronger: bool,
+        wdl: Wdl,
+    ) -> Result<TableProbe, TableError> {
+        let Some((side, file, index)) = self.encode(pieces, black_to_move, black_stronger) else {
+            return Ok(TableProbe::ChangeSideToMove);
+        };
+        let pairs = &self.pairs[side % self.sides()][file];
+        let value = i32::from(self.decompress(pairs, index)?);
+
+        Ok(TableProbe::Value(match self.kind {
+            TableKind::Wdl => value - 2,
+            TableKind::Dtz => self.map_dtz(file, value, wdl)?,
+        }))
+    }
+
+    /// Compute the (stored side, leading pawn file, index) of a position, `None` if a DTZ table doesn't store its side
+    fn encode(
+        &self,
+        pieces: &[(u8, u8)],
+        black_to_move: bool,
+        black_stronger: bool,
+    ) -> Option<(usize, usize, u64)> {
+        // Tables are stored with the stronger side as white, and symmetric tables only with white to move
+        let flip = black_stronger || (black_to_move && self.symmetric);
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let side = usize::from(flip ^ black_to_move);
+
+        let mut squares = [0u8; MAX_PIECES];
+        let mut codes = [0u8; MAX_PIECES];
+        let mut count = 0;
+        let mut lead_pawns = 0;
+        let mut file = 0;
+
+        let lead_code = self.pairs[0][0].pieces[0] ^ flip_color;
+        if self.has_pawns {
+            for &(square, code) in pieces.iter().filter(|&&(_, code)| code == lead_code) {
+                squares[count] = square ^ flip_squares;
+                codes[count] = code ^ flip_color;
+                count += 1;
+            }
+            lead_pawns = count;
+            let leading = (0..lead_pawns)
+                .rev()
+                .max_by_key(|&i| ENCODING.map_pawns[squares[i] as usize])
+                .unwrap_or(0);
+            squares.swap(0, leading);
+            file = file_of(squares[0]).min(7 - file_of(squares[0])) as usize;
+        }
+
+        if self.kind == TableKind::Dtz && !self.stores_side_to_move(side, file) {
+            return None;
+        }
+
+        for &(square, code) in pieces
+            .iter()
+            .filter(|&&(_, code)| !self.has_pawns || code != lead_code)
+        {
+            squares[count] = square ^ flip_squares;
+            codes[count] = code ^ flip_color;
+            count += 1;
+        }
+        let pairs = &self.pairs[side % self.sides()][file];
+
+        // Reorder to match the table's piece sequence
+        for i in lead_pawns..count.saturating_sub(1) {
+            if let Some(j) = (i + 1..count).find(|&j| pairs.pieces[i] == codes[j]) {
+                codes.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+        let squares = &mut squares[..count];
+
+        // The leading piece is mirrored into the a-d files
+        if file_of(squares[0]) > 3 {
+            for square in squares.iter_mut() {
+                *square ^= 7;
+            }
+        }
+
+        let index = if self.has_pawns {
+            let mut index = ENCODING.lead_pawn_index[lead_pawns][squares[0] as usize];
+            squares[1..lead_pawns].sort_by_key(|&square| ENCODING.map_pawns[square as usize]);
+            for (i, &square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                index += ENCODING.binomial[i][ENCODING.map_pawns[square as usize] as usize];
+            }
+            index
+        } else {
+            self.encode_leading_pieces(pairs, squares)
+        };
+
+        Some((side, file, self.encode_remaining(pairs, squares, index)))
+    }
+
+    /// Encode the leading group of a pawnless table, mirroring the board so it's in the a1-d1-d4 triangle
+    fn encode_leading_pieces(&self, pairs: &PairsData, squares: &mut [u8]) -> u64 {
+        if rank_of(squares[0]) > 3 {
+            for square in squares.iter_mut() {
+                *square ^= 0x38;
+            }
+        }
+        // The first leading piece off the a1-h8 diagonal is mirrored below it
+        for i in 0..pairs.group_len[0] {
+            match off_diagonal(squares[i]) {
+                0 => continue,
+                offset if offset > 0 => {
+                    squares[i..]
+                        .iter_mut()
+                        .for_each(|square| *square = flip_diagonal(*square));
+                }
+                _ => {}
+            }
+            break;
+        }
+
+        let encoding = &*ENCODING;
+        if !self.has_unique_pieces {
+            return u64::from(
+                encoding.map_kk[encoding.map_a1d1d4[squares[0] as usize] as usize]
+                    [squares[1] as usize],
+            );
+        }
+
+        let [first, second, third] = [squares[0], squares[1], squares[2]].map(u64::from);
+        let adjust1 = u64::from(second > first);
+        let adjust2 = u64::from(third > first) + u64::from(third > second);
+        let [first_rank, second_rank, third_rank] =
+            [squares[0], squares[1], squares[2]].map(|square| u64::from(rank_of(square)));
+        if off_diagonal(squares[0]) != 0 {
+            (u64::from(encoding.map_a1d1d4[first as usize]) * 63 + (second - adjust1)) * 62 + third
+                - adjust2
+        } else if off_diagonal(squares[1]) != 0 {
+            (6 * 63 + first_rank * 28 + u64::from(encoding.map_b1h1h7[second as usize])) * 62
+                + third
+                - adjust2
+        } else if off_diagonal(squares[2]) != 0 {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + first_rank * 7 * 28
+                + (second_rank - adjust1) * 28
+                + u64::from(encoding.map_b1h1h7[third as usize])
+        } else {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + 4 * 7 * 28
+                + first_rank * 7 * 6
+                + (second_rank - adjust1) * 6
+                + (third_rank - adjust2)
+        }
+    }
+
+    /// Encode the groups after the leading one, each as a combination of the squares not taken by earlier groups
+    fn encode_remaining(&self, pairs: &PairsData, squares: &mut [u8], leading_index: u64) -> u64 {
+        let mut index = leading_index * pairs.group_index[0];
+        let mut group_start = pairs.group_len[0];
+        let mut remaining_pawns = self.has_remaining_pawns();
+        let mut next = 1;
+        while pairs.group_len[next] != 0 {
+            let len = pairs.group_len[next];
+            squares[group_start..group_start + len].sort_unstable();
+            let mut group_index = 0;
+            for i in 0..len {
+                let square = squares[group_start + i];
+                let adjust = squares[..group_start]
+                    .iter()
+                    .filter(|&&earlier| square > earlier)
+                    .count();
+                let offset = square as usize - adjust - if remaining_pawns { 8 } else { 0 };
+                group_index += ENCODING.binomial[i + 1][offset];
+            }
+            remaining_pawns = false;
+            index += group_index * pairs.group_index[next];
+            group_start += len;
+            next += 1;
+        }
+
+        index
+    }
+
+    /// Decompress the value stored at an index
+    fn decompress(&self, pairs: &PairsData, index: u64) -> Result<u16, TableError> {
+        if pairs.flags & SINGLE_VALUE != 0 {
+            return Ok(u16::from(pairs.min_symbol_length));
+        }
+        let reader = Reader(&self.bytes);
+
+        // The sparse index points to the block (and offset in it) of the middle of each span
+        let sparse_entry = pairs.sparse_index + 6 * (index / pairs.span) as usize;
+        let mut block = reader.u32_le(sparse_entry)? as usize;
+        let mut offset = i64::from(reader.u16_le(sparse_entry + 4)?);
+        offset += (index % pairs.span) as i64 - (pairs.span / 2) as i64;
+
+        // Each block stores its length + 1 values
+        let block_length = |block: usize| -> Result<i64, TableError> {
+            if block >= pairs.block_lengths_size {
+                return Err(TableError::Truncated);
+            }
+            Ok(i64::from(reader.u16_le(pairs.block_lengths + 2 * block)?))
+        };
+        while offset < 0 {
+            block = block.checked_sub(1).ok_or(TableError::Truncated)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        let mut position = pairs.blocks + block * pairs.block_size;
+        let mut buffer = reader.u64_be(position)?;
+        position += 8;
+        let mut buffer_size = 64;
+        let min_length = u32::from(pairs.min_symbol_length);
+        let symbol_length = |symbol: usize| -> Result<i64, TableError> {
+            pairs
+                .symbol_lengths
+                .get(symbol)
+                .map(|&length| i64::from(length))
+                .ok_or(TableError::Truncated)
+        };
+
+        let mut symbol = loop {
+            let mut length = 0;
+            while buffer < *pairs.base.get(length).ok_or(TableError::Truncated)? {
+                length += 1;
+            }
+            let code = (buffer - pairs.base[length]) >> (64 - length as u32 - min_length);
+            let symbol = code as usize + reader.u16_le(pairs.lowest_symbols + 2 * length)? as usize;
+            if offset < symbol_length(symbol)? + 1 {
+                break symbol;
+            }
+            offset -= symbol_length(symbol)? + 1;
+
+            let length = length as u32 + min_length;
+            buffer <<= length;
+            buffer_size -= length;
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                buffer |= u64::from(reader.u32_be(position)?) << (64 - buffer_size);
+                position += 4;
+            }
+        };
+
+        // Expand the paired symbol until reaching the leaf at our offset
+        while symbol_length(symbol)? != 0 {
+            let left = reader.left(pairs.tree, symbol)?;
+            if offset < symbol_length(left)? + 1 {
+                symbol = left;
+            } else {
+                offset -= symbol_length(left)? + 1;
+                symbol = reader.right(pairs.tree, symbol)?;
+            }
+        }
+
+        Ok(reader.left(pairs.tree, symbol)? as u16)
+    }
+
+    /// Turn a stored DTZ value into plies, given the known result
+    fn map_dtz(&self, file: usize, value: i32, wdl: Wdl) -> Result<i32, TableError> {
+        let pairs = &self.pairs[0][file];
+        let reader = Reader(&self.bytes);
+        let value = if pairs.flags & MAPPED == 0 {
+            value
+        } else {
+            let map_index = pairs.map_index[match wdl {
+                Wdl::Loss => 1,
+                Wdl::BlessedLoss => 3,
+                Wdl::Draw | Wdl::Win => 0,
+                Wdl::CursedWin => 2,
+            }];
+            if pairs.flags & WIDE != 0 {
+                i32::from(reader.u16_le(self.map + 2 * (map_index + value as usize))?)
+            } else {
+                i32::from(reader.u8(self.map + map_index + value as usize)?)
+            }
+        };
+
+        let in_moves = match wdl {
+            Wdl::Win => pairs.flags & WIN_PLIES == 0,
+            Wdl::Loss => pairs.flags & LOSS_PLIES == 0,
+            Wdl::CursedWin | Wdl::BlessedLoss => true,
+            Wdl::Draw => false,
+        };
+
+        Ok(if in_moves { value * 2 + 1 } else { value + 1 })
+    }
+}
+
+/// Compute how many extra values a paired symbol expands into
+fn symbol_length(
+    reader: Reader,
+    pairs: &mut PairsData,
+    symbol: usize,
+    visited: &mut [bool],
+) -> Result<u8, TableError> {
+    *visited
+        .get_mut(symbol)
+        .ok_or(TableError::MismatchedHeader)? = true;
+    let right = reader.right(pairs.tree, symbol)?;
+    if right == 0xfff {
+        return Ok(0);
+    }
+    let left = reader.left(pairs.tree, symbol)?;
+    for child in [left, right] {
+        if !*visited.get(child).ok_or(TableError::MismatchedHeader)? {
+            pairs.symbol_lengths[child] = symbol_length(reader, pairs, child, visited)?;
+        }
+    }
+
+    Ok(pairs.symbol_lengths[left]
+        .wrapping_add(pairs.symbol_lengths[right])
+        .wrapping_add(1))
+}
+
+/// Collect the (square, [code](piece_code)) of every piece on the board
+pub fn placed_pieces(position: &LegalPosition) -> Vec<(u8, u8)> {
+    position
+        .occupied_mask()
+        .into_iter()
+        .filter_map(|square| {
+            position.owned_piece_on(square).map(|owned_piece| {
+                (
+                    square as u8,
+                    piece_code(owned_piece.player, owned_piece.piece),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::fen;
+    use test_case::test_case;
+
+    /// The values stored for one side to move (and leading pawn file) of a written table
+    pub enum SideValues {
+        /// Every position stores the same value
+        Single(u8),
+        /// A value for every index of the table, with the pairs flags to store
+        Values(u8, Vec<u8>),
+        /// A value for every index of a DTZ table, each turned into plies by the map of its result (indexed as
+        /// stored, loss, cursed win then blessed loss), with the pairs flags to store
+        Mapped(u8, Vec<u8>, [Vec<u16>; 4]),
+    }
+
+    /// The log2 of the block size used by [`write_table`]
+    const BLOCK_SIZE_LOG: u8 = 6;
+    /// The log2 of the sparse index span used by [`write_table`]
+    const SPAN_LOG: u8 = 6;
+
+    /// The flags and values of a side that stores a value for every index
+    fn stored_values(side: &SideValues) -> Option<(u8, &[u8])> {
+        match side {
+            SideValues::Single(_) => None,
+            SideValues::Values(flags, values) => Some((*flags, values)),
+            SideValues::Mapped(flags, values, _) => Some((*flags | MAPPED, values)),
+        }
+    }
+
+    /// Write the value maps of a DTZ table's mapped sides, then pad to an even length
+    fn write_maps(bytes: &mut Vec<u8>, files: &[&[SideValues]]) {
+        for side in files.iter().flat_map(|sides| sides.iter()) {
+            let SideValues::Mapped(flags, _, maps) = side else {
+                continue;
+            };
+            for map in maps {
+                if flags & WIDE == 0 {
+                    bytes.push(map.len() as u8);
+                    bytes.extend(map.iter().map(|&value| value as u8));
+                } else {
+                    if bytes.len() % 2 == 1 {
+                        bytes.push(0);
+                    }
+                    bytes.extend((map.len() as u16).to_le_bytes());
+                    bytes.extend(map.iter().flat_map(|value| value.to_le_bytes()));
+                }
+            }
+        }
+        if bytes.len() % 2 == 1 {
+            bytes.push(0);
+        }
+    }
+
+    /// Write a table in the Syzygy layout, with every distinct value a fixed length Huffman code (no pairing)
+    ///
+    /// `pieces` is the table's piece sequence, shared by both sides to move and every leading pawn file, and `files`
+    /// the values of each side to move for each leading pawn file (only the first for pawnless tables).
+    pub fn write_table(
+        material: &Material,
+        kind: TableKind,
+        pieces: &[u8],
+        files: &[&[SideValues]],
+    ) -> Vec<u8> {
+        let magic = match kind {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        };
+        let symmetric = *material == material.swapped();
+        let has_pawns = material.count(PlayerColor::White, PieceType::Pawn)
+            + material.count(PlayerColor::Black, PieceType::Pawn)
+            > 0;
+        let remaining_pawns = material.count(PlayerColor::White, PieceType::Pawn) > 0
+            && material.count(PlayerColor::Black, PieceType::Pawn) > 0;
+        let mut bytes = magic.to_le_bytes().to_vec();
+        bytes.push(if symmetric { 0 } else { SPLIT } | if has_pawns { HAS_PAWNS } else { 0 });
+        for _ in files {
+            // The leading group is first for both sides, then the remaining pawns
+            bytes.push(0);
+            if remaining_pawns {
+                bytes.push(0x11);
+            }
+            bytes.extend(pieces.iter().map(|&code| code | (code << 4)));
+        }
+        if bytes.len() % 2 == 1 {
+            bytes.push(0);
+        }
+
+        let mut layouts = Vec::new();
+        for side in files.iter().flat_map(|sides| sides.iter()) {
+            let Some((flags, values)) = stored_values(side) else {
+                let SideValues::Single(value) = side else {
+                    unreachable!()
+                };
+                bytes.extend([SINGLE_VALUE, *value]);
+                layouts.push(None);
+                continue;
+            };
+            // Each distinct value gets a symbol, the code of a value being its position among them
+            let mut symbols = values.to_vec();
+            symbols.sort_unstable();
+            symbols.dedup();
+            let bits = (usize::BITS - (symbols.len() - 1).leading_zeros()).max(1);
+            let per_block = (8 << BLOCK_SIZE_LOG) / bits as usize;
+            let blocks = values.len().div_ceil(per_block);
+            bytes.extend([flags, BLOCK_SIZE_LOG, SPAN_LOG, 1]);
+            bytes.extend((blocks as u32).to_le_bytes());
+            bytes.extend([bits as u8, bits as u8]);
+            bytes.extend(0u16.to_le_bytes());
+            bytes.extend((symbols.len() as u16).to_le_bytes());
+            for &value in &symbols {
+                bytes.extend([value, 0xf0, 0xff]);
+            }
+            if symbols.len() % 2 == 1 {
+                bytes.push(0);
+            }
+            let codes = values
+                .iter()
+                .map(|value| symbols.binary_search(value).unwrap() as u8)
+                .collect::<Vec<_>>();
+            layouts.push(Some((bits, per_block, blocks, codes)));
+        }
+        if kind == TableKind::Dtz {
+            write_maps(&mut bytes, files);
+        }
+
+        let span = 1 << SPAN_LOG;
+        for (_, per_block, _, codes) in layouts.iter().flatten() {
+            for k in 0..codes.len().div_ceil(span) {
+                let middle = k * span + span / 2;
+                bytes.extend(((middle / per_block) as u32).to_le_bytes());
+                bytes.extend(((middle % per_block) as u16).to_le_bytes());
+            }
+        }
+        for (_, per_block, blocks, _) in layouts.iter().flatten() {
+            for _ in 0..=*blocks {
+                bytes.extend((*per_block as u16 - 1).to_le_bytes());
+            }
+        }
+        for layout in &layouts {
+            // Every side's blocks are 64 byte aligned, even when it has none
+            bytes.resize((bytes.len() + 0x3f) & !0x3f, 0);
+            let Some((bits, per_block, _, codes)) = layout else {
+                continue;
+            };
+            for block in codes.chunks(*per_block) {
+                let mut block_bytes = vec![0u8; 1 << BLOCK_SIZE_LOG];
+                for (i, &code) in block.iter().enumerate() {
+                    for bit in 0..*bits as usize {
+                        if code >> (*bits as usize - 1 - bit) & 1 == 1 {
+                            let position = i * *bits as usize + bit;
+                            block_bytes[position / 8] |= 0x80 >> (position % 8);
+                        }
+                    }
+                }
+                bytes.extend(block_bytes);
+            }
+        }
+        bytes.extend([0; 8]);
+
+        bytes
+    }
+
+    impl Table {
+        /// The number of indices of a side to move and leading pawn file
+        pub fn size(&self, side: usize, file: usize) -> usize {
+            let pairs = &self.pairs[side][file];
+            let groups = pairs.group_len.iter().position(|&len| len == 0).unwrap();
+            pairs.group_index[groups] as usize
+        }
+
+        /// The (side, leading pawn file, index) a position is stored at
+        pub fn index_of(
+            &self,
+            position: &LegalPosition,
+            black_stronger: bool,
+        ) -> (usize, usize, usize) {
+            let black_to_move = position.player_to_move() == PlayerColor::Black;
+            let (side, file, index) = self
+                .encode(&placed_pieces(position), black_to_move, black_stronger)
+                .unwrap();
+            (side, file, index as usize)
+        }
+    }
+
+    pub const KQVK_PIECES: [u8; 3] = [6, 5, 14];
+    /// The pairs flags of a DTZ side storing both wins and losses in plies, for the given side to move
+    pub const fn dtz_plies_flags(black_to_move: bool) -> u8 {
+        WIN_PLIES | LOSS_PLIES | if black_to_move { STM } else { 0 }
+    }
+
+    pub fn kqvk() -> Material {
+        "KQvK".parse().unwrap()
+    }
+
+    #[test]
+    fn single_value_table_works() {
+        let bytes = write_table(
+            &kqvk(),
+            TableKind::Wdl,
+            &KQVK_PIECES,
+            &[&[SideValues::Single(4), SideValues::Single(0)]],
+        );
+        let table = Table::new(bytes, &kqvk(), TableKind::Wdl).unwrap();
+        let pieces = placed_pieces(&fen!("8/8/8/3k4/8/8/8/KQ6 w - - 0 1"));
+
+        assert_eq!(table.size(0, 0), UNIQUE_TRIPLET_PLACEMENTS as usize);
+        assert_eq!(
+            table.probe(&pieces, false, false, Wdl::Draw),
+            Ok(TableProbe::Value(2))
+        );
+        assert_eq!(
+            table.probe(&pieces, true, false, Wdl::Draw),
+            Ok(TableProbe::Value(-2))
+        );
+    }
+
+    #[test]
+    fn compressed_values_round_trip() {
+        let size = UNIQUE_TRIPLET_PLACEMENTS as usize;
+        let values: Vec<u8> = (0..size).map(|index| (index * 7 % 5) as u8).collect();
+        let bytes = write_table(
+            &kqvk(),
+            TableKind::Wdl,
+            &KQVK_PIECES,
+            &[&[SideValues::Values(0, values.clone()), SideValues::Single(0)]],
+        );
+        let table = Table::new(bytes, &kqvk(), TableKind::Wdl).unwrap();
+
+        for index in (0..size).step_by(97).chain([0, 1, 63, 64, size - 1]) {
+            let pairs = &table.pairs[0][0];
+            assert_eq!(
+                table.decompress(pairs, index as u64),
+                Ok(u16::from(values[index]))
+            );
+        }
+    }
+
+    #[test]
+    fn unique_triplet_indices_are_in_range_and_distinct() {
+        let bytes = write_table(
+            &kqvk(),
+            TableKind::Wdl,
+            &KQVK_PIECES,
+            &[&[SideValues::Single(4), SideValues::Single(0)]],
+        );
+        let table = Table::new(bytes, &kqvk(), TableKind::Wdl).unwrap();
+        let fens = [
+            "8/8/8/3k4/8/8/8/KQ6 w - - 0 1",
+            "8/8/8/3k4/8/8/8/QK6 w - - 0 1",
+            "8/8/8/8/3k4/8/8/KQ6 w - - 0 1",
+            "6k1/8/8/8/8/8/1Q6/K7 w - - 0 1",
+            "k7/8/8/8/8/8/8/K5Q1 w - - 0 1",
+        ];
+        let mut indices: Vec<usize> = fens
+            .iter()
+            .map(|fen| table.index_of(&fen!(fen), false).2)
+            .collect();
+        assert!(indices.iter().all(|&index| index < table.size(0, 0)));
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(indices.len(), fens.len());
+    }
+
+    #[test_case("8/8/8/4k3/8/8/8/6QK w - - 0 1"; "mirrored files")]
+    #[test_case("KQ6/8/8/8/3k4/8/8/8 w - - 0 1"; "mirrored ranks")]
+    #[test_case("8/8/8/8/4k3/8/Q7/K7 w - - 0 1"; "mirrored diagonal")]
+    fn symmetric_positions_share_indices(fen: &str) {
+        let bytes = write_table(
+            &kqvk(),
+            TableKind::Wdl,
+            &KQVK_PIECES,
+            &[&[SideValues::Single(4), SideValues::Single(0)]],
+        );
+        let table = Table::new(bytes, &kqvk(), TableKind::Wdl).unwrap();
+
+        assert_eq!(
+            table.index_of(&fen!(fen), false),
+            table.index_of(&fen!("8/8/8/3k4/8/8/8/KQ6 w - - 0 1"), false)
+        );
+    }
+
+    /// `KPvK`'s pieces, the leading pawn first
+    pub const KPVK_PIECES: [u8; 3] = [1, 6, 14];
+
+    /// A table with the same single value everywhere, for checking its indices
+    pub fn index_table(material: &str, kind: TableKind, pieces: &[u8]) -> Table {
+        let material: Material = material.parse().unwrap();
+        let side = [SideValues::Single(2), SideValues::Single(2)];
+        let sides = if kind == TableKind::Wdl && material != material.swapped() {
+            &side[..]
+        } else {
+            &side[..1]
+        };
+        let files = if pieces.contains(&1) || pieces.contains(&9) {
+            4
+        } else {
+            1
+        };
+        let bytes = write_table(&material, kind, pieces, &vec![sides; files]);
+
+        Table::new(bytes, &material, kind).unwrap()
+    }
+
+    #[test_case("KPvK", &KPVK_PIECES, 0, 6 * 63 * 62; "one pawn a file")]
+    #[test_case("KPvK", &KPVK_PIECES, 3, 6 * 63 * 62; "one pawn d file")]
+    #[test_case("KPvKP", &[1, 9, 6, 14], 0, 6 * 47 * 62 * 61; "pawns on both sides")]
+    #[test_case("KRvKP", &[9, 6, 4, 14], 2, 6 * 63 * 62 * 61; "leading black pawn")]
+    #[test_case("KQvKR", &[6, 5, 14, 12], 0, 31332 * 61; "four pieces without pawns")]
+    fn table_sizes_match_their_groups(material: &str, pieces: &[u8], file: usize, expected: usize) {
+        let table = index_table(material, TableKind::Wdl, pieces);
+
+        assert_eq!(table.size(0, file), expected);
+    }
+
+    #[test]
+    fn lead_pawn_indices_are_in_range_and_distinct() {
+        let table = index_table("KPvK", TableKind::Wdl, &KPVK_PIECES);
+        let mut seen = vec![vec![false; table.size(0, 0)]; 4];
+
+        for pawn in 8..56u8 {
+            for king in (0..64).filter(|&king| king != pawn) {
+                for other_king in (0..64).filter(|&other| other != pawn && other != king) {
+                    let pieces = [(pawn, 1), (king, 6), (other_king, 14)];
+                    let (side, file, index) = table.encode(&pieces, false, false).unwrap();
+                    assert_eq!(side, 0);
+                    assert_eq!(file, file_of(pawn).min(7 - file_of(pawn)) as usize);
+                    assert!(index < table.size(0, file) as u64);
+
+                    // Only the mirrored placement on the other half of the board shares an index
+                    let first = !seen[file][index as usize];
+                    assert_eq!(first, file_of(pawn) < 4);
+                    seen[file][index as usize] = true;
+                }
+            }
+        }
+        assert!(seen.iter().flatten().all(|&seen| seen));
+    }
+
+    #[test]
+    fn remaining_pawn_indices_are_in_range_and_distinct() {
+        let table = index_table("KPvKP", TableKind::Wdl, &[1, 9, 6, 14]);
+        let mut seen = vec![vec![false; table.size(0, 0)]; 4];
+
+        for pawn in (8..56u8).filter(|&square| file_of(square) < 4) {
+            for other_pawn in (8..56).filter(|&square| square != pawn) {
+                for king in [0, 7, 36, 63]
+                    .into_iter()
+                    .filter(|&king| king != pawn && king != other_pawn)
+                {
+                    for other_king in
+                        (0..64).filter(|&other| ![pawn, other_pawn, king].contains(&other))
+                    {
+                        let pieces = [(pawn, 1), (other_pawn, 9), (king, 6), (other_king, 14)];
+                        let (_, file, index) = table.encode(&pieces, false, false).unwrap();
+                        assert!(index < table.size(0, file) as u64);
+                        assert!(!seen[file][index as usize]);
+                        seen[file][index as usize] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test_case("KPvKP", "8/8/4k3/1p6/8/2P5/8/4K3 b - - 0 1", false, "4k3/8/2p5/8/1P6/4K3/8/8 w - - 0 1", false; "symmetric table")]
+    #[test_case("KRvKP", "8/8/8/8/4p3/8/1k6/R3K3 w - - 0 1", false, "r3k3/1K6/8/4P3/8/8/8/8 b - - 0 1", true; "stronger black")]
+    #[test_case("KRvKP", "8/8/8/8/4p3/8/1k6/R3K3 w - - 0 1", false, "8/8/8/8/3p4/8/6k1/3K3R w - - 0 1", false; "mirrored files")]
+    fn pawn_positions_share_indices(
+        material: &str,
+        fen: &str,
+        black_stronger: bool,
+        other_fen: &str,
+        other_black_stronger: bool,
+    ) {
+        let pieces = if material == "KPvKP" {
+            [1, 9, 6, 14]
+        } else {
+            [9, 6, 4, 14]
+        };
+        let table = index_table(material, TableKind::Wdl, &pieces);
+
+        assert_eq!(
+            table.index_of(&fen!(fen), black_stronger),
+            table.index_of(&fen!(other_fen), other_black_stronger)
+        );
+    }
+
+    #[test]
+    fn pawn_table_values_round_trip() {
+        let material = "KRvKP".parse().unwrap();
+        let pieces = [9, 6, 4, 14];
+        let table = index_table("KRvKP", TableKind::Wdl, &pieces);
+        let value =
+            |side: usize, file: usize, index: usize| ((index * 7 + file * 3 + side) % 5) as u8;
+        let sides: Vec<Vec<SideValues>> = (0..4)
+            .map(|file| {
+                (0..2)
+                    .map(|side| {
+                        let size = table.size(side, file);
+                        SideValues::Values(
+                            0,
+                            (0..size).map(|index| value(side, file, index)).collect(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        let files: Vec<&[SideValues]> = sides.iter().map(Vec::as_slice).collect();
+        let table = Table::new(
+            write_table(&material, TableKind::Wdl, &pieces, &files),
+            &material,
+            TableKind::Wdl,
+        )
+        .unwrap();
+
+        for fen in [
+            "8/8/8/8/4p3/8/1k6/R3K3 w - - 0 1",
+            "8/8/8/8/4p3/8/1k6/R3K3 b - - 0 1",
+            "8/p7/8/8/8/2k5/8/R3K3 w - - 0 1",
+            "8/7p/8/8/8/2k5/8/R3K3 b - - 0 1",
+            "8/8/2p5/8/1k6/8/8/R3K3 w - - 0 1",
+            "8/8/8/5p2/3k4/8/8/RK6 b - - 0 1",
+        ] {
+            let position = fen!(fen);
+            let (side, file, index) = table.index_of(&position, false);
+            let black_to_move = position.player_to_move() == PlayerColor::Black;
+            assert_eq!(
+                table.probe(&placed_pieces(&position), black_to_move, false, Wdl::Draw),
+                Ok(TableProbe::Value(i32::from(value(side, file, index)) - 2)),
+                "{fen}"
+            );
+        }
+    }
+
+    #[test_case(0, Wdl::Win, 10 + 1; "win in plies")]
+    #[test_case(1, Wdl::Loss, 21 + 1; "loss in plies")]
+    #[test_case(2, Wdl::CursedWin, 32 * 2 + 1; "cursed win in moves")]
+    #[test_case(3, Wdl::BlessedLoss, 43 * 2 + 1; "blessed loss in moves")]
+    fn mapped_dtz_values_work(value: u8, wdl: Wdl, expected: i32) {
+        let material = "KPvK".parse().unwrap();
+        let table = index_table("KPvK", TableKind::Dtz, &KPVK_PIECES);
+        let maps = |offset: u16| -> [Vec<u16>; 4] {
+            [10, 20, 30, 40].map(|base| (0..4).map(|value| offset + base + value).collect())
+        };
+        // Every index stores the same value, files alternating narrow and wide maps
+        let sides: Vec<[SideValues; 1]> = (0..4)
+            .map(|file| {
+                let (flags, offset) = if file % 2 == 0 { (0, 0) } else { (WIDE, 1000) };
+                [SideValues::Mapped(
+                    flags | WIN_PLIES | LOSS_PLIES,
+                    vec![value; table.size(0, file)],
+                    maps(offset),
+                )]
+            })
+            .collect();
+        let files: Vec<&[SideValues]> = sides.iter().map(<[SideValues; 1]>::as_slice).collect();
+        let table = Table::new(
+            write_table(&material, TableKind::Dtz, &KPVK_PIECES, &files),
+            &material,
+            TableKind::Dtz,
+        )
+        .unwrap();
+
+        let narrow = placed_pieces(&fen!("8/8/8/3k4/8/8/P7/4K3 w - - 0 1"));
+        let wide = placed_pieces(&fen!("8/8/8/3k4/8/8/1P6/4K3 w - - 0 1"));
+        assert_eq!(
+            table.probe(&narrow, false, false, wdl),
+            Ok(TableProbe::Value(expected))
+        );
+        // Results counted in moves double the offset of the wide maps
+        let wide_expected = if matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss) {
+            expected + 2000
+        } else {
+            expected + 1000
+        };
+        assert_eq!(
+            table.probe(&wide, false, false, wdl),
+            Ok(TableProbe::Value(wide_expected))
+        );
+        assert_eq!(
+            table.probe(&narrow, true, false, wdl),
+            Ok(TableProbe::ChangeSideToMove)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert_eq!(
+            Table::new(vec![0; 16], &kqvk(), TableKind::Wdl).err(),
+            Some(TableError::InvalidMagic)
+        );
+        assert_eq!(
+            Table::new(WDL_MAGIC.to_le_bytes().to_vec(), &kqvk(), TableKind::Wdl).err(),
+            Some(TableError::Truncated)
+        );
+        let bytes = write_table(
+            &kqvk(),
+            TableKind::Wdl,
+            &KQVK_PIECES,
+            &[&[SideValues::Single(4), SideValues::Single(0)]],
+        );
+        assert_eq!(
+            Table::new(bytes, &kqvk(), TableKind::Dtz).err(),
+            Some(TableError::InvalidMagic)
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use crate::evaluation::{EvaluationFeatures, EvaluationParameter, EvaluationParameters};
+use crate::position::{FenParseError, IllegalPosition, LegalPosition, PositionBuilder};
+use core::str::FromStr;
+
+/// The exponential decay rate of the running average of the gradient
+const FIRST_MOMENT_DECAY: f32 = 0.9;
+/// The exponential decay rate of the running average of the squared gradient
+const SECOND_MOMENT_DECAY: f32 = 0.999;
+/// Keeps the parameter update finite when the squared gradient is near zero
+const UPDATE_EPSILON: f32 = 1e-8;
+
+/// Errors that can occur while parsing a [`LabelledPosition`]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LabelledPositionParseError {
+    /// The FEN (or EPD) portion of the line could not be parsed
+    InvalidFen(FenParseError),
+    /// The FEN parsed but doesn't describe a legal position
+    IllegalPosition(IllegalPosition),
+    /// There was no result or score after the position
+    MissingResult,
+    /// The label after the position isn't a recognized result or a score between 0 and 1
+    InvalidResult,
+}
+
+/// A position along with the outcome of the game it was taken from, used as a single sample for tuning
+#[derive(Clone, PartialEq, Debug)]
+pub struct LabelledPosition {
+    features: EvaluationFeatures,
+    result: f32,
+}
+
+impl LabelledPosition {
+    /// Get the [features](EvaluationFeatures) of the position's static evaluation
+    #[must_use]
+    pub const fn features(&self) -> &EvaluationFeatures {
+        &self.features
+    }
+
+    /// Get the outcome from [white](crate::player_color::PlayerColor::White)'s perspective, 1 for a win, 0.5 for a draw and 0 for a loss
+    #[must_use]
+    pub const fn result(&self) -> f32 {
+        self.result
+    }
+}
+
+/// Parse the label following a position, either a game result (`1-0`, `1/2-1/2`, `0-1`) or a score between 0 and 1
+fn parse_result(label: &str) -> Result<f32, LabelledPositionParseError> {
+    let label = label.trim();
+    let label = label.strip_prefix("c9").unwrap_or(label);
+    let label =
+        label.trim_matches(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '"' | ';'));
+
+    match label {
+        "" => Err(LabelledPositionParseError::MissingResult),
+        "1-0" => Ok(1.0),
+        "1/2-1/2" => Ok(0.5),
+        "0-1" => Ok(0.0),
+        score => score
+            .parse::<f32>()
+            .ok()
+            .filter(|score| (0.0..=1.0).contains(score))
+            .ok_or(LabelledPositionParseError::InvalidResult),
+    }
+}
+
+impl FromStr for LabelledPosition {
+    type Err = LabelledPositionParseError;
+
+    /// Parse a FEN (or the four field EPD position) followed by a result label
+    ///
+    /// ```
+    /// use thermite_core::tuning::LabelledPosition;
+    ///
+    /// let position = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1 [0.5]".parse::<LabelledPosition>().unwrap();
+    /// assert_eq!(position.result(), 0.5);
+    /// let position = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 c9 \"1-0\";".parse::<LabelledPosition>().unwrap();
+    /// assert_eq!(position.result(), 1.0);
+    /// ```
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        const POSITION_FIELDS: usize = 4;
+        let fields = line.split_ascii_whitespace().collect::<Vec<_>>();
+        let mut fen_length = POSITION_FIELDS.min(fields.len());
+        // The move counters are optional, only treat them as part of the FEN when both are present
+        if fields
+            .get(fen_length..fen_length + 2)
+            .is_some_and(|counters| counters.iter().all(|field| field.parse::<u16>().is_ok()))
+        {
+            fen_length += 2;
+        }
+
+        let builder = fields[..fen_length]
+            .join(" ")
+            .parse::<PositionBuilder>()
+            .map_err(LabelledPositionParseError::InvalidFen)?;
+        let position = LegalPosition::try_from(builder)
+            .map_err(LabelledPositionParseError::IllegalPosition)?;
+        let result = parse_result(&fields[fen_length..].join(" "))?;
+
+        Ok(Self {
+            features: position.evaluation_features(),
+            result,
+        })
+    }
+}
+
+/// Texel-style tuner, fitting [evaluation parameters](EvaluationParameters) to game results by minimizing the
+/// mean squared error between the results and the sigmoid of the static evaluation.
+///
+/// Each [epoch](Self::epoch) is a full pass of gradient descent over every position, using Adam to adapt the step size per parameter.
+#[derive(Clone, Debug)]
+pub struct Tuner {
+    positions: Vec<LabelledPosition>,
+    parameters: EvaluationParameters,
+    scaling: f32,
+    first_moments: Vec<[f32; 2]>,
+    second_moments: Vec<[f32; 2]>,
+    steps: i32,
+}
+
+impl Tuner {
+    /// Create a tuner over a set of [positions](LabelledPosition), starting from the engine's current [parameters](EvaluationParameters)
+    #[must_use]
+    pub fn new(positions: Vec<LabelledPosition>) -> Self {
+        Self {
+            positions,
+            parameters: EvaluationParameters::default(),
+            scaling: 1.0,
+            first_moments: vec![[0.0; 2]; EvaluationParameter::COUNT],
+            second_moments: vec![[0.0; 2]; EvaluationParameter::COUNT],
+            steps: 0,
+        }
+    }
+
+    /// Get the current [parameters](EvaluationParameters)
+    #[must_use]
+    pub const fn parameters(&self) -> &EvaluationParameters {
+        &self.parameters
+    }
+
+    /// Get the scaling constant (`K`) used to map evaluations to expected results
+    #[must_use]
+    pub const fn scaling(&self) -> f32 {
+        self.scaling
+    }
+
+    /// The number of positions being fit, as a divisor for averaging over them
+    #[allow(clippy::cast_precision_loss)]
+    fn sample_count(&self) -> f32 {
+        self.positions.len().max(1) as f32
+    }
+
+    /// Map an evaluation (in pawns, from white's perspective) to an expected result between 0 and 1
+    fn sigmoid(scaling: f32, evaluation: f32) -> f32 {
+        1.0 / (1.0 + 10f32.powf(-scaling * evaluation / 4.0))
+    }
+
+    /// Get the mean squared error of the current [parameters](EvaluationParameters) with a given scaling constant
+    fn error_with_scaling(&self, scaling: f32) -> f32 {
+        let total_error = self
+            .positions
+            .iter()
+            .map(|position| {
+                let evaluation = *position.features.evaluate(&self.parameters);
+                (position.result - Self::sigmoid(scaling, evaluation)).powi(2)
+            })
+            .sum::<f32>();
+
+        total_error / self.sample_count()
+    }
+
+    /// Get the mean squared error of the current [parameters](EvaluationParameters) over every position
+    #[must_use]
+    pub fn error(&self) -> f32 {
+        self.error_with_scaling(self.scaling)
+    }
+
+    /// Find the scaling constant (`K`) that minimizes the error of the current parameters, this should be done once before tuning
+    pub fn fit_scaling(&mut self) -> f32 {
+        const STEPS: i8 = 10;
+        let mut best = (self.scaling, self.error());
+        let mut precision = 1.0;
+        for _ in 0..4 {
+            let center = best.0;
+            for step in -STEPS..=STEPS {
+                let scaling = f32::from(step).mul_add(precision, center);
+                if scaling <= 0.0 {
+                    continue;
+                }
+                let error = self.error_with_scaling(scaling);
+                if error < best.1 {
+                    best = (scaling, error);
+                }
+            }
+            precision /= 10.0;
+        }
+
+        self.scaling = best.0;
+        self.scaling
+    }
+
+    /// Get the gradient of the error for every parameter, split into its mid-game and end-game components
+    fn gradient(&self) -> Vec<[f32; 2]> {
+        let mut gradient = vec![[0.0; 2]; EvaluationParameter::COUNT];
+        let derivative_scale =
+            2.0 * core::f32::consts::LN_10 * self.scaling / 4.0 / self.sample_count();
+        for position in &self.positions {
+            let evaluation = *position.features.evaluate(&self.parameters);
+            let expected = Self::sigmoid(self.scaling, evaluation);
+            let error =
+                (expected - position.result) * expected * (1.0 - expected) * derivative_scale;
+            let mid_game_weight = position.features.phase.mid_game_weight();
+            let error = error * position.features.scale_factor;
+            for &(parameter, coefficient) in &position.features.coefficients {
+                let [mid_game, end_game] = &mut gradient[parameter.index()];
+                *mid_game += error * f32::from(coefficient) * mid_game_weight;
+                *end_game += error * f32::from(coefficient) * (1.0 - mid_game_weight);
+            }
+        }
+
+        // Parameters shared between both phases move together
+        for parameter in EvaluationParameter::all().filter(|parameter| !parameter.is_tapered()) {
+            let [mid_game, end_game] = gradient[parameter.index()];
+            gradient[parameter.index()] = [mid_game + end_game; 2];
+        }
+
+        gradient
+    }
+
+    /// Perform a single step of gradient descent over every position, returning the error after the step
+    pub fn epoch(&mut self, learning_rate: f32) -> f32 {
+        let gradient = self.gradient();
+        self.steps += 1;
+        let first_correction = 1.0 - FIRST_MOMENT_DECAY.powi(self.steps);
+        let second_correction = 1.0 - SECOND_MOMENT_DECAY.powi(self.steps);
+
+        for parameter in EvaluationParameter::all() {
+            let index = parameter.index();
+            let mut update = [0.0; 2];
+            for phase in 0..2 {
+                let first_moment = &mut self.first_moments[index][phase];
+                *first_moment = FIRST_MOMENT_DECAY.mul_add(
+                    *first_moment,
+                    (1.0 - FIRST_MOMENT_DECAY) * gradient[index][phase],
+                );
+                let second_moment = &mut self.second_moments[index][phase];
+                *second_moment = SECOND_MOMENT_DECAY.mul_add(
+                    *second_moment,
+                    (1.0 - SECOND_MOMENT_DECAY) * gradient[index][phase].powi(2),
+                );
+
+                update[phase] = learning_rate
+                    * (self.first_moments[index][phase] / first_correction)
+                    / ((self.second_moments[index][phase] / second_correction).sqrt()
+                        + UPDATE_EPSILON);
+            }
+
+            let value = &mut self.parameters[parameter];
+            *value.mid_game -= update[0];
+            *value.end_game -= update[1];
+        }
+
+        self.error()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tuning::{LabelledPosition, LabelledPositionParseError, Tuner};
+    use test_case::test_case;
+
+    #[test_case("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1 [1.0]", 1.0)]
+    #[test_case("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1 [0.5]", 0.5)]
+    #[test_case("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1 \"0-1\"", 0.0)]
+    #[test_case("4k3/8/8/8/8/8/3PP3/4K3 w - - 12 40 1/2-1/2", 0.5)]
+    #[test_case("4k3/8/8/8/8/8/3PP3/4K3 w - - c9 \"1-0\";", 1.0)]
+    #[test_case("4k3/8/8/8/8/8/3PP3/4K3 w - - 0.25", 0.25)]
+    fn parse_labelled_position_works(line: &str, expected: f32) {
+        assert_eq!(
+            line.parse::<LabelledPosition>()
+                .map(|position| position.result()),
+            Ok(expected)
+        );
+    }
+
+    #[test_case(
+        "4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1",
+        LabelledPositionParseError::MissingResult
+    )]
+    #[test_case(
+        "4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1 [2.0]",
+        LabelledPositionParseError::InvalidResult
+    )]
+    #[test_case(
+        "4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1 draw",
+        LabelledPositionParseError::InvalidResult
+    )]
+    fn parse_labelled_position_errors(line: &str, expected: LabelledPositionParseError) {
+        assert_eq!(line.parse::<LabelledPosition>(), Err(expected));
+    }
+
+    #[test]
+    fn epoch_reduces_error() {
+        let positions = [
+            "4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1 [1.0]",
+            "4k3/3pp3/8/8/8/8/8/4K3 w - - 0 1 [0.0]",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 [0.5]",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [0.5]",
+            "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [1.0]",
+        ]
+        .iter()
+        .map(|line| line.parse::<LabelledPosition>().unwrap())
+        .collect();
+        let mut tuner = Tuner::new(positions);
+        tuner.fit_scaling();
+        let initial_error = tuner.error();
+        let mut error = initial_error;
+        for _ in 0..50 {
+            error = tuner.epoch(0.01);
+        }
+
+        assert!(error < initial_error, "{error} >= {initial_error}");
+    }
+}
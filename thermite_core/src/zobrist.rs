@@ -1,6 +1,6 @@
 use crate::castles::CastleDirection;
 use crate::chess_move::castle::Castle;
-use crate::pieces::{PieceType, PlacedPiece};
+use crate::pieces::{NonKingPieceType, OwnedPiece, PieceType, PlacedPiece};
 use crate::player_color::PlayerColor;
 use crate::square::{EnPassantSquare, Square};
 use core::hash::Hasher;
@@ -59,6 +59,53 @@ impl ZobristHash {
     }
 }
 
+/// The Zobrist hash of only the pawns on the board, identical for positions sharing the same pawn structure
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, AsRef)]
+pub struct PawnHash(u64);
+
+impl PawnHash {
+    /// Toggle a pawn of a given side on a square.
+    /// Adds the pawn to the hash; otherwise, removes the pawn if it is already included.
+    pub fn toggle_pawn_square(&mut self, player: PlayerColor, square: Square) {
+        self.0 ^= PIECE_SQUARES[player][PieceType::Pawn][square];
+    }
+}
+
+/// The Zobrist hash of the number of each non-king piece each side has, identical for positions with the same material signature
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, AsRef)]
+pub struct MaterialHash(u64);
+
+impl MaterialHash {
+    /// Toggle the key for the `count`-th (zero-based) [piece](NonKingPieceType) of a given side.
+    /// Adding a piece toggles the count before it was added, while removing a piece toggles the count after it was removed.
+    ///
+    /// # Panics
+    /// Will panic if there are more than 64 of a single piece
+    pub fn toggle_piece_count(&mut self, owned_piece: OwnedPiece<NonKingPieceType>, count: u8) {
+        let OwnedPiece { player, piece } = owned_piece;
+        let count_square = Square::try_from(count).expect("piece count fits within a board");
+        self.0 ^= PIECE_SQUARES[player][PieceType::from(piece)][count_square];
+    }
+}
+
+/// The Zobrist hash of a single side's non-pawn pieces (including its king)
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, AsRef)]
+pub struct NonPawnHash(u64);
+
+impl NonPawnHash {
+    /// Toggle the placement of a non-pawn piece on a given square.
+    /// Adds the piece placement to the hash; otherwise, removes the piece if it is already included.
+    pub fn toggle_piece_square(&mut self, placed_piece: PlacedPiece) {
+        debug_assert_ne!(
+            placed_piece.owned_piece.piece,
+            PieceType::Pawn,
+            "attempting to toggle a pawn in a `NonPawnHash`"
+        );
+        self.0 ^= PIECE_SQUARES[placed_piece.owned_piece.player][placed_piece.owned_piece.piece]
+            [placed_piece.square];
+    }
+}
+
 impl Default for ZobristHash {
     fn default() -> Self {
         Self(EMPTY_ZOBRIST_KEY)
@@ -256,10 +303,10 @@ pub(crate) fn random_hash() -> ZobristHash {
 #[cfg(test)]
 mod test {
     use crate::castles::CastleDirection;
-    use crate::pieces::{Piece, PieceType};
+    use crate::pieces::{NonKingPieceType, Piece, PieceType};
     use crate::player_color::PlayerColor;
     use crate::square::Square;
-    use crate::zobrist::ZobristHash;
+    use crate::zobrist::{MaterialHash, PawnHash, ZobristHash};
 
     use crate::chess_move::castle::Castle;
     use test_case::test_case;
@@ -282,6 +329,30 @@ mod test {
         assert_eq!(hasher_original, hasher);
     }
 
+    #[test_case(PlayerColor::White, Square::E2)]
+    #[test_case(PlayerColor::Black, Square::E2)]
+    #[test_case(PlayerColor::Black, Square::H7)]
+    fn toggle_pawn_square_symmetric(player: PlayerColor, square: Square) {
+        let mut hasher = PawnHash::default();
+        let hasher_original = hasher;
+        hasher.toggle_pawn_square(player, square);
+        assert_ne!(hasher_original, hasher);
+        hasher.toggle_pawn_square(player, square);
+        assert_eq!(hasher_original, hasher);
+    }
+
+    #[test_case(NonKingPieceType::Pawn, PlayerColor::White, 0)]
+    #[test_case(NonKingPieceType::Queen, PlayerColor::Black, 1)]
+    #[test_case(NonKingPieceType::Knight, PlayerColor::White, 9)]
+    fn toggle_piece_count_symmetric(piece: NonKingPieceType, player: PlayerColor, count: u8) {
+        let mut hasher = MaterialHash::default();
+        let hasher_original = hasher;
+        hasher.toggle_piece_count(piece.owned_by(player), count);
+        assert_ne!(hasher_original, hasher);
+        hasher.toggle_piece_count(piece.owned_by(player), count);
+        assert_eq!(hasher_original, hasher);
+    }
+
     #[test]
     fn switch_sides_symmetric() {
         let mut hasher = ZobristHash::default();